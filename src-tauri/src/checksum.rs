@@ -0,0 +1,321 @@
+use serde::{Deserialize, Serialize};
+
+// 校验和参数，发送时追加与接收时校验共用
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecksumParams {
+    pub algorithm: String,          // "crc16_modbus"、"crc16_ccitt"、"crc16_xmodem"、"crc32"、"xor"、"sum8" 或 "lrc"
+    pub start: Option<i64>,         // 参与计算的起始偏移，负数表示从末尾倒数，默认为 0
+    pub end: Option<i64>,           // 参与计算的结束偏移（不含），负数表示从末尾倒数，默认为数据末尾
+    pub endianness: Option<String>, // "big" 或 "little"，crc16_modbus 默认为 "little"，其余默认为 "big"
+    pub placement: Option<String>,  // "end"（默认）、"start" 或 "after_range"（紧跟在计算范围之后）
+}
+
+// 接收帧的校验结果，附加在接收事件上
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecksumCheck {
+    pub valid: bool,
+    pub expected: String, // 按数据计算出的校验值（十六进制，按传输顺序）
+    pub received: String, // 帧中携带的校验值，帧长度不足时为空
+}
+
+// 校验算法
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChecksumAlgorithm {
+    Crc16Modbus, // 多项式 0x8005（反射），初值 0xFFFF
+    Crc16Ccitt,  // CRC-16/CCITT-FALSE：多项式 0x1021，初值 0xFFFF
+    Crc16Xmodem, // 多项式 0x1021，初值 0x0000
+    Crc32,       // IEEE 802.3
+    Xor,         // 逐字节异或
+    Sum8,        // 逐字节累加，取低8位
+    Lrc,         // Modbus ASCII 的纵向冗余校验：逐字节累加后取二进制补码
+}
+
+impl ChecksumAlgorithm {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().replace(['-', '/'], "_").as_str() {
+            "crc16_modbus" | "modbus" => Ok(ChecksumAlgorithm::Crc16Modbus),
+            "crc16_ccitt" | "crc_ccitt" | "ccitt" => Ok(ChecksumAlgorithm::Crc16Ccitt),
+            "crc16_xmodem" | "xmodem" => Ok(ChecksumAlgorithm::Crc16Xmodem),
+            "crc32" => Ok(ChecksumAlgorithm::Crc32),
+            "xor" => Ok(ChecksumAlgorithm::Xor),
+            "sum8" | "sum_8" => Ok(ChecksumAlgorithm::Sum8),
+            "lrc" => Ok(ChecksumAlgorithm::Lrc),
+            _ => Err(format!("Unsupported checksum algorithm: {}", name)),
+        }
+    }
+
+    // 校验值的字节数
+    pub fn width(self) -> usize {
+        match self {
+            ChecksumAlgorithm::Crc16Modbus | ChecksumAlgorithm::Crc16Ccitt | ChecksumAlgorithm::Crc16Xmodem => 2,
+            ChecksumAlgorithm::Crc32 => 4,
+            ChecksumAlgorithm::Xor | ChecksumAlgorithm::Sum8 | ChecksumAlgorithm::Lrc => 1,
+        }
+    }
+
+    pub fn compute(self, data: &[u8]) -> u32 {
+        match self {
+            ChecksumAlgorithm::Crc16Modbus => crc16_modbus(data) as u32,
+            ChecksumAlgorithm::Crc16Ccitt => crc16_ccitt(data, 0xFFFF) as u32,
+            ChecksumAlgorithm::Crc16Xmodem => crc16_ccitt(data, 0x0000) as u32,
+            ChecksumAlgorithm::Crc32 => crc32(data),
+            ChecksumAlgorithm::Xor => data.iter().fold(0u8, |acc, byte| acc ^ byte) as u32,
+            ChecksumAlgorithm::Sum8 => data.iter().fold(0u8, |acc, byte| acc.wrapping_add(*byte)) as u32,
+            ChecksumAlgorithm::Lrc => data.iter().fold(0u8, |acc, byte| acc.wrapping_add(*byte)).wrapping_neg() as u32,
+        }
+    }
+}
+
+fn crc16_modbus(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+fn crc16_ccitt(data: &[u8], init: u16) -> u16 {
+    let mut crc = init;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+// 校验值的放置位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placement {
+    End,
+    Start,
+    AfterRange,
+}
+
+// 解析后的校验和设置
+// 计算范围和放置位置都以不含校验值的数据为准，因此同一设置既可追加也可校验
+#[derive(Debug, Clone)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    start: i64,
+    end: Option<i64>,
+    big_endian: bool,
+    placement: Placement,
+}
+
+impl Checksum {
+    pub fn from_params(params: Option<&ChecksumParams>) -> Result<Option<Self>, String> {
+        let params = match params {
+            Some(params) => params,
+            None => return Ok(None),
+        };
+
+        let algorithm = ChecksumAlgorithm::parse(&params.algorithm)?;
+        let big_endian = match params.endianness.as_deref() {
+            None | Some("") => algorithm != ChecksumAlgorithm::Crc16Modbus,
+            Some("big") => true,
+            Some("little") => false,
+            Some(other) => return Err(format!("Unsupported checksum endianness: {}", other)),
+        };
+        let placement = match params.placement.as_deref() {
+            None | Some("") | Some("end") => Placement::End,
+            Some("start") => Placement::Start,
+            Some("after_range") => Placement::AfterRange,
+            Some(other) => return Err(format!("Unsupported checksum placement: {}", other)),
+        };
+
+        Ok(Some(Checksum {
+            algorithm,
+            start: params.start.unwrap_or(0),
+            end: params.end,
+            big_endian,
+            placement,
+        }))
+    }
+
+    // 将偏移解析为 len 字节数据中的范围
+    fn range(&self, len: usize) -> Result<(usize, usize), String> {
+        let resolve = |offset: i64| if offset < 0 { len as i64 + offset } else { offset };
+        let start = resolve(self.start);
+        let end = self.end.map(resolve).unwrap_or(len as i64);
+        if start < 0 || end < start || end > len as i64 {
+            return Err(format!(
+                "Checksum range {}..{} is invalid for a {}-byte payload",
+                self.start,
+                self.end.map(|end| end.to_string()).unwrap_or_default(),
+                len
+            ));
+        }
+        Ok((start as usize, end as usize))
+    }
+
+    fn position(&self, len: usize, range_end: usize) -> usize {
+        match self.placement {
+            Placement::End => len,
+            Placement::Start => 0,
+            Placement::AfterRange => range_end,
+        }
+    }
+
+    fn encode(&self, value: u32) -> Vec<u8> {
+        let width = self.algorithm.width();
+        let bytes = value.to_be_bytes()[4 - width..].to_vec();
+        if self.big_endian {
+            bytes
+        } else {
+            bytes.into_iter().rev().collect()
+        }
+    }
+
+    // 计算校验值并插入到放置位置
+    pub fn append(&self, mut data: Vec<u8>) -> Result<Vec<u8>, String> {
+        let (start, end) = self.range(data.len())?;
+        let value = self.encode(self.algorithm.compute(&data[start..end]));
+        let position = self.position(data.len(), end);
+        data.splice(position..position, value);
+        Ok(data)
+    }
+
+    // 校验接收帧：取出放置位置上的校验值，对剩余数据重新计算
+    pub fn verify(&self, frame: &[u8]) -> ChecksumCheck {
+        let width = self.algorithm.width();
+        let mut check = ChecksumCheck {
+            valid: false,
+            expected: String::new(),
+            received: String::new(),
+        };
+
+        let len = match frame.len().checked_sub(width) {
+            Some(len) => len,
+            None => return check,
+        };
+        let (start, end) = match self.range(len) {
+            Ok(range) => range,
+            Err(_) => return check,
+        };
+        let position = self.position(len, end);
+        let received = &frame[position..position + width];
+        let payload = [&frame[..position], &frame[position + width..]].concat();
+        let expected = self.encode(self.algorithm.compute(&payload[start..end]));
+
+        check.valid = expected == received;
+        check.expected = hex::encode_upper(&expected);
+        check.received = hex::encode_upper(received);
+        check
+    }
+}
+
+// 按需为待发送的数据追加校验值
+pub fn append_checksum(data: Vec<u8>, params: Option<&ChecksumParams>) -> Result<Vec<u8>, String> {
+    match Checksum::from_params(params)? {
+        Some(checksum) => checksum.append(data),
+        None => Ok(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    fn checksum(algorithm: &str, endianness: Option<&str>, placement: Option<&str>) -> Checksum {
+        let params = ChecksumParams {
+            algorithm: algorithm.to_string(),
+            start: None,
+            end: None,
+            endianness: endianness.map(str::to_string),
+            placement: placement.map(str::to_string),
+        };
+        Checksum::from_params(Some(&params)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn known_answers() {
+        let compute = |name: &str| ChecksumAlgorithm::parse(name).unwrap().compute(CHECK);
+        assert_eq!(compute("crc16_modbus"), 0x4B37);
+        assert_eq!(compute("crc16_ccitt"), 0x29B1);
+        assert_eq!(compute("crc16_xmodem"), 0x31C3);
+        assert_eq!(compute("crc32"), 0xCBF4_3926);
+        assert_eq!(compute("sum8"), 0xDD);
+        assert_eq!(compute("lrc"), 0x23);
+        assert_eq!(compute("xor"), 0x31);
+    }
+
+    #[test]
+    fn appends_in_default_byte_order() {
+        // CRC-16/MODBUS 默认低字节在前，其余默认高字节在前
+        let append = |name: &str| checksum(name, None, None).append(CHECK.to_vec()).unwrap()[CHECK.len()..].to_vec();
+        assert_eq!(append("crc16_modbus"), [0x37, 0x4B]);
+        assert_eq!(append("crc16_ccitt"), [0x29, 0xB1]);
+        assert_eq!(append("crc16_xmodem"), [0x31, 0xC3]);
+        assert_eq!(append("crc32"), [0xCB, 0xF4, 0x39, 0x26]);
+        assert_eq!(append("sum8"), [0xDD]);
+        assert_eq!(append("lrc"), [0x23]);
+        assert_eq!(append("xor"), [0x31]);
+    }
+
+    #[test]
+    fn appends_with_explicit_byte_order_and_placement() {
+        let frame = checksum("crc16_modbus", Some("big"), None).append(CHECK.to_vec()).unwrap();
+        assert_eq!(frame[CHECK.len()..], [0x4B, 0x37]);
+        let frame = checksum("crc32", Some("little"), Some("start")).append(CHECK.to_vec()).unwrap();
+        assert_eq!(frame[..4], [0x26, 0x39, 0xF4, 0xCB]);
+        assert_eq!(&frame[4..], CHECK);
+    }
+
+    #[test]
+    fn range_and_after_range_placement() {
+        let params = ChecksumParams {
+            algorithm: "sum8".to_string(),
+            start: Some(1),
+            end: Some(-1),
+            endianness: None,
+            placement: Some("after_range".to_string()),
+        };
+        let checksum = Checksum::from_params(Some(&params)).unwrap().unwrap();
+        // 计算 0x02、0x03，校验值插在末尾字节之前
+        let frame = checksum.append(vec![0x01, 0x02, 0x03, 0x04]).unwrap();
+        assert_eq!(frame, [0x01, 0x02, 0x03, 0x05, 0x04]);
+        assert!(checksum.verify(&frame).valid);
+        assert!(checksum.append(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn verifies_received_frames() {
+        let checksum = checksum("crc16_modbus", None, None);
+        let frame = checksum.append(CHECK.to_vec()).unwrap();
+        let check = checksum.verify(&frame);
+        assert!(check.valid);
+        assert_eq!(check.expected, "374B");
+        assert_eq!(check.received, "374B");
+
+        let mut corrupted = frame.clone();
+        corrupted[0] ^= 0x01;
+        let check = checksum.verify(&corrupted);
+        assert!(!check.valid);
+        assert_eq!(check.received, "374B");
+
+        // 帧比校验值还短时没有可比较的内容
+        let check = checksum.verify(&[0x01]);
+        assert!(!check.valid);
+        assert!(check.received.is_empty());
+    }
+}
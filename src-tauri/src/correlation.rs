@@ -0,0 +1,414 @@
+use std::collections::VecDeque;
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, State};
+use tokio::sync::{Mutex, Notify};
+use tokio::time::Instant;
+
+use crate::load_test::{LatencyHistogram, LatencySummary};
+use crate::tcp_client::TcpClientManager;
+use crate::udp_client::UdpClientManager;
+use crate::websocket_client::WebSocketClientManager;
+
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+// 请求/响应关联参数
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationParams {
+    pub match_mode: String,       // "any"（下一帧即为响应）、"id"（JSON字段值相同）或 "regex"
+    pub id_field: Option<String>, // id 模式下的JSON字段路径，例如 "id" 或 "header.seq"
+    pub pattern: Option<String>,  // regex 模式下响应需匹配的正则；含捕获组时请求和响应的第一个捕获组需相同
+    pub timeout_ms: Option<u64>,  // 等待响应的超时，默认为 5000
+}
+
+// 设置关联模式的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCorrelationParams {
+    pub connection_type: String, // "tcp_client"、"udp_client" 或 "websocket_client"
+    pub connection_id: String,
+    pub correlation: Option<CorrelationParams>, // 为None则关闭关联，丢弃等待中的请求
+    pub reset_stats: Option<bool>,              // 同时清零计数
+}
+
+// 关联设置和计数（发送给前端）
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationInfo {
+    pub connection_type: String,
+    pub connection_id: String,
+    pub correlation: Option<CorrelationParams>,
+    pub pending: usize,   // 等待响应的请求数
+    pub requests: u64,
+    pub responses: u64,
+    pub timeouts: u64,
+    pub cancelled: u64,   // 连接关闭时仍未收到响应的请求数
+    pub unmatched: u64,   // 没有对应请求的接收帧数
+    pub round_trip: LatencySummary,
+}
+
+// 关联事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationEvent {
+    pub connection_type: String,
+    pub connection_id: String,
+    pub request_id: u64,
+    pub event_type: String,   // "request"、"response"、"timeout" 或 "cancelled"
+    pub key: Option<String>,  // 用于匹配的 id 或捕获组
+    pub round_trip_ms: Option<f64>, // 往返时间，仅 response 事件包含
+    pub message: String,
+    pub timestamp: String,
+}
+
+// 解析以 "." 分隔的JSON字段路径，数组元素用下标表示
+pub fn parse_field_path(path: &str) -> Result<Vec<String>, String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("Field path cannot be empty".to_string());
+    }
+    Ok(path.split('.').map(str::to_string).collect())
+}
+
+// 按字段路径取出JSON值
+pub fn json_field<'a>(value: &'a serde_json::Value, path: &[String]) -> Option<&'a serde_json::Value> {
+    path.iter().try_fold(value, |value, segment| match value {
+        serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => value.get(segment),
+    })
+}
+
+// 请求和响应的匹配规则
+enum Matcher {
+    Any,
+    Id(Vec<String>),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn from_params(params: &CorrelationParams) -> Result<Self, String> {
+        match params.match_mode.as_str() {
+            "any" => Ok(Matcher::Any),
+            "id" => {
+                let field = params.id_field.as_deref().ok_or("Id field is required for id matching")?;
+                Ok(Matcher::Id(parse_field_path(field)?))
+            }
+            "regex" => {
+                let pattern = params.pattern.as_deref().ok_or("Pattern is required for regex matching")?;
+                Regex::new(pattern)
+                    .map(Matcher::Regex)
+                    .map_err(|e| format!("Invalid pattern: {}", e))
+            }
+            other => Err(format!("Unsupported match mode: {}", other)),
+        }
+    }
+
+    fn id(field: &[String], text: &str) -> Option<String> {
+        let root: serde_json::Value = serde_json::from_str(text).ok()?;
+        match json_field(&root, field)? {
+            serde_json::Value::String(id) => Some(id.clone()),
+            serde_json::Value::Null | serde_json::Value::Array(_) | serde_json::Value::Object(_) => None,
+            id => Some(id.to_string()),
+        }
+    }
+
+    // 请求的匹配键；需要键但请求中没有时报错
+    fn request_key(&self, text: &str) -> Result<Option<String>, String> {
+        match self {
+            Matcher::Any => Ok(None),
+            Matcher::Id(field) => Self::id(field, text)
+                .map(Some)
+                .ok_or_else(|| format!("Message has no id field {}", field.join("."))),
+            Matcher::Regex(regex) if regex.captures_len() > 1 => regex
+                .captures(text)
+                .and_then(|captures| captures.get(1))
+                .map(|capture| Some(capture.as_str().to_string()))
+                .ok_or_else(|| "Message does not match the correlation pattern".to_string()),
+            Matcher::Regex(_) => Ok(None),
+        }
+    }
+
+    // 响应的匹配键；外层为None表示该帧不是响应
+    fn response_key(&self, text: &str) -> Option<Option<String>> {
+        match self {
+            Matcher::Any => Some(None),
+            Matcher::Id(field) => Self::id(field, text).map(Some),
+            Matcher::Regex(regex) if regex.captures_len() > 1 => {
+                regex.captures(text)?.get(1).map(|capture| Some(capture.as_str().to_string()))
+            }
+            Matcher::Regex(regex) => regex.is_match(text).then_some(None),
+        }
+    }
+}
+
+// 等待响应的请求
+struct PendingRequest {
+    request_id: u64,
+    key: Option<String>,
+    queued_at: Instant,          // 登记时间，超时从此时计算
+    sent_at: Option<Instant>,    // 发送任务写出数据的时间，往返时间从此时计算
+}
+
+#[derive(Default)]
+struct CorrelationState {
+    params: Option<CorrelationParams>,
+    matcher: Option<Matcher>,
+    timeout: Duration,
+    pending: VecDeque<PendingRequest>,
+    next_request_id: u64,
+    requests: u64,
+    responses: u64,
+    timeouts: u64,
+    cancelled: u64,
+    unmatched: u64,
+    round_trip: LatencyHistogram,
+}
+
+// 连接的请求/响应关联状态，可在运行时修改
+pub struct Correlation {
+    connection_type: String,
+    connection_id: String,
+    state: StdMutex<CorrelationState>,
+    changed: Notify, // 新请求或设置变化时唤醒超时等待
+}
+
+impl Correlation {
+    pub fn new(connection_type: &str, connection_id: &str) -> Self {
+        Correlation {
+            connection_type: connection_type.to_string(),
+            connection_id: connection_id.to_string(),
+            state: StdMutex::new(CorrelationState::default()),
+            changed: Notify::new(),
+        }
+    }
+
+    // 修改设置，等待中的请求被丢弃
+    pub fn configure(&self, params: Option<&CorrelationParams>) -> Result<(), String> {
+        let matcher = params.map(Matcher::from_params).transpose()?;
+        let timeout_ms = params.and_then(|params| params.timeout_ms).unwrap_or(DEFAULT_TIMEOUT_MS);
+        if timeout_ms == 0 {
+            return Err("Timeout must be greater than 0".to_string());
+        }
+        let mut state = self.state.lock().unwrap();
+        state.params = params.cloned();
+        state.matcher = matcher;
+        state.timeout = Duration::from_millis(timeout_ms);
+        state.pending.clear();
+        drop(state);
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    pub fn reset_stats(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.requests = 0;
+        state.responses = 0;
+        state.timeouts = 0;
+        state.cancelled = 0;
+        state.unmatched = 0;
+        state.round_trip = LatencyHistogram::default();
+    }
+
+    pub fn info(&self) -> CorrelationInfo {
+        let state = self.state.lock().unwrap();
+        CorrelationInfo {
+            connection_type: self.connection_type.clone(),
+            connection_id: self.connection_id.clone(),
+            correlation: state.params.clone(),
+            pending: state.pending.len(),
+            requests: state.requests,
+            responses: state.responses,
+            timeouts: state.timeouts,
+            cancelled: state.cancelled,
+            unmatched: state.unmatched,
+            round_trip: state.round_trip.summary(),
+        }
+    }
+
+    fn emit(&self, app_handle: &Option<tauri::AppHandle>, request_id: u64, event_type: &str, key: Option<String>, round_trip: Option<Duration>, message: String) {
+        if let Some(app_handle) = app_handle {
+            let event = CorrelationEvent {
+                connection_type: self.connection_type.clone(),
+                connection_id: self.connection_id.clone(),
+                request_id,
+                event_type: event_type.to_string(),
+                key,
+                round_trip_ms: round_trip.map(|round_trip| round_trip.as_secs_f64() * 1000.0),
+                message,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            let _ = app_handle.emit("correlation-event", &event);
+        }
+    }
+
+    // 发送前登记请求，未启用关联时返回None；写出后应调用 sent，发送失败时应调用 cancel
+    pub fn open(&self, app_handle: &Option<tauri::AppHandle>, data: &[u8]) -> Result<Option<u64>, String> {
+        let text = String::from_utf8_lossy(data);
+        let mut state = self.state.lock().unwrap();
+        let key = match &state.matcher {
+            Some(matcher) => matcher.request_key(&text)?,
+            None => return Ok(None),
+        };
+        state.next_request_id += 1;
+        state.requests += 1;
+        let request_id = state.next_request_id;
+        state.pending.push_back(PendingRequest {
+            request_id,
+            key: key.clone(),
+            queued_at: Instant::now(),
+            sent_at: None,
+        });
+        drop(state);
+        self.changed.notify_one();
+        self.emit(app_handle, request_id, "request", key, None, text.to_string());
+        Ok(Some(request_id))
+    }
+
+    // 由发送任务在数据写出后调用，排队等待写出的时间不计入往返时间
+    pub fn sent(&self, request_id: Option<u64>) {
+        let Some(request_id) = request_id else {
+            return;
+        };
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if let Some(request) = state.pending.iter_mut().find(|request| request.request_id == request_id) {
+            request.sent_at.get_or_insert(now);
+        }
+    }
+
+    pub fn cancel(&self, request_id: Option<u64>) {
+        let mut state = self.state.lock().unwrap();
+        state.pending.retain(|request| Some(request.request_id) != request_id);
+    }
+
+    // 处理收到的一帧：匹配到等待中的请求时记录往返时间并发送响应事件
+    pub fn received(&self, app_handle: &Option<tauri::AppHandle>, data: &[u8]) {
+        let received_at = Instant::now();
+        let text = String::from_utf8_lossy(data);
+        let mut state = self.state.lock().unwrap();
+        let Some(key) = state.matcher.as_ref().and_then(|matcher| matcher.response_key(&text)) else {
+            return;
+        };
+        let position = match &key {
+            Some(key) => state.pending.iter().position(|request| request.key.as_ref() == Some(key)),
+            None if state.pending.is_empty() => None,
+            None => Some(0),
+        };
+        let Some(request) = position.and_then(|position| state.pending.remove(position)) else {
+            state.unmatched += 1;
+            return;
+        };
+        // 响应可能在发送任务调用 sent 之前就被处理，此时退回到登记时间
+        let round_trip = received_at - request.sent_at.unwrap_or(request.queued_at);
+        state.responses += 1;
+        state.round_trip.record(round_trip);
+        drop(state);
+        self.emit(app_handle, request.request_id, "response", request.key, Some(round_trip), text.to_string());
+    }
+
+    // 等到最早的请求超时，移除所有已超时的请求并发送超时事件
+    // 可以安全地在 select! 中取消
+    pub async fn expire(&self, app_handle: &Option<tauri::AppHandle>) {
+        loop {
+            let deadline = {
+                let state = self.state.lock().unwrap();
+                state.pending.front().map(|request| request.queued_at + state.timeout)
+            };
+            match deadline {
+                Some(deadline) if deadline <= Instant::now() => break,
+                Some(deadline) => tokio::select! {
+                    _ = tokio::time::sleep_until(deadline) => {}
+                    _ = self.changed.notified() => {}
+                },
+                None => self.changed.notified().await,
+            }
+        }
+
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let timeout = state.timeout;
+        let mut expired = Vec::new();
+        while state.pending.front().is_some_and(|request| request.queued_at + timeout <= now) {
+            expired.extend(state.pending.pop_front());
+        }
+        state.timeouts += expired.len() as u64;
+        drop(state);
+        for request in expired {
+            let message = format!("No response within {} ms", timeout.as_millis());
+            self.emit(app_handle, request.request_id, "timeout", request.key, None, message);
+        }
+    }
+
+    // 连接关闭时放弃所有等待中的请求
+    pub fn close(&self, app_handle: &Option<tauri::AppHandle>) {
+        let mut state = self.state.lock().unwrap();
+        let pending: Vec<PendingRequest> = state.pending.drain(..).collect();
+        state.cancelled += pending.len() as u64;
+        drop(state);
+        for request in pending {
+            self.emit(app_handle, request.request_id, "cancelled", request.key, None, "Connection closed before response".to_string());
+        }
+    }
+}
+
+// Tauri命令：设置TCP、UDP或WebSocket客户端的请求/响应关联
+#[tauri::command]
+pub async fn set_correlation(
+    correlation_params: SetCorrelationParams,
+    tcp_clients: State<'_, Mutex<TcpClientManager>>,
+    udp_clients: State<'_, Mutex<UdpClientManager>>,
+    websocket_clients: State<'_, Mutex<WebSocketClientManager>>,
+) -> Result<CorrelationInfo, String> {
+    let apply = |correlation: &Correlation| {
+        correlation.configure(correlation_params.correlation.as_ref())?;
+        if correlation_params.reset_stats.unwrap_or(false) {
+            correlation.reset_stats();
+        }
+        Ok(correlation.info())
+    };
+    let connection_id = &correlation_params.connection_id;
+    match correlation_params.connection_type.as_str() {
+        "tcp_client" => match tcp_clients.lock().await.clients.get(connection_id) {
+            Some(client) => apply(&client.correlation),
+            None => Err(format!("TCP client {} not found", connection_id)),
+        },
+        "udp_client" => match udp_clients.lock().await.clients.get(connection_id) {
+            Some(client) => apply(&client.correlation),
+            None => Err(format!("UDP client {} not found", connection_id)),
+        },
+        "websocket_client" => match websocket_clients.lock().await.clients.get(connection_id) {
+            Some(client) => apply(&client.correlation),
+            None => Err(format!("WebSocket client {} not found", connection_id)),
+        },
+        other => Err(format!("Unsupported connection type: {}", other)),
+    }
+}
+
+// Tauri命令：获取客户端的关联设置和往返时间统计
+#[tauri::command]
+pub async fn get_correlation(
+    connection_type: String,
+    connection_id: String,
+    tcp_clients: State<'_, Mutex<TcpClientManager>>,
+    udp_clients: State<'_, Mutex<UdpClientManager>>,
+    websocket_clients: State<'_, Mutex<WebSocketClientManager>>,
+) -> Result<CorrelationInfo, String> {
+    match connection_type.as_str() {
+        "tcp_client" => match tcp_clients.lock().await.clients.get(&connection_id) {
+            Some(client) => Ok(client.correlation.info()),
+            None => Err(format!("TCP client {} not found", connection_id)),
+        },
+        "udp_client" => match udp_clients.lock().await.clients.get(&connection_id) {
+            Some(client) => Ok(client.correlation.info()),
+            None => Err(format!("UDP client {} not found", connection_id)),
+        },
+        "websocket_client" => match websocket_clients.lock().await.clients.get(&connection_id) {
+            Some(client) => Ok(client.correlation.info()),
+            None => Err(format!("WebSocket client {} not found", connection_id)),
+        },
+        other => Err(format!("Unsupported connection type: {}", other)),
+    }
+}
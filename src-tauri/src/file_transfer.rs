@@ -0,0 +1,537 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::{Data, OpCode};
+use tokio_tungstenite::tungstenite::protocol::frame::Frame;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use crate::net_address;
+use crate::tcp_client::TcpClientManager;
+use crate::tcp_server::TcpServerManager;
+use crate::udp_client::UdpClientManager;
+use crate::websocket_client::WebSocketClientManager;
+use crate::websocket_queue;
+use crate::websocket_server::WebSocketServerManager;
+use crate::write_ack::{self, WriteAckReceiver};
+
+const DEFAULT_CHUNK_SIZE: usize = 1024;
+// UDP单个数据报的最大载荷
+const MAX_DATAGRAM_SIZE: usize = 65507;
+// 整段作为一条WebSocket消息时的大小上限，与tungstenite默认的最大帧长度一致
+const MAX_SINGLE_MESSAGE_SIZE: u64 = 16 << 20;
+// 进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+// 发送文件的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendFileParams {
+    pub transfer_id: Option<String>,
+    pub connection_type: String, // "tcp_client"、"tcp_server"、"udp_client"、"websocket_server" 或 "websocket_client"
+    pub connection_id: String,   // 客户端或服务器ID
+    pub target_client_id: Option<String>, // 服务器模式下的目标客户端，为None则广播给所有客户端
+    pub target_host: Option<String>,      // UDP目标主机
+    pub target_port: Option<u16>,         // UDP目标端口
+    pub path: String,
+    pub offset: Option<u64>,      // 起始偏移，默认为 0
+    pub length: Option<u64>,      // 发送的字节数，默认到文件末尾
+    pub chunk_size: Option<usize>, // 每块字节数，默认为 1024；UDP每块为一个数据报
+    pub delay_ms: Option<u64>,     // 块间延迟
+    pub rate_limit: Option<u64>,   // 速率上限（字节/秒）
+    pub websocket_mode: Option<String>, // "message"（默认，整段作为一条二进制消息）或 "fragments"（每块一个分片帧）
+}
+
+// 传输状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTransferInfo {
+    pub transfer_id: String,
+    pub path: String,
+    pub connection_type: String,
+    pub connection_id: String,
+    pub bytes_sent: u64,
+    pub total_bytes: u64,
+}
+
+// 文件传输事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTransferEvent {
+    pub transfer_id: String,
+    pub event_type: String, // "started"、"progress"、"completed"、"cancelled" 或 "error"
+    pub message: String,
+    pub bytes_sent: u64,
+    pub total_bytes: u64,
+    pub timestamp: String,
+}
+
+// 进行中的文件传输
+pub struct FileTransfer {
+    pub transfer_id: String,
+    pub path: String,
+    pub connection_type: String,
+    pub connection_id: String,
+    pub total_bytes: u64,
+    pub bytes_sent: Arc<AtomicU64>,
+    pub cancel_sender: broadcast::Sender<()>,
+    pub handle: JoinHandle<()>,
+}
+
+// 文件传输管理器
+pub struct FileTransferManager {
+    pub transfers: HashMap<String, FileTransfer>,
+}
+
+impl FileTransferManager {
+    pub fn new() -> Self {
+        FileTransferManager {
+            transfers: HashMap::new(),
+        }
+    }
+
+    // 清理已结束的传输
+    fn prune(&mut self) {
+        self.transfers.retain(|_, transfer| !transfer.handle.is_finished());
+    }
+}
+
+impl Default for FileTransferManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 文件数据的发送目标
+enum FileTarget {
+    TcpClient(String),
+    TcpServer { server_id: String, client_id: Option<String> },
+    UdpClient { client_id: String, target: SocketAddr },
+    // 接收者在传输开始时确定，之后连接的客户端不会收到缺少开头的分片；stream 为分片消息标识，None 表示整段一条消息
+    WebSocketServer { server_id: String, client_ids: Vec<String>, stream: Option<u64> },
+    WebSocketClient { client_id: String, stream: Option<u64> },
+}
+
+impl FileTarget {
+    async fn resolve(app_handle: &tauri::AppHandle, params: &SendFileParams) -> Result<Self, String> {
+        let id = params.connection_id.clone();
+        let stream = match params.websocket_mode.as_deref() {
+            None | Some("") | Some("message") => None,
+            Some("fragments") => Some(websocket_queue::next_fragment_stream()),
+            Some(other) => return Err(format!("Unsupported WebSocket mode: {}", other)),
+        };
+
+        let target = match params.connection_type.as_str() {
+            "tcp_client" => FileTarget::TcpClient(id),
+            "tcp_server" => FileTarget::TcpServer { server_id: id, client_id: params.target_client_id.clone() },
+            "udp_client" => {
+                let host = params.target_host.as_deref().ok_or("Target host is required for UDP")?;
+                let port = params.target_port.ok_or("Target port is required for UDP")?;
+                let local_addr = {
+                    let manager = app_handle.state::<Mutex<UdpClientManager>>();
+                    let manager = manager.lock().await;
+                    match manager.clients.get(&id) {
+                        Some(client) => client.local_addr,
+                        None => return Err(format!("UDP client {} not found", id)),
+                    }
+                };
+                let mut target = net_address::resolve_target(host, port, local_addr).await?;
+                if let Some(local_addr) = local_addr {
+                    target = net_address::map_to_local_family(target, local_addr);
+                }
+                FileTarget::UdpClient { client_id: id, target }
+            }
+            "websocket_server" => {
+                let manager = app_handle.state::<Mutex<WebSocketServerManager>>();
+                let manager = manager.lock().await;
+                let server = manager
+                    .servers
+                    .get(&id)
+                    .ok_or_else(|| format!("Server with ID {} not found", id))?;
+                if server.config.socketio {
+                    return Err("File transfer is not supported in Socket.IO mode".to_string());
+                }
+                let client_ids = match &params.target_client_id {
+                    Some(client_id) => vec![client_id.clone()],
+                    None => server.client_ids().await,
+                };
+                if client_ids.is_empty() {
+                    return Err("No clients connected".to_string());
+                }
+                FileTarget::WebSocketServer { server_id: id, client_ids, stream }
+            }
+            "websocket_client" => FileTarget::WebSocketClient { client_id: id, stream },
+            other => return Err(format!("Unsupported connection type: {}", other)),
+        };
+
+        // 启动前确认连接存在，发送时会再次查找以发现中途断开的连接
+        target.check(app_handle).await?;
+        Ok(target)
+    }
+
+    async fn check(&self, app_handle: &tauri::AppHandle) -> Result<(), String> {
+        let found = match self {
+            FileTarget::TcpClient(client_id) => {
+                app_handle.state::<Mutex<TcpClientManager>>().lock().await.clients.contains_key(client_id)
+            }
+            FileTarget::TcpServer { server_id, .. } => {
+                app_handle.state::<Mutex<TcpServerManager>>().lock().await.servers.contains_key(server_id)
+            }
+            FileTarget::UdpClient { .. } => true,
+            FileTarget::WebSocketServer { server_id, .. } => {
+                app_handle.state::<Mutex<WebSocketServerManager>>().lock().await.servers.contains_key(server_id)
+            }
+            FileTarget::WebSocketClient { client_id, .. } => {
+                app_handle.state::<Mutex<WebSocketClientManager>>().lock().await.clients.contains_key(client_id)
+            }
+        };
+        if found {
+            Ok(())
+        } else {
+            Err("Connection not found".to_string())
+        }
+    }
+
+    // 整段作为一条WebSocket消息发送时不分块
+    fn single_message(&self) -> bool {
+        matches!(
+            self,
+            FileTarget::WebSocketServer { stream: None, .. } | FileTarget::WebSocketClient { stream: None, .. }
+        )
+    }
+
+    fn is_udp(&self) -> bool {
+        matches!(self, FileTarget::UdpClient { .. })
+    }
+
+    // 构造WebSocket帧：分片模式下第一块为二进制帧，其余为延续帧，最后一块置FIN
+    fn websocket_message(stream: Option<u64>, chunk: Vec<u8>, first: bool, last: bool) -> Message {
+        if stream.is_none() {
+            return Message::Binary(chunk);
+        }
+        let opcode = if first { OpCode::Data(Data::Binary) } else { OpCode::Data(Data::Continue) };
+        Message::Frame(Frame::message(chunk, opcode, last))
+    }
+
+    // 把一块数据交给连接的发送队列，返回写出后完成的通知
+    async fn send(&self, app_handle: &tauri::AppHandle, chunk: Vec<u8>, first: bool, last: bool) -> Result<Vec<WriteAckReceiver>, String> {
+        match self {
+            FileTarget::TcpClient(client_id) => {
+                let manager = app_handle.state::<Mutex<TcpClientManager>>();
+                let manager = manager.lock().await;
+                match manager.clients.get(client_id) {
+                    Some(client) => client.send_message_with_ack(chunk).map(|ack| vec![ack]),
+                    None => Err(format!("TCP client {} not found", client_id)),
+                }
+            }
+            FileTarget::TcpServer { server_id, client_id } => {
+                let manager = app_handle.state::<Mutex<TcpServerManager>>();
+                let manager = manager.lock().await;
+                let server = manager
+                    .servers
+                    .get(server_id)
+                    .ok_or_else(|| format!("TCP Server with ID {} not found", server_id))?;
+                let acks = server.send_message_with_ack(client_id.as_deref(), chunk).await?;
+                if acks.is_empty() {
+                    return Err("No clients connected".to_string());
+                }
+                Ok(acks)
+            }
+            FileTarget::UdpClient { client_id, target } => {
+                let manager = app_handle.state::<Mutex<UdpClientManager>>();
+                let manager = manager.lock().await;
+                match manager.clients.get(client_id) {
+                    Some(client) => client.send_message_with_ack(chunk, *target).map(|ack| vec![ack]),
+                    None => Err(format!("UDP client {} not found", client_id)),
+                }
+            }
+            FileTarget::WebSocketServer { server_id, client_ids, stream } => {
+                let message = Self::websocket_message(*stream, chunk, first, last);
+                let manager = app_handle.state::<Mutex<WebSocketServerManager>>();
+                let manager = manager.lock().await;
+                let server = manager
+                    .servers
+                    .get(server_id)
+                    .ok_or_else(|| format!("Server with ID {} not found", server_id))?;
+                let acks = server.send_to_clients_with_ack(client_ids, message, *stream).await;
+                if acks.is_empty() {
+                    return Err("No clients connected".to_string());
+                }
+                Ok(acks)
+            }
+            FileTarget::WebSocketClient { client_id, stream } => {
+                let message = Self::websocket_message(*stream, chunk, first, last);
+                let manager = app_handle.state::<Mutex<WebSocketClientManager>>();
+                let manager = manager.lock().await;
+                match manager.clients.get(client_id) {
+                    Some(client) => client.send_message_with_ack(message, *stream).map(|ack| vec![ack]),
+                    None => Err(format!("WebSocket client {} not found", client_id)),
+                }
+            }
+        }
+    }
+
+    // 取消或出错时用空的结束帧结束未完成的分片消息，连接上暂存的其他消息随后才能发出
+    async fn finish_fragments(&self, app_handle: &tauri::AppHandle) {
+        if matches!(self, FileTarget::WebSocketServer { stream: Some(_), .. } | FileTarget::WebSocketClient { stream: Some(_), .. }) {
+            let _ = self.send(app_handle, Vec::new(), false, true).await;
+        }
+    }
+}
+
+// 传输计划：已定位到起始偏移的文件和节奏设置
+struct TransferPlan {
+    file: File,
+    total_bytes: u64,
+    chunk_size: usize,
+    delay: Option<Duration>,
+    rate_limit: Option<u64>,
+}
+
+fn emit_transfer_event(app_handle: &tauri::AppHandle, transfer_id: &str, event_type: &str, message: String, bytes_sent: u64, total_bytes: u64) {
+    let event = FileTransferEvent {
+        transfer_id: transfer_id.to_string(),
+        event_type: event_type.to_string(),
+        message,
+        bytes_sent,
+        total_bytes,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    let _ = app_handle.emit("file-transfer-event", &event);
+}
+
+// 传输任务：按块读取文件并交给连接的发送队列，等每块写出后再计算进度和速率
+async fn run_transfer(
+    app_handle: tauri::AppHandle,
+    transfer_id: String,
+    target: FileTarget,
+    mut plan: TransferPlan,
+    bytes_sent: Arc<AtomicU64>,
+    mut cancel_rx: broadcast::Receiver<()>,
+) {
+    let total = plan.total_bytes;
+    let started = Instant::now();
+    let mut last_progress = started;
+    let mut sent: u64 = 0;
+    // 已入队首块但还未入队末块，分片消息处于未结束状态
+    let mut message_open = false;
+    let mut buffer = vec![0u8; plan.chunk_size];
+
+    let result: Result<bool, String> = async {
+        loop {
+            let want = (total - sent).min(plan.chunk_size as u64) as usize;
+            plan.file
+                .read_exact(&mut buffer[..want])
+                .await
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+
+            let end = sent + want as u64;
+            let acks = target.send(&app_handle, buffer[..want].to_vec(), sent == 0, end == total).await?;
+            message_open = end != total;
+            // 等待数据写出，连接写不动时在此处形成背压，等待期间可取消
+            for ack in acks {
+                tokio::select! {
+                    _ = cancel_rx.recv() => return Ok(false),
+                    result = write_ack::written(ack) => result?,
+                }
+            }
+            sent = end;
+            bytes_sent.store(sent, Ordering::Relaxed);
+
+            if sent == total {
+                return Ok(true);
+            }
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                emit_transfer_event(&app_handle, &transfer_id, "progress", format!("Sent {} of {} bytes", sent, total), sent, total);
+            }
+
+            // 块间延迟和速率限制，等待期间可取消
+            let mut wake = Instant::now() + plan.delay.unwrap_or_default();
+            if let Some(rate) = plan.rate_limit {
+                wake = wake.max(started + Duration::from_secs_f64(sent as f64 / rate as f64));
+            }
+            tokio::select! {
+                _ = cancel_rx.recv() => return Ok(false),
+                _ = tokio::time::sleep_until(wake) => {}
+            }
+            if cancel_rx.try_recv().is_ok() {
+                return Ok(false);
+            }
+        }
+    }
+    .await;
+
+    if message_open {
+        target.finish_fragments(&app_handle).await;
+    }
+
+    let elapsed = started.elapsed().as_secs_f64();
+    match result {
+        Ok(true) => {
+            let message = format!("Sent {} bytes in {:.3}s", sent, elapsed);
+            emit_transfer_event(&app_handle, &transfer_id, "completed", message, sent, total);
+        }
+        Ok(false) => {
+            let message = format!("Cancelled after {} of {} bytes", sent, total);
+            emit_transfer_event(&app_handle, &transfer_id, "cancelled", message, sent, total);
+        }
+        Err(e) => emit_transfer_event(&app_handle, &transfer_id, "error", e, sent, total),
+    }
+}
+
+// Tauri命令：通过已有连接发送文件内容
+#[tauri::command]
+pub async fn send_file(
+    transfer_params: SendFileParams,
+    transfers: State<'_, Mutex<FileTransferManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let transfer_id = transfer_params.transfer_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    {
+        let mut transfers = transfers.lock().await;
+        transfers.prune();
+        if transfers.transfers.contains_key(&transfer_id) {
+            return Err(format!("File transfer {} already exists", transfer_id));
+        }
+    }
+
+    let target = FileTarget::resolve(&app_handle, &transfer_params).await?;
+
+    let mut file = File::open(&transfer_params.path)
+        .await
+        .map_err(|e| format!("Failed to open file {}: {}", transfer_params.path, e))?;
+    let file_len = file
+        .metadata()
+        .await
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?
+        .len();
+    let offset = transfer_params.offset.unwrap_or(0);
+    if offset > file_len {
+        return Err(format!("Offset {} is beyond the end of the {}-byte file", offset, file_len));
+    }
+    let total_bytes = match transfer_params.length {
+        Some(length) if offset.checked_add(length).is_none_or(|end| end > file_len) => {
+            return Err(format!("Range {}+{} is beyond the end of the {}-byte file", offset, length, file_len));
+        }
+        Some(length) => length,
+        None => file_len - offset,
+    };
+    file.seek(SeekFrom::Start(offset))
+        .await
+        .map_err(|e| format!("Failed to seek file: {}", e))?;
+
+    let chunk_size = if target.single_message() {
+        if total_bytes > MAX_SINGLE_MESSAGE_SIZE {
+            return Err(format!(
+                "{} bytes exceed the {}-byte limit for a single WebSocket message; use fragments mode",
+                total_bytes, MAX_SINGLE_MESSAGE_SIZE
+            ));
+        }
+        total_bytes as usize
+    } else {
+        let chunk_size = transfer_params.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+        if chunk_size == 0 {
+            return Err("Chunk size must be greater than 0".to_string());
+        }
+        if target.is_udp() && chunk_size > MAX_DATAGRAM_SIZE {
+            return Err(format!("Chunk size {} exceeds the UDP datagram limit of {}", chunk_size, MAX_DATAGRAM_SIZE));
+        }
+        chunk_size
+    };
+    if transfer_params.rate_limit == Some(0) {
+        return Err("Rate limit must be greater than 0".to_string());
+    }
+
+    let plan = TransferPlan {
+        file,
+        total_bytes,
+        chunk_size,
+        delay: transfer_params.delay_ms.filter(|ms| *ms > 0).map(Duration::from_millis),
+        rate_limit: transfer_params.rate_limit,
+    };
+
+    emit_transfer_event(
+        &app_handle,
+        &transfer_id,
+        "started",
+        format!("Sending {} bytes from {}", total_bytes, transfer_params.path),
+        0,
+        total_bytes,
+    );
+
+    let bytes_sent = Arc::new(AtomicU64::new(0));
+    let (cancel_tx, cancel_rx) = broadcast::channel(1);
+    let handle = tokio::spawn(run_transfer(
+        app_handle,
+        transfer_id.clone(),
+        target,
+        plan,
+        Arc::clone(&bytes_sent),
+        cancel_rx,
+    ));
+
+    transfers.lock().await.transfers.insert(
+        transfer_id.clone(),
+        FileTransfer {
+            transfer_id: transfer_id.clone(),
+            path: transfer_params.path,
+            connection_type: transfer_params.connection_type,
+            connection_id: transfer_params.connection_id,
+            total_bytes,
+            bytes_sent,
+            cancel_sender: cancel_tx,
+            handle,
+        },
+    );
+
+    Ok(transfer_id)
+}
+
+// Tauri命令：取消文件传输
+#[tauri::command]
+pub async fn cancel_file_transfer(
+    transfer_id: String,
+    transfers: State<'_, Mutex<FileTransferManager>>,
+) -> Result<(), String> {
+    let mut transfers = transfers.lock().await;
+    transfers.prune();
+    match transfers.transfers.remove(&transfer_id) {
+        Some(transfer) => {
+            let _ = transfer.cancel_sender.send(());
+            let _ = transfer.handle.await;
+            Ok(())
+        }
+        None => Err(format!("File transfer {} not found", transfer_id)),
+    }
+}
+
+// Tauri命令：获取进行中的文件传输
+#[tauri::command]
+pub async fn get_file_transfers(
+    transfers: State<'_, Mutex<FileTransferManager>>,
+) -> Result<Vec<FileTransferInfo>, String> {
+    let mut transfers = transfers.lock().await;
+    transfers.prune();
+    Ok(transfers
+        .transfers
+        .values()
+        .map(|transfer| FileTransferInfo {
+            transfer_id: transfer.transfer_id.clone(),
+            path: transfer.path.clone(),
+            connection_type: transfer.connection_type.clone(),
+            connection_id: transfer.connection_id.clone(),
+            bytes_sent: transfer.bytes_sent.load(Ordering::Relaxed),
+            total_bytes: transfer.total_bytes,
+        })
+        .collect())
+}
@@ -0,0 +1,1337 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path};
+use std::time::Duration;
+use base64::Engine;
+use prost::Message as _;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor, SerializeOptions};
+use prost_types::FileDescriptorProto;
+use protox::file::{ChainFileResolver, File, FileResolver, GoogleFileResolver};
+use protox::Compiler;
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder, Streaming};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{AsciiMetadataKey, BinaryMetadataKey, BinaryMetadataValue, MetadataMap, MetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::{Code, Status};
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::{ServerReflectionRequest, ServerReflectionResponse};
+use uuid::Uuid;
+
+const REFLECTION_V1_PATH: &str = "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo";
+const REFLECTION_V1ALPHA_PATH: &str = "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo";
+
+// 一次进行中的gRPC调用
+struct GrpcCall {
+    request_descriptor: MessageDescriptor,
+    request_sender: Option<mpsc::UnboundedSender<DynamicMessage>>,
+    call_handle: JoinHandle<()>,
+}
+
+// gRPC客户端
+pub struct GrpcClient {
+    pub client_id: String,
+    pub address: String,
+    pub channel: Channel,
+    pub pool: DescriptorPool,
+    pub reflection: bool,
+    calls: HashMap<String, GrpcCall>,
+    events: GrpcEventSink,
+}
+
+// gRPC客户端管理器
+pub struct GrpcClientManager {
+    pub clients: HashMap<String, GrpcClient>,
+}
+
+impl GrpcClientManager {
+    pub fn new() -> Self {
+        GrpcClientManager {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl Default for GrpcClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 上传的 .proto 文件
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtoFile {
+    pub name: String, // 相对路径，import语句按此路径查找，例如 "foo/bar.proto"
+    pub content: String,
+}
+
+// gRPC元数据（请求头/响应头/尾部）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcMetadata {
+    pub name: String,
+    pub value: String, // 以 "-bin" 结尾的键使用base64编码
+}
+
+// 连接gRPC服务的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectGrpcClientParams {
+    pub client_id: Option<String>,
+    pub address: String,                    // "http://host:port"、"https://host:port" 或 "host:port"
+    pub use_tls: Option<bool>,              // 地址未带scheme时是否使用TLS，默认为 false
+    pub server_name: Option<String>,        // TLS的SNI名称
+    pub use_reflection: Option<bool>,       // 是否通过服务反射获取服务定义，默认为 true
+    pub proto_files: Option<Vec<ProtoFile>>,
+    pub timeout_ms: Option<u64>,            // 连接超时，默认为 10000
+}
+
+// 加载 .proto 文件的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadGrpcProtoFilesParams {
+    pub client_id: String,
+    pub files: Vec<ProtoFile>,
+}
+
+// 发起gRPC调用的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartGrpcCallParams {
+    pub client_id: String,
+    pub method: String,                   // "package.Service/Method"
+    pub messages: Vec<String>,            // JSON格式的请求消息
+    pub metadata: Option<Vec<GrpcMetadata>>,
+    pub timeout_ms: Option<u64>,
+    pub close_send: Option<bool>,         // 发送完 messages 后是否结束请求流，默认为 true
+}
+
+// 向进行中的调用发送消息的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendGrpcCallMessageParams {
+    pub client_id: String,
+    pub call_id: String,
+    pub message: String,
+}
+
+// 方法信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcMethodInfo {
+    pub name: String,
+    pub path: String,
+    pub input_type: String,
+    pub output_type: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+    pub request_template: String,
+}
+
+// 服务信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcServiceInfo {
+    pub name: String,
+    pub methods: Vec<GrpcMethodInfo>,
+}
+
+// 客户端状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcClientInfo {
+    pub client_id: String,
+    pub address: String,
+    pub reflection: bool,
+    pub service_count: usize,
+    pub active_calls: usize,
+}
+
+// gRPC客户端事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcClientEvent {
+    pub client_id: String,
+    pub call_id: Option<String>,
+    pub event_type: String,
+    pub message: String,
+    pub metadata: Option<Vec<GrpcMetadata>>,
+    pub status_code: Option<i32>,
+    pub timestamp: String,
+}
+
+// gRPC事件的去向：前端或测试中的通道
+#[derive(Clone, Default)]
+enum GrpcEventSink {
+    #[default]
+    None,
+    App(tauri::AppHandle),
+    #[cfg(test)]
+    Channel(mpsc::UnboundedSender<GrpcClientEvent>),
+}
+
+impl GrpcEventSink {
+    fn emit(&self, event: GrpcClientEvent) {
+        match self {
+            GrpcEventSink::None => {}
+            GrpcEventSink::App(app_handle) => {
+                let _ = app_handle.emit("grpc-client-event", &event);
+            }
+            #[cfg(test)]
+            GrpcEventSink::Channel(sender) => {
+                let _ = sender.send(event);
+            }
+        }
+    }
+}
+
+// 调用任务发送事件时使用的上下文
+#[derive(Clone)]
+struct CallContext {
+    client_id: String,
+    call_id: String,
+    events: GrpcEventSink,
+}
+
+impl CallContext {
+    fn emit(&self, event_type: &str, message: String, metadata: Option<Vec<GrpcMetadata>>, status_code: Option<i32>) {
+        self.events.emit(GrpcClientEvent {
+            client_id: self.client_id.clone(),
+            call_id: Some(self.call_id.clone()),
+            event_type: event_type.to_string(),
+            message,
+            metadata,
+            status_code,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    fn emit_status(&self, status: &Status) {
+        self.emit(
+            "call_failed",
+            format!("Status {:?} ({}): {}", status.code(), status.code() as i32, status.message()),
+            Some(metadata_to_list(status.metadata())),
+            Some(status.code() as i32),
+        );
+    }
+}
+
+// 使用运行时描述符编解码的动态消息编解码器
+struct DynamicCodec {
+    response_descriptor: MessageDescriptor,
+}
+
+struct DynamicEncoder;
+
+struct DynamicDecoder {
+    response_descriptor: MessageDescriptor,
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder {
+            response_descriptor: self.response_descriptor.clone(),
+        }
+    }
+}
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        item.encode(dst)
+            .map_err(|e| Status::internal(format!("Failed to encode request: {}", e)))
+    }
+}
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.response_descriptor.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(format!("Failed to decode response: {}", e)))
+    }
+}
+
+// 拼接错误及其来源，tonic的传输错误本身只有 "transport error"
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn metadata_to_list(metadata: &MetadataMap) -> Vec<GrpcMetadata> {
+    metadata
+        .clone()
+        .into_headers()
+        .iter()
+        .map(|(name, value)| GrpcMetadata {
+            name: name.to_string(),
+            value: match value.to_str() {
+                Ok(value) => value.to_string(),
+                Err(_) => hex::encode(value.as_bytes()),
+            },
+        })
+        .collect()
+}
+
+fn apply_metadata(metadata: &mut MetadataMap, entries: &[GrpcMetadata]) -> Result<(), String> {
+    for entry in entries {
+        let name = entry.name.trim().to_ascii_lowercase();
+        if name.ends_with("-bin") {
+            let key = BinaryMetadataKey::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid metadata key: {}", entry.name))?;
+            let value = base64::engine::general_purpose::STANDARD
+                .decode(entry.value.trim())
+                .map_err(|e| format!("Invalid base64 value for {}: {}", entry.name, e))?;
+            metadata.append_bin(key, BinaryMetadataValue::from_bytes(&value));
+        } else {
+            let key = AsciiMetadataKey::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid metadata key: {}", entry.name))?;
+            let value = MetadataValue::try_from(entry.value.as_str())
+                .map_err(|_| format!("Invalid metadata value for {}", entry.name))?;
+            metadata.append(key, value);
+        }
+    }
+    Ok(())
+}
+
+// 将JSON解析为请求消息，空字符串视为空消息
+fn parse_json_message(descriptor: &MessageDescriptor, json: &str) -> Result<DynamicMessage, String> {
+    let json = if json.trim().is_empty() { "{}" } else { json };
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let message = DynamicMessage::deserialize(descriptor.clone(), &mut deserializer)
+        .map_err(|e| format!("Invalid JSON for {}: {}", descriptor.full_name(), e))?;
+    deserializer
+        .end()
+        .map_err(|e| format!("Invalid JSON for {}: {}", descriptor.full_name(), e))?;
+    Ok(message)
+}
+
+fn message_to_json(message: &DynamicMessage) -> String {
+    let mut serializer = serde_json::Serializer::pretty(Vec::new());
+    let options = SerializeOptions::new().skip_default_fields(false);
+    match message.serialize_with_options(&mut serializer, &options) {
+        Ok(()) => String::from_utf8_lossy(&serializer.into_inner()).to_string(),
+        Err(e) => format!("<failed to render message: {}>", e),
+    }
+}
+
+// 按名称查找上传的 .proto 文件
+struct UploadedFileResolver {
+    files: HashMap<String, String>,
+}
+
+impl FileResolver for UploadedFileResolver {
+    fn open_file(&self, name: &str) -> Result<File, protox::Error> {
+        match self.files.get(name) {
+            Some(source) => File::from_source(name, source),
+            None => Err(protox::Error::file_not_found(name)),
+        }
+    }
+}
+
+// 用 .proto 文件内容生成文件描述符（纯Rust解析，不依赖protoc），内置类型由全局描述符池提供
+fn parse_proto_files(files: &[ProtoFile]) -> Result<Vec<FileDescriptorProto>, String> {
+    if files.is_empty() {
+        return Err("No .proto files provided".to_string());
+    }
+
+    let mut sources = HashMap::new();
+    for file in files {
+        let name = file.name.trim();
+        let relative = Path::new(name);
+        if relative.as_os_str().is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("Invalid .proto file name: {}", file.name));
+        }
+        sources.insert(name.replace('\\', "/"), file.content.clone());
+    }
+    let mut names: Vec<String> = sources.keys().cloned().collect();
+    names.sort();
+
+    let mut resolver = ChainFileResolver::new();
+    resolver.add(UploadedFileResolver { files: sources });
+    resolver.add(GoogleFileResolver::new());
+    let mut compiler = Compiler::with_file_resolver(resolver);
+    compiler.include_imports(true);
+    compiler.open_files(&names).map_err(|e| e.to_string())?;
+
+    Ok(compiler
+        .file_descriptor_set()
+        .file
+        .into_iter()
+        .filter(|file| !is_well_known_file(file.name()))
+        .collect())
+}
+
+// google/protobuf/*.proto 等内置类型无需从服务端或上传文件获取
+fn is_well_known_file(name: &str) -> bool {
+    DescriptorPool::global().get_file_by_name(name).is_some()
+}
+
+// 按依赖顺序排列文件描述符
+fn sort_by_dependencies(files: HashMap<String, FileDescriptorProto>) -> Vec<FileDescriptorProto> {
+    fn visit(
+        name: &str,
+        files: &HashMap<String, FileDescriptorProto>,
+        visited: &mut HashSet<String>,
+        ordered: &mut Vec<FileDescriptorProto>,
+    ) {
+        if !visited.insert(name.to_string()) {
+            return;
+        }
+        if let Some(file) = files.get(name) {
+            for dependency in &file.dependency {
+                visit(dependency, files, visited, ordered);
+            }
+            ordered.push(file.clone());
+        }
+    }
+
+    let mut names: Vec<&String> = files.keys().collect();
+    names.sort();
+
+    let mut visited = HashSet::new();
+    let mut ordered = Vec::new();
+    for name in names {
+        visit(name, &files, &mut visited, &mut ordered);
+    }
+    ordered
+}
+
+// 服务反射会话：在同一个双向流上依次发送请求并读取响应
+struct ReflectionSession {
+    grpc: tonic::client::Grpc<Channel>,
+    path: &'static str,
+    sender: mpsc::UnboundedSender<ServerReflectionRequest>,
+    receiver: Option<mpsc::UnboundedReceiver<ServerReflectionRequest>>,
+    responses: Option<Streaming<ServerReflectionResponse>>,
+}
+
+impl ReflectionSession {
+    fn new(channel: Channel, path: &'static str) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        ReflectionSession {
+            grpc: tonic::client::Grpc::new(channel),
+            path,
+            sender,
+            receiver: Some(receiver),
+            responses: None,
+        }
+    }
+
+    async fn query(&mut self, request: MessageRequest) -> Result<MessageResponse, Status> {
+        self.sender
+            .send(ServerReflectionRequest {
+                host: String::new(),
+                message_request: Some(request),
+            })
+            .map_err(|_| Status::unavailable("Reflection stream closed"))?;
+
+        // 首个请求已在队列中再建立流，避免服务端等待请求时双方互相等待
+        if let Some(receiver) = self.receiver.take() {
+            self.grpc
+                .ready()
+                .await
+                .map_err(|e| Status::unavailable(error_chain(&e)))?;
+            let response = self
+                .grpc
+                .streaming(
+                    tonic::Request::new(UnboundedReceiverStream::new(receiver)),
+                    PathAndQuery::from_static(self.path),
+                    tonic_prost::ProstCodec::<ServerReflectionRequest, ServerReflectionResponse>::default(),
+                )
+                .await?;
+            self.responses = Some(response.into_inner());
+        }
+
+        let responses = self.responses.as_mut().expect("reflection stream is open");
+        match responses.message().await? {
+            Some(ServerReflectionResponse { message_response: Some(MessageResponse::ErrorResponse(error)), .. }) => {
+                Err(Status::new(Code::from(error.error_code), error.error_message))
+            }
+            Some(ServerReflectionResponse { message_response: Some(response), .. }) => Ok(response),
+            _ => Err(Status::unknown("Empty reflection response")),
+        }
+    }
+
+    // 查询文件描述符，并继续获取其尚未加载的依赖
+    async fn collect_files(
+        &mut self,
+        request: MessageRequest,
+        files: &mut HashMap<String, FileDescriptorProto>,
+    ) -> Result<(), Status> {
+        let mut queue = vec![request];
+        while let Some(request) = queue.pop() {
+            let descriptors = match self.query(request).await? {
+                MessageResponse::FileDescriptorResponse(response) => response.file_descriptor_proto,
+                _ => return Err(Status::unknown("Unexpected reflection response")),
+            };
+
+            for bytes in descriptors {
+                let file = FileDescriptorProto::decode(bytes.as_slice())
+                    .map_err(|e| Status::unknown(format!("Invalid file descriptor: {}", e)))?;
+                for dependency in &file.dependency {
+                    if !files.contains_key(dependency) && !is_well_known_file(dependency) {
+                        queue.push(MessageRequest::FileByFilename(dependency.clone()));
+                    }
+                }
+                files.entry(file.name().to_string()).or_insert(file);
+            }
+            queue.retain(|request| !matches!(request, MessageRequest::FileByFilename(name) if files.contains_key(name)));
+        }
+        Ok(())
+    }
+}
+
+async fn reflect_with(channel: Channel, path: &'static str) -> Result<Vec<FileDescriptorProto>, Status> {
+    let mut session = ReflectionSession::new(channel, path);
+
+    let services = match session.query(MessageRequest::ListServices(String::new())).await? {
+        MessageResponse::ListServicesResponse(response) => response.service,
+        _ => return Err(Status::unknown("Unexpected reflection response")),
+    };
+
+    let mut files = HashMap::new();
+    for service in services {
+        if service.name.starts_with("grpc.reflection.") {
+            continue;
+        }
+        session
+            .collect_files(MessageRequest::FileContainingSymbol(service.name), &mut files)
+            .await?;
+    }
+
+    Ok(sort_by_dependencies(files))
+}
+
+// 通过服务反射获取服务定义，v1不可用时回退到v1alpha
+async fn fetch_reflection_descriptors(channel: Channel) -> Result<Vec<FileDescriptorProto>, String> {
+    let result = match reflect_with(channel.clone(), REFLECTION_V1_PATH).await {
+        Err(status) if status.code() == Code::Unimplemented => reflect_with(channel, REFLECTION_V1ALPHA_PATH).await,
+        result => result,
+    };
+    result.map_err(|status| format!("Server reflection failed: {:?}: {}", status.code(), status.message()))
+}
+
+fn describe_method(method: &MethodDescriptor) -> GrpcMethodInfo {
+    GrpcMethodInfo {
+        name: method.name().to_string(),
+        path: format!("{}/{}", method.parent_service().full_name(), method.name()),
+        input_type: method.input().full_name().to_string(),
+        output_type: method.output().full_name().to_string(),
+        client_streaming: method.is_client_streaming(),
+        server_streaming: method.is_server_streaming(),
+        request_template: message_to_json(&DynamicMessage::new(method.input())),
+    }
+}
+
+async fn run_call(
+    mut grpc: tonic::client::Grpc<Channel>,
+    request: tonic::Request<UnboundedReceiverStream<DynamicMessage>>,
+    path: PathAndQuery,
+    codec: DynamicCodec,
+    context: CallContext,
+) {
+    if let Err(e) = grpc.ready().await {
+        context.emit("call_failed", format!("Channel not ready: {}", error_chain(&e)), None, None);
+        return;
+    }
+
+    let response = match grpc.streaming(request, path, codec).await {
+        Ok(response) => response,
+        Err(status) => {
+            context.emit_status(&status);
+            return;
+        }
+    };
+
+    context.emit(
+        "response_headers",
+        "Response headers received".to_string(),
+        Some(metadata_to_list(response.metadata())),
+        None,
+    );
+
+    let mut stream = response.into_inner();
+    loop {
+        match stream.message().await {
+            Ok(Some(message)) => context.emit("message_received", message_to_json(&message), None, None),
+            Ok(None) => break,
+            Err(status) => {
+                context.emit_status(&status);
+                return;
+            }
+        }
+    }
+
+    let trailers = match stream.trailers().await {
+        Ok(trailers) => trailers.map(|trailers| metadata_to_list(&trailers)),
+        Err(status) => {
+            context.emit_status(&status);
+            return;
+        }
+    };
+    context.emit("call_completed", "Status Ok (0)".to_string(), trailers, Some(Code::Ok as i32));
+}
+
+impl GrpcClient {
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.events = GrpcEventSink::App(app_handle);
+    }
+
+    fn emit(&self, event_type: &str, message: String) {
+        self.events.emit(GrpcClientEvent {
+            client_id: self.client_id.clone(),
+            call_id: None,
+            event_type: event_type.to_string(),
+            message,
+            metadata: None,
+            status_code: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    fn add_descriptors(&mut self, files: Vec<FileDescriptorProto>) -> Result<(), String> {
+        self.pool
+            .add_file_descriptor_protos(files)
+            .map_err(|e| format!("Invalid descriptors: {}", e))
+    }
+
+    fn services(&self) -> Vec<GrpcServiceInfo> {
+        let mut services: Vec<GrpcServiceInfo> = self
+            .pool
+            .services()
+            .filter(|service| !service.full_name().starts_with("grpc.reflection."))
+            .map(|service| GrpcServiceInfo {
+                name: service.full_name().to_string(),
+                methods: service.methods().map(|method| describe_method(&method)).collect(),
+            })
+            .collect();
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        services
+    }
+
+    // 根据 "package.Service/Method" 或 "package.Service.Method" 查找方法
+    fn find_method(&self, method: &str) -> Result<MethodDescriptor, String> {
+        let method = method.trim().trim_start_matches('/');
+        let (service_name, method_name) = method
+            .rsplit_once('/')
+            .or_else(|| method.rsplit_once('.'))
+            .ok_or_else(|| format!("Invalid method name: {}", method))?;
+
+        let service = self
+            .pool
+            .get_service_by_name(service_name)
+            .ok_or_else(|| format!("Service {} not found", service_name))?;
+        let found = service.methods().find(|m| m.name() == method_name);
+        found.ok_or_else(|| format!("Method {} not found in {}", method_name, service_name))
+    }
+
+    fn start_call(&mut self, params: StartGrpcCallParams) -> Result<String, String> {
+        let method = self.find_method(&params.method)?;
+        let close_send = params.close_send.unwrap_or(true) || !method.is_client_streaming();
+        if !method.is_client_streaming() && params.messages.len() != 1 {
+            return Err(format!("{} expects exactly one request message", method.full_name()));
+        }
+
+        let request_descriptor = method.input();
+        let messages = params
+            .messages
+            .iter()
+            .map(|json| parse_json_message(&request_descriptor, json))
+            .collect::<Result<Vec<DynamicMessage>, String>>()?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut request = tonic::Request::new(UnboundedReceiverStream::new(receiver));
+        apply_metadata(request.metadata_mut(), params.metadata.as_deref().unwrap_or_default())?;
+        if let Some(timeout_ms) = params.timeout_ms {
+            request.set_timeout(Duration::from_millis(timeout_ms));
+        }
+
+        let path = format!("/{}/{}", method.parent_service().full_name(), method.name());
+        let path = PathAndQuery::try_from(path.as_str()).map_err(|e| format!("Invalid method path {}: {}", path, e))?;
+
+        // 清理已结束的调用
+        self.calls.retain(|_, call| !call.call_handle.is_finished());
+
+        let call_id = Uuid::new_v4().to_string();
+        let context = CallContext {
+            client_id: self.client_id.clone(),
+            call_id: call_id.clone(),
+            events: self.events.clone(),
+        };
+        context.emit("call_started", format!("Calling {}", path), params.metadata.clone(), None);
+
+        for message in messages {
+            context.emit("message_sent", message_to_json(&message), None, None);
+            let _ = sender.send(message);
+        }
+
+        let codec = DynamicCodec {
+            response_descriptor: method.output(),
+        };
+        let grpc = tonic::client::Grpc::new(self.channel.clone());
+        let call_context = context.clone();
+        let call_handle = match params.timeout_ms {
+            Some(timeout_ms) => tokio::spawn(async move {
+                let call = run_call(grpc, request, path, codec, call_context.clone());
+                if tokio::time::timeout(Duration::from_millis(timeout_ms), call).await.is_err() {
+                    call_context.emit_status(&Status::deadline_exceeded("Deadline exceeded"));
+                }
+            }),
+            None => tokio::spawn(run_call(grpc, request, path, codec, call_context)),
+        };
+
+        self.calls.insert(
+            call_id.clone(),
+            GrpcCall {
+                request_descriptor,
+                request_sender: if close_send { None } else { Some(sender) },
+                call_handle,
+            },
+        );
+
+        Ok(call_id)
+    }
+
+    fn send_call_message(&mut self, call_id: &str, json: &str) -> Result<(), String> {
+        let call = self
+            .calls
+            .get(call_id)
+            .ok_or_else(|| format!("Call {} not found", call_id))?;
+        let sender = call
+            .request_sender
+            .as_ref()
+            .ok_or_else(|| "Request stream already closed".to_string())?;
+
+        let message = parse_json_message(&call.request_descriptor, json)?;
+        let rendered = message_to_json(&message);
+        sender.send(message).map_err(|_| "Call already finished".to_string())?;
+
+        CallContext {
+            client_id: self.client_id.clone(),
+            call_id: call_id.to_string(),
+            events: self.events.clone(),
+        }
+        .emit("message_sent", rendered, None, None);
+        Ok(())
+    }
+
+    fn close_call_send(&mut self, call_id: &str) -> Result<(), String> {
+        let call = self
+            .calls
+            .get_mut(call_id)
+            .ok_or_else(|| format!("Call {} not found", call_id))?;
+        call.request_sender = None;
+        Ok(())
+    }
+
+    fn cancel_call(&mut self, call_id: &str) -> Result<(), String> {
+        let call = self
+            .calls
+            .remove(call_id)
+            .ok_or_else(|| format!("Call {} not found", call_id))?;
+        if call.call_handle.is_finished() {
+            return Ok(());
+        }
+
+        // 丢弃响应流会向服务端发送 RST_STREAM(CANCEL)
+        call.call_handle.abort();
+        CallContext {
+            client_id: self.client_id.clone(),
+            call_id: call_id.to_string(),
+            events: self.events.clone(),
+        }
+        .emit("call_cancelled", "Call cancelled".to_string(), None, Some(Code::Cancelled as i32));
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        for (_, call) in self.calls.drain() {
+            call.call_handle.abort();
+        }
+    }
+
+    fn info(&self) -> GrpcClientInfo {
+        GrpcClientInfo {
+            client_id: self.client_id.clone(),
+            address: self.address.clone(),
+            reflection: self.reflection,
+            service_count: self.services().len(),
+            active_calls: self.calls.values().filter(|call| !call.call_handle.is_finished()).count(),
+        }
+    }
+}
+
+// Tauri命令：连接gRPC服务并加载服务定义
+#[tauri::command]
+pub async fn connect_grpc_client(
+    connect_params: ConnectGrpcClientParams,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let address = connect_params.address.trim().to_string();
+    let uri = if address.contains("://") {
+        address.clone()
+    } else if connect_params.use_tls.unwrap_or(false) {
+        format!("https://{}", address)
+    } else {
+        format!("http://{}", address)
+    };
+
+    let mut endpoint = Endpoint::from_shared(uri.clone())
+        .map_err(|e| format!("Invalid address {}: {}", address, e))?
+        .connect_timeout(Duration::from_millis(connect_params.timeout_ms.unwrap_or(10000)));
+    if uri.starts_with("https://") {
+        let mut tls = ClientTlsConfig::new().with_webpki_roots();
+        if let Some(server_name) = connect_params.server_name.filter(|name| !name.is_empty()) {
+            tls = tls.domain_name(server_name);
+        }
+        endpoint = endpoint
+            .tls_config(tls)
+            .map_err(|e| format!("Failed to configure TLS: {}", error_chain(&e)))?;
+    }
+
+    let channel = endpoint
+        .connect()
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", uri, error_chain(&e)))?;
+
+    let client_id = connect_params.client_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut client = GrpcClient {
+        client_id: client_id.clone(),
+        address: uri.clone(),
+        channel: channel.clone(),
+        pool: DescriptorPool::global(),
+        reflection: false,
+        calls: HashMap::new(),
+        events: GrpcEventSink::None,
+    };
+    client.set_app_handle(app_handle);
+    client.emit("connected", format!("Connected to {}", uri));
+
+    if let Some(files) = connect_params.proto_files.filter(|files| !files.is_empty()) {
+        client.add_descriptors(parse_proto_files(&files)?)?;
+        client.emit("descriptors_loaded", format!("Loaded {} .proto file(s)", files.len()));
+    } else if connect_params.use_reflection.unwrap_or(true) {
+        // 服务未开启反射时仍保持连接，可以随后上传 .proto 文件
+        match fetch_reflection_descriptors(channel).await {
+            Ok(files) => {
+                client.add_descriptors(files)?;
+                client.reflection = true;
+                client.emit("descriptors_loaded", format!("Loaded {} service(s) via reflection", client.services().len()));
+            }
+            Err(e) => client.emit("error", format!("{}; upload .proto files instead", e)),
+        }
+    }
+
+    let mut manager = manager.lock().await;
+    manager.clients.insert(client_id.clone(), client);
+
+    Ok(client_id)
+}
+
+// Tauri命令：断开gRPC客户端，取消所有进行中的调用
+#[tauri::command]
+pub async fn disconnect_grpc_client(
+    client_id: String,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(mut client) = manager.clients.remove(&client_id) {
+        client.close();
+        client.emit("disconnected", "Disconnected".to_string());
+        Ok(())
+    } else {
+        Err(format!("gRPC client {} not found", client_id))
+    }
+}
+
+// Tauri命令：加载 .proto 文件，返回全部服务
+#[tauri::command]
+pub async fn load_grpc_proto_files(
+    load_params: LoadGrpcProtoFilesParams,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<Vec<GrpcServiceInfo>, String> {
+    let files = parse_proto_files(&load_params.files)?;
+
+    let mut manager = manager.lock().await;
+    let client = manager
+        .clients
+        .get_mut(&load_params.client_id)
+        .ok_or_else(|| format!("gRPC client {} not found", load_params.client_id))?;
+    client.add_descriptors(files)?;
+    client.emit("descriptors_loaded", format!("Loaded {} .proto file(s)", load_params.files.len()));
+    Ok(client.services())
+}
+
+// Tauri命令：列出服务和方法，refresh 为 true 时重新通过反射获取
+#[tauri::command]
+pub async fn list_grpc_services(
+    client_id: String,
+    refresh: Option<bool>,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<Vec<GrpcServiceInfo>, String> {
+    if refresh.unwrap_or(false) {
+        let channel = {
+            let manager = manager.lock().await;
+            let client = manager
+                .clients
+                .get(&client_id)
+                .ok_or_else(|| format!("gRPC client {} not found", client_id))?;
+            client.channel.clone()
+        };
+
+        // 反射期间不持有锁
+        let files = fetch_reflection_descriptors(channel).await?;
+
+        let mut manager = manager.lock().await;
+        let client = manager
+            .clients
+            .get_mut(&client_id)
+            .ok_or_else(|| format!("gRPC client {} not found", client_id))?;
+        client.add_descriptors(files)?;
+        client.reflection = true;
+    }
+
+    let manager = manager.lock().await;
+    match manager.clients.get(&client_id) {
+        Some(client) => Ok(client.services()),
+        None => Err(format!("gRPC client {} not found", client_id)),
+    }
+}
+
+// Tauri命令：发起调用，返回调用ID；响应、元数据和状态码通过事件上报
+#[tauri::command]
+pub async fn start_grpc_call(
+    call_params: StartGrpcCallParams,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<String, String> {
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&call_params.client_id) {
+        Some(client) => client.start_call(call_params),
+        None => Err(format!("gRPC client {} not found", call_params.client_id)),
+    }
+}
+
+// Tauri命令：在客户端流/双向流调用中继续发送消息
+#[tauri::command]
+pub async fn send_grpc_call_message(
+    message_params: SendGrpcCallMessageParams,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&message_params.client_id) {
+        Some(client) => client.send_call_message(&message_params.call_id, &message_params.message),
+        None => Err(format!("gRPC client {} not found", message_params.client_id)),
+    }
+}
+
+// Tauri命令：结束请求流（half-close）
+#[tauri::command]
+pub async fn close_grpc_call_send(
+    client_id: String,
+    call_id: String,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&client_id) {
+        Some(client) => client.close_call_send(&call_id),
+        None => Err(format!("gRPC client {} not found", client_id)),
+    }
+}
+
+// Tauri命令：取消调用
+#[tauri::command]
+pub async fn cancel_grpc_call(
+    client_id: String,
+    call_id: String,
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&client_id) {
+        Some(client) => client.cancel_call(&call_id),
+        None => Err(format!("gRPC client {} not found", client_id)),
+    }
+}
+
+// Tauri命令：获取所有gRPC客户端
+#[tauri::command]
+pub async fn get_grpc_clients(
+    manager: State<'_, Mutex<GrpcClientManager>>,
+) -> Result<Vec<GrpcClientInfo>, String> {
+    let manager = manager.lock().await;
+    Ok(manager.clients.values().map(GrpcClient::info).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tokio_stream::StreamExt;
+    use tonic::body::Body;
+    use tonic::codegen::{http, BoxFuture, Context, Poll, Service};
+    use tonic::server::NamedService;
+    use tonic::transport::Server;
+    use tonic_prost::ProstCodec;
+    use tower::service_fn;
+
+    const CALCULATOR_PROTO: &str = r#"
+        syntax = "proto3";
+        package test.calc;
+        import "google/protobuf/timestamp.proto";
+        import "types.proto";
+
+        service Calculator {
+            rpc Double(Number) returns (Number);
+            rpc Count(Number) returns (stream Number);
+            rpc Sum(stream Number) returns (Number);
+            rpc Echo(stream Number) returns (stream Number);
+        }
+
+        message Stamp {
+            google.protobuf.Timestamp at = 1;
+        }
+    "#;
+
+    const TYPES_PROTO: &str = r#"
+        syntax = "proto3";
+        package test.calc;
+
+        message Number {
+            int32 value = 1;
+            string note = 2;
+        }
+    "#;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Number {
+        #[prost(int32, tag = "1")]
+        value: i32,
+        #[prost(string, tag = "2")]
+        note: String,
+    }
+
+    fn proto_files() -> Vec<ProtoFile> {
+        vec![
+            ProtoFile {
+                name: "calculator.proto".to_string(),
+                content: CALCULATOR_PROTO.to_string(),
+            },
+            ProtoFile {
+                name: "types.proto".to_string(),
+                content: TYPES_PROTO.to_string(),
+            },
+        ]
+    }
+
+    fn number(value: i32) -> Number {
+        Number {
+            value,
+            note: String::new(),
+        }
+    }
+
+    async fn double(request: tonic::Request<Number>) -> Result<tonic::Response<Number>, Status> {
+        Ok(tonic::Response::new(number(request.into_inner().value * 2)))
+    }
+
+    async fn count(
+        request: tonic::Request<Number>,
+    ) -> Result<tonic::Response<tokio_stream::Iter<std::vec::IntoIter<Result<Number, Status>>>>, Status> {
+        let numbers: Vec<Result<Number, Status>> = (1..=request.into_inner().value).map(|value| Ok(number(value))).collect();
+        Ok(tonic::Response::new(tokio_stream::iter(numbers)))
+    }
+
+    async fn sum(request: tonic::Request<Streaming<Number>>) -> Result<tonic::Response<Number>, Status> {
+        let mut stream = request.into_inner();
+        let mut total = 0;
+        while let Some(number) = stream.message().await? {
+            total += number.value;
+        }
+        Ok(tonic::Response::new(number(total)))
+    }
+
+    async fn echo(
+        request: tonic::Request<Streaming<Number>>,
+    ) -> Result<tonic::Response<tokio_stream::Iter<std::vec::IntoIter<Result<Number, Status>>>>, Status> {
+        let numbers: Vec<Result<Number, Status>> = request.into_inner().collect().await;
+        Ok(tonic::Response::new(tokio_stream::iter(numbers)))
+    }
+
+    // 手写的测试服务，相当于 tonic-build 为 test.calc.Calculator 生成的路由
+    #[derive(Clone)]
+    struct Calculator;
+
+    impl NamedService for Calculator {
+        const NAME: &'static str = "test.calc.Calculator";
+    }
+
+    impl Service<http::Request<Body>> for Calculator {
+        type Response = http::Response<Body>;
+        type Error = Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(ProstCodec::<Number, Number>::default());
+                let response = match request.uri().path() {
+                    "/test.calc.Calculator/Double" => grpc.unary(service_fn(double), request).await,
+                    "/test.calc.Calculator/Count" => grpc.server_streaming(service_fn(count), request).await,
+                    "/test.calc.Calculator/Sum" => grpc.client_streaming(service_fn(sum), request).await,
+                    "/test.calc.Calculator/Echo" => grpc.streaming(service_fn(echo), request).await,
+                    _ => Status::unimplemented("Unknown method").into_http(),
+                };
+                Ok(response)
+            })
+        }
+    }
+
+    enum Reflection {
+        Disabled,
+        V1,
+        V1Alpha,
+    }
+
+    // 在本地随机端口启动测试服务，返回已连接的通道
+    async fn serve(reflection: Reflection) -> Channel {
+        let files = prost_types::FileDescriptorSet {
+            file: parse_proto_files(&proto_files()).unwrap(),
+        };
+        let builder = tonic_reflection::server::Builder::configure().register_file_descriptor_set(files);
+        let (v1, v1alpha) = match reflection {
+            Reflection::Disabled => (None, None),
+            Reflection::V1 => (Some(builder.build_v1().unwrap()), None),
+            Reflection::V1Alpha => (None, Some(builder.build_v1alpha().unwrap())),
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let router = Server::builder()
+            .add_service(Calculator)
+            .add_optional_service(v1)
+            .add_optional_service(v1alpha);
+        tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+
+        Endpoint::from_shared(format!("http://{}", address))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap()
+    }
+
+    fn client(channel: Channel) -> GrpcClient {
+        GrpcClient {
+            client_id: "test".to_string(),
+            address: "test".to_string(),
+            channel,
+            pool: DescriptorPool::global(),
+            reflection: false,
+            calls: HashMap::new(),
+            events: GrpcEventSink::None,
+        }
+    }
+
+    // 通过 start_call 发起调用，收集该调用的事件直到调用结束
+    async fn invoke(client: &mut GrpcClient, method: &str, requests: &[&str]) -> Vec<GrpcClientEvent> {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        client.events = GrpcEventSink::Channel(sender);
+        let call_id = client
+            .start_call(StartGrpcCallParams {
+                client_id: client.client_id.clone(),
+                method: method.to_string(),
+                messages: requests.iter().map(|request| request.to_string()).collect(),
+                metadata: None,
+                timeout_ms: Some(5000),
+                close_send: None,
+            })
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            assert_eq!(event.call_id.as_deref(), Some(call_id.as_str()));
+            let finished = matches!(event.event_type.as_str(), "call_completed" | "call_failed");
+            events.push(event);
+            if finished {
+                break;
+            }
+        }
+        events
+    }
+
+    // 成功结束的调用中收到的响应值
+    fn values(events: &[GrpcClientEvent]) -> Vec<i64> {
+        let last = events.last().unwrap();
+        assert_eq!(last.event_type, "call_completed", "{}", last.message);
+        assert_eq!(last.status_code, Some(0));
+        events
+            .iter()
+            .filter(|event| event.event_type == "message_received")
+            .map(|event| serde_json::from_str::<serde_json::Value>(&event.message).unwrap()["value"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn parses_uploaded_files_with_imports() {
+        let files = parse_proto_files(&proto_files()).unwrap();
+        let names: Vec<&str> = files.iter().map(|file| file.name()).collect();
+        assert_eq!(names, ["types.proto", "calculator.proto"]);
+
+        let mut client = client(Channel::from_static("http://127.0.0.1:1").connect_lazy());
+        client.add_descriptors(files).unwrap();
+        let services = client.services();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].name, "test.calc.Calculator");
+        let echo = services[0].methods.iter().find(|method| method.name == "Echo").unwrap();
+        assert!(echo.client_streaming && echo.server_streaming);
+        assert!(client.find_method("test.calc.Calculator.Double").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_proto_files() {
+        let mut files = proto_files();
+        files[0].name = "../calculator.proto".to_string();
+        assert!(parse_proto_files(&files).unwrap_err().contains("Invalid .proto file name"));
+
+        let error = parse_proto_files(&proto_files()[..1]).unwrap_err();
+        assert!(error.contains("types.proto"), "{}", error);
+
+        let mut files = proto_files();
+        files[1].content = "syntax = \"proto3\"; message {".to_string();
+        assert!(parse_proto_files(&files).is_err());
+    }
+
+    #[tokio::test]
+    async fn converts_between_json_and_messages() {
+        let mut client = client(Channel::from_static("http://127.0.0.1:1").connect_lazy());
+        client.add_descriptors(parse_proto_files(&proto_files()).unwrap()).unwrap();
+        let number_descriptor = client.pool.get_message_by_name("test.calc.Number").unwrap();
+        let stamp_descriptor = client.pool.get_message_by_name("test.calc.Stamp").unwrap();
+
+        let message = parse_json_message(&number_descriptor, r#"{"value": 7, "note": "seven"}"#).unwrap();
+        let decoded = Number::decode(message.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded.value, 7);
+        assert_eq!(decoded.note, "seven");
+
+        let message = DynamicMessage::decode(number_descriptor.clone(), number(3).encode_to_vec().as_slice()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&message_to_json(&message)).unwrap();
+        assert_eq!(json, serde_json::json!({"value": 3, "note": ""}));
+
+        let message = parse_json_message(&stamp_descriptor, r#"{"at": "2024-01-01T00:00:00Z"}"#).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&message_to_json(&message)).unwrap();
+        assert_eq!(json["at"], "2024-01-01T00:00:00Z");
+
+        assert_eq!(parse_json_message(&number_descriptor, "  ").unwrap(), DynamicMessage::new(number_descriptor.clone()));
+        let error = parse_json_message(&number_descriptor, r#"{"value": "x"}"#).unwrap_err();
+        assert!(error.starts_with("Invalid JSON for test.calc.Number"), "{}", error);
+        assert!(parse_json_message(&number_descriptor, r#"{"missing": 1}"#).is_err());
+        assert!(parse_json_message(&number_descriptor, "{} {}").is_err());
+    }
+
+    #[tokio::test]
+    async fn loads_descriptors_via_reflection_v1() {
+        let channel = serve(Reflection::V1).await;
+        let mut client = client(channel.clone());
+        client.add_descriptors(fetch_reflection_descriptors(channel).await.unwrap()).unwrap();
+        assert_eq!(values(&invoke(&mut client, "test.calc.Calculator/Double", &[r#"{"value": 21}"#]).await), [42]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_reflection_v1alpha() {
+        let channel = serve(Reflection::V1Alpha).await;
+        let files = fetch_reflection_descriptors(channel.clone()).await.unwrap();
+        let names: Vec<&str> = files.iter().map(|file| file.name()).collect();
+        assert_eq!(names, ["types.proto", "calculator.proto"]);
+
+        let mut client = client(channel);
+        client.add_descriptors(files).unwrap();
+        assert_eq!(client.services()[0].methods.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_reflection() {
+        let channel = serve(Reflection::Disabled).await;
+        let error = fetch_reflection_descriptors(channel).await.unwrap_err();
+        assert!(error.contains("Unimplemented"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn invokes_all_method_kinds() {
+        let mut client = client(serve(Reflection::Disabled).await);
+        client.add_descriptors(parse_proto_files(&proto_files()).unwrap()).unwrap();
+
+        let unary = invoke(&mut client, "test.calc.Calculator/Double", &[r#"{"value": 5}"#]).await;
+        assert_eq!(values(&unary), [10]);
+        let event_types: Vec<&str> = unary.iter().map(|event| event.event_type.as_str()).collect();
+        assert_eq!(event_types, ["call_started", "message_sent", "response_headers", "message_received", "call_completed"]);
+        assert_eq!(unary[0].message, "Calling /test.calc.Calculator/Double");
+
+        let server_streaming = invoke(&mut client, "test.calc.Calculator/Count", &[r#"{"value": 3}"#]).await;
+        assert_eq!(values(&server_streaming), [1, 2, 3]);
+
+        let requests = [r#"{"value": 1}"#, r#"{"value": 2}"#, r#"{"value": 4}"#];
+        let client_streaming = invoke(&mut client, "test.calc.Calculator/Sum", &requests).await;
+        assert_eq!(values(&client_streaming), [7]);
+
+        let bidi = invoke(&mut client, "test.calc.Calculator/Echo", &requests).await;
+        assert_eq!(values(&bidi), [1, 2, 4]);
+    }
+
+    #[tokio::test]
+    async fn validates_call_parameters() {
+        let mut client = client(serve(Reflection::Disabled).await);
+        client.add_descriptors(parse_proto_files(&proto_files()).unwrap()).unwrap();
+
+        let params = |method: &str, messages: &[&str]| StartGrpcCallParams {
+            client_id: "test".to_string(),
+            method: method.to_string(),
+            messages: messages.iter().map(|message| message.to_string()).collect(),
+            metadata: None,
+            timeout_ms: None,
+            close_send: Some(false),
+        };
+
+        let error = client.start_call(params("test.calc.Calculator/Double", &["{}", "{}"])).unwrap_err();
+        assert!(error.contains("expects exactly one request message"), "{}", error);
+        let error = client.start_call(params("test.calc.Calculator/Divide", &["{}"])).unwrap_err();
+        assert!(error.contains("Method Divide not found"), "{}", error);
+
+        let call_id = client.start_call(params("test.calc.Calculator/Sum", &[])).unwrap();
+        client.send_call_message(&call_id, r#"{"value": 1}"#).unwrap();
+        assert!(client.send_call_message(&call_id, r#"{"value": "x"}"#).is_err());
+        client.close_call_send(&call_id).unwrap();
+        assert_eq!(client.send_call_message(&call_id, "{}").unwrap_err(), "Request stream already closed");
+        client.cancel_call(&call_id).unwrap();
+        assert!(client.cancel_call(&call_id).is_err());
+    }
+}
@@ -0,0 +1,454 @@
+use std::io::Read;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use url::Url;
+use uuid::Uuid;
+
+use crate::payload_codec;
+use crate::transport::{self, ClientStream, LocalEndpoint, TlsOptions};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const MAX_HEADERS: usize = 128;
+// 响应正文（解码前后）的大小上限，防止异常的长度字段耗尽内存
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+// 响应头部（含状态行）和分块尾部头字段的总长度上限
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+// 分块编码中单行（块大小行、尾部头字段）的长度上限
+const MAX_CHUNK_LINE: usize = 4096;
+
+// HTTP头
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpHeader {
+    pub name: String,
+    pub value: String,
+}
+
+// 发送HTTP请求的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendHttpRequestParams {
+    pub request_id: Option<String>,
+    pub method: String,
+    pub url: String, // 支持 http:// 和 https://
+    pub headers: Option<Vec<HttpHeader>>,
+    pub body: Option<String>,
+    pub body_type: Option<String>, // "file"（body为文件路径）或发送消息的格式之一，默认为 "text"
+    pub timeout_ms: Option<u64>,   // 整个请求的超时时间，默认为 30000
+    pub insecure: Option<bool>,    // 跳过TLS证书校验，默认为 false
+    pub local_address: Option<String>,
+    pub local_port: Option<u16>,
+}
+
+// 请求各阶段耗时（毫秒）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpTimings {
+    pub dns_ms: f64,
+    pub connect_ms: f64,
+    pub tls_ms: Option<f64>, // 仅 https 请求
+    pub first_byte_ms: f64,  // 从发送请求到收到第一个响应字节
+    pub total_ms: f64,
+}
+
+// 解析后的HTTP响应
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpResponse {
+    pub request_id: String,
+    pub remote_address: String,
+    pub local_address: String,
+    pub version: String,
+    pub status_code: u16,
+    pub reason: String,
+    pub headers: Vec<HttpHeader>,
+    pub body: Option<String>, // 解码后的正文，非UTF-8时为None
+    pub body_hex: String,     // 解码后的正文（十六进制）
+    pub body_size: usize,     // 解码后的正文长度
+    pub transfer_size: usize, // 线路上收到的正文长度（解码前）
+    pub chunked: bool,
+    pub content_encoding: Option<String>,
+    pub timings: HttpTimings,
+}
+
+// 响应头部
+struct ResponseHead {
+    version: String,
+    status_code: u16,
+    reason: String,
+    headers: Vec<HttpHeader>,
+}
+
+impl ResponseHead {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+    }
+}
+
+fn elapsed_ms(since: Instant) -> f64 {
+    since.elapsed().as_secs_f64() * 1000.0
+}
+
+// 辅助函数：根据正文类型读取请求正文
+async fn load_body(body: Option<&str>, body_type: Option<&str>) -> Result<Vec<u8>, String> {
+    let body = match body {
+        Some(body) if !body.is_empty() => body,
+        _ => return Ok(Vec::new()),
+    };
+
+    match body_type {
+        Some("file") => tokio::fs::read(body)
+            .await
+            .map_err(|e| format!("Failed to read body file {}: {}", body, e)),
+        _ => payload_codec::decode(body, body_type),
+    }
+}
+
+// 构造请求报文，自动补充 Host、Content-Length 等缺省头
+fn build_request(method: &str, url: &Url, headers: &[HttpHeader], body: &[u8]) -> Vec<u8> {
+    let has_header = |name: &str| headers.iter().any(|header| header.name.eq_ignore_ascii_case(name));
+
+    let mut target = url.path().to_string();
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+
+    let mut request = format!("{} {} HTTP/1.1\r\n", method, target);
+
+    if !has_header("Host") {
+        let host = url.host_str().unwrap_or_default();
+        match url.port() {
+            Some(port) => request.push_str(&format!("Host: {}:{}\r\n", host, port)),
+            None => request.push_str(&format!("Host: {}\r\n", host)),
+        }
+    }
+    if !has_header("User-Agent") {
+        request.push_str(&format!("User-Agent: Socketor/{}\r\n", env!("CARGO_PKG_VERSION")));
+    }
+    if !has_header("Accept") {
+        request.push_str("Accept: */*\r\n");
+    }
+    if !has_header("Accept-Encoding") {
+        request.push_str("Accept-Encoding: gzip, deflate\r\n");
+    }
+    if !has_header("Connection") {
+        request.push_str("Connection: close\r\n");
+    }
+    if !body.is_empty() && !has_header("Content-Length") && !has_header("Transfer-Encoding") {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+
+    for header in headers {
+        request.push_str(&format!("{}: {}\r\n", header.name, header.value));
+    }
+    request.push_str("\r\n");
+
+    let mut data = request.into_bytes();
+    data.extend_from_slice(body);
+    data
+}
+
+// 读取响应头部
+async fn read_head(reader: &mut BufReader<ClientStream>) -> Result<ResponseHead, String> {
+    let mut raw = Vec::new();
+
+    loop {
+        // 多读一个字节用于判断是否超限
+        let read = (&mut *reader)
+            .take((MAX_HEAD_SIZE + 1 - raw.len()) as u64)
+            .read_until(b'\n', &mut raw)
+            .await
+            .map_err(|e| format!("Failed to read response: {}", e))?;
+        if raw.len() > MAX_HEAD_SIZE {
+            return Err(format!("Response headers exceed the {} byte limit", MAX_HEAD_SIZE));
+        }
+        if read == 0 {
+            return Err("Connection closed before the response headers were complete".to_string());
+        }
+        if raw.ends_with(b"\r\n\r\n") || raw.ends_with(b"\n\n") {
+            break;
+        }
+    }
+
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut response = httparse::Response::new(&mut headers);
+    response
+        .parse(&raw)
+        .map_err(|e| format!("Invalid response headers: {}", e))?;
+
+    Ok(ResponseHead {
+        version: format!("HTTP/1.{}", response.version.unwrap_or(1)),
+        status_code: response.code.unwrap_or(0),
+        reason: response.reason.unwrap_or_default().to_string(),
+        headers: response
+            .headers
+            .iter()
+            .map(|header| HttpHeader {
+                name: header.name.to_string(),
+                value: String::from_utf8_lossy(header.value).to_string(),
+            })
+            .collect(),
+    })
+}
+
+fn body_too_large() -> String {
+    format!("Response body exceeds the {} byte limit", MAX_BODY_SIZE)
+}
+
+// 追加读取 length 字节正文，读到的数据随实际到达增长，不按声明的长度预先分配
+async fn read_body_exact(reader: &mut BufReader<ClientStream>, body: &mut Vec<u8>, length: usize) -> Result<(), String> {
+    if body.len().checked_add(length).is_none_or(|end| end > MAX_BODY_SIZE) {
+        return Err(body_too_large());
+    }
+    let read = reader
+        .take(length as u64)
+        .read_to_end(body)
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+    if read < length {
+        return Err(format!("Response body truncated: expected {} bytes, got {}", length, read));
+    }
+    Ok(())
+}
+
+// 读取分块编码中的一行到 line，返回读取的字节数
+async fn read_chunk_line(reader: &mut BufReader<ClientStream>, line: &mut Vec<u8>, what: &str) -> Result<usize, String> {
+    line.clear();
+    let read = (&mut *reader)
+        .take(MAX_CHUNK_LINE as u64 + 1)
+        .read_until(b'\n', line)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", what, e))?;
+    if read > MAX_CHUNK_LINE {
+        return Err(format!("Chunked body line exceeds the {} byte limit", MAX_CHUNK_LINE));
+    }
+    Ok(read)
+}
+
+// 读取分块传输编码的正文
+async fn read_chunked_body(reader: &mut BufReader<ClientStream>) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    let mut line = Vec::new();
+
+    loop {
+        read_chunk_line(reader, &mut line, "chunk size").await?;
+
+        let text = String::from_utf8_lossy(&line);
+        let size_text = text.trim().split(';').next().unwrap_or_default();
+        let size = usize::from_str_radix(size_text, 16)
+            .map_err(|_| format!("Invalid chunk size: {:?}", text.trim()))?;
+
+        if size == 0 {
+            // 跳过尾部头字段直到空行
+            let mut trailers = 0;
+            loop {
+                let read = read_chunk_line(reader, &mut line, "chunk trailer").await?;
+                if read == 0 || line.trim_ascii().is_empty() {
+                    break;
+                }
+                trailers += read;
+                if trailers > MAX_HEAD_SIZE {
+                    return Err(format!("Chunk trailers exceed the {} byte limit", MAX_HEAD_SIZE));
+                }
+            }
+            return Ok(body);
+        }
+
+        read_body_exact(reader, &mut body, size).await?;
+        read_chunk_line(reader, &mut line, "chunk terminator").await?;
+    }
+}
+
+// 根据 Content-Encoding 解压正文
+fn decode_content(body: Vec<u8>, encoding: Option<&str>) -> Result<Vec<u8>, String> {
+    let encoding = match encoding {
+        Some(encoding) => encoding.trim().to_ascii_lowercase(),
+        None => return Ok(body),
+    };
+
+    // 解压后的大小同样受限，多读一个字节用于判断是否超限
+    let limit = MAX_BODY_SIZE as u64 + 1;
+    let mut decoded = Vec::new();
+    match encoding.as_str() {
+        "gzip" | "x-gzip" => {
+            flate2::read::GzDecoder::new(body.as_slice())
+                .take(limit)
+                .read_to_end(&mut decoded)
+                .map_err(|e| format!("Failed to decode gzip body: {}", e))?;
+        }
+        "deflate" => {
+            // 部分服务器发送不带zlib头的原始deflate数据
+            if flate2::read::ZlibDecoder::new(body.as_slice()).take(limit).read_to_end(&mut decoded).is_err() {
+                decoded.clear();
+                flate2::read::DeflateDecoder::new(body.as_slice())
+                    .take(limit)
+                    .read_to_end(&mut decoded)
+                    .map_err(|e| format!("Failed to decode deflate body: {}", e))?;
+            }
+        }
+        // identity 或未知编码时原样返回
+        _ => return Ok(body),
+    }
+
+    if decoded.len() > MAX_BODY_SIZE {
+        return Err(body_too_large());
+    }
+    Ok(decoded)
+}
+
+pub async fn execute_request(params: SendHttpRequestParams, request_id: String) -> Result<HttpResponse, String> {
+    let url = Url::parse(&params.url).map_err(|e| format!("Invalid URL {}: {}", params.url, e))?;
+    let use_tls = match url.scheme() {
+        "http" => false,
+        "https" => true,
+        scheme => return Err(format!("Unsupported URL scheme: {}", scheme)),
+    };
+    let host = url.host_str().ok_or("URL has no host")?.to_string();
+    let port = url.port_or_known_default().ok_or("URL has no port")?;
+    let method = params.method.trim().to_ascii_uppercase();
+    if method.is_empty() {
+        return Err("Method cannot be empty".to_string());
+    }
+
+    let headers = params.headers.unwrap_or_default();
+    let body = load_body(params.body.as_deref(), params.body_type.as_deref()).await?;
+    let request = build_request(&method, &url, &headers, &body);
+
+    let local = LocalEndpoint {
+        address: params.local_address,
+        port: params.local_port,
+        reuse_address: false,
+    };
+
+    // DNS解析
+    let started = Instant::now();
+    let (target, local_bind) = transport::resolve_endpoint(&host, port, &local).await?;
+    let dns_ms = elapsed_ms(started);
+
+    // 建立TCP连接
+    let connect_started = Instant::now();
+    let tcp_stream = transport::connect_resolved(target, local_bind, local.reuse_address).await?;
+    let connect_ms = elapsed_ms(connect_started);
+
+    // TLS握手
+    let (stream, tls_ms) = if use_tls {
+        let tls_started = Instant::now();
+        let options = TlsOptions {
+            insecure: params.insecure.unwrap_or(false),
+            alpn_protocols: vec![b"http/1.1".to_vec()],
+            ..TlsOptions::default()
+        };
+        let tls_stream = transport::start_tls(tcp_stream, &host, &options).await?;
+        (ClientStream::Tls(Box::new(tls_stream)), Some(elapsed_ms(tls_started)))
+    } else {
+        (ClientStream::Plain(tcp_stream), None)
+    };
+    let local_address = stream.local_addr().map(|addr| addr.to_string()).unwrap_or_default();
+
+    // 发送请求
+    let mut reader = BufReader::new(stream);
+    let request_started = Instant::now();
+    reader
+        .get_mut()
+        .write_all(&request)
+        .await
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+    reader
+        .get_mut()
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+
+    // 等待第一个响应字节
+    reader
+        .fill_buf()
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let first_byte_ms = elapsed_ms(request_started);
+
+    // 跳过 1xx 临时响应
+    let head = loop {
+        let head = read_head(&mut reader).await?;
+        if !(100..200).contains(&head.status_code) || head.status_code == 101 {
+            break head;
+        }
+    };
+
+    let chunked = head
+        .header("Transfer-Encoding")
+        .map(|value| value.to_ascii_lowercase().contains("chunked"))
+        .unwrap_or(false);
+    let no_body = method == "HEAD" || head.status_code == 204 || head.status_code == 304 || head.status_code == 101;
+
+    let raw_body = if no_body {
+        Vec::new()
+    } else if chunked {
+        read_chunked_body(&mut reader).await?
+    } else if let Some(length) = head.header("Content-Length") {
+        let length: u64 = length
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid Content-Length: {}", length))?;
+        let length = usize::try_from(length).map_err(|_| body_too_large())?;
+        let mut body = Vec::new();
+        read_body_exact(&mut reader, &mut body, length).await?;
+        body
+    } else {
+        // 没有长度信息时读取到连接关闭
+        let mut body = Vec::new();
+        (&mut reader)
+            .take(MAX_BODY_SIZE as u64 + 1)
+            .read_to_end(&mut body)
+            .await
+            .map_err(|e| format!("Failed to read response body: {}", e))?;
+        if body.len() > MAX_BODY_SIZE {
+            return Err(body_too_large());
+        }
+        body
+    };
+
+    let transfer_size = raw_body.len();
+    let content_encoding = head.header("Content-Encoding").map(str::to_string);
+    let body = decode_content(raw_body, content_encoding.as_deref())?;
+
+    Ok(HttpResponse {
+        request_id,
+        remote_address: target.to_string(),
+        local_address,
+        version: head.version,
+        status_code: head.status_code,
+        reason: head.reason,
+        headers: head.headers,
+        body: String::from_utf8(body.clone()).ok(),
+        body_hex: hex::encode(&body),
+        body_size: body.len(),
+        transfer_size,
+        chunked,
+        content_encoding,
+        timings: HttpTimings {
+            dns_ms,
+            connect_ms,
+            tls_ms,
+            first_byte_ms,
+            total_ms: elapsed_ms(started),
+        },
+    })
+}
+
+// Tauri命令：发送HTTP请求并返回解析后的响应
+#[tauri::command]
+pub async fn send_http_request(
+    request_params: SendHttpRequestParams,
+) -> Result<HttpResponse, String> {
+    let request_id = request_params.request_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let timeout = Duration::from_millis(request_params.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+
+    tokio::time::timeout(timeout, execute_request(request_params, request_id))
+        .await
+        .map_err(|_| format!("Request timed out after {} ms", timeout.as_millis()))?
+}
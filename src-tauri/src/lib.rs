@@ -1,15 +1,70 @@
 use tauri::Manager;
 use tokio::sync::Mutex;
 
+use file_transfer::FileTransferManager;
+use grpc_client::GrpcClientManager;
+use load_test::LoadTestManager;
+use message_template::TemplateEngine;
+use modbus_client::ModbusClientManager;
+use mqtt_client::MqttClientManager;
+use receive_capture::ReceiveCaptureManager;
+use signalr_client::SignalRClientManager;
+use socketio_client::SocketIoClientManager;
 use tcp_client::TcpClientManager;
 use tcp_server::TcpServerManager;
 use udp_client::UdpClientManager;
+#[cfg(unix)]
+use unix_client::UnixClientManager;
+#[cfg(unix)]
+use unix_datagram::UnixDatagramManager;
+#[cfg(unix)]
+use unix_server::UnixServerManager;
+use websocket_client::WebSocketClientManager;
 use websocket_server::WebSocketServerManager;
 
+mod checksum;
+mod correlation;
+mod file_transfer;
+mod grpc_client;
+mod http_client;
+mod load_test;
+mod message_template;
+mod modbus_client;
+mod modbus_codec;
+mod modbus_slave;
+mod mqtt_client;
+mod mqtt_codec;
+mod net_address;
+mod payload_codec;
+mod receive_capture;
+mod signalr_client;
+mod socketio_client;
+mod socketio_codec;
+mod socketio_server;
 mod tcp_client;
+mod tcp_framing;
+mod tcp_load_test;
 mod tcp_server;
+mod tcp_shaping;
+mod transport;
 mod udp_client;
+mod udp_impairment;
+#[cfg(unix)]
+mod unix_address;
+#[cfg(unix)]
+mod unix_client;
+#[cfg(unix)]
+mod unix_datagram;
+#[cfg(unix)]
+mod unix_server;
+mod websocket_client;
+mod websocket_deflate;
+mod websocket_handshake;
+mod websocket_load_test;
+mod websocket_queue;
 mod websocket_server;
+mod websocket_transport;
+mod write_ack;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -19,6 +74,22 @@ pub fn run() {
             app.manage(Mutex::new(TcpServerManager::default()));
             app.manage(Mutex::new(TcpClientManager::default()));
             app.manage(Mutex::new(UdpClientManager::default()));
+            app.manage(Mutex::new(MqttClientManager::default()));
+            app.manage(Mutex::new(GrpcClientManager::default()));
+            app.manage(Mutex::new(SignalRClientManager::default()));
+            app.manage(Mutex::new(SocketIoClientManager::default()));
+            app.manage(Mutex::new(ModbusClientManager::default()));
+            app.manage(Mutex::new(WebSocketClientManager::default()));
+            app.manage(Mutex::new(TemplateEngine::default()));
+            app.manage(Mutex::new(FileTransferManager::default()));
+            app.manage(Mutex::new(ReceiveCaptureManager::default()));
+            app.manage(Mutex::new(LoadTestManager::default()));
+            #[cfg(unix)]
+            app.manage(Mutex::new(UnixServerManager::default()));
+            #[cfg(unix)]
+            app.manage(Mutex::new(UnixClientManager::default()));
+            #[cfg(unix)]
+            app.manage(Mutex::new(UnixDatagramManager::default()));
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -26,8 +97,13 @@ pub fn run() {
             websocket_server::start_websocket_server,
             websocket_server::stop_websocket_server,
             websocket_server::send_websocket_message,
+            websocket_server::ping_websocket_clients,
             websocket_server::get_websocket_servers,
             websocket_server::get_websocket_server_info,
+            websocket_client::connect_websocket_client,
+            websocket_client::disconnect_websocket_client,
+            websocket_client::send_websocket_client_message,
+            websocket_client::get_websocket_clients,
             tcp_server::start_tcp_server,
             tcp_server::stop_tcp_server,
             tcp_server::send_tcp_message,
@@ -38,11 +114,99 @@ pub fn run() {
             tcp_client::send_tcp_client_message,
             tcp_client::get_tcp_clients,
             tcp_client::get_tcp_client_info,
+            tcp_shaping::set_tcp_shaping,
             udp_client::start_udp_client,
             udp_client::stop_udp_client,
             udp_client::send_udp_client_message,
             udp_client::get_udp_clients,
-            udp_client::get_udp_client_info
+            udp_client::get_udp_client_info,
+            udp_impairment::set_udp_impairment,
+            udp_impairment::get_udp_impairment,
+            correlation::set_correlation,
+            correlation::get_correlation,
+            http_client::send_http_request,
+            mqtt_client::connect_mqtt_client,
+            mqtt_client::disconnect_mqtt_client,
+            mqtt_client::mqtt_subscribe,
+            mqtt_client::mqtt_unsubscribe,
+            mqtt_client::mqtt_publish,
+            mqtt_client::get_mqtt_clients,
+            mqtt_client::get_mqtt_client_info,
+            grpc_client::connect_grpc_client,
+            grpc_client::disconnect_grpc_client,
+            grpc_client::load_grpc_proto_files,
+            grpc_client::list_grpc_services,
+            grpc_client::start_grpc_call,
+            grpc_client::send_grpc_call_message,
+            grpc_client::close_grpc_call_send,
+            grpc_client::cancel_grpc_call,
+            grpc_client::get_grpc_clients,
+            signalr_client::connect_signalr_client,
+            signalr_client::disconnect_signalr_client,
+            signalr_client::signalr_invoke,
+            signalr_client::signalr_stream,
+            signalr_client::signalr_cancel_invocation,
+            signalr_client::signalr_on,
+            signalr_client::signalr_off,
+            signalr_client::get_signalr_clients,
+            socketio_client::connect_socketio_client,
+            socketio_client::disconnect_socketio_client,
+            socketio_client::socketio_join_namespace,
+            socketio_client::socketio_leave_namespace,
+            socketio_client::socketio_emit,
+            socketio_client::socketio_send_ack,
+            socketio_client::get_socketio_clients,
+            modbus_client::connect_modbus_client,
+            modbus_client::disconnect_modbus_client,
+            modbus_client::modbus_request,
+            modbus_client::get_modbus_clients,
+            modbus_slave::get_modbus_slave_registers,
+            modbus_slave::set_modbus_slave_registers,
+            message_template::set_template_variables,
+            message_template::get_template_variables,
+            message_template::preview_message_template,
+            message_template::reset_template_counters,
+            file_transfer::send_file,
+            file_transfer::cancel_file_transfer,
+            file_transfer::get_file_transfers,
+            receive_capture::start_receive_capture,
+            receive_capture::stop_receive_capture,
+            receive_capture::get_receive_captures,
+            tcp_load_test::start_tcp_load_test,
+            websocket_load_test::start_websocket_load_test,
+            load_test::stop_load_test,
+            load_test::remove_load_test,
+            load_test::get_load_tests,
+            #[cfg(unix)]
+            unix_server::start_unix_server,
+            #[cfg(unix)]
+            unix_server::stop_unix_server,
+            #[cfg(unix)]
+            unix_server::send_unix_message,
+            #[cfg(unix)]
+            unix_server::get_unix_servers,
+            #[cfg(unix)]
+            unix_server::get_unix_server_info,
+            #[cfg(unix)]
+            unix_client::connect_unix_client,
+            #[cfg(unix)]
+            unix_client::disconnect_unix_client,
+            #[cfg(unix)]
+            unix_client::send_unix_client_message,
+            #[cfg(unix)]
+            unix_client::get_unix_clients,
+            #[cfg(unix)]
+            unix_client::get_unix_client_info,
+            #[cfg(unix)]
+            unix_datagram::start_unix_datagram,
+            #[cfg(unix)]
+            unix_datagram::stop_unix_datagram,
+            #[cfg(unix)]
+            unix_datagram::send_unix_datagram_message,
+            #[cfg(unix)]
+            unix_datagram::get_unix_datagrams,
+            #[cfg(unix)]
+            unix_datagram::get_unix_datagram_info
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
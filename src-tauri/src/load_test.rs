@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, State};
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
+
+// 延迟直方图每个2的幂区间内的子桶数，相对误差约为 1/64
+const SUB_BUCKETS: u64 = 64;
+// 报告中直方图的区间上界（毫秒），最后一个区间为溢出区间
+const REPORT_BOUNDS_MS: [f64; 16] = [
+    0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+// 延迟统计摘要（毫秒）
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySummary {
+    pub count: u64,
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+// 直方图区间
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    pub le_ms: Option<f64>, // 区间上界（含），None 表示超过最大上界
+    pub count: u64,
+}
+
+// 负载测试报告，进度事件和最终报告共用
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTestReport {
+    pub elapsed_ms: u64,
+    pub connections_target: usize,
+    pub connections_active: u64,
+    pub connects: u64,           // 成功建立的连接数（含重连）
+    pub connect_failures: u64,
+    pub disconnects: u64,        // 非测试主动关闭的断开次数
+    pub reconnects: u64,
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    pub messages_received: u64,
+    pub bytes_received: u64,
+    pub errors: u64,
+    pub timeouts: u64,           // 等待响应超时的次数
+    pub sequence_gaps: u64,      // 按序号统计时缺失的消息数
+    pub send_rate: f64,          // 消息/秒，进度事件中为本周期的速率，最终报告中为全程平均
+    pub receive_rate: f64,
+    pub send_throughput: f64,    // 字节/秒
+    pub receive_throughput: f64,
+    pub connect_latency: LatencySummary,
+    pub response_latency: LatencySummary, // 响应延迟；WebSocket测试中为推送延迟
+    pub histogram: Option<Vec<HistogramBucket>>, // 响应延迟直方图，仅最终报告包含
+    pub last_error: Option<String>,
+}
+
+// 负载测试事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTestEvent {
+    pub test_id: String,
+    pub event_type: String, // "started"、"progress" 或 "completed"
+    pub message: String,
+    pub report: LoadTestReport,
+    pub timestamp: String,
+}
+
+// 负载测试状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTestInfo {
+    pub test_id: String,
+    pub protocol: String, // "tcp" 或 "websocket"
+    pub target: String,
+    pub running: bool,
+    pub report: LoadTestReport,
+}
+
+// 对数分桶的延迟直方图（微秒），内存占用与样本数无关
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum_us: u128,
+    min_us: u64,
+    max_us: u64,
+}
+
+impl LatencyHistogram {
+    // 小于 2*SUB_BUCKETS 的值每个值一个桶，更大的值每个2的幂区间分 SUB_BUCKETS 个桶
+    fn index(us: u64) -> usize {
+        if us < 2 * SUB_BUCKETS {
+            return us as usize;
+        }
+        let msb = 63 - us.leading_zeros() as u64;
+        let shift = msb - SUB_BUCKETS.trailing_zeros() as u64;
+        (2 * SUB_BUCKETS + (shift - 1) * SUB_BUCKETS + ((us >> shift) - SUB_BUCKETS)) as usize
+    }
+
+    // 桶的下界（含）
+    fn lower_bound(index: usize) -> u64 {
+        let index = index as u64;
+        if index < 2 * SUB_BUCKETS {
+            return index;
+        }
+        let shift = (index - 2 * SUB_BUCKETS) / SUB_BUCKETS + 1;
+        let mantissa = (index - 2 * SUB_BUCKETS) % SUB_BUCKETS + SUB_BUCKETS;
+        mantissa << shift
+    }
+
+    // 桶的上界（含）
+    fn upper_bound(index: usize) -> u64 {
+        let index = index as u64;
+        if index < 2 * SUB_BUCKETS {
+            return index;
+        }
+        let shift = (index - 2 * SUB_BUCKETS) / SUB_BUCKETS + 1;
+        let mantissa = (index - 2 * SUB_BUCKETS) % SUB_BUCKETS + SUB_BUCKETS;
+        // 先取下界再加桶宽，最后一个桶的上界为 u64::MAX，不能先计算 (mantissa + 1) << shift
+        (mantissa << shift) + ((1 << shift) - 1)
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let us = latency.as_micros().min(u64::MAX as u128) as u64;
+        let index = Self::index(us);
+        if self.buckets.len() <= index {
+            self.buckets.resize(index + 1, 0);
+        }
+        self.buckets[index] += 1;
+        self.min_us = if self.count == 0 { us } else { self.min_us.min(us) };
+        self.max_us = self.max_us.max(us);
+        self.count += 1;
+        self.sum_us += us as u128;
+    }
+
+    fn percentile(&self, quantile: f64) -> u64 {
+        let rank = ((quantile * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Self::upper_bound(index).clamp(self.min_us, self.max_us);
+            }
+        }
+        self.max_us
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        if self.count == 0 {
+            return LatencySummary::default();
+        }
+        let ms = |us: u64| us as f64 / 1000.0;
+        LatencySummary {
+            count: self.count,
+            min: ms(self.min_us),
+            mean: self.sum_us as f64 / self.count as f64 / 1000.0,
+            p50: ms(self.percentile(0.50)),
+            p90: ms(self.percentile(0.90)),
+            p95: ms(self.percentile(0.95)),
+            p99: ms(self.percentile(0.99)),
+            max: ms(self.max_us),
+        }
+    }
+
+    // 按固定区间汇总，便于前端绘制
+    // 按桶的下界归入区间，正好落在区间上界的样本（例如 1 ms）计入该区间；
+    // 与上界同桶、略大于上界的样本也会计入，误差不超过 1/SUB_BUCKETS
+    pub fn report_buckets(&self) -> Vec<HistogramBucket> {
+        let mut buckets: Vec<HistogramBucket> = REPORT_BOUNDS_MS
+            .iter()
+            .map(|bound| HistogramBucket { le_ms: Some(*bound), count: 0 })
+            .chain(std::iter::once(HistogramBucket { le_ms: None, count: 0 }))
+            .collect();
+        for (index, count) in self.buckets.iter().enumerate().filter(|(_, count)| **count > 0) {
+            let ms = Self::lower_bound(index) as f64 / 1000.0;
+            let slot = REPORT_BOUNDS_MS.iter().position(|bound| ms <= *bound).unwrap_or(REPORT_BOUNDS_MS.len());
+            buckets[slot].count += count;
+        }
+        buckets
+    }
+}
+
+// 负载测试的计数，由所有连接任务共同更新
+#[derive(Debug, Default)]
+pub struct LoadCounters {
+    pub connections_active: u64,
+    pub connects: u64,
+    pub connect_failures: u64,
+    pub disconnects: u64,
+    pub reconnects: u64,
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    pub messages_received: u64,
+    pub bytes_received: u64,
+    pub errors: u64,
+    pub timeouts: u64,
+    pub sequence_gaps: u64,
+    pub connect_latency: LatencyHistogram,
+    pub response_latency: LatencyHistogram,
+    pub last_error: Option<String>,
+    finished_at: Option<Instant>,
+}
+
+impl LoadCounters {
+    pub fn error(&mut self, error: String) {
+        self.errors += 1;
+        self.last_error = Some(error);
+    }
+}
+
+// 负载测试的共享统计
+pub struct LoadStats {
+    pub counters: StdMutex<LoadCounters>,
+    started_at: Instant,
+    connections_target: usize,
+}
+
+impl LoadStats {
+    pub fn new(connections_target: usize) -> Self {
+        LoadStats {
+            counters: StdMutex::new(LoadCounters::default()),
+            started_at: Instant::now(),
+            connections_target,
+        }
+    }
+
+    pub fn update<R>(&self, update: impl FnOnce(&mut LoadCounters) -> R) -> R {
+        update(&mut self.counters.lock().unwrap())
+    }
+
+    fn finish(&self) {
+        self.update(|counters| counters.finished_at = Some(Instant::now()));
+    }
+
+    // 生成报告；final_report 为 true 时速率为全程平均并附带直方图
+    pub fn report(&self, final_report: bool) -> LoadTestReport {
+        let counters = self.counters.lock().unwrap();
+        let elapsed = counters.finished_at.unwrap_or_else(Instant::now) - self.started_at;
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+        LoadTestReport {
+            elapsed_ms: elapsed.as_millis() as u64,
+            connections_target: self.connections_target,
+            connections_active: counters.connections_active,
+            connects: counters.connects,
+            connect_failures: counters.connect_failures,
+            disconnects: counters.disconnects,
+            reconnects: counters.reconnects,
+            messages_sent: counters.messages_sent,
+            bytes_sent: counters.bytes_sent,
+            messages_received: counters.messages_received,
+            bytes_received: counters.bytes_received,
+            errors: counters.errors,
+            timeouts: counters.timeouts,
+            sequence_gaps: counters.sequence_gaps,
+            send_rate: counters.messages_sent as f64 / seconds,
+            receive_rate: counters.messages_received as f64 / seconds,
+            send_throughput: counters.bytes_sent as f64 / seconds,
+            receive_throughput: counters.bytes_received as f64 / seconds,
+            connect_latency: counters.connect_latency.summary(),
+            response_latency: counters.response_latency.summary(),
+            histogram: final_report.then(|| counters.response_latency.report_buckets()),
+            last_error: counters.last_error.clone(),
+        }
+    }
+}
+
+// 进度报告：速率按上一次报告以来的增量计算
+struct IntervalReporter {
+    at: Instant,
+    report: LoadTestReport,
+}
+
+impl IntervalReporter {
+    fn next(&mut self, stats: &LoadStats) -> LoadTestReport {
+        let mut report = stats.report(false);
+        let seconds = self.at.elapsed().as_secs_f64().max(f64::EPSILON);
+        let rate = |current: u64, previous: u64| current.saturating_sub(previous) as f64 / seconds;
+        let previous = std::mem::replace(&mut self.report, report.clone());
+        report.send_rate = rate(report.messages_sent, previous.messages_sent);
+        report.receive_rate = rate(report.messages_received, previous.messages_received);
+        report.send_throughput = rate(report.bytes_sent, previous.bytes_sent);
+        report.receive_throughput = rate(report.bytes_received, previous.bytes_received);
+        self.at = Instant::now();
+        report
+    }
+}
+
+// 连接数、爬坡和时长设置
+pub struct LoadPlan {
+    pub connections: usize,
+    pub ramp_up: Duration,        // 在该时间内均匀建立所有连接
+    pub duration: Option<Duration>, // 从开始计时，到期后停止所有连接
+    pub report_interval: Duration,
+}
+
+impl LoadPlan {
+    pub fn new(connections: usize, ramp_up_ms: Option<u64>, duration_ms: Option<u64>, report_interval_ms: Option<u64>) -> Result<Self, String> {
+        if connections == 0 {
+            return Err("Connection count must be greater than 0".to_string());
+        }
+        Ok(LoadPlan {
+            connections,
+            ramp_up: Duration::from_millis(ramp_up_ms.unwrap_or(0)),
+            duration: duration_ms.filter(|ms| *ms > 0).map(Duration::from_millis),
+            report_interval: Duration::from_millis(report_interval_ms.unwrap_or(1000).max(100)),
+        })
+    }
+}
+
+pub fn emit_load_test_event(app_handle: &tauri::AppHandle, test_id: &str, event_type: &str, message: String, report: LoadTestReport) {
+    let event = LoadTestEvent {
+        test_id: test_id.to_string(),
+        event_type: event_type.to_string(),
+        message,
+        report,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    let _ = app_handle.emit("load-test-event", &event);
+}
+
+// 调度任务：按爬坡启动连接任务，定期发送进度，到期或被停止后通知所有连接结束并发送最终报告
+// 连接任务收到停止信号后应尽快返回
+pub async fn supervise<F, Fut>(
+    app_handle: tauri::AppHandle,
+    test_id: String,
+    stats: Arc<LoadStats>,
+    plan: LoadPlan,
+    stop_sender: broadcast::Sender<()>,
+    mut spawn_connection: F,
+) where
+    F: FnMut(usize, broadcast::Receiver<()>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let started = Instant::now();
+    let step = plan.ramp_up / plan.connections as u32;
+    let deadline = plan.duration.map(|duration| started + duration);
+    let mut stop_rx = stop_sender.subscribe();
+    let mut workers = JoinSet::new();
+    let mut spawned = 0;
+    let mut reporter = IntervalReporter { at: started, report: LoadTestReport::default() };
+    let mut progress = tokio::time::interval_at(started + plan.report_interval, plan.report_interval);
+    progress.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let reason = loop {
+        tokio::select! {
+            _ = stop_rx.recv() => break "Stopped",
+            _ = tokio::time::sleep_until(started + step * spawned as u32), if spawned < plan.connections => {
+                workers.spawn(spawn_connection(spawned, stop_sender.subscribe()));
+                spawned += 1;
+            }
+            Some(_) = workers.join_next(), if !workers.is_empty() => {
+                if spawned == plan.connections && workers.is_empty() {
+                    break "All connections finished";
+                }
+            }
+            _ = progress.tick() => {
+                let report = reporter.next(&stats);
+                emit_load_test_event(&app_handle, &test_id, "progress", format!("{} connections active", report.connections_active), report);
+            }
+            _ = tokio::time::sleep_until(deadline.unwrap_or(started)), if deadline.is_some() => break "Duration elapsed",
+        }
+    };
+
+    let _ = stop_sender.send(());
+    while workers.join_next().await.is_some() {}
+    stats.finish();
+
+    let report = stats.report(true);
+    let message = format!(
+        "{}: {} messages sent, {} received, {} errors",
+        reason, report.messages_sent, report.messages_received, report.errors
+    );
+    emit_load_test_event(&app_handle, &test_id, "completed", message, report);
+}
+
+// 进行中的负载测试
+pub struct LoadTest {
+    pub test_id: String,
+    pub protocol: String,
+    pub target: String,
+    pub stats: Arc<LoadStats>,
+    pub stop_sender: broadcast::Sender<()>,
+    pub handle: JoinHandle<()>,
+}
+
+// 负载测试管理器，已结束的测试保留到下次启动同ID测试或被移除，以便读取最终报告
+pub struct LoadTestManager {
+    pub tests: HashMap<String, LoadTest>,
+}
+
+impl LoadTestManager {
+    pub fn new() -> Self {
+        LoadTestManager {
+            tests: HashMap::new(),
+        }
+    }
+
+    // 登记新测试；同ID的测试仍在运行时报错
+    pub fn insert(&mut self, test: LoadTest) -> Result<(), String> {
+        if self.tests.get(&test.test_id).is_some_and(|existing| !existing.handle.is_finished()) {
+            return Err(format!("Load test {} is already running", test.test_id));
+        }
+        self.tests.insert(test.test_id.clone(), test);
+        Ok(())
+    }
+
+    pub fn is_running(&self, test_id: &str) -> bool {
+        self.tests.get(test_id).is_some_and(|test| !test.handle.is_finished())
+    }
+}
+
+impl Default for LoadTestManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tauri命令：停止负载测试并返回最终报告
+#[tauri::command]
+pub async fn stop_load_test(
+    test_id: String,
+    tests: State<'_, Mutex<LoadTestManager>>,
+) -> Result<LoadTestReport, String> {
+    let mut tests = tests.lock().await;
+    let test = tests
+        .tests
+        .get_mut(&test_id)
+        .ok_or_else(|| format!("Load test {} not found", test_id))?;
+    if !test.handle.is_finished() {
+        let _ = test.stop_sender.send(());
+        let _ = (&mut test.handle).await;
+    }
+    Ok(test.stats.report(true))
+}
+
+// Tauri命令：移除已结束的负载测试
+#[tauri::command]
+pub async fn remove_load_test(
+    test_id: String,
+    tests: State<'_, Mutex<LoadTestManager>>,
+) -> Result<(), String> {
+    let mut tests = tests.lock().await;
+    if tests.is_running(&test_id) {
+        return Err(format!("Load test {} is still running", test_id));
+    }
+    tests
+        .tests
+        .remove(&test_id)
+        .map(|_| ())
+        .ok_or_else(|| format!("Load test {} not found", test_id))
+}
+
+// Tauri命令：获取所有负载测试及其当前报告
+#[tauri::command]
+pub async fn get_load_tests(
+    tests: State<'_, Mutex<LoadTestManager>>,
+) -> Result<Vec<LoadTestInfo>, String> {
+    let tests = tests.lock().await;
+    Ok(tests
+        .tests
+        .values()
+        .map(|test| {
+            let running = !test.handle.is_finished();
+            LoadTestInfo {
+                test_id: test.test_id.clone(),
+                protocol: test.protocol.clone(),
+                target: test.target.clone(),
+                running,
+                report: test.stats.report(!running),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(samples_us: impl IntoIterator<Item = u64>) -> LatencyHistogram {
+        let mut histogram = LatencyHistogram::default();
+        for us in samples_us {
+            histogram.record(Duration::from_micros(us));
+        }
+        histogram
+    }
+
+    #[test]
+    fn small_values_have_exact_buckets() {
+        for us in 0..2 * SUB_BUCKETS {
+            assert_eq!(LatencyHistogram::index(us), us as usize);
+            assert_eq!(LatencyHistogram::upper_bound(us as usize), us);
+        }
+        let first = LatencyHistogram::index(2 * SUB_BUCKETS);
+        assert_eq!(first, 2 * SUB_BUCKETS as usize);
+        assert_eq!(LatencyHistogram::upper_bound(first), 2 * SUB_BUCKETS + 1);
+    }
+
+    #[test]
+    fn powers_of_two_start_new_buckets() {
+        for bit in 7..64 {
+            let power = 1u64 << bit;
+            let index = LatencyHistogram::index(power);
+            assert_eq!(index, LatencyHistogram::index(power - 1) + 1, "2^{}", bit);
+            assert_eq!(LatencyHistogram::upper_bound(index - 1), power - 1, "2^{}", bit);
+            assert_eq!(LatencyHistogram::upper_bound(index), power + (power / SUB_BUCKETS) - 1, "2^{}", bit);
+        }
+    }
+
+    #[test]
+    fn upper_bounds_cover_values_within_relative_error() {
+        let mut us = 1u64;
+        while us < u64::MAX / 3 {
+            for value in [us, us * 3 / 2 + 7, us * 2 - 1] {
+                let index = LatencyHistogram::index(value);
+                let upper = LatencyHistogram::upper_bound(index);
+                assert!(upper >= value && LatencyHistogram::upper_bound(index - 1) < value, "{}", value);
+                assert!(upper - value <= value / SUB_BUCKETS, "{}", value);
+            }
+            us *= 2;
+        }
+    }
+
+    #[test]
+    fn handles_u64_max() {
+        let index = LatencyHistogram::index(u64::MAX);
+        assert_eq!(LatencyHistogram::upper_bound(index), u64::MAX);
+        assert_eq!(LatencyHistogram::upper_bound(index - 1), u64::MAX - (1 << 57));
+
+        let mut histogram = LatencyHistogram::default();
+        histogram.record(Duration::MAX);
+        assert_eq!(histogram.percentile(0.99), u64::MAX);
+        assert_eq!(histogram.report_buckets().last().unwrap().count, 1);
+    }
+
+    #[test]
+    fn percentiles_of_uniform_distribution() {
+        let histogram = histogram(1..=10_000);
+        let summary = histogram.summary();
+        assert_eq!(summary.count, 10_000);
+        assert_eq!(summary.min, 0.001);
+        assert_eq!(summary.max, 10.0);
+        assert!((summary.mean - 5.0005).abs() < 1e-9);
+
+        // 百分位取所在桶的上界，不小于真实值且相对误差不超过 1/SUB_BUCKETS
+        for (quantile, exact_us) in [(0.50, 5_000), (0.90, 9_000), (0.99, 9_900)] {
+            let us = histogram.percentile(quantile);
+            assert!(us >= exact_us && us - exact_us <= exact_us / SUB_BUCKETS, "p{}: {}", quantile * 100.0, us);
+        }
+        for (reported, exact_ms) in [(summary.p50, 5.0), (summary.p90, 9.0), (summary.p95, 9.5), (summary.p99, 9.9)] {
+            assert!(reported >= exact_ms && reported - exact_ms <= exact_ms / SUB_BUCKETS as f64 + 1e-9, "{} vs {}", reported, exact_ms);
+        }
+    }
+
+    #[test]
+    fn percentiles_are_clamped_to_observed_range() {
+        assert_eq!(LatencyHistogram::default().summary().p50, 0.0);
+
+        let summary = histogram([1_000_000]).summary();
+        assert_eq!((summary.p50, summary.p99), (1000.0, 1000.0));
+
+        let skewed = histogram(std::iter::repeat_n(100, 990).chain([50_000; 10]));
+        assert_eq!(skewed.percentile(0.50), 100);
+        assert_eq!(skewed.percentile(0.99), 100);
+        assert_eq!(skewed.percentile(1.0), 50_000);
+    }
+
+    #[test]
+    fn report_buckets_group_by_milliseconds() {
+        let buckets = histogram([50, 50, 700, 20_000_000]).report_buckets();
+        assert_eq!(buckets.len(), REPORT_BOUNDS_MS.len() + 1);
+        assert_eq!(buckets[0].count, 2);
+        assert_eq!(buckets[3].count, 1);
+        assert_eq!((buckets.last().unwrap().le_ms, buckets.last().unwrap().count), (None, 1));
+        assert_eq!(buckets.iter().map(|bucket| bucket.count).sum::<u64>(), 4);
+    }
+    #[test]
+    fn report_buckets_include_their_upper_bound() {
+        // 正好等于区间上界的样本计入该区间，而不是下一个区间
+        let samples = REPORT_BOUNDS_MS.map(|bound| (bound * 1000.0) as u64);
+        let buckets = histogram(samples).report_buckets();
+        for (bucket, bound) in buckets.iter().zip(REPORT_BOUNDS_MS) {
+            assert_eq!(bucket.count, 1, "{} ms", bound);
+        }
+        assert_eq!(buckets.last().unwrap().count, 0);
+
+        // 略大于上界的样本在误差范围内可能计入同一区间，超出误差范围的计入下一个区间
+        let buckets = histogram([1_001, 1_000 + 1_000 / SUB_BUCKETS + 8]).report_buckets();
+        assert_eq!((buckets[3].count, buckets[4].count), (1, 1));
+    }
+}
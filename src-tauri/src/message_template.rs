@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use chrono::format::{Item, StrftimeItems};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+// 内置占位符，工作区变量不能与之重名
+const BUILTINS: [&str; 5] = ["counter", "now", "rand", "uuid", "var"];
+// {{rand}} 未指定范围时的默认范围
+const DEFAULT_RAND_RANGE: (u64, u64) = (0, 255);
+
+// 消息模板引擎：发送前展开 {{...}} 占位符
+//   {{counter}}             每个发送方独立的计数器，从 1 开始，同一条消息中取值相同
+//   {{now}} / {{now:%H%M%S}} 本地时间，默认RFC 3339；"unix"、"unix_ms" 输出时间戳
+//   {{rand:0-255:hex}}      范围内的随机数
+//   {{uuid}}                随机UUID，"simple" 格式不带连字符
+//   {{name}} / {{var:name}} 工作区变量
+// 数值格式为 dec/hex/HEX，可带宽度，例如 hex4、dec8；十六进制消息中数值默认为补齐到整字节的hex
+pub struct TemplateEngine {
+    pub variables: HashMap<String, String>,
+    counters: HashMap<String, u64>, // 按发送方（服务器/客户端/套接字ID）计数
+}
+
+impl TemplateEngine {
+    pub fn new() -> Self {
+        TemplateEngine {
+            variables: HashMap::new(),
+            counters: HashMap::new(),
+        }
+    }
+
+    // 展开模板；advance 为 false 时只预览，不推进计数器；展开失败时计数器保持不变
+    pub fn render(&mut self, scope: &str, template: &str, hex: bool, advance: bool) -> Result<String, String> {
+        let mut output = String::with_capacity(template.len());
+        let mut counter = None;
+        let mut rest = template;
+
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let body = &rest[start + 2..];
+            let end = body
+                .find("}}")
+                .ok_or_else(|| format!("Unclosed placeholder: {}", &rest[start..]))?;
+            output.push_str(&self.expand(scope, body[..end].trim(), hex, &mut counter)?);
+            rest = &body[end + 2..];
+        }
+        output.push_str(rest);
+
+        if let (Some(value), true) = (counter, advance) {
+            self.counters.insert(scope.to_string(), value);
+        }
+        Ok(output)
+    }
+
+    fn expand(&self, scope: &str, body: &str, hex: bool, counter: &mut Option<u64>) -> Result<String, String> {
+        let (name, argument) = match body.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument.trim())),
+            None => (body, None),
+        };
+
+        match name {
+            "counter" => {
+                let value = *counter.get_or_insert_with(|| self.counters.get(scope).copied().unwrap_or(0) + 1);
+                format_number(value, argument, hex, 0)
+            }
+            "now" => format_time(argument),
+            "rand" => {
+                let (range, format) = match argument.map(|argument| argument.split_once(':')) {
+                    Some(Some((range, format))) => (Some(range), Some(format)),
+                    Some(None) => (argument, None),
+                    None => (None, None),
+                };
+                let (min, max) = parse_range(range)?;
+                let value = rand::thread_rng().gen_range(min..=max);
+                format_number(value, format, hex, format!("{:x}", max).len())
+            }
+            "uuid" => {
+                let id = Uuid::new_v4();
+                match argument {
+                    Some("simple") => Ok(id.simple().to_string()),
+                    None | Some("") if hex => Ok(id.simple().to_string()),
+                    None | Some("") => Ok(id.to_string()),
+                    Some(other) => Err(format!("Invalid uuid format: {}", other)),
+                }
+            }
+            "var" => self.variable(argument.unwrap_or_default()),
+            _ => self.variable(body),
+        }
+    }
+
+    fn variable(&self, name: &str) -> Result<String, String> {
+        self.variables
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown template variable: {}", name))
+    }
+
+    pub fn set_variables(&mut self, variables: HashMap<String, String>) -> Result<(), String> {
+        for name in variables.keys() {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.') {
+                return Err(format!("Invalid variable name: {}", name));
+            }
+            if BUILTINS.contains(&name.as_str()) {
+                return Err(format!("Variable name {} is reserved", name));
+            }
+        }
+        self.variables = variables;
+        Ok(())
+    }
+
+    pub fn reset_counters(&mut self, scope: Option<&str>) {
+        match scope {
+            Some(scope) => {
+                self.counters.remove(scope);
+            }
+            None => self.counters.clear(),
+        }
+    }
+}
+
+impl Default for TemplateEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 数值格式："dec"、"hex"、"HEX"，后接可选宽度；十六进制未指定宽度时补齐到整字节
+fn format_number(value: u64, format: Option<&str>, hex: bool, min_hex_digits: usize) -> Result<String, String> {
+    let format = format.filter(|format| !format.is_empty()).unwrap_or(if hex { "hex" } else { "dec" });
+    let (base, width) = ["dec", "hex", "HEX"]
+        .iter()
+        .find_map(|base| format.strip_prefix(base).map(|width| (*base, width)))
+        .ok_or_else(|| format!("Invalid number format: {}", format))?;
+    let width: usize = match width {
+        "" => 0,
+        width => width.parse().map_err(|_| format!("Invalid number format: {}", format))?,
+    };
+
+    match base {
+        "dec" => Ok(format!("{:0width$}", value, width = width)),
+        _ => {
+            let width = if width > 0 {
+                width
+            } else {
+                format!("{:x}", value).len().max(min_hex_digits).div_ceil(2) * 2
+            };
+            if base == "hex" {
+                Ok(format!("{:0width$x}", value, width = width))
+            } else {
+                Ok(format!("{:0width$X}", value, width = width))
+            }
+        }
+    }
+}
+
+fn format_time(format: Option<&str>) -> Result<String, String> {
+    let now = chrono::Local::now();
+    match format {
+        None | Some("") => Ok(now.to_rfc3339()),
+        Some("unix") => Ok(now.timestamp().to_string()),
+        Some("unix_ms") => Ok(now.timestamp_millis().to_string()),
+        Some(format) => {
+            // 无效的格式说明符在格式化时会panic，需要提前检查
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(format!("Invalid time format: {}", format));
+            }
+            Ok(now.format(format).to_string())
+        }
+    }
+}
+
+// 范围 "min-max"，均为十进制
+fn parse_range(range: Option<&str>) -> Result<(u64, u64), String> {
+    let range = match range {
+        Some(range) if !range.is_empty() => range,
+        _ => return Ok(DEFAULT_RAND_RANGE),
+    };
+    let (min, max) = range
+        .split_once('-')
+        .and_then(|(min, max)| Some((min.trim().parse::<u64>().ok()?, max.trim().parse::<u64>().ok()?)))
+        .ok_or_else(|| format!("Invalid random range: {}", range))?;
+    if min > max {
+        return Err(format!("Invalid random range: {}", range));
+    }
+    Ok((min, max))
+}
+
+// 按需展开待发送的消息，未启用模板时原样返回
+pub async fn expand_message(
+    templates: &Mutex<TemplateEngine>,
+    enabled: Option<bool>,
+    scope: &str,
+    message: String,
+    message_type: Option<&str>,
+) -> Result<String, String> {
+    if !enabled.unwrap_or(false) {
+        return Ok(message);
+    }
+    templates.lock().await.render(scope, &message, message_type == Some("hex"), true)
+}
+
+// 预览模板的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePreviewParams {
+    pub message: String,
+    pub message_type: Option<String>, // 格式同发送消息，"hex" 时数值默认输出十六进制
+    pub scope: Option<String>,        // 预览该发送方的下一个计数值
+}
+
+// Tauri命令：设置工作区变量（整体替换）
+#[tauri::command]
+pub async fn set_template_variables(
+    variables: HashMap<String, String>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<(), String> {
+    templates.lock().await.set_variables(variables)
+}
+
+// Tauri命令：获取工作区变量
+#[tauri::command]
+pub async fn get_template_variables(
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<HashMap<String, String>, String> {
+    Ok(templates.lock().await.variables.clone())
+}
+
+// Tauri命令：预览模板展开结果，不推进计数器
+#[tauri::command]
+pub async fn preview_message_template(
+    preview_params: TemplatePreviewParams,
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<String, String> {
+    let hex = preview_params.message_type.as_deref() == Some("hex");
+    let scope = preview_params.scope.unwrap_or_default();
+    templates.lock().await.render(&scope, &preview_params.message, hex, false)
+}
+
+// Tauri命令：重置计数器，不指定发送方时全部重置
+#[tauri::command]
+pub async fn reset_template_counters(
+    scope: Option<String>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<(), String> {
+    templates.lock().await.reset_counters(scope.as_deref());
+    Ok(())
+}
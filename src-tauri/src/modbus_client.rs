@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::modbus_codec::{self, ModbusRequest};
+use crate::transport::{self, LocalEndpoint};
+
+const DEFAULT_PORT: u16 = 502;
+const DEFAULT_TIMEOUT_MS: u64 = 3000;
+
+// Modbus TCP主站
+pub struct ModbusClient {
+    pub client_id: String,
+    pub host: String,
+    pub port: u16,
+    pub unit_id: u8,
+    pub timeout: Duration,
+    pub local_endpoint: LocalEndpoint,
+    // 一个连接同一时间只处理一个请求；连接断开或请求超时后置为 None，下一个请求重新连接
+    pub connection: Arc<Mutex<Option<TcpStream>>>,
+    pub next_transaction_id: Arc<AtomicU16>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// Modbus主站管理器
+pub struct ModbusClientManager {
+    pub clients: HashMap<String, ModbusClient>,
+}
+
+impl ModbusClientManager {
+    pub fn new() -> Self {
+        ModbusClientManager {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl Default for ModbusClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 连接Modbus从站的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectModbusClientParams {
+    pub client_id: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,             // 默认为 502
+    pub unit_id: Option<u8>,           // 默认单元ID，默认为 1
+    pub timeout_ms: Option<u64>,       // 应答超时，默认为 3000
+    pub local_address: Option<String>, // 本地源地址：IPv4/IPv6地址或网卡名称
+    pub local_port: Option<u16>,       // 本地源端口
+}
+
+// Modbus请求参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusRequestParams {
+    pub client_id: String,
+    pub unit_id: Option<u8>, // 不指定时使用连接时设置的单元ID
+    pub function_code: u8,   // 1/2/3/4 读取，5/6/15/16 写入
+    pub address: u16,
+    pub count: Option<u16>,       // 读取数量，默认为 1
+    pub values: Option<Vec<u16>>, // 写入的值，线圈以 0/1 表示
+}
+
+// Modbus请求结果
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusResult {
+    pub transaction_id: u16,
+    pub unit_id: u8,
+    pub function_code: u8,
+    pub function_name: String,
+    pub address: u16,
+    pub values: Vec<u16>,
+    pub exception_code: Option<u8>,
+    pub exception: Option<String>,
+    pub request: String,  // 请求帧（十六进制）
+    pub response: String, // 应答帧（十六进制）
+    pub elapsed_ms: f64,
+}
+
+// 主站状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusClientInfo {
+    pub client_id: String,
+    pub host: String,
+    pub port: u16,
+    pub unit_id: u8,
+    pub is_connected: bool,
+}
+
+// Modbus主站事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusClientEvent {
+    pub client_id: String,
+    pub event_type: String,
+    pub message: String,
+    pub transaction_id: Option<u16>,
+    pub data: Option<String>, // 帧内容（十六进制）
+    pub timestamp: String,
+}
+
+fn emit_modbus_event(
+    app_handle: &Option<tauri::AppHandle>,
+    client_id: &str,
+    event_type: &str,
+    message: String,
+    transaction_id: Option<u16>,
+    data: Option<String>,
+) {
+    if let Some(app_handle) = app_handle {
+        let event = ModbusClientEvent {
+            client_id: client_id.to_string(),
+            event_type: event_type.to_string(),
+            message,
+            transaction_id,
+            data,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let _ = app_handle.emit("modbus-client-event", &event);
+    }
+}
+
+// 执行请求所需的连接句柄，请求期间不持有管理器的锁
+pub struct ModbusSession {
+    client_id: String,
+    host: String,
+    port: u16,
+    local_endpoint: LocalEndpoint,
+    timeout: Duration,
+    connection: Arc<Mutex<Option<TcpStream>>>,
+    app_handle: Option<tauri::AppHandle>,
+}
+
+impl ModbusClient {
+    pub fn new(host: String, port: u16, client_id: String) -> Self {
+        ModbusClient {
+            client_id,
+            host,
+            port,
+            unit_id: 1,
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            local_endpoint: LocalEndpoint::default(),
+            connection: Arc::new(Mutex::new(None)),
+            next_transaction_id: Arc::new(AtomicU16::new(1)),
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    pub async fn connect(&mut self, local_endpoint: &LocalEndpoint) -> Result<(), String> {
+        self.local_endpoint = local_endpoint.clone();
+        let (stream, message) = open_connection(&self.host, self.port, local_endpoint).await?;
+        *self.connection.lock().await = Some(stream);
+        emit_modbus_event(&self.app_handle, &self.client_id, "connected", message, None, None);
+        Ok(())
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), String> {
+        if let Some(mut stream) = self.connection.lock().await.take() {
+            let _ = stream.shutdown().await;
+            emit_modbus_event(&self.app_handle, &self.client_id, "disconnected", "Disconnected from server".to_string(), None, None);
+        }
+        Ok(())
+    }
+
+    pub fn session(&self) -> ModbusSession {
+        ModbusSession {
+            client_id: self.client_id.clone(),
+            host: self.host.clone(),
+            port: self.port,
+            local_endpoint: self.local_endpoint.clone(),
+            timeout: self.timeout,
+            connection: self.connection.clone(),
+            app_handle: self.app_handle.clone(),
+        }
+    }
+
+    // 事务ID按请求递增，跳过 0
+    pub fn next_transaction_id(&self) -> u16 {
+        loop {
+            let id = self.next_transaction_id.fetch_add(1, Ordering::Relaxed);
+            if id != 0 {
+                return id;
+            }
+        }
+    }
+
+    async fn info(&self) -> ModbusClientInfo {
+        ModbusClientInfo {
+            client_id: self.client_id.clone(),
+            host: self.host.clone(),
+            port: self.port,
+            unit_id: self.unit_id,
+            is_connected: self.connection.lock().await.is_some(),
+        }
+    }
+}
+
+impl ModbusSession {
+    fn emit(&self, event_type: &str, message: String, transaction_id: Option<u16>, data: Option<String>) {
+        emit_modbus_event(&self.app_handle, &self.client_id, event_type, message, transaction_id, data);
+    }
+
+    pub async fn request(&self, transaction_id: u16, unit_id: u8, request: ModbusRequest) -> Result<ModbusResult, String> {
+        let mut connection = self.connection.lock().await;
+        let stream = match &mut *connection {
+            Some(stream) => stream,
+            None => {
+                let (stream, message) = open_connection(&self.host, self.port, &self.local_endpoint)
+                    .await
+                    .map_err(|e| format!("Reconnect failed: {}", e))?;
+                self.emit("connected", message, None, None);
+                connection.insert(stream)
+            }
+        };
+
+        let frame = modbus_codec::encode_frame(transaction_id, unit_id, &request.encode());
+        let request_hex = modbus_codec::to_hex(&frame);
+        let started = Instant::now();
+
+        if let Err(e) = stream.write_all(&frame).await {
+            *connection = None;
+            let message = format!("Connection lost: {}", e);
+            self.emit("disconnected", message.clone(), Some(transaction_id), None);
+            return Err(message);
+        }
+        self.emit(
+            "request_sent",
+            format!("[unit {}] {}", unit_id, request.describe()),
+            Some(transaction_id),
+            Some(request_hex.clone()),
+        );
+
+        // 等待对应事务ID的应答，丢弃事务ID不符的应答
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let response = loop {
+            match tokio::time::timeout_at(deadline, modbus_codec::read_frame(stream)).await {
+                Err(_) => {
+                    // 超时可能发生在读取一帧的中途，已读的字节破坏了帧边界，丢弃连接
+                    *connection = None;
+                    let message = format!("No response within {} ms", self.timeout.as_millis());
+                    self.emit("timeout", message.clone(), Some(transaction_id), None);
+                    return Err(message);
+                }
+                Ok(Ok(Some(response))) if response.transaction_id == transaction_id => break response,
+                Ok(Ok(Some(response))) => {
+                    self.emit(
+                        "error",
+                        format!("Discarded response with unexpected transaction ID {}", response.transaction_id),
+                        Some(response.transaction_id),
+                        Some(modbus_codec::to_hex(&response.to_bytes())),
+                    );
+                }
+                Ok(Ok(None)) => {
+                    *connection = None;
+                    let message = "Connection closed by server".to_string();
+                    self.emit("disconnected", message.clone(), Some(transaction_id), None);
+                    return Err(message);
+                }
+                Ok(Err(e)) => {
+                    // 帧格式错误后无法再同步帧边界，断开连接
+                    *connection = None;
+                    self.emit("disconnected", e.clone(), Some(transaction_id), None);
+                    return Err(e);
+                }
+            }
+        };
+
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let response_hex = modbus_codec::to_hex(&response.to_bytes());
+        let reply = match modbus_codec::decode_response(&request, &response.pdu) {
+            Ok(reply) => reply,
+            Err(e) => {
+                self.emit("error", format!("Invalid response: {}", e), Some(transaction_id), Some(response_hex));
+                return Err(e);
+            }
+        };
+
+        match reply.exception {
+            Some(code) => self.emit(
+                "exception",
+                format!("{} failed: {} (0x{:02X})", request.describe(), modbus_codec::exception_name(code), code),
+                Some(transaction_id),
+                Some(response_hex.clone()),
+            ),
+            None => self.emit(
+                "response_received",
+                format!("[unit {}] {}: {:?} ({:.1} ms)", response.unit_id, request.describe(), reply.values, elapsed_ms),
+                Some(transaction_id),
+                Some(response_hex.clone()),
+            ),
+        }
+
+        Ok(ModbusResult {
+            transaction_id,
+            unit_id: response.unit_id,
+            function_code: request.function,
+            function_name: modbus_codec::function_name(request.function).to_string(),
+            address: request.address,
+            values: reply.values,
+            exception_code: reply.exception,
+            exception: reply.exception.map(|code| modbus_codec::exception_name(code).to_string()),
+            request: request_hex,
+            response: response_hex,
+            elapsed_ms,
+        })
+    }
+}
+
+// 建立到从站的连接，返回连接和描述信息
+async fn open_connection(host: &str, port: u16, local_endpoint: &LocalEndpoint) -> Result<(TcpStream, String), String> {
+    let stream = transport::connect_tcp(host, port, local_endpoint).await?;
+    let _ = stream.set_nodelay(true);
+    let addr = format!("{}:{}", host, port);
+    let message = match stream.local_addr() {
+        Ok(local_addr) => format!("Connected to {} from {}", addr, local_addr),
+        Err(_) => format!("Connected to {}", addr),
+    };
+    Ok((stream, message))
+}
+
+// Tauri命令：连接Modbus从站
+#[tauri::command]
+pub async fn connect_modbus_client(
+    connect_params: ConnectModbusClientParams,
+    manager: State<'_, Mutex<ModbusClientManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let client_id = connect_params.client_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut client = ModbusClient::new(connect_params.host, connect_params.port.unwrap_or(DEFAULT_PORT), client_id.clone());
+    client.unit_id = connect_params.unit_id.unwrap_or(1);
+    client.timeout = Duration::from_millis(connect_params.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    client.set_app_handle(app_handle);
+
+    let local_endpoint = LocalEndpoint {
+        address: connect_params.local_address,
+        port: connect_params.local_port,
+        reuse_address: false,
+    };
+    client.connect(&local_endpoint).await?;
+
+    let mut manager = manager.lock().await;
+    manager.clients.insert(client_id.clone(), client);
+
+    Ok(client_id)
+}
+
+// Tauri命令：断开Modbus主站
+#[tauri::command]
+pub async fn disconnect_modbus_client(
+    client_id: String,
+    manager: State<'_, Mutex<ModbusClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(mut client) = manager.clients.remove(&client_id) {
+        client.disconnect().await
+    } else {
+        Err(format!("Modbus client {} not found", client_id))
+    }
+}
+
+// Tauri命令：发送Modbus请求并等待应答
+#[tauri::command]
+pub async fn modbus_request(
+    request_params: ModbusRequestParams,
+    manager: State<'_, Mutex<ModbusClientManager>>,
+) -> Result<ModbusResult, String> {
+    let request = ModbusRequest::new(
+        request_params.function_code,
+        request_params.address,
+        request_params.count,
+        request_params.values.unwrap_or_default(),
+    )?;
+
+    let (session, transaction_id, unit_id) = {
+        let manager = manager.lock().await;
+        let client = manager
+            .clients
+            .get(&request_params.client_id)
+            .ok_or_else(|| format!("Modbus client {} not found", request_params.client_id))?;
+        (
+            client.session(),
+            client.next_transaction_id(),
+            request_params.unit_id.unwrap_or(client.unit_id),
+        )
+    };
+
+    session.request(transaction_id, unit_id, request).await
+}
+
+// Tauri命令：获取所有Modbus主站
+#[tauri::command]
+pub async fn get_modbus_clients(
+    manager: State<'_, Mutex<ModbusClientManager>>,
+) -> Result<Vec<ModbusClientInfo>, String> {
+    let manager = manager.lock().await;
+    let mut clients = Vec::new();
+    for client in manager.clients.values() {
+        clients.push(client.info().await);
+    }
+    Ok(clients)
+}
@@ -0,0 +1,373 @@
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+// Modbus 功能码
+pub const READ_COILS: u8 = 0x01;
+pub const READ_DISCRETE_INPUTS: u8 = 0x02;
+pub const READ_HOLDING_REGISTERS: u8 = 0x03;
+pub const READ_INPUT_REGISTERS: u8 = 0x04;
+pub const WRITE_SINGLE_COIL: u8 = 0x05;
+pub const WRITE_SINGLE_REGISTER: u8 = 0x06;
+pub const WRITE_MULTIPLE_COILS: u8 = 0x0F;
+pub const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+// Modbus 异常码
+pub const ILLEGAL_FUNCTION: u8 = 0x01;
+pub const ILLEGAL_DATA_ADDRESS: u8 = 0x02;
+pub const ILLEGAL_DATA_VALUE: u8 = 0x03;
+
+// MBAP头长度：事务ID(2) + 协议ID(2) + 长度(2) + 单元ID(1)
+const MBAP_HEADER_LEN: usize = 7;
+
+pub fn function_name(function: u8) -> &'static str {
+    match function & 0x7F {
+        READ_COILS => "Read Coils",
+        READ_DISCRETE_INPUTS => "Read Discrete Inputs",
+        READ_HOLDING_REGISTERS => "Read Holding Registers",
+        READ_INPUT_REGISTERS => "Read Input Registers",
+        WRITE_SINGLE_COIL => "Write Single Coil",
+        WRITE_SINGLE_REGISTER => "Write Single Register",
+        WRITE_MULTIPLE_COILS => "Write Multiple Coils",
+        WRITE_MULTIPLE_REGISTERS => "Write Multiple Registers",
+        _ => "Unknown Function",
+    }
+}
+
+pub fn exception_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "Illegal Function",
+        0x02 => "Illegal Data Address",
+        0x03 => "Illegal Data Value",
+        0x04 => "Server Device Failure",
+        0x05 => "Acknowledge",
+        0x06 => "Server Device Busy",
+        0x08 => "Memory Parity Error",
+        0x0A => "Gateway Path Unavailable",
+        0x0B => "Gateway Target Device Failed to Respond",
+        _ => "Unknown Exception",
+    }
+}
+
+// 单次请求允许的最大数量（Modbus应用协议规范）
+fn max_count(function: u8) -> u16 {
+    match function {
+        READ_COILS | READ_DISCRETE_INPUTS => 2000,
+        READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => 125,
+        WRITE_MULTIPLE_COILS => 1968,
+        WRITE_MULTIPLE_REGISTERS => 123,
+        _ => 1,
+    }
+}
+
+pub fn is_bit_function(function: u8) -> bool {
+    matches!(function, READ_COILS | READ_DISCRETE_INPUTS | WRITE_SINGLE_COIL | WRITE_MULTIPLE_COILS)
+}
+
+fn pack_bits(values: &[u16]) -> Vec<u8> {
+    let mut bytes = vec![0u8; values.len().div_ceil(8)];
+    for (index, value) in values.iter().enumerate() {
+        if *value != 0 {
+            bytes[index / 8] |= 1 << (index % 8);
+        }
+    }
+    bytes
+}
+
+fn unpack_bits(bytes: &[u8], count: u16) -> Vec<u16> {
+    (0..count as usize)
+        .map(|index| ((bytes[index / 8] >> (index % 8)) & 1) as u16)
+        .collect()
+}
+
+fn read_u16(pdu: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*pdu.get(offset)?, *pdu.get(offset + 1)?]))
+}
+
+// Modbus 请求；线圈的值用 0/1 表示
+#[derive(Debug, Clone)]
+pub struct ModbusRequest {
+    pub function: u8,
+    pub address: u16,
+    pub count: u16,
+    pub values: Vec<u16>,
+}
+
+impl ModbusRequest {
+    // 根据功能码构造请求并检查数量限制
+    pub fn new(function: u8, address: u16, count: Option<u16>, values: Vec<u16>) -> Result<Self, String> {
+        let count = match function {
+            READ_COILS | READ_DISCRETE_INPUTS | READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => count.unwrap_or(1),
+            WRITE_SINGLE_COIL | WRITE_SINGLE_REGISTER => {
+                if values.len() != 1 {
+                    return Err(format!("{} requires exactly one value", function_name(function)));
+                }
+                1
+            }
+            WRITE_MULTIPLE_COILS | WRITE_MULTIPLE_REGISTERS => values.len() as u16,
+            other => return Err(format!("Unsupported function code: 0x{:02X}", other)),
+        };
+
+        if count == 0 || count > max_count(function) {
+            return Err(format!(
+                "{} count must be between 1 and {}, got {}",
+                function_name(function),
+                max_count(function),
+                count
+            ));
+        }
+        if address as u32 + count as u32 > 0x10000 {
+            return Err(format!("Address range {}..{} exceeds 65535", address, address as u32 + count as u32 - 1));
+        }
+
+        Ok(ModbusRequest {
+            function,
+            address,
+            count,
+            values,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut pdu = vec![self.function];
+        pdu.extend_from_slice(&self.address.to_be_bytes());
+        match self.function {
+            WRITE_SINGLE_COIL => {
+                let value: u16 = if self.values[0] != 0 { 0xFF00 } else { 0x0000 };
+                pdu.extend_from_slice(&value.to_be_bytes());
+            }
+            WRITE_SINGLE_REGISTER => pdu.extend_from_slice(&self.values[0].to_be_bytes()),
+            WRITE_MULTIPLE_COILS => {
+                let bytes = pack_bits(&self.values);
+                pdu.extend_from_slice(&self.count.to_be_bytes());
+                pdu.push(bytes.len() as u8);
+                pdu.extend_from_slice(&bytes);
+            }
+            WRITE_MULTIPLE_REGISTERS => {
+                pdu.extend_from_slice(&self.count.to_be_bytes());
+                pdu.push((self.values.len() * 2) as u8);
+                for value in &self.values {
+                    pdu.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            _ => pdu.extend_from_slice(&self.count.to_be_bytes()),
+        }
+        pdu
+    }
+
+    // 从站解析请求PDU，失败时返回应答的异常码
+    pub fn decode(pdu: &[u8]) -> Result<Self, u8> {
+        let function = *pdu.first().ok_or(ILLEGAL_FUNCTION)?;
+        let address = read_u16(pdu, 1).ok_or(ILLEGAL_DATA_VALUE)?;
+        let field = read_u16(pdu, 3).ok_or(ILLEGAL_DATA_VALUE)?;
+
+        let (count, values) = match function {
+            READ_COILS | READ_DISCRETE_INPUTS | READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => (field, Vec::new()),
+            WRITE_SINGLE_COIL => match field {
+                0xFF00 => (1, vec![1]),
+                0x0000 => (1, vec![0]),
+                _ => return Err(ILLEGAL_DATA_VALUE),
+            },
+            WRITE_SINGLE_REGISTER => (1, vec![field]),
+            WRITE_MULTIPLE_COILS | WRITE_MULTIPLE_REGISTERS => {
+                let byte_count = *pdu.get(5).ok_or(ILLEGAL_DATA_VALUE)? as usize;
+                let data = pdu.get(6..6 + byte_count).ok_or(ILLEGAL_DATA_VALUE)?;
+                if function == WRITE_MULTIPLE_COILS {
+                    if byte_count != (field as usize).div_ceil(8) {
+                        return Err(ILLEGAL_DATA_VALUE);
+                    }
+                    (field, unpack_bits(data, field))
+                } else {
+                    if byte_count != field as usize * 2 {
+                        return Err(ILLEGAL_DATA_VALUE);
+                    }
+                    (field, data.chunks(2).map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]])).collect())
+                }
+            }
+            _ => return Err(ILLEGAL_FUNCTION),
+        };
+
+        if count == 0 || count > max_count(function) {
+            return Err(ILLEGAL_DATA_VALUE);
+        }
+        if address as u32 + count as u32 > 0x10000 {
+            return Err(ILLEGAL_DATA_ADDRESS);
+        }
+
+        Ok(ModbusRequest {
+            function,
+            address,
+            count,
+            values,
+        })
+    }
+
+    // 简短描述，例如 "Read Holding Registers 100 x10"
+    pub fn describe(&self) -> String {
+        match self.function {
+            READ_COILS | READ_DISCRETE_INPUTS | READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => {
+                format!("{} {} x{}", function_name(self.function), self.address, self.count)
+            }
+            _ => format!("{} {} = {:?}", function_name(self.function), self.address, self.values),
+        }
+    }
+}
+
+// 从站应答PDU：读操作返回数据，写操作回显地址和数量
+pub fn encode_response(request: &ModbusRequest, values: &[u16]) -> Vec<u8> {
+    match request.function {
+        READ_COILS | READ_DISCRETE_INPUTS => {
+            let bytes = pack_bits(values);
+            let mut pdu = vec![request.function, bytes.len() as u8];
+            pdu.extend_from_slice(&bytes);
+            pdu
+        }
+        READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => {
+            let mut pdu = vec![request.function, (values.len() * 2) as u8];
+            for value in values {
+                pdu.extend_from_slice(&value.to_be_bytes());
+            }
+            pdu
+        }
+        // 单个写入的应答与请求相同
+        WRITE_SINGLE_COIL | WRITE_SINGLE_REGISTER => request.encode(),
+        _ => {
+            let mut pdu = vec![request.function];
+            pdu.extend_from_slice(&request.address.to_be_bytes());
+            pdu.extend_from_slice(&request.count.to_be_bytes());
+            pdu
+        }
+    }
+}
+
+pub fn encode_exception(function: u8, code: u8) -> Vec<u8> {
+    vec![function | 0x80, code]
+}
+
+// 主站解析的应答
+#[derive(Debug, Clone)]
+pub struct ModbusReply {
+    pub exception: Option<u8>,
+    pub values: Vec<u16>,
+}
+
+// 主站解析应答PDU并与请求核对
+pub fn decode_response(request: &ModbusRequest, pdu: &[u8]) -> Result<ModbusReply, String> {
+    let function = *pdu.first().ok_or("Empty response PDU")?;
+    if function == request.function | 0x80 {
+        let code = *pdu.get(1).ok_or("Exception response without exception code")?;
+        return Ok(ModbusReply {
+            exception: Some(code),
+            values: Vec::new(),
+        });
+    }
+    if function != request.function {
+        return Err(format!(
+            "Function code mismatch: expected 0x{:02X}, got 0x{:02X}",
+            request.function, function
+        ));
+    }
+
+    let values = match function {
+        READ_COILS | READ_DISCRETE_INPUTS | READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => {
+            let byte_count = *pdu.get(1).ok_or("Response is missing byte count")? as usize;
+            let data = pdu
+                .get(2..2 + byte_count)
+                .ok_or_else(|| format!("Response is truncated: byte count {} but {} bytes of data", byte_count, pdu.len().saturating_sub(2)))?;
+            if is_bit_function(function) {
+                if byte_count < (request.count as usize).div_ceil(8) {
+                    return Err(format!("Response has {} bytes, not enough for {} bits", byte_count, request.count));
+                }
+                unpack_bits(data, request.count)
+            } else {
+                if byte_count != request.count as usize * 2 {
+                    return Err(format!("Response has {} bytes, expected {} registers", byte_count, request.count));
+                }
+                data.chunks(2).map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]])).collect()
+            }
+        }
+        _ => {
+            // 写操作应答回显地址和值/数量
+            let address = read_u16(pdu, 1).ok_or("Response is truncated")?;
+            let field = read_u16(pdu, 3).ok_or("Response is truncated")?;
+            if address != request.address {
+                return Err(format!("Response address {} does not match request address {}", address, request.address));
+            }
+            match function {
+                WRITE_SINGLE_COIL => vec![(field == 0xFF00) as u16],
+                WRITE_SINGLE_REGISTER => vec![field],
+                _ => {
+                    if field != request.count {
+                        return Err(format!("Response quantity {} does not match request quantity {}", field, request.count));
+                    }
+                    request.values.clone()
+                }
+            }
+        }
+    };
+
+    Ok(ModbusReply {
+        exception: None,
+        values,
+    })
+}
+
+// 封装MBAP帧
+pub fn encode_frame(transaction_id: u16, unit_id: u8, pdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(MBAP_HEADER_LEN + pdu.len());
+    frame.extend_from_slice(&transaction_id.to_be_bytes());
+    frame.extend_from_slice(&0u16.to_be_bytes());
+    frame.extend_from_slice(&(pdu.len() as u16 + 1).to_be_bytes());
+    frame.push(unit_id);
+    frame.extend_from_slice(pdu);
+    frame
+}
+
+// MBAP帧
+#[derive(Debug, Clone)]
+pub struct ModbusFrame {
+    pub transaction_id: u16,
+    pub unit_id: u8,
+    pub pdu: Vec<u8>,
+}
+
+impl ModbusFrame {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_frame(self.transaction_id, self.unit_id, &self.pdu)
+    }
+}
+
+// 读取一个完整的MBAP帧，连接正常关闭时返回 None
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<ModbusFrame>, String> {
+    let mut header = [0u8; MBAP_HEADER_LEN];
+    match reader.read_exact(&mut header).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(format!("Read error: {}", e)),
+    }
+
+    let transaction_id = u16::from_be_bytes([header[0], header[1]]);
+    let protocol_id = u16::from_be_bytes([header[2], header[3]]);
+    let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+    if protocol_id != 0 {
+        return Err(format!("Invalid MBAP protocol identifier: {}", protocol_id));
+    }
+    // 长度包含单元ID，PDU最长253字节
+    if !(2..=254).contains(&length) {
+        return Err(format!("Invalid MBAP length: {}", length));
+    }
+
+    let mut pdu = vec![0u8; length - 1];
+    reader
+        .read_exact(&mut pdu)
+        .await
+        .map_err(|e| format!("Read error: {}", e))?;
+
+    Ok(Some(ModbusFrame {
+        transaction_id,
+        unit_id: header[6],
+        pdu,
+    }))
+}
+
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join(" ")
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::sync::{mpsc, Mutex, RwLock};
+
+use crate::modbus_codec::{self, ModbusRequest};
+use crate::tcp_server::{TcpServerEvent, TcpServerManager};
+use crate::write_ack::WriteAck;
+
+// 每张表覆盖完整的 0-65535 地址空间
+const TABLE_SIZE: usize = 0x10000;
+
+// Modbus从站寄存器表
+pub struct ModbusDataStore {
+    pub coils: Vec<u16>,
+    pub discrete_inputs: Vec<u16>,
+    pub holding_registers: Vec<u16>,
+    pub input_registers: Vec<u16>,
+}
+
+impl ModbusDataStore {
+    pub fn new() -> Self {
+        ModbusDataStore {
+            coils: vec![0; TABLE_SIZE],
+            discrete_inputs: vec![0; TABLE_SIZE],
+            holding_registers: vec![0; TABLE_SIZE],
+            input_registers: vec![0; TABLE_SIZE],
+        }
+    }
+
+    fn table(&self, table: &str) -> Result<&Vec<u16>, String> {
+        match table {
+            "coils" => Ok(&self.coils),
+            "discrete_inputs" => Ok(&self.discrete_inputs),
+            "holding_registers" => Ok(&self.holding_registers),
+            "input_registers" => Ok(&self.input_registers),
+            other => Err(format!("Unknown Modbus table: {}", other)),
+        }
+    }
+
+    fn table_mut(&mut self, table: &str) -> Result<&mut Vec<u16>, String> {
+        match table {
+            "coils" => Ok(&mut self.coils),
+            "discrete_inputs" => Ok(&mut self.discrete_inputs),
+            "holding_registers" => Ok(&mut self.holding_registers),
+            "input_registers" => Ok(&mut self.input_registers),
+            other => Err(format!("Unknown Modbus table: {}", other)),
+        }
+    }
+
+    pub fn read(&self, table: &str, address: u16, count: u16) -> Result<Vec<u16>, String> {
+        let start = address as usize;
+        let end = start + count as usize;
+        if end > TABLE_SIZE {
+            return Err(format!("Address range {}..{} exceeds 65535", start, end - 1));
+        }
+        Ok(self.table(table)?[start..end].to_vec())
+    }
+
+    pub fn write(&mut self, table: &str, address: u16, values: &[u16]) -> Result<(), String> {
+        let start = address as usize;
+        let end = start + values.len();
+        if end > TABLE_SIZE {
+            return Err(format!("Address range {}..{} exceeds 65535", start, end - 1));
+        }
+        let is_bits = table == "coils" || table == "discrete_inputs";
+        let target = self.table_mut(table)?;
+        for (slot, value) in target[start..end].iter_mut().zip(values) {
+            *slot = if is_bits { (*value != 0) as u16 } else { *value };
+        }
+        Ok(())
+    }
+
+    // 处理一个请求PDU，返回应答PDU、事件类型和事件描述
+    pub fn handle_request(&mut self, pdu: &[u8]) -> (Vec<u8>, &'static str, String) {
+        let function = pdu.first().copied().unwrap_or_default();
+        let request = match ModbusRequest::decode(pdu) {
+            Ok(request) => request,
+            Err(code) => {
+                let message = format!(
+                    "{} (0x{:02X}) rejected: {}",
+                    modbus_codec::function_name(function),
+                    function,
+                    modbus_codec::exception_name(code)
+                );
+                return (modbus_codec::encode_exception(function, code), "modbus_exception", message);
+            }
+        };
+
+        let table = match request.function {
+            modbus_codec::READ_COILS | modbus_codec::WRITE_SINGLE_COIL | modbus_codec::WRITE_MULTIPLE_COILS => "coils",
+            modbus_codec::READ_DISCRETE_INPUTS => "discrete_inputs",
+            modbus_codec::READ_INPUT_REGISTERS => "input_registers",
+            _ => "holding_registers",
+        };
+
+        if request.values.is_empty() {
+            // 读请求，地址范围已在解码时检查
+            let values = self.read(table, request.address, request.count).unwrap_or_default();
+            let message = format!("{}: {:?}", request.describe(), values);
+            (modbus_codec::encode_response(&request, &values), "modbus_read", message)
+        } else {
+            let _ = self.write(table, request.address, &request.values);
+            (modbus_codec::encode_response(&request, &[]), "modbus_write", request.describe())
+        }
+    }
+}
+
+impl Default for ModbusDataStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 读取/修改寄存器表的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusRegistersParams {
+    pub server_id: String,
+    pub table: String, // "coils" / "discrete_inputs" / "holding_registers" / "input_registers"
+    pub address: u16,
+    pub count: Option<u16>,       // 读取时使用，默认为 1
+    pub values: Option<Vec<u16>>, // 写入时使用，线圈以 0/1 表示
+}
+
+fn emit_server_event(app_handle: &Option<tauri::AppHandle>, server_id: &str, client_id: &str, event_type: &str, message: String) {
+    if let Some(app) = app_handle {
+        let event = TcpServerEvent {
+            server_id: server_id.to_string(),
+            event_type: event_type.to_string(),
+            client_id: client_id.to_string(),
+            message,
+            checksum: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+
+        if let Err(e) = app.emit("tcp-server-event", &event) {
+            eprintln!("Failed to emit event to frontend: {}", e);
+        }
+    }
+}
+
+// 从站连接：读取MBAP帧，按寄存器表应答，每次读写都发送事件
+pub async fn serve_connection(
+    mut reader: OwnedReadHalf,
+    responder: mpsc::UnboundedSender<(Vec<u8>, Option<WriteAck>)>,
+    store: Arc<RwLock<ModbusDataStore>>,
+    client_id: String,
+    server_id: String,
+    app_handle: Option<tauri::AppHandle>,
+) {
+    loop {
+        let frame = match modbus_codec::read_frame(&mut reader).await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => {
+                println!("Client {} disconnected", client_id);
+                emit_server_event(&app_handle, &server_id, &client_id, "client_disconnected", "Client disconnected".to_string());
+                break;
+            }
+            Err(e) => {
+                // 帧格式错误时无法再定位后续帧的边界，直接断开
+                eprintln!("Modbus error for client {}: {}", client_id, e);
+                emit_server_event(&app_handle, &server_id, &client_id, "client_disconnected", e);
+                break;
+            }
+        };
+
+        let (pdu, event_type, message) = store.write().await.handle_request(&frame.pdu);
+        let response = modbus_codec::encode_frame(frame.transaction_id, frame.unit_id, &pdu);
+        emit_server_event(
+            &app_handle,
+            &server_id,
+            &client_id,
+            event_type,
+            format!("[unit {} tx {}] {}", frame.unit_id, frame.transaction_id, message),
+        );
+
+        if responder.send((response, None)).is_err() {
+            break;
+        }
+    }
+}
+
+// Tauri命令：读取从站寄存器表
+#[tauri::command]
+pub async fn get_modbus_slave_registers(
+    registers_params: ModbusRegistersParams,
+    state: State<'_, Mutex<TcpServerManager>>,
+) -> Result<Vec<u16>, String> {
+    let manager = state.lock().await;
+    let server = manager
+        .servers
+        .get(&registers_params.server_id)
+        .ok_or_else(|| format!("TCP Server with ID {} not found", registers_params.server_id))?;
+    let store = server
+        .modbus_store
+        .as_ref()
+        .ok_or("Server is not running in Modbus mode")?;
+
+    let values = store
+        .read()
+        .await
+        .read(&registers_params.table, registers_params.address, registers_params.count.unwrap_or(1))?;
+    Ok(values)
+}
+
+// Tauri命令：修改从站寄存器表
+#[tauri::command]
+pub async fn set_modbus_slave_registers(
+    registers_params: ModbusRegistersParams,
+    state: State<'_, Mutex<TcpServerManager>>,
+) -> Result<(), String> {
+    let values = registers_params.values.ok_or("Values are required")?;
+
+    let manager = state.lock().await;
+    let server = manager
+        .servers
+        .get(&registers_params.server_id)
+        .ok_or_else(|| format!("TCP Server with ID {} not found", registers_params.server_id))?;
+    let store = server
+        .modbus_store
+        .as_ref()
+        .ok_or("Server is not running in Modbus mode")?;
+
+    let mut store = store.write().await;
+    store.write(&registers_params.table, registers_params.address, &values)
+}
@@ -0,0 +1,937 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::sync::{mpsc, Mutex, broadcast};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use uuid::Uuid;
+
+use crate::mqtt_codec::{self, ConnectOptions, IncomingPacket, LastWill, MqttProperty, MqttVersion};
+use crate::payload_codec;
+use crate::transport::{self, ClientStream, LocalEndpoint, TlsOptions};
+
+const CONNACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// MQTT客户端连接状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MqttClientState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Error,
+}
+
+// 发送给连接任务的请求
+pub enum MqttOutgoing {
+    Publish { topic: String, payload: Vec<u8>, qos: u8, retain: bool, packet_id: Option<u16> },
+    Subscribe { packet_id: u16, topics: Vec<(String, u8)> },
+    Unsubscribe { packet_id: u16, topics: Vec<String> },
+}
+
+// MQTT客户端
+pub struct MqttClient {
+    pub host: String,
+    pub port: u16,
+    pub transport: String,
+    pub path: String,
+    pub version: MqttVersion,
+    pub client_id: String,
+    pub connect_options: ConnectOptions,
+    pub insecure: bool,
+    pub state: MqttClientState,
+    pub next_packet_id: u16,
+    pub connection_handle: Option<JoinHandle<()>>,
+    pub shutdown_sender: Option<broadcast::Sender<()>>,
+    pub message_sender: Option<mpsc::UnboundedSender<MqttOutgoing>>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// MQTT客户端管理器
+pub struct MqttClientManager {
+    pub clients: HashMap<String, MqttClient>,
+}
+
+impl MqttClientManager {
+    pub fn new() -> Self {
+        MqttClientManager {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl Default for MqttClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 遗嘱消息参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttWillParams {
+    pub topic: String,
+    pub message: String,
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub qos: Option<u8>,
+    pub retain: Option<bool>,
+}
+
+// 连接MQTT服务器的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectMqttClientParams {
+    pub client_id: Option<String>,
+    pub host: String,
+    pub port: u16,
+    pub transport: Option<String>,        // "tcp"、"tls"、"ws" 或 "wss"，默认为 "tcp"
+    pub path: Option<String>,             // WebSocket路径，默认为 "/mqtt"
+    pub protocol_version: Option<String>, // "3.1.1" 或 "5"，默认为 "3.1.1"
+    pub mqtt_client_id: Option<String>,   // CONNECT报文中的客户端标识
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub clean_session: Option<bool>,      // 默认为 true
+    pub keep_alive: Option<u16>,          // 心跳间隔（秒），默认为 60，0表示关闭
+    pub will: Option<MqttWillParams>,
+    pub insecure: Option<bool>,           // 跳过TLS证书校验，默认为 false
+}
+
+// 订阅主题
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttTopicFilter {
+    pub topic: String,
+    pub qos: Option<u8>,
+}
+
+// 订阅的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttSubscribeParams {
+    pub client_id: String,
+    pub topics: Vec<MqttTopicFilter>,
+}
+
+// 取消订阅的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttUnsubscribeParams {
+    pub client_id: String,
+    pub topics: Vec<String>,
+}
+
+// 发布消息的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttPublishParams {
+    pub client_id: String,
+    pub topic: String,
+    pub message: String,
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub qos: Option<u8>,
+    pub retain: Option<bool>,
+}
+
+// 客户端状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttClientInfo {
+    pub client_id: String,
+    pub host: String,
+    pub port: u16,
+    pub transport: String,
+    pub protocol_version: String,
+    pub mqtt_client_id: String,
+    pub state: MqttClientState,
+}
+
+// MQTT客户端事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttClientEvent {
+    pub client_id: String,
+    pub event_type: String,
+    pub message: String,
+    pub topic: Option<String>,
+    pub qos: Option<u8>,
+    pub retain: Option<bool>,
+    pub payload: Option<Vec<u8>>,
+    pub properties: Vec<MqttProperty>,
+    pub timestamp: String,
+}
+
+impl MqttClientEvent {
+    fn new(client_id: &str, event_type: &str, message: String) -> Self {
+        MqttClientEvent {
+            client_id: client_id.to_string(),
+            event_type: event_type.to_string(),
+            message,
+            topic: None,
+            qos: None,
+            retain: None,
+            payload: None,
+            properties: Vec::new(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+fn emit_mqtt_event(app_handle: &Option<tauri::AppHandle>, event: MqttClientEvent) {
+    if let Some(app_handle) = app_handle {
+        let _ = app_handle.emit("mqtt-client-event", &event);
+    }
+}
+
+// 底层连接的读端
+enum MqttReader {
+    Stream(ReadHalf<ClientStream>),
+    WebSocket(SplitStream<WebSocketStream<ClientStream>>),
+}
+
+impl MqttReader {
+    // 读取一段数据，连接关闭时返回 None
+    async fn read_chunk(&mut self, buffer: &mut [u8]) -> Result<Option<Vec<u8>>, String> {
+        match self {
+            MqttReader::Stream(reader) => match reader.read(buffer).await {
+                Ok(0) => Ok(None),
+                Ok(n) => Ok(Some(buffer[..n].to_vec())),
+                Err(e) => Err(format!("Read error: {}", e)),
+            },
+            MqttReader::WebSocket(reader) => loop {
+                match reader.next().await {
+                    Some(Ok(Message::Binary(data))) => return Ok(Some(data)),
+                    Some(Ok(Message::Close(_))) | None => return Ok(None),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(format!("WebSocket error: {}", e)),
+                }
+            },
+        }
+    }
+}
+
+// 底层连接的写端
+enum MqttWriter {
+    Stream(WriteHalf<ClientStream>),
+    WebSocket(SplitSink<WebSocketStream<ClientStream>, Message>),
+}
+
+impl MqttWriter {
+    async fn write_packet(&mut self, packet: Vec<u8>) -> Result<(), String> {
+        match self {
+            MqttWriter::Stream(writer) => {
+                writer.write_all(&packet).await.map_err(|e| format!("Write error: {}", e))?;
+                writer.flush().await.map_err(|e| format!("Write error: {}", e))
+            }
+            MqttWriter::WebSocket(writer) => writer
+                .send(Message::Binary(packet))
+                .await
+                .map_err(|e| format!("WebSocket error: {}", e)),
+        }
+    }
+
+    async fn close(&mut self) {
+        match self {
+            MqttWriter::Stream(writer) => {
+                let _ = writer.shutdown().await;
+            }
+            MqttWriter::WebSocket(writer) => {
+                let _ = writer.close().await;
+            }
+        }
+    }
+}
+
+impl MqttClient {
+    pub fn new(host: String, port: u16, client_id: String, version: MqttVersion, connect_options: ConnectOptions) -> Self {
+        MqttClient {
+            host,
+            port,
+            transport: "tcp".to_string(),
+            path: "/mqtt".to_string(),
+            version,
+            client_id,
+            connect_options,
+            insecure: false,
+            state: MqttClientState::Disconnected,
+            next_packet_id: 0,
+            connection_handle: None,
+            shutdown_sender: None,
+            message_sender: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    // 按传输方式建立底层连接
+    async fn open_connection(&self) -> Result<(MqttReader, MqttWriter), String> {
+        let tcp_stream = transport::connect_tcp(&self.host, self.port, &LocalEndpoint::default()).await?;
+
+        let use_tls = matches!(self.transport.as_str(), "tls" | "wss");
+        let stream = if use_tls {
+            let options = TlsOptions {
+                insecure: self.insecure,
+                ..TlsOptions::default()
+            };
+            ClientStream::Tls(Box::new(transport::start_tls(tcp_stream, &self.host, &options).await?))
+        } else {
+            ClientStream::Plain(tcp_stream)
+        };
+
+        match self.transport.as_str() {
+            "ws" | "wss" => {
+                let host = if self.host.contains(':') && !self.host.starts_with('[') {
+                    format!("[{}]", self.host)
+                } else {
+                    self.host.clone()
+                };
+                let url = format!("{}://{}:{}{}", self.transport, host, self.port, self.path);
+                let mut request = url
+                    .as_str()
+                    .into_client_request()
+                    .map_err(|e| format!("Invalid WebSocket URL {}: {}", url, e))?;
+                request
+                    .headers_mut()
+                    .insert("Sec-WebSocket-Protocol", "mqtt".parse().unwrap());
+
+                let (ws_stream, _) = tokio_tungstenite::client_async(request, stream)
+                    .await
+                    .map_err(|e| format!("WebSocket handshake failed: {}", e))?;
+                let (writer, reader) = ws_stream.split();
+                Ok((MqttReader::WebSocket(reader), MqttWriter::WebSocket(writer)))
+            }
+            _ => {
+                let (reader, writer) = tokio::io::split(stream);
+                Ok((MqttReader::Stream(reader), MqttWriter::Stream(writer)))
+            }
+        }
+    }
+
+    pub async fn connect(&mut self) -> Result<(), String> {
+        if self.state == MqttClientState::Connected {
+            return Err("Already connected".to_string());
+        }
+
+        self.state = MqttClientState::Connecting;
+        match self.handshake().await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.state = MqttClientState::Error;
+                Err(e)
+            }
+        }
+    }
+
+    // 建立连接并完成 CONNECT/CONNACK 交互
+    async fn handshake(&mut self) -> Result<(), String> {
+        let (mut reader, mut writer) = self.open_connection().await?;
+
+        writer
+            .write_packet(mqtt_codec::encode_connect(self.version, &self.connect_options)?)
+            .await?;
+
+        let mut buffer = Vec::new();
+        let mut chunk = vec![0; 4096];
+        let deadline = Instant::now() + CONNACK_TIMEOUT;
+
+        let (session_present, properties) = loop {
+            if let Some((packet, consumed)) = mqtt_codec::decode_packet(&buffer, self.version)? {
+                buffer.drain(..consumed);
+                match packet {
+                    IncomingPacket::ConnAck { session_present, code, properties } => {
+                        if code != 0 {
+                            return Err(format!(
+                                "Connection refused: {} (0x{:02X})",
+                                mqtt_codec::connack_reason(self.version, code),
+                                code
+                            ));
+                        }
+                        break (session_present, properties);
+                    }
+                    _ => return Err("Expected CONNACK from server".to_string()),
+                }
+            }
+
+            match tokio::time::timeout_at(deadline, reader.read_chunk(&mut chunk)).await {
+                Ok(Ok(Some(data))) => buffer.extend(data),
+                Ok(Ok(None)) => return Err("Connection closed before CONNACK".to_string()),
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err("Timed out waiting for CONNACK".to_string()),
+            }
+        };
+
+        self.state = MqttClientState::Connected;
+
+        // 发送连接成功事件
+        let mut event = MqttClientEvent::new(
+            &self.client_id,
+            "connected",
+            format!(
+                "Connected to {}:{} over {} (session present: {})",
+                self.host, self.port, self.transport, session_present
+            ),
+        );
+        event.properties = properties;
+        emit_mqtt_event(&self.app_handle, event);
+
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+        self.shutdown_sender = Some(shutdown_tx);
+        self.message_sender = Some(message_tx);
+
+        let connection = MqttConnection {
+            client_id: self.client_id.clone(),
+            version: self.version,
+            keep_alive: self.connect_options.keep_alive,
+            app_handle: self.app_handle.clone(),
+            buffer,
+            pending_publishes: HashMap::new(),
+            pending_subscribes: HashMap::new(),
+            pending_unsubscribes: HashMap::new(),
+            incoming_qos2: HashSet::new(),
+            topic_aliases: HashMap::new(),
+            last_sent: Instant::now(),
+        };
+        self.connection_handle = Some(tokio::spawn(connection.run(reader, writer, message_rx, shutdown_rx)));
+
+        Ok(())
+    }
+
+    // 连接任务因断线或出错自行结束时已发送断开事件，此后客户端视为已断开
+    fn current_state(&self) -> MqttClientState {
+        match &self.connection_handle {
+            Some(handle) if self.state == MqttClientState::Connected && handle.is_finished() => MqttClientState::Disconnected,
+            _ => self.state.clone(),
+        }
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), String> {
+        if self.current_state() != MqttClientState::Connected {
+            self.connection_handle = None;
+            self.shutdown_sender = None;
+            self.message_sender = None;
+            if self.state == MqttClientState::Connected {
+                self.state = MqttClientState::Disconnected;
+            }
+            return Ok(());
+        }
+
+        // 发送关闭信号，连接任务会发送 DISCONNECT 报文
+        if let Some(shutdown_sender) = &self.shutdown_sender {
+            let _ = shutdown_sender.send(());
+        }
+
+        // 等待任务完成
+        if let Some(connection_handle) = self.connection_handle.take() {
+            let _ = connection_handle.await;
+        }
+
+        self.shutdown_sender = None;
+        self.message_sender = None;
+        self.state = MqttClientState::Disconnected;
+
+        emit_mqtt_event(&self.app_handle, MqttClientEvent::new(&self.client_id, "disconnected", "Disconnected from broker".to_string()));
+
+        Ok(())
+    }
+
+    fn allocate_packet_id(&mut self) -> u16 {
+        self.next_packet_id = self.next_packet_id.wrapping_add(1);
+        if self.next_packet_id == 0 {
+            self.next_packet_id = 1;
+        }
+        self.next_packet_id
+    }
+
+    fn send_outgoing(&self, outgoing: MqttOutgoing) -> Result<(), String> {
+        if self.current_state() != MqttClientState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        if let Some(sender) = &self.message_sender {
+            sender.send(outgoing).map_err(|e| format!("Failed to send message: {}", e.0.describe()))?;
+            Ok(())
+        } else {
+            Err("Message sender not available".to_string())
+        }
+    }
+
+    pub fn publish(&mut self, topic: String, payload: Vec<u8>, qos: u8, retain: bool) -> Result<Option<u16>, String> {
+        if qos > 2 {
+            return Err(format!("Invalid QoS level: {}", qos));
+        }
+        let packet_id = if qos > 0 { Some(self.allocate_packet_id()) } else { None };
+        self.send_outgoing(MqttOutgoing::Publish { topic, payload, qos, retain, packet_id })?;
+        Ok(packet_id)
+    }
+
+    pub fn subscribe(&mut self, topics: Vec<(String, u8)>) -> Result<u16, String> {
+        if topics.is_empty() {
+            return Err("At least one topic is required".to_string());
+        }
+        if let Some((_, qos)) = topics.iter().find(|(_, qos)| *qos > 2) {
+            return Err(format!("Invalid QoS level: {}", qos));
+        }
+        let packet_id = self.allocate_packet_id();
+        self.send_outgoing(MqttOutgoing::Subscribe { packet_id, topics })?;
+        Ok(packet_id)
+    }
+
+    pub fn unsubscribe(&mut self, topics: Vec<String>) -> Result<u16, String> {
+        if topics.is_empty() {
+            return Err("At least one topic is required".to_string());
+        }
+        let packet_id = self.allocate_packet_id();
+        self.send_outgoing(MqttOutgoing::Unsubscribe { packet_id, topics })?;
+        Ok(packet_id)
+    }
+
+    fn info(&self) -> MqttClientInfo {
+        MqttClientInfo {
+            client_id: self.client_id.clone(),
+            host: self.host.clone(),
+            port: self.port,
+            transport: self.transport.clone(),
+            protocol_version: match self.version {
+                MqttVersion::V311 => "3.1.1".to_string(),
+                MqttVersion::V5 => "5".to_string(),
+            },
+            mqtt_client_id: self.connect_options.client_id.clone(),
+            state: self.current_state(),
+        }
+    }
+}
+
+impl MqttOutgoing {
+    fn describe(&self) -> &'static str {
+        match self {
+            MqttOutgoing::Publish { .. } => "channel closed while publishing",
+            MqttOutgoing::Subscribe { .. } => "channel closed while subscribing",
+            MqttOutgoing::Unsubscribe { .. } => "channel closed while unsubscribing",
+        }
+    }
+}
+
+// 连接任务的状态：负责报文收发、QoS确认流程和心跳
+struct MqttConnection {
+    client_id: String,
+    version: MqttVersion,
+    keep_alive: u16,
+    app_handle: Option<tauri::AppHandle>,
+    buffer: Vec<u8>,
+    pending_publishes: HashMap<u16, (String, u8)>,
+    pending_subscribes: HashMap<u16, Vec<String>>,
+    pending_unsubscribes: HashMap<u16, Vec<String>>,
+    incoming_qos2: HashSet<u16>,
+    topic_aliases: HashMap<u16, String>,
+    last_sent: Instant, // 最后一次发送报文的时间，用于安排下一次心跳
+}
+
+impl MqttConnection {
+    fn emit(&self, event_type: &str, message: String) {
+        emit_mqtt_event(&self.app_handle, MqttClientEvent::new(&self.client_id, event_type, message));
+    }
+
+    // 写入报文并记录发送时间
+    async fn write(&mut self, writer: &mut MqttWriter, packet: Vec<u8>) -> Result<(), String> {
+        writer.write_packet(packet).await?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    async fn run(
+        mut self,
+        mut reader: MqttReader,
+        mut writer: MqttWriter,
+        mut message_rx: mpsc::UnboundedReceiver<MqttOutgoing>,
+        mut shutdown_rx: broadcast::Receiver<()>,
+    ) {
+        let keep_alive = Duration::from_secs(self.keep_alive.max(1) as u64);
+        let mut ping_sent: Option<Instant> = None;
+        let mut chunk = vec![0; 8192];
+
+        // CONNACK之后可能已经缓存了其他报文
+        if let Err(e) = self.process_buffer(&mut writer).await {
+            self.emit("disconnected", e);
+            return;
+        }
+
+        let reason = loop {
+            // 距上次发送满一个心跳间隔时发送PINGREQ；已发送PINGREQ时在一个心跳间隔内等待PINGRESP
+            let keep_alive_deadline = match ping_sent {
+                Some(sent_at) => sent_at + keep_alive,
+                None => self.last_sent + keep_alive,
+            };
+
+            tokio::select! {
+                // 检查是否收到关闭信号
+                _ = shutdown_rx.recv() => {
+                    let _ = writer.write_packet(mqtt_codec::encode_disconnect()).await;
+                    writer.close().await;
+                    return;
+                }
+                // 读取数据
+                result = reader.read_chunk(&mut chunk) => {
+                    match result {
+                        Ok(Some(data)) => {
+                            self.buffer.extend(data);
+                            if let Err(e) = self.process_buffer(&mut writer).await {
+                                break e;
+                            }
+                            ping_sent = None;
+                        }
+                        Ok(None) => break "Connection closed by broker".to_string(),
+                        Err(e) => break e,
+                    }
+                }
+                // 发送报文
+                outgoing = message_rx.recv() => {
+                    let outgoing = match outgoing {
+                        Some(outgoing) => outgoing,
+                        None => break "Client dropped".to_string(),
+                    };
+                    if let Err(e) = self.send(&mut writer, outgoing).await {
+                        break e;
+                    }
+                }
+                // 心跳
+                _ = tokio::time::sleep_until(keep_alive_deadline), if self.keep_alive > 0 => {
+                    if ping_sent.is_some() {
+                        break "No PINGRESP received within keep alive interval".to_string();
+                    }
+                    if let Err(e) = self.write(&mut writer, mqtt_codec::encode_pingreq()).await {
+                        break e;
+                    }
+                    ping_sent = Some(self.last_sent);
+                }
+            }
+        };
+
+        self.emit("disconnected", reason);
+    }
+
+    async fn send(&mut self, writer: &mut MqttWriter, outgoing: MqttOutgoing) -> Result<(), String> {
+        match outgoing {
+            MqttOutgoing::Publish { topic, payload, qos, retain, packet_id } => {
+                let packet = mqtt_codec::encode_publish(self.version, &topic, &payload, qos, retain, packet_id)?;
+                self.write(writer, packet).await?;
+                match packet_id {
+                    Some(packet_id) => {
+                        self.pending_publishes.insert(packet_id, (topic, qos));
+                    }
+                    None => {
+                        let mut event = MqttClientEvent::new(&self.client_id, "published", format!("Published to {}", topic));
+                        event.topic = Some(topic);
+                        event.qos = Some(0);
+                        event.retain = Some(retain);
+                        emit_mqtt_event(&self.app_handle, event);
+                    }
+                }
+            }
+            MqttOutgoing::Subscribe { packet_id, topics } => {
+                self.write(writer, mqtt_codec::encode_subscribe(self.version, packet_id, &topics)?).await?;
+                self.pending_subscribes.insert(packet_id, topics.into_iter().map(|(topic, _)| topic).collect());
+            }
+            MqttOutgoing::Unsubscribe { packet_id, topics } => {
+                self.write(writer, mqtt_codec::encode_unsubscribe(self.version, packet_id, &topics)?).await?;
+                self.pending_unsubscribes.insert(packet_id, topics);
+            }
+        }
+        Ok(())
+    }
+
+    // 处理缓冲区中所有完整的报文
+    async fn process_buffer(&mut self, writer: &mut MqttWriter) -> Result<(), String> {
+        while let Some((packet, consumed)) = mqtt_codec::decode_packet(&self.buffer, self.version)? {
+            self.buffer.drain(..consumed);
+            self.handle_packet(writer, packet).await?;
+        }
+        Ok(())
+    }
+
+    fn complete_publish(&mut self, packet_id: u16, code: u8) {
+        if let Some((topic, qos)) = self.pending_publishes.remove(&packet_id) {
+            let event_type = if code < 0x80 { "published" } else { "publish_failed" };
+            let mut event = MqttClientEvent::new(
+                &self.client_id,
+                event_type,
+                format!("Publish {} to {} completed with reason code 0x{:02X}", packet_id, topic, code),
+            );
+            event.topic = Some(topic);
+            event.qos = Some(qos);
+            emit_mqtt_event(&self.app_handle, event);
+        }
+    }
+
+    async fn handle_packet(&mut self, writer: &mut MqttWriter, packet: IncomingPacket) -> Result<(), String> {
+        match packet {
+            IncomingPacket::Publish { dup, qos, retain, topic, packet_id, payload, properties } => {
+                // 处理主题别名（MQTT 5）
+                let alias = properties
+                    .iter()
+                    .find(|property| property.name == "topicAlias")
+                    .and_then(|property| property.value.parse::<u16>().ok());
+                let topic = match alias {
+                    Some(alias) if topic.is_empty() => self
+                        .topic_aliases
+                        .get(&alias)
+                        .cloned()
+                        .ok_or_else(|| format!("Unknown topic alias {}", alias))?,
+                    Some(alias) => {
+                        self.topic_aliases.insert(alias, topic.clone());
+                        topic
+                    }
+                    None => topic,
+                };
+
+                // QoS 2 的重复报文只确认不再上报
+                let duplicate = match (qos, packet_id) {
+                    (1, Some(packet_id)) => {
+                        self.write(writer, mqtt_codec::encode_puback(packet_id)).await?;
+                        false
+                    }
+                    (2, Some(packet_id)) => {
+                        self.write(writer, mqtt_codec::encode_pubrec(packet_id)).await?;
+                        !self.incoming_qos2.insert(packet_id)
+                    }
+                    _ => false,
+                };
+
+                if !duplicate {
+                    let mut event = MqttClientEvent::new(
+                        &self.client_id,
+                        "message_received",
+                        String::from_utf8_lossy(&payload).to_string(),
+                    );
+                    if dup {
+                        event.message = format!("(dup) {}", event.message);
+                    }
+                    event.topic = Some(topic);
+                    event.qos = Some(qos);
+                    event.retain = Some(retain);
+                    event.payload = Some(payload);
+                    event.properties = properties;
+                    emit_mqtt_event(&self.app_handle, event);
+                }
+            }
+            IncomingPacket::PubAck { packet_id, code } | IncomingPacket::PubComp { packet_id, code } => {
+                self.complete_publish(packet_id, code);
+            }
+            IncomingPacket::PubRec { packet_id, code } => {
+                if code >= 0x80 {
+                    self.complete_publish(packet_id, code);
+                } else {
+                    self.write(writer, mqtt_codec::encode_pubrel(packet_id)).await?;
+                }
+            }
+            IncomingPacket::PubRel { packet_id } => {
+                self.incoming_qos2.remove(&packet_id);
+                self.write(writer, mqtt_codec::encode_pubcomp(packet_id)).await?;
+            }
+            IncomingPacket::SubAck { packet_id, codes } => {
+                let topics = self.pending_subscribes.remove(&packet_id).unwrap_or_default();
+                let results = topics
+                    .iter()
+                    .zip(codes.iter())
+                    .map(|(topic, code)| match code {
+                        0..=2 => format!("{} (granted QoS {})", topic, code),
+                        _ => format!("{} (failed 0x{:02X})", topic, code),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                self.emit("subscribed", format!("Subscribed: {}", results));
+            }
+            IncomingPacket::UnsubAck { packet_id, codes } => {
+                // MQTT 3.1.1 的 UNSUBACK 不带原因码
+                let topics = self.pending_unsubscribes.remove(&packet_id).unwrap_or_default();
+                let results = topics
+                    .iter()
+                    .enumerate()
+                    .map(|(index, topic)| match codes.get(index) {
+                        Some(code) if *code >= 0x80 => format!("{} (failed 0x{:02X})", topic, code),
+                        _ => topic.clone(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                self.emit("unsubscribed", format!("Unsubscribed: {}", results));
+            }
+            IncomingPacket::PingResp => {}
+            IncomingPacket::Disconnect { code, properties } => {
+                let mut event = MqttClientEvent::new(
+                    &self.client_id,
+                    "error",
+                    format!("Broker sent DISCONNECT with reason code 0x{:02X}", code),
+                );
+                event.properties = properties;
+                emit_mqtt_event(&self.app_handle, event);
+            }
+            IncomingPacket::ConnAck { .. } => {
+                return Err("Unexpected CONNACK from broker".to_string());
+            }
+            IncomingPacket::Auth => {
+                self.emit("error", "Enhanced authentication is not supported".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+// Tauri命令：连接MQTT服务器
+#[tauri::command]
+pub async fn connect_mqtt_client(
+    connect_params: ConnectMqttClientParams,
+    manager: State<'_, Mutex<MqttClientManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let version = MqttVersion::parse(connect_params.protocol_version.as_deref())?;
+    let transport = connect_params.transport.unwrap_or_else(|| "tcp".to_string());
+    if !matches!(transport.as_str(), "tcp" | "tls" | "ws" | "wss") {
+        return Err(format!("Unsupported transport: {}", transport));
+    }
+
+    let will = match connect_params.will {
+        Some(will) => {
+            let qos = will.qos.unwrap_or(0);
+            if qos > 2 {
+                return Err(format!("Invalid QoS level: {}", qos));
+            }
+            Some(LastWill {
+                topic: will.topic,
+                payload: payload_codec::decode(&will.message, will.message_type.as_deref())?,
+                qos,
+                retain: will.retain.unwrap_or(false),
+            })
+        }
+        None => None,
+    };
+
+    let client_id = connect_params.client_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let connect_options = ConnectOptions {
+        client_id: connect_params
+            .mqtt_client_id
+            .unwrap_or_else(|| format!("socketor-{}", &Uuid::new_v4().simple().to_string()[..8])),
+        username: connect_params.username.filter(|username| !username.is_empty()),
+        password: connect_params.password.filter(|password| !password.is_empty()),
+        clean_session: connect_params.clean_session.unwrap_or(true),
+        keep_alive: connect_params.keep_alive.unwrap_or(60),
+        will,
+    };
+
+    let mut client = MqttClient::new(connect_params.host, connect_params.port, client_id.clone(), version, connect_options);
+    client.transport = transport;
+    if let Some(path) = connect_params.path.filter(|path| !path.is_empty()) {
+        client.path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    }
+    client.insecure = connect_params.insecure.unwrap_or(false);
+    client.set_app_handle(app_handle);
+
+    client.connect().await?;
+
+    let mut manager = manager.lock().await;
+    manager.clients.insert(client_id.clone(), client);
+
+    Ok(client_id)
+}
+
+// Tauri命令：断开MQTT客户端
+#[tauri::command]
+pub async fn disconnect_mqtt_client(
+    client_id: String,
+    manager: State<'_, Mutex<MqttClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(client) = manager.clients.get_mut(&client_id) {
+        client.disconnect().await?;
+        manager.clients.remove(&client_id);
+        Ok(())
+    } else {
+        Err(format!("MQTT client {} not found", client_id))
+    }
+}
+
+// Tauri命令：订阅主题，返回SUBSCRIBE报文ID
+#[tauri::command]
+pub async fn mqtt_subscribe(
+    subscribe_params: MqttSubscribeParams,
+    manager: State<'_, Mutex<MqttClientManager>>,
+) -> Result<u16, String> {
+    let topics = subscribe_params
+        .topics
+        .into_iter()
+        .map(|filter| (filter.topic, filter.qos.unwrap_or(0)))
+        .collect();
+
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&subscribe_params.client_id) {
+        Some(client) => client.subscribe(topics),
+        None => Err(format!("MQTT client {} not found", subscribe_params.client_id)),
+    }
+}
+
+// Tauri命令：取消订阅，返回UNSUBSCRIBE报文ID
+#[tauri::command]
+pub async fn mqtt_unsubscribe(
+    unsubscribe_params: MqttUnsubscribeParams,
+    manager: State<'_, Mutex<MqttClientManager>>,
+) -> Result<u16, String> {
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&unsubscribe_params.client_id) {
+        Some(client) => client.unsubscribe(unsubscribe_params.topics),
+        None => Err(format!("MQTT client {} not found", unsubscribe_params.client_id)),
+    }
+}
+
+// Tauri命令：发布消息，QoS大于0时返回报文ID
+#[tauri::command]
+pub async fn mqtt_publish(
+    publish_params: MqttPublishParams,
+    manager: State<'_, Mutex<MqttClientManager>>,
+) -> Result<Option<u16>, String> {
+    if publish_params.topic.is_empty() {
+        return Err("Topic cannot be empty".to_string());
+    }
+    let payload = payload_codec::decode(&publish_params.message, publish_params.message_type.as_deref())?;
+
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&publish_params.client_id) {
+        Some(client) => client.publish(
+            publish_params.topic,
+            payload,
+            publish_params.qos.unwrap_or(0),
+            publish_params.retain.unwrap_or(false),
+        ),
+        None => Err(format!("MQTT client {} not found", publish_params.client_id)),
+    }
+}
+
+// Tauri命令：获取所有MQTT客户端
+#[tauri::command]
+pub async fn get_mqtt_clients(
+    manager: State<'_, Mutex<MqttClientManager>>,
+) -> Result<Vec<MqttClientInfo>, String> {
+    let manager = manager.lock().await;
+    Ok(manager.clients.values().map(MqttClient::info).collect())
+}
+
+// Tauri命令：获取MQTT客户端信息
+#[tauri::command]
+pub async fn get_mqtt_client_info(
+    client_id: String,
+    manager: State<'_, Mutex<MqttClientManager>>,
+) -> Result<MqttClientInfo, String> {
+    let manager = manager.lock().await;
+    if let Some(client) = manager.clients.get(&client_id) {
+        Ok(client.info())
+    } else {
+        Err(format!("MQTT client {} not found", client_id))
+    }
+}
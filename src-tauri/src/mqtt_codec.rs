@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+
+// MQTT协议版本
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MqttVersion {
+    V311,
+    V5,
+}
+
+impl MqttVersion {
+    pub fn parse(version: Option<&str>) -> Result<Self, String> {
+        match version.unwrap_or("3.1.1") {
+            "3.1.1" | "4" => Ok(MqttVersion::V311),
+            "5" | "5.0" => Ok(MqttVersion::V5),
+            other => Err(format!("Unsupported MQTT version: {}", other)),
+        }
+    }
+
+    fn level(self) -> u8 {
+        match self {
+            MqttVersion::V311 => 4,
+            MqttVersion::V5 => 5,
+        }
+    }
+}
+
+// MQTT 5 属性（以可读形式展示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttProperty {
+    pub name: String,
+    pub value: String,
+}
+
+// 遗嘱消息
+#[derive(Debug, Clone)]
+pub struct LastWill {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub qos: u8,
+    pub retain: bool,
+}
+
+// CONNECT报文的参数
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub clean_session: bool,
+    pub keep_alive: u16,
+    pub will: Option<LastWill>,
+}
+
+// 服务器发送给客户端的报文
+#[derive(Debug, Clone)]
+pub enum IncomingPacket {
+    ConnAck { session_present: bool, code: u8, properties: Vec<MqttProperty> },
+    Publish { dup: bool, qos: u8, retain: bool, topic: String, packet_id: Option<u16>, payload: Vec<u8>, properties: Vec<MqttProperty> },
+    PubAck { packet_id: u16, code: u8 },
+    PubRec { packet_id: u16, code: u8 },
+    PubRel { packet_id: u16 },
+    PubComp { packet_id: u16, code: u8 },
+    SubAck { packet_id: u16, codes: Vec<u8> },
+    UnsubAck { packet_id: u16, codes: Vec<u8> },
+    PingResp,
+    Disconnect { code: u8, properties: Vec<MqttProperty> },
+    Auth,
+}
+
+// CONNACK返回码描述（3.1.1 与 5 的编码不同）
+pub fn connack_reason(version: MqttVersion, code: u8) -> &'static str {
+    match (version, code) {
+        (_, 0x00) => "Connection accepted",
+        (MqttVersion::V311, 0x01) => "Unacceptable protocol version",
+        (MqttVersion::V311, 0x02) => "Identifier rejected",
+        (MqttVersion::V311, 0x03) => "Server unavailable",
+        (MqttVersion::V311, 0x04) => "Bad user name or password",
+        (MqttVersion::V311, 0x05) => "Not authorized",
+        (MqttVersion::V5, 0x80) => "Unspecified error",
+        (MqttVersion::V5, 0x81) => "Malformed packet",
+        (MqttVersion::V5, 0x82) => "Protocol error",
+        (MqttVersion::V5, 0x84) => "Unsupported protocol version",
+        (MqttVersion::V5, 0x85) => "Client identifier not valid",
+        (MqttVersion::V5, 0x86) => "Bad user name or password",
+        (MqttVersion::V5, 0x87) => "Not authorized",
+        (MqttVersion::V5, 0x88) => "Server unavailable",
+        (MqttVersion::V5, 0x89) => "Server busy",
+        (MqttVersion::V5, 0x8A) => "Banned",
+        (MqttVersion::V5, 0x8C) => "Bad authentication method",
+        (MqttVersion::V5, 0x95) => "Packet too large",
+        (MqttVersion::V5, 0x97) => "Quota exceeded",
+        (MqttVersion::V5, 0x9C) => "Use another server",
+        (MqttVersion::V5, 0x9D) => "Server moved",
+        (MqttVersion::V5, 0x9F) => "Connection rate exceeded",
+        _ => "Connection refused",
+    }
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+// 带两字节长度前缀的字段，超过u16::MAX时无法编码
+fn put_bytes(buf: &mut Vec<u8>, name: &str, data: &[u8]) -> Result<(), String> {
+    let length = u16::try_from(data.len()).map_err(|_| format!("{} is too long", name))?;
+    put_u16(buf, length);
+    buf.extend_from_slice(data);
+    Ok(())
+}
+
+// 剩余长度的变长编码最多 4 字节
+const MAX_REMAINING_LENGTH: usize = 268_435_455;
+
+fn put_varint(buf: &mut Vec<u8>, mut value: usize) -> Result<(), String> {
+    if value > MAX_REMAINING_LENGTH {
+        return Err(format!("Packet of {} bytes exceeds the MQTT limit of {} bytes", value, MAX_REMAINING_LENGTH));
+    }
+    loop {
+        let mut byte = (value % 128) as u8;
+        value /= 128;
+        if value > 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+        if value == 0 {
+            return Ok(());
+        }
+    }
+}
+
+// 组装固定头和剩余部分
+fn finish(first_byte: u8, body: Vec<u8>) -> Result<Vec<u8>, String> {
+    let mut packet = vec![first_byte];
+    put_varint(&mut packet, body.len())?;
+    packet.extend(body);
+    Ok(packet)
+}
+
+// MQTT 5 的空属性列表
+fn put_empty_properties(buf: &mut Vec<u8>, version: MqttVersion) {
+    if version == MqttVersion::V5 {
+        buf.push(0);
+    }
+}
+
+pub fn encode_connect(version: MqttVersion, options: &ConnectOptions) -> Result<Vec<u8>, String> {
+    let mut flags = 0u8;
+    if options.clean_session {
+        flags |= 0x02;
+    }
+    if let Some(will) = &options.will {
+        flags |= 0x04 | (will.qos << 3);
+        if will.retain {
+            flags |= 0x20;
+        }
+    }
+    if options.password.is_some() {
+        flags |= 0x40;
+    }
+    if options.username.is_some() {
+        flags |= 0x80;
+    }
+
+    let mut body = Vec::new();
+    put_bytes(&mut body, "Protocol name", b"MQTT")?;
+    body.push(version.level());
+    body.push(flags);
+    put_u16(&mut body, options.keep_alive);
+    put_empty_properties(&mut body, version);
+
+    put_bytes(&mut body, "Client ID", options.client_id.as_bytes())?;
+    if let Some(will) = &options.will {
+        put_empty_properties(&mut body, version);
+        put_bytes(&mut body, "Will topic", will.topic.as_bytes())?;
+        put_bytes(&mut body, "Will payload", &will.payload)?;
+    }
+    if let Some(username) = &options.username {
+        put_bytes(&mut body, "Username", username.as_bytes())?;
+    }
+    if let Some(password) = &options.password {
+        put_bytes(&mut body, "Password", password.as_bytes())?;
+    }
+
+    finish(0x10, body)
+}
+
+pub fn encode_publish(version: MqttVersion, topic: &str, payload: &[u8], qos: u8, retain: bool, packet_id: Option<u16>) -> Result<Vec<u8>, String> {
+    let mut first_byte = 0x30 | (qos << 1);
+    if retain {
+        first_byte |= 0x01;
+    }
+
+    let mut body = Vec::new();
+    put_bytes(&mut body, "Topic", topic.as_bytes())?;
+    if let Some(packet_id) = packet_id {
+        put_u16(&mut body, packet_id);
+    }
+    put_empty_properties(&mut body, version);
+    body.extend_from_slice(payload);
+
+    finish(first_byte, body)
+}
+
+// PUBACK/PUBREC/PUBREL/PUBCOMP，成功时可省略原因码，剩余长度固定为 2
+fn encode_ack(first_byte: u8, packet_id: u16) -> Vec<u8> {
+    let mut packet = vec![first_byte, 2];
+    put_u16(&mut packet, packet_id);
+    packet
+}
+
+pub fn encode_puback(packet_id: u16) -> Vec<u8> {
+    encode_ack(0x40, packet_id)
+}
+
+pub fn encode_pubrec(packet_id: u16) -> Vec<u8> {
+    encode_ack(0x50, packet_id)
+}
+
+pub fn encode_pubrel(packet_id: u16) -> Vec<u8> {
+    encode_ack(0x62, packet_id)
+}
+
+pub fn encode_pubcomp(packet_id: u16) -> Vec<u8> {
+    encode_ack(0x70, packet_id)
+}
+
+pub fn encode_subscribe(version: MqttVersion, packet_id: u16, topics: &[(String, u8)]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    put_u16(&mut body, packet_id);
+    put_empty_properties(&mut body, version);
+    for (topic, qos) in topics {
+        put_bytes(&mut body, "Topic filter", topic.as_bytes())?;
+        body.push(*qos);
+    }
+    finish(0x82, body)
+}
+
+pub fn encode_unsubscribe(version: MqttVersion, packet_id: u16, topics: &[String]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    put_u16(&mut body, packet_id);
+    put_empty_properties(&mut body, version);
+    for topic in topics {
+        put_bytes(&mut body, "Topic filter", topic.as_bytes())?;
+    }
+    finish(0xA2, body)
+}
+
+pub fn encode_pingreq() -> Vec<u8> {
+    vec![0xC0, 0x00]
+}
+
+pub fn encode_disconnect() -> Vec<u8> {
+    vec![0xE0, 0x00]
+}
+
+// 报文读取游标
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.remaining() < len {
+            return Err("Malformed packet: unexpected end of data".to_string());
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut value = 0usize;
+        for shift in 0..4 {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7F) as usize) << (7 * shift);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Malformed packet: variable byte integer too long".to_string())
+    }
+
+    fn binary(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u16()? as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        Ok(String::from_utf8_lossy(self.binary()?).to_string())
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let slice = &self.data[self.pos..];
+        self.pos = self.data.len();
+        slice
+    }
+
+    // 读取 MQTT 5 属性列表
+    fn properties(&mut self, version: MqttVersion) -> Result<Vec<MqttProperty>, String> {
+        if version != MqttVersion::V5 {
+            return Ok(Vec::new());
+        }
+
+        let len = self.varint()?;
+        let mut reader = Reader::new(self.take(len)?);
+        let mut properties = Vec::new();
+
+        while reader.remaining() > 0 {
+            let id = reader.u8()?;
+            let (name, value) = match id {
+                0x01 => ("payloadFormatIndicator", reader.u8()?.to_string()),
+                0x02 => ("messageExpiryInterval", reader.u32()?.to_string()),
+                0x03 => ("contentType", reader.string()?),
+                0x08 => ("responseTopic", reader.string()?),
+                0x09 => ("correlationData", hex::encode(reader.binary()?)),
+                0x0B => ("subscriptionIdentifier", reader.varint()?.to_string()),
+                0x11 => ("sessionExpiryInterval", reader.u32()?.to_string()),
+                0x12 => ("assignedClientIdentifier", reader.string()?),
+                0x13 => ("serverKeepAlive", reader.u16()?.to_string()),
+                0x15 => ("authenticationMethod", reader.string()?),
+                0x16 => ("authenticationData", hex::encode(reader.binary()?)),
+                0x17 => ("requestProblemInformation", reader.u8()?.to_string()),
+                0x18 => ("willDelayInterval", reader.u32()?.to_string()),
+                0x19 => ("requestResponseInformation", reader.u8()?.to_string()),
+                0x1A => ("responseInformation", reader.string()?),
+                0x1C => ("serverReference", reader.string()?),
+                0x1F => ("reasonString", reader.string()?),
+                0x21 => ("receiveMaximum", reader.u16()?.to_string()),
+                0x22 => ("topicAliasMaximum", reader.u16()?.to_string()),
+                0x23 => ("topicAlias", reader.u16()?.to_string()),
+                0x24 => ("maximumQos", reader.u8()?.to_string()),
+                0x25 => ("retainAvailable", reader.u8()?.to_string()),
+                0x26 => {
+                    let key = reader.string()?;
+                    let value = reader.string()?;
+                    ("userProperty", format!("{}={}", key, value))
+                }
+                0x27 => ("maximumPacketSize", reader.u32()?.to_string()),
+                0x28 => ("wildcardSubscriptionAvailable", reader.u8()?.to_string()),
+                0x29 => ("subscriptionIdentifierAvailable", reader.u8()?.to_string()),
+                0x2A => ("sharedSubscriptionAvailable", reader.u8()?.to_string()),
+                other => return Err(format!("Malformed packet: unknown property 0x{:02X}", other)),
+            };
+            properties.push(MqttProperty {
+                name: name.to_string(),
+                value,
+            });
+        }
+
+        Ok(properties)
+    }
+}
+
+// 解析 PUBACK 等确认报文的报文ID和可选的原因码
+fn decode_ack(reader: &mut Reader<'_>, version: MqttVersion) -> Result<(u16, u8), String> {
+    let packet_id = reader.u16()?;
+    let code = if version == MqttVersion::V5 && reader.remaining() > 0 {
+        reader.u8()?
+    } else {
+        0
+    };
+    if version == MqttVersion::V5 && reader.remaining() > 0 {
+        reader.properties(version)?;
+    }
+    Ok((packet_id, code))
+}
+
+// 从缓冲区中解析一个完整报文，数据不足时返回 None
+pub fn decode_packet(buffer: &[u8], version: MqttVersion) -> Result<Option<(IncomingPacket, usize)>, String> {
+    if buffer.len() < 2 {
+        return Ok(None);
+    }
+
+    // 解析剩余长度
+    let mut remaining_length = 0usize;
+    let mut header_length = 1;
+    loop {
+        if header_length > 4 {
+            return Err("Malformed packet: remaining length too long".to_string());
+        }
+        let byte = match buffer.get(header_length) {
+            Some(byte) => *byte,
+            None => return Ok(None),
+        };
+        remaining_length |= ((byte & 0x7F) as usize) << (7 * (header_length - 1));
+        header_length += 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let total_length = header_length + remaining_length;
+    if buffer.len() < total_length {
+        return Ok(None);
+    }
+
+    let first_byte = buffer[0];
+    let mut reader = Reader::new(&buffer[header_length..total_length]);
+
+    let packet = match first_byte >> 4 {
+        2 => {
+            let flags = reader.u8()?;
+            let code = reader.u8()?;
+            let properties = reader.properties(version)?;
+            IncomingPacket::ConnAck { session_present: flags & 0x01 != 0, code, properties }
+        }
+        3 => {
+            let qos = (first_byte >> 1) & 0x03;
+            let topic = reader.string()?;
+            let packet_id = if qos > 0 { Some(reader.u16()?) } else { None };
+            let properties = reader.properties(version)?;
+            IncomingPacket::Publish {
+                dup: first_byte & 0x08 != 0,
+                qos,
+                retain: first_byte & 0x01 != 0,
+                topic,
+                packet_id,
+                payload: reader.rest().to_vec(),
+                properties,
+            }
+        }
+        4 => {
+            let (packet_id, code) = decode_ack(&mut reader, version)?;
+            IncomingPacket::PubAck { packet_id, code }
+        }
+        5 => {
+            let (packet_id, code) = decode_ack(&mut reader, version)?;
+            IncomingPacket::PubRec { packet_id, code }
+        }
+        6 => {
+            let (packet_id, _) = decode_ack(&mut reader, version)?;
+            IncomingPacket::PubRel { packet_id }
+        }
+        7 => {
+            let (packet_id, code) = decode_ack(&mut reader, version)?;
+            IncomingPacket::PubComp { packet_id, code }
+        }
+        9 => {
+            let packet_id = reader.u16()?;
+            reader.properties(version)?;
+            IncomingPacket::SubAck { packet_id, codes: reader.rest().to_vec() }
+        }
+        11 => {
+            let packet_id = reader.u16()?;
+            reader.properties(version)?;
+            IncomingPacket::UnsubAck { packet_id, codes: reader.rest().to_vec() }
+        }
+        13 => IncomingPacket::PingResp,
+        14 => {
+            let code = if reader.remaining() > 0 { reader.u8()? } else { 0 };
+            let properties = if reader.remaining() > 0 { reader.properties(version)? } else { Vec::new() };
+            IncomingPacket::Disconnect { code, properties }
+        }
+        15 => IncomingPacket::Auth,
+        other => return Err(format!("Unexpected packet type {} from server", other)),
+    };
+
+    Ok(Some((packet, total_length)))
+}
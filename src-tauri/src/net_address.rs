@@ -0,0 +1,146 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+// 解析IP地址，支持方括号形式的IPv6字面量以及作用域ID（如 "fe80::1%eth0" 或 "[fe80::1%3]"）
+// 返回地址和作用域ID（无作用域时为0）
+pub fn parse_scoped_ip(text: &str) -> Result<Option<(IpAddr, u32)>, String> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+        .unwrap_or(text);
+
+    let (address, scope) = match text.split_once('%') {
+        Some((address, scope)) => (address, Some(scope)),
+        None => (text, None),
+    };
+
+    let ip: IpAddr = match address.parse() {
+        Ok(ip) => ip,
+        Err(_) => return Ok(None),
+    };
+
+    let scope_id = match scope {
+        None => 0,
+        Some(_) if ip.is_ipv4() => {
+            return Err(format!("Scope ID is only allowed on IPv6 addresses: {}", text));
+        }
+        Some(scope) => match scope.parse::<u32>() {
+            Ok(index) => index,
+            Err(_) => interface_index(scope)?,
+        },
+    };
+
+    Ok(Some((ip, scope_id)))
+}
+
+// 根据网卡名称获取网卡索引
+pub fn interface_index(name: &str) -> Result<u32, String> {
+    let interfaces = if_addrs::get_if_addrs()
+        .map_err(|e| format!("Failed to list network interfaces: {}", e))?;
+
+    interfaces
+        .iter()
+        .find(|interface| interface.name == name)
+        .and_then(|interface| interface.index)
+        .ok_or_else(|| format!("Network interface {} not found", name))
+}
+
+// 根据网卡名称获取其地址，优先使用IPv4地址；IPv6链路本地地址会带上网卡的作用域ID
+pub fn interface_address(name: &str) -> Result<(IpAddr, u32), String> {
+    let interfaces = if_addrs::get_if_addrs()
+        .map_err(|e| format!("Failed to list network interfaces: {}", e))?;
+
+    let candidates: Vec<_> = interfaces
+        .into_iter()
+        .filter(|interface| interface.name == name)
+        .collect();
+
+    if candidates.is_empty() {
+        return Err(format!("Network interface {} not found", name));
+    }
+
+    let interface = candidates
+        .iter()
+        .find(|interface| interface.ip().is_ipv4())
+        .unwrap_or(&candidates[0]);
+
+    let ip = interface.ip();
+    let scope_id = match ip {
+        IpAddr::V6(v6) if is_unicast_link_local(&v6) => interface.index.unwrap_or(0),
+        _ => 0,
+    };
+
+    Ok((ip, scope_id))
+}
+
+// 解析本地绑定地址：IPv4、IPv6（可带作用域ID）、"::"（双栈）或网卡名称，为空时绑定 0.0.0.0
+pub fn resolve_bind_address(bind_address: Option<&str>, port: u16) -> Result<SocketAddr, String> {
+    let bind_address = match bind_address.map(str::trim) {
+        Some(address) if !address.is_empty() => address,
+        _ => return Ok(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port)),
+    };
+
+    let (ip, scope_id) = match parse_scoped_ip(bind_address)? {
+        Some(parsed) => parsed,
+        None => interface_address(bind_address)?,
+    };
+
+    Ok(to_socket_addr(ip, scope_id, port))
+}
+
+// 解析目标地址：IP字面量（可带方括号和作用域ID）或主机名
+// 主机名解析出多个地址时，优先选择与本地套接字相同地址族的地址
+pub async fn resolve_target(host: &str, port: u16, local_addr: Option<SocketAddr>) -> Result<SocketAddr, String> {
+    if let Some((ip, scope_id)) = parse_scoped_ip(host)? {
+        return Ok(to_socket_addr(ip, scope_id, port));
+    }
+
+    let host = host.trim();
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .collect();
+
+    let preferred = local_addr.and_then(|local| {
+        addresses
+            .iter()
+            .find(|address| address.is_ipv4() == local.is_ipv4())
+            .copied()
+    });
+
+    preferred
+        .or_else(|| addresses.first().copied())
+        .ok_or_else(|| format!("No addresses found for {}", host))
+}
+
+// 双栈IPv6套接字发送到IPv4目标时需要使用IPv4映射地址
+pub fn map_to_local_family(target: SocketAddr, local_addr: SocketAddr) -> SocketAddr {
+    match (target, local_addr) {
+        (SocketAddr::V4(v4), SocketAddr::V6(_)) => {
+            SocketAddr::V6(SocketAddrV6::new(v4.ip().to_ipv6_mapped(), v4.port(), 0, 0))
+        }
+        _ => target,
+    }
+}
+
+// 将IPv4映射的IPv6地址还原为IPv4地址用于显示
+pub fn display_addr(addr: SocketAddr) -> String {
+    match addr {
+        SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+            Some(v4) => SocketAddr::new(IpAddr::V4(v4), v6.port()).to_string(),
+            None => addr.to_string(),
+        },
+        SocketAddr::V4(_) => addr.to_string(),
+    }
+}
+
+fn to_socket_addr(ip: IpAddr, scope_id: u32, port: u16) -> SocketAddr {
+    match ip {
+        IpAddr::V4(v4) => SocketAddr::new(IpAddr::V4(v4), port),
+        IpAddr::V6(v6) => SocketAddr::V6(SocketAddrV6::new(v6, port, 0, scope_id)),
+    }
+}
+
+fn is_unicast_link_local(ip: &Ipv6Addr) -> bool {
+    (ip.segments()[0] & 0xffc0) == 0xfe80
+}
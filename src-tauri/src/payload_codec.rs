@@ -0,0 +1,294 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::{DecodeError, Engine};
+
+// 发送内容的输入格式，所有发送命令共用
+//   text     原样按UTF-8编码（默认）
+//   hex      十六进制字节，可用空白、","、":"、"-"、";" 分隔，每组可带 "0x" 前缀，
+//            例如 "02 41 0d"、"0x02,0x41,0x0D"、"02:41:0d"、"02410d"
+//   escaped  带C风格转义的文本：\r \n \t \0 \a \b \f \v \e \\ \" \' \xHH 和八进制 \NNN
+//   base64   标准或URL安全字母表，忽略空白，填充可省略
+//   decimal  以空白、","、";" 分隔的字节值，每个值为 0-255 的十进制数或 "0x" 前缀的十六进制数
+// 解析错误中的位置为从1开始的字符序号
+pub fn decode(message: &str, message_type: Option<&str>) -> Result<Vec<u8>, String> {
+    match message_type.unwrap_or("text") {
+        "" | "text" => Ok(message.as_bytes().to_vec()),
+        "hex" => decode_hex(message),
+        "escaped" => decode_escaped(message),
+        "base64" => decode_base64(message),
+        "decimal" => decode_decimal(message),
+        other => Err(format!("Unsupported message type: {}", other)),
+    }
+}
+
+// 字节偏移对应的字符位置（从1开始）
+fn position(message: &str, offset: usize) -> usize {
+    message[..offset].chars().count() + 1
+}
+
+// 按分隔符切分，返回每段的字节偏移和内容
+fn tokens<'a>(message: &'a str, separators: &[char]) -> Vec<(usize, &'a str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (index, c) in message.char_indices() {
+        let separator = c.is_whitespace() || separators.contains(&c);
+        match (separator, start) {
+            (true, Some(begin)) => {
+                tokens.push((begin, &message[begin..index]));
+                start = None;
+            }
+            (false, None) => start = Some(index),
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push((begin, &message[begin..]));
+    }
+    tokens
+}
+
+fn strip_hex_prefix(token: &str) -> Option<&str> {
+    token.strip_prefix("0x").or_else(|| token.strip_prefix("0X"))
+}
+
+fn decode_hex(message: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for (offset, token) in tokens(message, &[',', ':', '-', ';']) {
+        let (digits_offset, digits) = match strip_hex_prefix(token) {
+            Some(digits) => (offset + 2, digits),
+            None => (offset, token),
+        };
+        if let Some((index, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid hex digit '{}' at position {}", c, position(message, digits_offset + index)));
+        }
+        if digits.is_empty() {
+            return Err(format!("Missing hex digits after \"0x\" at position {}", position(message, offset)));
+        }
+
+        // 带前缀的单个值允许奇数位，左侧补0，例如 0x2 -> 02；不带前缀时必须成对
+        let digits = if digits.len() % 2 == 0 {
+            digits.to_string()
+        } else if digits_offset != offset {
+            format!("0{}", digits)
+        } else {
+            return Err(format!(
+                "Odd number of hex digits in \"{}\" at position {}",
+                token,
+                position(message, offset)
+            ));
+        };
+        bytes.extend(hex::decode(&digits).map_err(|e| format!("Invalid hex string: {}", e))?);
+    }
+    Ok(bytes)
+}
+
+fn decode_decimal(message: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for (offset, token) in tokens(message, &[',', ';']) {
+        let value = match strip_hex_prefix(token) {
+            Some(digits) => u64::from_str_radix(digits, 16),
+            None => token.parse::<u64>(),
+        };
+        match value {
+            Ok(value) if value <= u8::MAX as u64 => bytes.push(value as u8),
+            Ok(_) => {
+                return Err(format!("Byte value {} out of range 0-255 at position {}", token, position(message, offset)));
+            }
+            Err(_) => {
+                return Err(format!("Invalid byte value \"{}\" at position {}", token, position(message, offset)));
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+// 读取最多 max 个指定进制的数字
+fn take_digits(chars: &mut Peekable<CharIndices>, radix: u32, max: usize) -> Vec<u32> {
+    let mut digits = Vec::new();
+    while digits.len() < max {
+        match chars.peek().and_then(|(_, c)| c.to_digit(radix)) {
+            Some(digit) => {
+                digits.push(digit);
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    digits
+}
+
+fn decode_escaped(message: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(message.len());
+    let mut chars = message.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+
+        let (_, escape) = chars
+            .next()
+            .ok_or_else(|| format!("Incomplete escape sequence at position {}", position(message, index)))?;
+        let byte = match escape {
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            'a' => 0x07,
+            'b' => 0x08,
+            'f' => 0x0C,
+            'v' => 0x0B,
+            'e' => 0x1B,
+            '\\' | '\'' | '"' | '?' => escape as u8,
+            'x' => {
+                let digits = take_digits(&mut chars, 16, 2);
+                if digits.is_empty() {
+                    return Err(format!("Missing hex digits in \\x escape at position {}", position(message, index)));
+                }
+                digits.iter().fold(0, |value, digit| value * 16 + digit) as u8
+            }
+            '0'..='7' => {
+                let mut digits = vec![escape as u32 - '0' as u32];
+                digits.extend(take_digits(&mut chars, 8, 2));
+                let value = digits.iter().fold(0, |value, digit| value * 8 + digit);
+                if value > u8::MAX as u32 {
+                    return Err(format!("Octal escape \\{:o} out of range at position {}", value, position(message, index)));
+                }
+                value as u8
+            }
+            other => {
+                return Err(format!("Unknown escape sequence \\{} at position {}", other, position(message, index)));
+            }
+        };
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+fn decode_base64(message: &str) -> Result<Vec<u8>, String> {
+    // 去掉空白（按行折叠的base64），同时记录每个字符在原文中的偏移
+    let (offsets, compact): (Vec<usize>, String) = message.char_indices().filter(|(_, c)| !c.is_whitespace()).unzip();
+    let alphabet = if compact.contains(['-', '_']) { &alphabet::URL_SAFE } else { &alphabet::STANDARD };
+    let engine = GeneralPurpose::new(
+        alphabet,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+
+    // 错误偏移是去掉空白后的字节偏移，换算回原文中的字符位置
+    let locate = |index: usize| {
+        let char_index = compact.get(..index).map(|prefix| prefix.chars().count()).unwrap_or(0);
+        let c = compact.get(index..).and_then(|rest| rest.chars().next()).unwrap_or('?');
+        let offset = offsets.get(char_index).copied().unwrap_or(message.len());
+        (c, position(message, offset))
+    };
+    engine.decode(&compact).map_err(|e| match e {
+        DecodeError::InvalidByte(index, _) => {
+            let (c, position) = locate(index);
+            format!("Invalid base64 character '{}' at position {}", c, position)
+        }
+        DecodeError::InvalidLastSymbol(index, _) => {
+            let (c, position) = locate(index);
+            format!("Invalid trailing base64 character '{}' at position {}", c, position)
+        }
+        DecodeError::InvalidLength(length) => format!("Invalid base64 length: {} symbols", length),
+        DecodeError::InvalidPadding => "Invalid base64 padding".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(message: &str) -> Result<Vec<u8>, String> {
+        decode(message, Some("hex"))
+    }
+
+    fn escaped(message: &str) -> Result<Vec<u8>, String> {
+        decode(message, Some("escaped"))
+    }
+
+    fn base64(message: &str) -> Result<Vec<u8>, String> {
+        decode(message, Some("base64"))
+    }
+
+    #[test]
+    fn text_is_passed_through() {
+        assert_eq!(decode("héllo", None).unwrap(), "héllo".as_bytes());
+        assert_eq!(decode("a", Some("")).unwrap(), b"a");
+        assert_eq!(decode("a", Some("binary")), Err("Unsupported message type: binary".to_string()));
+    }
+
+    #[test]
+    fn hex_accepts_prefixes_and_separators() {
+        let expected = vec![0x02, 0x41, 0x0d];
+        for message in ["02 41 0d", "0x02,0x41,0x0D", "02:41:0d", "02-41-0D", "02;41;0d", "02410d", " 0X02\t41\n0d "] {
+            assert_eq!(hex(message).unwrap(), expected, "{:?}", message);
+        }
+        assert_eq!(hex("0x2 0xabc").unwrap(), [0x02, 0x0a, 0xbc]);
+        assert_eq!(hex("0x0241").unwrap(), [0x02, 0x41]);
+        assert_eq!(hex("").unwrap(), b"");
+    }
+
+    #[test]
+    fn hex_errors_report_positions() {
+        assert_eq!(hex("02 4g"), Err("Invalid hex digit 'g' at position 5".to_string()));
+        assert_eq!(hex("0x02,0xzz"), Err("Invalid hex digit 'z' at position 8".to_string()));
+        // 位置按字符计算，多字节字符只算一个
+        assert_eq!(hex("é 41"), Err("Invalid hex digit 'é' at position 1".to_string()));
+        assert_eq!(hex("ab é"), Err("Invalid hex digit 'é' at position 4".to_string()));
+        assert_eq!(hex("02 abc"), Err("Odd number of hex digits in \"abc\" at position 4".to_string()));
+        assert_eq!(hex("02 0x"), Err("Missing hex digits after \"0x\" at position 4".to_string()));
+    }
+
+    #[test]
+    fn decimal_values() {
+        assert_eq!(decode("2, 65;13 0xff", Some("decimal")).unwrap(), [2, 65, 13, 255]);
+        assert_eq!(decode("1 256", Some("decimal")), Err("Byte value 256 out of range 0-255 at position 3".to_string()));
+        assert_eq!(decode("1,x", Some("decimal")), Err("Invalid byte value \"x\" at position 3".to_string()));
+    }
+
+    #[test]
+    fn escape_sequences() {
+        assert_eq!(escaped(r"a\r\n\t\0").unwrap(), b"a\r\n\t\0");
+        assert_eq!(escaped(r"\a\b\f\v\e").unwrap(), [0x07, 0x08, 0x0c, 0x0b, 0x1b]);
+        assert_eq!(escaped(r#"\\\"\'\?"#).unwrap(), br#"\"'?"#);
+        assert_eq!(escaped(r"\x41\x4a\x7").unwrap(), [0x41, 0x4a, 0x07]);
+        // \x 最多读两位，之后的字符原样保留
+        assert_eq!(escaped(r"\x414").unwrap(), b"A4");
+        assert_eq!(escaped(r"\101\7\0018").unwrap(), [0x41, 0x07, 0x01, b'8']);
+        assert_eq!(escaped(r"\377").unwrap(), [0xff]);
+        assert_eq!(escaped("é\\n").unwrap(), "é\n".as_bytes());
+    }
+
+    #[test]
+    fn escape_errors_report_positions() {
+        assert_eq!(escaped(r"ab\q"), Err(r"Unknown escape sequence \q at position 3".to_string()));
+        assert_eq!(escaped("é\\"), Err("Incomplete escape sequence at position 2".to_string()));
+        assert_eq!(escaped(r"a\xg"), Err(r"Missing hex digits in \x escape at position 2".to_string()));
+        assert_eq!(escaped(r"\400"), Err(r"Octal escape \400 out of range at position 1".to_string()));
+    }
+
+    #[test]
+    fn base64_standard_and_url_safe() {
+        assert_eq!(base64("+/8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(base64("-_8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(base64("SGVsbG8=").unwrap(), b"Hello");
+        // 填充可省略，按行折叠的空白被忽略
+        assert_eq!(base64("SGVsbG8").unwrap(), b"Hello");
+        assert_eq!(base64("SGVs\r\n bG8=").unwrap(), b"Hello");
+        assert_eq!(base64("").unwrap(), b"");
+    }
+
+    #[test]
+    fn base64_errors_report_positions() {
+        // 混用两种字母表时按URL安全字母表解析
+        assert_eq!(base64("+_8="), Err("Invalid base64 character '+' at position 1".to_string()));
+        assert_eq!(base64("SGVs\nbG*="), Err("Invalid base64 character '*' at position 8".to_string()));
+        assert_eq!(base64("SGVsbG9="), Err("Invalid trailing base64 character '9' at position 7".to_string()));
+        assert_eq!(base64("SGVsb"), Err("Invalid base64 length: 5 symbols".to_string()));
+        assert_eq!(base64("SG=Vs"), Err("Invalid base64 character '=' at position 3".to_string()));
+    }
+}
@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+// 进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+// 支持接收记录的连接类型
+const CONNECTION_TYPES: [&str; 5] = ["tcp_client", "tcp_server", "udp_client", "websocket_server", "websocket_client"];
+
+// 开始接收记录的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartReceiveCaptureParams {
+    pub capture_id: Option<String>,
+    pub connection_type: String, // "tcp_client"、"tcp_server"、"udp_client"、"websocket_server" 或 "websocket_client"
+    pub connection_id: String,   // 客户端或服务器ID，连接可以稍后建立
+    pub source: Option<String>,  // 服务器模式下的客户端ID或UDP远端地址，为None则记录所有数据
+    pub path: String,
+    pub format: Option<String>,  // "raw"（默认，原始字节）、"length_prefixed"（4字节大端长度 + 帧）或 "hex_lines"（每帧一行：时间戳、来源、十六进制）
+    pub append: Option<bool>,    // 追加到已有文件，默认覆盖
+    pub max_file_size: Option<u64>, // 单个文件的字节上限，超过后轮转到 name.1.ext、name.2.ext ...
+    pub byte_limit: Option<u64>,    // 记录的接收字节数上限，达到后停止
+    pub idle_timeout_ms: Option<u64>, // 超过该时间未收到数据则停止
+    pub suppress_events: Option<bool>, // 记录的数据不再发送接收事件，避免大量数据涌入消息框
+}
+
+// 接收记录状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiveCaptureInfo {
+    pub capture_id: String,
+    pub connection_type: String,
+    pub connection_id: String,
+    pub source: Option<String>,
+    pub path: String,
+    pub bytes_received: u64,
+}
+
+// 接收记录事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiveCaptureEvent {
+    pub capture_id: String,
+    pub event_type: String, // "started"、"progress"、"rotated"、"completed" 或 "error"
+    pub message: String,
+    pub file_path: String,  // 当前写入的文件
+    pub bytes_received: u64, // 已记录的接收字节数（不含记录格式的额外开销）
+    pub timestamp: String,
+}
+
+// 交给写入任务的接收帧
+struct CapturedFrame {
+    source: String,
+    data: Vec<u8>,
+    received_at: DateTime<Utc>,
+}
+
+// 进行中的接收记录
+pub struct ReceiveCapture {
+    pub capture_id: String,
+    pub connection_type: String,
+    pub connection_id: String,
+    pub source: Option<String>,
+    pub path: String,
+    pub suppress_events: bool,
+    pub bytes_received: Arc<AtomicU64>,
+    frame_sender: mpsc::UnboundedSender<CapturedFrame>,
+    handle: JoinHandle<()>,
+}
+
+// 连接的接收循环持有的记录入口
+// active 为该连接上进行中的记录数，为 0 时无需锁定管理器查找记录
+pub struct CaptureTap {
+    app_handle: Option<tauri::AppHandle>,
+    connection_type: &'static str,
+    connection_id: String,
+    active: Arc<AtomicUsize>,
+}
+
+impl CaptureTap {
+    // 把收到的数据转发给匹配的记录，返回 true 表示调用方不再发送接收事件
+    pub async fn capture(&self, source: &str, data: &[u8]) -> bool {
+        match &self.app_handle {
+            Some(app_handle) if self.active.load(Ordering::Relaxed) > 0 => {
+                capture(app_handle, self.connection_type, &self.connection_id, source, data).await
+            }
+            _ => false,
+        }
+    }
+}
+
+impl ReceiveCapture {
+    fn matches(&self, connection_type: &str, connection_id: &str, source: &str) -> bool {
+        self.connection_type == connection_type
+            && self.connection_id == connection_id
+            && self.source.as_deref().is_none_or(|filter| filter == source)
+    }
+}
+
+// 接收记录管理器
+pub struct ReceiveCaptureManager {
+    pub captures: HashMap<String, ReceiveCapture>,
+    active: HashMap<(String, String), Arc<AtomicUsize>>, // 按连接类型和ID共享的进行中记录数
+}
+
+impl ReceiveCaptureManager {
+    pub fn new() -> Self {
+        ReceiveCaptureManager {
+            captures: HashMap::new(),
+            active: HashMap::new(),
+        }
+    }
+
+    fn active_count(&mut self, connection_type: &str, connection_id: &str) -> Arc<AtomicUsize> {
+        let key = (connection_type.to_string(), connection_id.to_string());
+        Arc::clone(self.active.entry(key).or_default())
+    }
+
+    // 清理已结束的记录，以及连接和记录都已不再持有的计数
+    fn prune(&mut self) {
+        self.captures.retain(|_, capture| !capture.handle.is_finished());
+        self.active.retain(|_, active| Arc::strong_count(active) > 1);
+    }
+}
+
+impl Default for ReceiveCaptureManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 记录格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RecordFormat {
+    Raw,
+    LengthPrefixed,
+    HexLines,
+}
+
+impl RecordFormat {
+    fn parse(format: Option<&str>) -> Result<Self, String> {
+        match format {
+            None | Some("") | Some("raw") => Ok(RecordFormat::Raw),
+            Some("length_prefixed") => Ok(RecordFormat::LengthPrefixed),
+            Some("hex_lines") => Ok(RecordFormat::HexLines),
+            Some(other) => Err(format!("Unsupported capture format: {}", other)),
+        }
+    }
+}
+
+// 轮转文件名：第 n 个文件在扩展名前插入序号，例如 dump.bin -> dump.2.bin
+fn rotated_path(base: &Path, index: u32) -> PathBuf {
+    if index == 0 {
+        return base.to_path_buf();
+    }
+    let stem = base.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let name = match base.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, index),
+    };
+    base.with_file_name(name)
+}
+
+async fn open_file(path: &Path, append: bool) -> Result<(File, u64), String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let size = if append {
+        file.metadata().await.map_err(|e| format!("Failed to read file metadata: {}", e))?.len()
+    } else {
+        0
+    };
+    Ok((file, size))
+}
+
+// 按格式写入文件并处理轮转
+struct CaptureWriter {
+    base_path: PathBuf,
+    format: RecordFormat,
+    max_file_size: Option<u64>,
+    file: BufWriter<File>,
+    file_index: u32,
+    file_size: u64,
+}
+
+impl CaptureWriter {
+    async fn open(base_path: PathBuf, format: RecordFormat, max_file_size: Option<u64>, append: bool) -> Result<Self, String> {
+        // 追加时从已有的最后一个轮转文件继续，之前轮转出的文件不会被覆盖
+        let mut file_index = 0;
+        if append {
+            while tokio::fs::try_exists(rotated_path(&base_path, file_index + 1)).await.unwrap_or(false) {
+                file_index += 1;
+            }
+        }
+        let (file, file_size) = open_file(&rotated_path(&base_path, file_index), append).await?;
+        Ok(CaptureWriter {
+            base_path,
+            format,
+            max_file_size,
+            file: BufWriter::new(file),
+            file_index,
+            file_size,
+        })
+    }
+
+    fn current_path(&self) -> String {
+        rotated_path(&self.base_path, self.file_index).display().to_string()
+    }
+
+    async fn rotate(&mut self) -> Result<(), String> {
+        self.flush().await?;
+        self.file_index += 1;
+        let (file, _) = open_file(&rotated_path(&self.base_path, self.file_index), false).await?;
+        self.file = BufWriter::new(file);
+        self.file_size = 0;
+        Ok(())
+    }
+
+    async fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.file
+            .write_all(bytes)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", self.current_path(), e))?;
+        self.file_size += bytes.len() as u64;
+        Ok(())
+    }
+
+    // 原始字节在文件上限处切分；记录格式不拆分记录，放不下时整条写入新文件
+    async fn write(&mut self, frame: &CapturedFrame) -> Result<(), String> {
+        let record = match self.format {
+            RecordFormat::Raw => {
+                let mut remaining = frame.data.as_slice();
+                while !remaining.is_empty() {
+                    let space = match self.max_file_size {
+                        Some(max) if self.file_size >= max => {
+                            self.rotate().await?;
+                            max
+                        }
+                        Some(max) => max - self.file_size,
+                        None => u64::MAX,
+                    };
+                    let (head, tail) = remaining.split_at(remaining.len().min(space.min(usize::MAX as u64) as usize));
+                    self.write_bytes(head).await?;
+                    remaining = tail;
+                }
+                return Ok(());
+            }
+            RecordFormat::LengthPrefixed => {
+                let mut record = (frame.data.len() as u32).to_be_bytes().to_vec();
+                record.extend_from_slice(&frame.data);
+                record
+            }
+            RecordFormat::HexLines => format!(
+                "{}\t{}\t{}\n",
+                frame.received_at.to_rfc3339(),
+                frame.source,
+                hex::encode_upper(&frame.data)
+            )
+            .into_bytes(),
+        };
+
+        if let Some(max) = self.max_file_size {
+            if self.file_size > 0 && self.file_size + record.len() as u64 > max {
+                self.rotate().await?;
+            }
+        }
+        self.write_bytes(&record).await
+    }
+
+    async fn flush(&mut self) -> Result<(), String> {
+        self.file
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", self.current_path(), e))
+    }
+}
+
+// 记录的停止条件
+struct CaptureLimits {
+    byte_limit: Option<u64>,
+    idle_timeout: Option<Duration>,
+}
+
+fn emit_capture_event(app_handle: &tauri::AppHandle, capture_id: &str, event_type: &str, message: String, file_path: String, bytes_received: u64) {
+    let event = ReceiveCaptureEvent {
+        capture_id: capture_id.to_string(),
+        event_type: event_type.to_string(),
+        message,
+        file_path,
+        bytes_received,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    let _ = app_handle.emit("receive-capture-event", &event);
+}
+
+// 写入任务：从接收循环取出帧写入文件，直到达到字节上限、空闲超时或被停止
+async fn run_capture(
+    app_handle: tauri::AppHandle,
+    capture_id: String,
+    mut writer: CaptureWriter,
+    limits: CaptureLimits,
+    mut frame_rx: mpsc::UnboundedReceiver<CapturedFrame>,
+    bytes_received: Arc<AtomicU64>,
+    active: Arc<AtomicUsize>,
+) {
+    let mut received: u64 = 0;
+    let mut last_progress = Instant::now();
+
+    let result: Result<String, String> = async {
+        loop {
+            let frame = match limits.idle_timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, frame_rx.recv()).await {
+                    Ok(frame) => frame,
+                    Err(_) => return Ok(format!("Idle timeout after {} ms", timeout.as_millis())),
+                },
+                None => frame_rx.recv().await,
+            };
+            let mut frame = match frame {
+                Some(frame) => frame,
+                None => return Ok("Capture stopped".to_string()),
+            };
+
+            // 原始字节按上限截断，记录格式保留完整的最后一帧
+            if let (Some(limit), RecordFormat::Raw) = (limits.byte_limit, writer.format) {
+                frame.data.truncate((limit - received).min(usize::MAX as u64) as usize);
+            }
+
+            let file_index = writer.file_index;
+            writer.write(&frame).await?;
+            received += frame.data.len() as u64;
+            bytes_received.store(received, Ordering::Relaxed);
+
+            if writer.file_index != file_index {
+                emit_capture_event(&app_handle, &capture_id, "rotated", format!("Rotated to {}", writer.current_path()), writer.current_path(), received);
+            }
+            if limits.byte_limit.is_some_and(|limit| received >= limit) {
+                return Ok(format!("Byte limit of {} reached", limits.byte_limit.unwrap_or_default()));
+            }
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                writer.flush().await?;
+                emit_capture_event(&app_handle, &capture_id, "progress", format!("Received {} bytes", received), writer.current_path(), received);
+            }
+        }
+    }
+    .await;
+
+    // 提前关闭通道，让接收循环不再转发数据
+    frame_rx.close();
+    active.fetch_sub(1, Ordering::Relaxed);
+    let result = match result {
+        Ok(reason) => writer.flush().await.map(|_| reason),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(reason) => {
+            let message = format!("{}: {} bytes written to {}", reason, received, writer.current_path());
+            emit_capture_event(&app_handle, &capture_id, "completed", message, writer.current_path(), received);
+        }
+        Err(e) => emit_capture_event(&app_handle, &capture_id, "error", e, writer.current_path(), received),
+    }
+}
+
+// 接收循环开始时调用，取得连接的记录入口
+pub async fn tap(app_handle: &Option<tauri::AppHandle>, connection_type: &'static str, connection_id: &str) -> CaptureTap {
+    let active = match app_handle {
+        Some(app) => app.state::<Mutex<ReceiveCaptureManager>>().lock().await.active_count(connection_type, connection_id),
+        None => Arc::default(),
+    };
+    CaptureTap {
+        app_handle: app_handle.clone(),
+        connection_type,
+        connection_id: connection_id.to_string(),
+        active,
+    }
+}
+
+// 把收到的数据转发给匹配的记录
+// 返回 true 表示数据已被设置了 suppressEvents 的记录接收，调用方不再发送接收事件
+async fn capture(app_handle: &tauri::AppHandle, connection_type: &str, connection_id: &str, source: &str, data: &[u8]) -> bool {
+    let manager = app_handle.state::<Mutex<ReceiveCaptureManager>>();
+    let manager = manager.lock().await;
+    let mut suppress = false;
+    for capture in manager.captures.values() {
+        if !capture.matches(connection_type, connection_id, source) {
+            continue;
+        }
+        let frame = CapturedFrame {
+            source: source.to_string(),
+            data: data.to_vec(),
+            received_at: Utc::now(),
+        };
+        if capture.frame_sender.send(frame).is_ok() {
+            suppress |= capture.suppress_events;
+        }
+    }
+    suppress
+}
+
+// Tauri命令：开始把连接收到的数据写入文件
+#[tauri::command]
+pub async fn start_receive_capture(
+    capture_params: StartReceiveCaptureParams,
+    captures: State<'_, Mutex<ReceiveCaptureManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    if !CONNECTION_TYPES.contains(&capture_params.connection_type.as_str()) {
+        return Err(format!("Unsupported connection type: {}", capture_params.connection_type));
+    }
+    let format = RecordFormat::parse(capture_params.format.as_deref())?;
+    if capture_params.max_file_size == Some(0) {
+        return Err("Max file size must be greater than 0".to_string());
+    }
+    if capture_params.byte_limit == Some(0) {
+        return Err("Byte limit must be greater than 0".to_string());
+    }
+
+    let capture_id = capture_params.capture_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut captures = captures.lock().await;
+    captures.prune();
+    if captures.captures.contains_key(&capture_id) {
+        return Err(format!("Receive capture {} already exists", capture_id));
+    }
+
+    let writer = CaptureWriter::open(
+        PathBuf::from(&capture_params.path),
+        format,
+        capture_params.max_file_size,
+        capture_params.append.unwrap_or(false),
+    )
+    .await?;
+    let limits = CaptureLimits {
+        byte_limit: capture_params.byte_limit,
+        idle_timeout: capture_params.idle_timeout_ms.filter(|ms| *ms > 0).map(Duration::from_millis),
+    };
+
+    emit_capture_event(
+        &app_handle,
+        &capture_id,
+        "started",
+        format!(
+            "Capturing {} {} to {}",
+            capture_params.connection_type, capture_params.connection_id, capture_params.path
+        ),
+        writer.current_path(),
+        0,
+    );
+
+    let bytes_received = Arc::new(AtomicU64::new(0));
+    let active = captures.active_count(&capture_params.connection_type, &capture_params.connection_id);
+    active.fetch_add(1, Ordering::Relaxed);
+    let (frame_tx, frame_rx) = mpsc::unbounded_channel();
+    let handle = tokio::spawn(run_capture(
+        app_handle,
+        capture_id.clone(),
+        writer,
+        limits,
+        frame_rx,
+        Arc::clone(&bytes_received),
+        active,
+    ));
+
+    captures.captures.insert(
+        capture_id.clone(),
+        ReceiveCapture {
+            capture_id: capture_id.clone(),
+            connection_type: capture_params.connection_type,
+            connection_id: capture_params.connection_id,
+            source: capture_params.source,
+            path: capture_params.path,
+            suppress_events: capture_params.suppress_events.unwrap_or(false),
+            bytes_received,
+            frame_sender: frame_tx,
+            handle,
+        },
+    );
+
+    Ok(capture_id)
+}
+
+// Tauri命令：停止接收记录，已收到的数据写完后返回
+#[tauri::command]
+pub async fn stop_receive_capture(
+    capture_id: String,
+    captures: State<'_, Mutex<ReceiveCaptureManager>>,
+) -> Result<(), String> {
+    let capture = {
+        let mut captures = captures.lock().await;
+        captures.prune();
+        captures.captures.remove(&capture_id)
+    };
+    match capture {
+        Some(capture) => {
+            drop(capture.frame_sender);
+            let _ = capture.handle.await;
+            Ok(())
+        }
+        None => Err(format!("Receive capture {} not found", capture_id)),
+    }
+}
+
+// Tauri命令：获取进行中的接收记录
+#[tauri::command]
+pub async fn get_receive_captures(
+    captures: State<'_, Mutex<ReceiveCaptureManager>>,
+) -> Result<Vec<ReceiveCaptureInfo>, String> {
+    let mut captures = captures.lock().await;
+    captures.prune();
+    Ok(captures
+        .captures
+        .values()
+        .map(|capture| ReceiveCaptureInfo {
+            capture_id: capture.capture_id.clone(),
+            connection_type: capture.connection_type.clone(),
+            connection_id: capture.connection_id.clone(),
+            source: capture.source.clone(),
+            path: capture.path.clone(),
+            bytes_received: capture.bytes_received.load(Ordering::Relaxed),
+        })
+        .collect())
+}
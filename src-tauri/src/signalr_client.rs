@@ -0,0 +1,852 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{State, Emitter};
+use tokio::sync::{mpsc, Mutex, RwLock, broadcast};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use url::Url;
+use uuid::Uuid;
+
+use crate::http_client::{self, HttpHeader, SendHttpRequestParams};
+use crate::transport::TlsOptions;
+use crate::websocket_transport::{self, ClientWebSocket, WebSocketConnectOptions};
+
+// 记录分隔符，JSON协议中每条消息都以它结尾
+const RECORD_SEPARATOR: char = '\u{1e}';
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_NEGOTIATE_REDIRECTS: usize = 10;
+
+// Hub消息类型
+const INVOCATION: u64 = 1;
+const STREAM_ITEM: u64 = 2;
+const COMPLETION: u64 = 3;
+const STREAM_INVOCATION: u64 = 4;
+const CANCEL_INVOCATION: u64 = 5;
+const PING: u64 = 6;
+const CLOSE: u64 = 7;
+
+// SignalR客户端连接状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SignalRClientState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Error,
+}
+
+// SignalR客户端
+pub struct SignalRClient {
+    pub client_id: String,
+    pub url: String,
+    pub connection_id: Option<String>,
+    pub state: SignalRClientState,
+    pub subscriptions: Arc<RwLock<HashSet<String>>>,
+    pub next_invocation_id: u64,
+    pub connection_handle: Option<JoinHandle<()>>,
+    pub shutdown_sender: Option<broadcast::Sender<()>>,
+    pub message_sender: Option<mpsc::UnboundedSender<String>>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// SignalR客户端管理器
+pub struct SignalRClientManager {
+    pub clients: HashMap<String, SignalRClient>,
+}
+
+impl SignalRClientManager {
+    pub fn new() -> Self {
+        SignalRClientManager {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl Default for SignalRClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 连接Hub的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectSignalRClientParams {
+    pub client_id: Option<String>,
+    pub url: String,                        // Hub地址，例如 "https://host/chathub"
+    pub access_token: Option<String>,       // 以 Bearer 方式发送
+    pub headers: Option<Vec<HttpHeader>>,
+    pub skip_negotiation: Option<bool>,     // 直接使用WebSocket连接，默认为 false
+    pub subscriptions: Option<Vec<String>>, // 连接时即订阅的客户端方法
+    pub keep_alive_interval_ms: Option<u64>, // 默认为 15000
+    pub server_timeout_ms: Option<u64>,     // 默认为 30000
+    pub insecure: Option<bool>,             // 跳过TLS证书校验，默认为 false
+}
+
+// 调用Hub方法的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalRInvokeParams {
+    pub client_id: String,
+    pub target: String,
+    pub arguments: Option<String>, // JSON数组，例如 "[\"user\", 1]"
+    pub non_blocking: Option<bool>, // true 时不等待结果（相当于 SendAsync），默认为 false
+}
+
+// 订阅客户端方法的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalRSubscriptionParams {
+    pub client_id: String,
+    pub method: String,
+}
+
+// 客户端状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalRClientInfo {
+    pub client_id: String,
+    pub url: String,
+    pub connection_id: Option<String>,
+    pub state: SignalRClientState,
+    pub subscriptions: Vec<String>,
+}
+
+// SignalR客户端事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalRClientEvent {
+    pub client_id: String,
+    pub event_type: String,
+    pub message: String,
+    pub target: Option<String>,
+    pub invocation_id: Option<String>,
+    pub arguments: Option<Value>,
+    pub result: Option<Value>, // 流式调用的数据项或调用结果
+    pub error: Option<String>,
+    pub timestamp: String,
+}
+
+impl SignalRClientEvent {
+    fn new(client_id: &str, event_type: &str, message: String) -> Self {
+        SignalRClientEvent {
+            client_id: client_id.to_string(),
+            event_type: event_type.to_string(),
+            message,
+            target: None,
+            invocation_id: None,
+            arguments: None,
+            result: None,
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+fn emit_signalr_event(app_handle: &Option<tauri::AppHandle>, event: SignalRClientEvent) {
+    if let Some(app_handle) = app_handle {
+        let _ = app_handle.emit("signalr-client-event", &event);
+    }
+}
+
+// negotiate 得到的连接信息
+struct NegotiateResult {
+    url: Url,
+    connection_token: Option<String>,
+    connection_id: Option<String>,
+    access_token: Option<String>,
+}
+
+// 调用 negotiate 接口，处理重定向（如Azure SignalR Service）
+async fn negotiate(
+    url: Url,
+    access_token: Option<String>,
+    headers: &[HttpHeader],
+    insecure: bool,
+) -> Result<NegotiateResult, String> {
+    let mut url = url;
+    let mut access_token = access_token;
+
+    for _ in 0..MAX_NEGOTIATE_REDIRECTS {
+        let mut negotiate_url = url.clone();
+        negotiate_url.set_path(&format!("{}/negotiate", url.path().trim_end_matches('/')));
+        negotiate_url.query_pairs_mut().append_pair("negotiateVersion", "1");
+
+        let mut request_headers = headers.to_vec();
+        request_headers.push(HttpHeader {
+            name: "Content-Length".to_string(),
+            value: "0".to_string(),
+        });
+        if let Some(token) = &access_token {
+            request_headers.push(HttpHeader {
+                name: "Authorization".to_string(),
+                value: format!("Bearer {}", token),
+            });
+        }
+
+        let request = SendHttpRequestParams {
+            request_id: None,
+            method: "POST".to_string(),
+            url: negotiate_url.to_string(),
+            headers: Some(request_headers),
+            body: None,
+            body_type: None,
+            timeout_ms: None,
+            insecure: Some(insecure),
+            local_address: None,
+            local_port: None,
+        };
+        let response = tokio::time::timeout(HANDSHAKE_TIMEOUT, http_client::execute_request(request, String::new()))
+            .await
+            .map_err(|_| "Negotiate request timed out".to_string())??;
+
+        let body = response.body.unwrap_or_default();
+        if response.status_code != 200 {
+            return Err(format!(
+                "Negotiate failed with HTTP {} {}: {}",
+                response.status_code,
+                response.reason,
+                body.trim()
+            ));
+        }
+
+        let negotiate: Value = serde_json::from_str(&body)
+            .map_err(|e| format!("Invalid negotiate response: {}", e))?;
+        if let Some(error) = negotiate["error"].as_str() {
+            return Err(format!("Negotiate failed: {}", error));
+        }
+
+        // 重定向到新的地址
+        if let Some(redirect) = negotiate["url"].as_str() {
+            url = Url::parse(redirect).map_err(|e| format!("Invalid redirect URL {}: {}", redirect, e))?;
+            if let Some(token) = negotiate["accessToken"].as_str() {
+                access_token = Some(token.to_string());
+            }
+            continue;
+        }
+
+        let supports_websockets = negotiate["availableTransports"]
+            .as_array()
+            .map(|transports| {
+                transports.iter().any(|transport| {
+                    transport["transport"] == "WebSockets"
+                        && transport["transferFormats"]
+                            .as_array()
+                            .is_some_and(|formats| formats.iter().any(|format| format == "Text"))
+                })
+            })
+            .unwrap_or(false);
+        if !supports_websockets {
+            return Err("Server does not offer the WebSockets transport with text transfer format".to_string());
+        }
+
+        let connection_id = negotiate["connectionId"].as_str().map(str::to_string);
+        // negotiateVersion 1 使用 connectionToken，旧版本使用 connectionId
+        let connection_token = negotiate["connectionToken"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| connection_id.clone());
+
+        return Ok(NegotiateResult {
+            url,
+            connection_token,
+            connection_id,
+            access_token,
+        });
+    }
+
+    Err("Too many negotiate redirects".to_string())
+}
+
+// 拆分以记录分隔符结尾的消息，未完整的部分保留在缓冲区
+fn split_records(buffer: &mut String) -> Vec<String> {
+    let mut records = Vec::new();
+    while let Some(index) = buffer.find(RECORD_SEPARATOR) {
+        let record: String = buffer.drain(..index + RECORD_SEPARATOR.len_utf8()).collect();
+        let record = record.trim_end_matches(RECORD_SEPARATOR);
+        if !record.trim().is_empty() {
+            records.push(record.to_string());
+        }
+    }
+    records
+}
+
+fn encode_record(message: &Value) -> String {
+    format!("{}{}", message, RECORD_SEPARATOR)
+}
+
+// 解析参数JSON，必须是数组
+fn parse_arguments(arguments: Option<&str>) -> Result<Value, String> {
+    let arguments = arguments.map(str::trim).unwrap_or_default();
+    if arguments.is_empty() {
+        return Ok(json!([]));
+    }
+
+    let value: Value = serde_json::from_str(arguments).map_err(|e| format!("Invalid arguments JSON: {}", e))?;
+    if !value.is_array() {
+        return Err("Arguments must be a JSON array".to_string());
+    }
+    Ok(value)
+}
+
+impl SignalRClient {
+    pub fn new(url: String, client_id: String) -> Self {
+        SignalRClient {
+            client_id,
+            url,
+            connection_id: None,
+            state: SignalRClientState::Disconnected,
+            subscriptions: Arc::new(RwLock::new(HashSet::new())),
+            next_invocation_id: 0,
+            connection_handle: None,
+            shutdown_sender: None,
+            message_sender: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    pub async fn connect(&mut self, params: &ConnectSignalRClientParams) -> Result<(), String> {
+        if self.state == SignalRClientState::Connected {
+            return Err("Already connected".to_string());
+        }
+
+        self.state = SignalRClientState::Connecting;
+        match self.start(params).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.state = SignalRClientState::Error;
+                Err(e)
+            }
+        }
+    }
+
+    async fn start(&mut self, params: &ConnectSignalRClientParams) -> Result<(), String> {
+        let url = Url::parse(self.url.trim()).map_err(|e| format!("Invalid URL {}: {}", self.url, e))?;
+        let headers = params.headers.clone().unwrap_or_default();
+        let insecure = params.insecure.unwrap_or(false);
+        let access_token = params.access_token.clone().filter(|token| !token.is_empty());
+
+        // 协商连接，跳过协商时只能使用WebSocket传输
+        let negotiated = if params.skip_negotiation.unwrap_or(false) {
+            NegotiateResult {
+                url,
+                connection_token: None,
+                connection_id: None,
+                access_token,
+            }
+        } else {
+            negotiate(url, access_token, &headers, insecure).await?
+        };
+
+        let mut ws_url = websocket_transport::to_websocket_url(&negotiated.url)?;
+        if let Some(token) = &negotiated.connection_token {
+            ws_url.query_pairs_mut().append_pair("id", token);
+        }
+
+        let mut options = WebSocketConnectOptions {
+            headers: headers.into_iter().map(|header| (header.name, header.value)).collect(),
+            tls: TlsOptions {
+                insecure,
+                ..TlsOptions::default()
+            },
+            ..WebSocketConnectOptions::default()
+        };
+        if let Some(token) = &negotiated.access_token {
+            options.headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+        }
+
+        let (mut ws_stream, _) = websocket_transport::connect_websocket(ws_url.as_str(), &options).await?;
+
+        // JSON协议握手
+        let handshake = encode_record(&json!({ "protocol": "json", "version": 1 }));
+        ws_stream
+            .send(Message::Text(handshake))
+            .await
+            .map_err(|e| format!("Failed to send handshake: {}", e))?;
+        let buffer = tokio::time::timeout(HANDSHAKE_TIMEOUT, read_handshake_response(&mut ws_stream))
+            .await
+            .map_err(|_| "Timed out waiting for handshake response".to_string())??;
+
+        self.connection_id = negotiated.connection_id;
+        self.state = SignalRClientState::Connected;
+        {
+            let mut subscriptions = self.subscriptions.write().await;
+            for method in params.subscriptions.iter().flatten() {
+                subscriptions.insert(method.to_lowercase());
+            }
+        }
+
+        // 发送连接成功事件
+        emit_signalr_event(
+            &self.app_handle,
+            SignalRClientEvent::new(
+                &self.client_id,
+                "connected",
+                format!(
+                    "Connected to {} (connection id: {})",
+                    negotiated.url,
+                    self.connection_id.as_deref().unwrap_or("none")
+                ),
+            ),
+        );
+
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+        self.shutdown_sender = Some(shutdown_tx);
+        self.message_sender = Some(message_tx);
+
+        let connection = HubConnection {
+            client_id: self.client_id.clone(),
+            subscriptions: self.subscriptions.clone(),
+            keep_alive_interval: Duration::from_millis(params.keep_alive_interval_ms.unwrap_or(15000).max(1000)),
+            server_timeout: Duration::from_millis(params.server_timeout_ms.unwrap_or(30000).max(1000)),
+            app_handle: self.app_handle.clone(),
+            buffer,
+        };
+        self.connection_handle = Some(tokio::spawn(connection.run(ws_stream, message_rx, shutdown_rx)));
+
+        Ok(())
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), String> {
+        if self.state != SignalRClientState::Connected {
+            return Ok(());
+        }
+
+        // 发送关闭信号
+        if let Some(shutdown_sender) = &self.shutdown_sender {
+            let _ = shutdown_sender.send(());
+        }
+
+        // 等待任务完成
+        if let Some(connection_handle) = self.connection_handle.take() {
+            let _ = connection_handle.await;
+        }
+
+        self.shutdown_sender = None;
+        self.message_sender = None;
+        self.state = SignalRClientState::Disconnected;
+
+        emit_signalr_event(&self.app_handle, SignalRClientEvent::new(&self.client_id, "disconnected", "Disconnected from hub".to_string()));
+
+        Ok(())
+    }
+
+    fn allocate_invocation_id(&mut self) -> String {
+        self.next_invocation_id += 1;
+        self.next_invocation_id.to_string()
+    }
+
+    fn send_record(&self, message: Value) -> Result<(), String> {
+        if self.state != SignalRClientState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        if let Some(sender) = &self.message_sender {
+            sender
+                .send(encode_record(&message))
+                .map_err(|_| "Connection closed".to_string())
+        } else {
+            Err("Message sender not available".to_string())
+        }
+    }
+
+    pub fn invoke(&mut self, target: &str, arguments: Value, non_blocking: bool) -> Result<Option<String>, String> {
+        if non_blocking {
+            self.send_record(json!({ "type": INVOCATION, "target": target, "arguments": arguments }))?;
+            return Ok(None);
+        }
+
+        let invocation_id = self.allocate_invocation_id();
+        self.send_record(json!({
+            "type": INVOCATION,
+            "invocationId": invocation_id,
+            "target": target,
+            "arguments": arguments,
+        }))?;
+        Ok(Some(invocation_id))
+    }
+
+    pub fn stream(&mut self, target: &str, arguments: Value) -> Result<String, String> {
+        let invocation_id = self.allocate_invocation_id();
+        self.send_record(json!({
+            "type": STREAM_INVOCATION,
+            "invocationId": invocation_id,
+            "target": target,
+            "arguments": arguments,
+        }))?;
+        Ok(invocation_id)
+    }
+
+    pub fn cancel_invocation(&self, invocation_id: &str) -> Result<(), String> {
+        self.send_record(json!({ "type": CANCEL_INVOCATION, "invocationId": invocation_id }))
+    }
+
+    async fn info(&self) -> SignalRClientInfo {
+        let mut subscriptions: Vec<String> = self.subscriptions.read().await.iter().cloned().collect();
+        subscriptions.sort();
+        SignalRClientInfo {
+            client_id: self.client_id.clone(),
+            url: self.url.clone(),
+            connection_id: self.connection_id.clone(),
+            state: self.state.clone(),
+            subscriptions,
+        }
+    }
+}
+
+// 读取握手响应，返回同一帧中握手之后的剩余数据
+async fn read_handshake_response(ws_stream: &mut ClientWebSocket) -> Result<String, String> {
+    let mut buffer = String::new();
+    loop {
+        match ws_stream.next().await {
+            Some(Ok(Message::Text(text))) => buffer.push_str(&text),
+            Some(Ok(Message::Binary(_))) => return Err("Unexpected binary frame during handshake".to_string()),
+            Some(Ok(Message::Close(frame))) => {
+                let reason = frame.map(|frame| frame.reason.to_string()).unwrap_or_default();
+                return Err(format!("Connection closed during handshake {}", reason).trim().to_string());
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(format!("WebSocket error: {}", e)),
+            None => return Err("Connection closed during handshake".to_string()),
+        }
+
+        if let Some(index) = buffer.find(RECORD_SEPARATOR) {
+            let response: Value = serde_json::from_str(&buffer[..index])
+                .map_err(|e| format!("Invalid handshake response: {}", e))?;
+            if let Some(error) = response["error"].as_str() {
+                return Err(format!("Handshake rejected: {}", error));
+            }
+            return Ok(buffer[index + RECORD_SEPARATOR.len_utf8()..].to_string());
+        }
+    }
+}
+
+// 连接任务：收发Hub消息并维持心跳
+struct HubConnection {
+    client_id: String,
+    subscriptions: Arc<RwLock<HashSet<String>>>,
+    keep_alive_interval: Duration,
+    server_timeout: Duration,
+    app_handle: Option<tauri::AppHandle>,
+    buffer: String,
+}
+
+impl HubConnection {
+    fn emit(&self, event: SignalRClientEvent) {
+        emit_signalr_event(&self.app_handle, event);
+    }
+
+    async fn run(
+        mut self,
+        ws_stream: ClientWebSocket,
+        mut message_rx: mpsc::UnboundedReceiver<String>,
+        mut shutdown_rx: broadcast::Receiver<()>,
+    ) {
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        let mut keep_alive_timer = tokio::time::interval(Duration::from_secs(1));
+        let mut last_sent = Instant::now();
+        let mut last_received = Instant::now();
+
+        let reason = 'connection: loop {
+            // 处理缓冲区中的完整消息（握手之后可能已经带有消息）
+            for record in split_records(&mut self.buffer) {
+                match self.handle_record(&record).await {
+                    Ok(Some(reply)) => {
+                        if let Err(e) = ws_sender.send(Message::Text(reply)).await {
+                            break 'connection format!("WebSocket error: {}", e);
+                        }
+                        last_sent = Instant::now();
+                    }
+                    Ok(None) => {}
+                    Err(reason) => break 'connection reason,
+                }
+            }
+
+            tokio::select! {
+                // 检查是否收到关闭信号
+                _ = shutdown_rx.recv() => {
+                    let _ = ws_sender.close().await;
+                    return;
+                }
+                // 读取消息
+                message = ws_receiver.next() => {
+                    last_received = Instant::now();
+                    match message {
+                        Some(Ok(Message::Text(text))) => self.buffer.push_str(&text),
+                        Some(Ok(Message::Binary(data))) => {
+                            self.emit(SignalRClientEvent::new(
+                                &self.client_id,
+                                "error",
+                                format!("Unexpected binary frame ({} bytes) on JSON protocol", data.len()),
+                            ));
+                        }
+                        Some(Ok(Message::Close(frame))) => {
+                            break match frame {
+                                Some(frame) => format!("Connection closed by server ({}): {}", u16::from(frame.code), frame.reason),
+                                None => "Connection closed by server".to_string(),
+                            };
+                        }
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => break format!("WebSocket error: {}", e),
+                        None => break "Connection closed".to_string(),
+                    }
+                }
+                // 发送消息
+                record = message_rx.recv() => {
+                    let record = match record {
+                        Some(record) => record,
+                        None => break "Client dropped".to_string(),
+                    };
+                    if let Err(e) = ws_sender.send(Message::Text(record)).await {
+                        break format!("WebSocket error: {}", e);
+                    }
+                    last_sent = Instant::now();
+                }
+                // 心跳和服务端超时检测
+                _ = keep_alive_timer.tick() => {
+                    if last_received.elapsed() >= self.server_timeout {
+                        break format!(
+                            "Server timeout elapsed without receiving a message from the server ({} ms)",
+                            self.server_timeout.as_millis()
+                        );
+                    }
+                    if last_sent.elapsed() >= self.keep_alive_interval {
+                        if let Err(e) = ws_sender.send(Message::Text(encode_record(&json!({ "type": PING })))).await {
+                            break format!("WebSocket error: {}", e);
+                        }
+                        last_sent = Instant::now();
+                    }
+                }
+            }
+        };
+
+        let _ = ws_sender.close().await;
+        self.emit(SignalRClientEvent::new(&self.client_id, "disconnected", reason));
+    }
+
+    // 处理一条Hub消息，返回需要回复的消息；返回Err表示连接应当关闭
+    async fn handle_record(&self, record: &str) -> Result<Option<String>, String> {
+        let message: Value = match serde_json::from_str(record) {
+            Ok(message) => message,
+            Err(e) => {
+                self.emit(SignalRClientEvent::new(&self.client_id, "error", format!("Invalid hub message: {}: {}", e, record)));
+                return Ok(None);
+            }
+        };
+
+        let invocation_id = message["invocationId"].as_str().map(str::to_string);
+        match message["type"].as_u64() {
+            Some(INVOCATION) => {
+                let target = message["target"].as_str().unwrap_or_default().to_string();
+                let subscribed = self.subscriptions.read().await.contains(&target.to_lowercase());
+
+                let mut event = if subscribed {
+                    SignalRClientEvent::new(&self.client_id, "invocation", format!("{}({})", target, message["arguments"]))
+                } else {
+                    SignalRClientEvent::new(
+                        &self.client_id,
+                        "unhandled_invocation",
+                        format!("No client method with the name '{}' found", target),
+                    )
+                };
+                event.target = Some(target);
+                event.invocation_id = invocation_id.clone();
+                event.arguments = Some(message["arguments"].clone());
+                self.emit(event);
+
+                // 服务端等待客户端返回结果时，回复错误以免服务端一直等待
+                Ok(invocation_id.map(|invocation_id| {
+                    encode_record(&json!({
+                        "type": COMPLETION,
+                        "invocationId": invocation_id,
+                        "error": "Client didn't provide a result.",
+                    }))
+                }))
+            }
+            Some(STREAM_ITEM) => {
+                let mut event = SignalRClientEvent::new(&self.client_id, "stream_item", message["item"].to_string());
+                event.invocation_id = invocation_id;
+                event.result = Some(message["item"].clone());
+                self.emit(event);
+                Ok(None)
+            }
+            Some(COMPLETION) => {
+                let error = message["error"].as_str().map(str::to_string);
+                let text = match &error {
+                    Some(error) => format!("Invocation failed: {}", error),
+                    None if message.get("result").is_some() => message["result"].to_string(),
+                    None => "Invocation completed".to_string(),
+                };
+                let mut event = SignalRClientEvent::new(&self.client_id, "completion", text);
+                event.invocation_id = invocation_id;
+                event.result = message.get("result").cloned();
+                event.error = error;
+                self.emit(event);
+                Ok(None)
+            }
+            Some(PING) => Ok(None),
+            Some(CLOSE) => {
+                let error = message["error"].as_str().map(str::to_string);
+                let allow_reconnect = message["allowReconnect"].as_bool().unwrap_or(false);
+                let mut event = SignalRClientEvent::new(
+                    &self.client_id,
+                    "close",
+                    format!("Server closed the connection (allow reconnect: {})", allow_reconnect),
+                );
+                event.error = error.clone();
+                self.emit(event);
+                Err(match error {
+                    Some(error) => format!("Server closed the connection with an error: {}", error),
+                    None => "Server closed the connection".to_string(),
+                })
+            }
+            _ => {
+                self.emit(SignalRClientEvent::new(&self.client_id, "error", format!("Unsupported hub message: {}", record)));
+                Ok(None)
+            }
+        }
+    }
+}
+
+// Tauri命令：连接SignalR Hub
+#[tauri::command]
+pub async fn connect_signalr_client(
+    connect_params: ConnectSignalRClientParams,
+    manager: State<'_, Mutex<SignalRClientManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let client_id = connect_params.client_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut client = SignalRClient::new(connect_params.url.clone(), client_id.clone());
+    client.set_app_handle(app_handle);
+
+    client.connect(&connect_params).await?;
+
+    let mut manager = manager.lock().await;
+    manager.clients.insert(client_id.clone(), client);
+
+    Ok(client_id)
+}
+
+// Tauri命令：断开SignalR客户端
+#[tauri::command]
+pub async fn disconnect_signalr_client(
+    client_id: String,
+    manager: State<'_, Mutex<SignalRClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(client) = manager.clients.get_mut(&client_id) {
+        client.disconnect().await?;
+        manager.clients.remove(&client_id);
+        Ok(())
+    } else {
+        Err(format!("SignalR client {} not found", client_id))
+    }
+}
+
+// Tauri命令：调用Hub方法，返回调用ID（non_blocking 时为空）
+#[tauri::command]
+pub async fn signalr_invoke(
+    invoke_params: SignalRInvokeParams,
+    manager: State<'_, Mutex<SignalRClientManager>>,
+) -> Result<Option<String>, String> {
+    let arguments = parse_arguments(invoke_params.arguments.as_deref())?;
+
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&invoke_params.client_id) {
+        Some(client) => client.invoke(&invoke_params.target, arguments, invoke_params.non_blocking.unwrap_or(false)),
+        None => Err(format!("SignalR client {} not found", invoke_params.client_id)),
+    }
+}
+
+// Tauri命令：发起流式调用，返回调用ID
+#[tauri::command]
+pub async fn signalr_stream(
+    invoke_params: SignalRInvokeParams,
+    manager: State<'_, Mutex<SignalRClientManager>>,
+) -> Result<String, String> {
+    let arguments = parse_arguments(invoke_params.arguments.as_deref())?;
+
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&invoke_params.client_id) {
+        Some(client) => client.stream(&invoke_params.target, arguments),
+        None => Err(format!("SignalR client {} not found", invoke_params.client_id)),
+    }
+}
+
+// Tauri命令：取消流式调用
+#[tauri::command]
+pub async fn signalr_cancel_invocation(
+    client_id: String,
+    invocation_id: String,
+    manager: State<'_, Mutex<SignalRClientManager>>,
+) -> Result<(), String> {
+    let manager = manager.lock().await;
+    match manager.clients.get(&client_id) {
+        Some(client) => client.cancel_invocation(&invocation_id),
+        None => Err(format!("SignalR client {} not found", client_id)),
+    }
+}
+
+// Tauri命令：订阅服务端调用的客户端方法（相当于 connection.On）
+#[tauri::command]
+pub async fn signalr_on(
+    subscription_params: SignalRSubscriptionParams,
+    manager: State<'_, Mutex<SignalRClientManager>>,
+) -> Result<(), String> {
+    let method = subscription_params.method.trim().to_lowercase();
+    if method.is_empty() {
+        return Err("Method name cannot be empty".to_string());
+    }
+
+    let manager = manager.lock().await;
+    match manager.clients.get(&subscription_params.client_id) {
+        Some(client) => {
+            client.subscriptions.write().await.insert(method);
+            Ok(())
+        }
+        None => Err(format!("SignalR client {} not found", subscription_params.client_id)),
+    }
+}
+
+// Tauri命令：取消订阅客户端方法（相当于 connection.Off）
+#[tauri::command]
+pub async fn signalr_off(
+    subscription_params: SignalRSubscriptionParams,
+    manager: State<'_, Mutex<SignalRClientManager>>,
+) -> Result<(), String> {
+    let manager = manager.lock().await;
+    match manager.clients.get(&subscription_params.client_id) {
+        Some(client) => {
+            client
+                .subscriptions
+                .write()
+                .await
+                .remove(&subscription_params.method.trim().to_lowercase());
+            Ok(())
+        }
+        None => Err(format!("SignalR client {} not found", subscription_params.client_id)),
+    }
+}
+
+// Tauri命令：获取所有SignalR客户端
+#[tauri::command]
+pub async fn get_signalr_clients(
+    manager: State<'_, Mutex<SignalRClientManager>>,
+) -> Result<Vec<SignalRClientInfo>, String> {
+    let manager = manager.lock().await;
+    let mut clients = Vec::new();
+    for client in manager.clients.values() {
+        clients.push(client.info().await);
+    }
+    Ok(clients)
+}
@@ -0,0 +1,700 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{State, Emitter};
+use tokio::sync::{mpsc, Mutex, RwLock, broadcast};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use url::Url;
+use uuid::Uuid;
+
+use crate::http_client::HttpHeader;
+use crate::socketio_codec::{self, EnginePacket, PacketDecoder, SocketIoPacket};
+use crate::transport::TlsOptions;
+use crate::websocket_transport::{self, ClientWebSocket, WebSocketConnectOptions};
+
+const OPEN_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Socket.IO客户端连接状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SocketIoClientState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Error,
+}
+
+// Socket.IO客户端
+pub struct SocketIoClient {
+    pub client_id: String,
+    pub url: String,
+    pub sid: Option<String>,
+    pub state: SocketIoClientState,
+    pub namespaces: Arc<RwLock<HashSet<String>>>,
+    pub next_ack_id: u64,
+    pub connection_handle: Option<JoinHandle<()>>,
+    pub shutdown_sender: Option<broadcast::Sender<()>>,
+    pub message_sender: Option<mpsc::UnboundedSender<Vec<Message>>>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// Socket.IO客户端管理器
+pub struct SocketIoClientManager {
+    pub clients: HashMap<String, SocketIoClient>,
+}
+
+impl SocketIoClientManager {
+    pub fn new() -> Self {
+        SocketIoClientManager {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl Default for SocketIoClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 连接Socket.IO服务器的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectSocketIoClientParams {
+    pub client_id: Option<String>,
+    pub url: String,                     // 例如 "http://host:3000"
+    pub path: Option<String>,            // 默认为 "/socket.io/"
+    pub namespaces: Option<Vec<String>>, // 连接后加入的命名空间，默认为 ["/"]
+    pub auth: Option<String>,            // CONNECT报文携带的JSON对象
+    pub headers: Option<Vec<HttpHeader>>,
+    pub auto_ack: Option<bool>,          // 服务端事件请求确认时自动回复空确认，默认为 true
+    pub insecure: Option<bool>,          // 跳过TLS证书校验，默认为 false
+}
+
+// 加入/离开命名空间的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketIoNamespaceParams {
+    pub client_id: String,
+    pub namespace: String,
+    pub auth: Option<String>,
+}
+
+// 发送事件的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketIoEmitParams {
+    pub client_id: String,
+    pub namespace: Option<String>, // 默认为 "/"
+    pub event: String,
+    pub arguments: Option<String>, // JSON数组；二进制参数写作 {"$hex": "0102"}
+    pub ack: Option<bool>,         // 是否请求确认，默认为 false
+}
+
+// 回复服务端确认请求的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketIoAckParams {
+    pub client_id: String,
+    pub namespace: Option<String>,
+    pub ack_id: u64,
+    pub arguments: Option<String>,
+}
+
+// 客户端状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketIoClientInfo {
+    pub client_id: String,
+    pub url: String,
+    pub sid: Option<String>,
+    pub state: SocketIoClientState,
+    pub namespaces: Vec<String>,
+}
+
+// Socket.IO客户端事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketIoClientEvent {
+    pub client_id: String,
+    pub event_type: String,
+    pub message: String,
+    pub namespace: Option<String>,
+    pub event: Option<String>,
+    pub arguments: Option<Vec<Value>>,
+    pub ack_id: Option<u64>,
+    pub timestamp: String,
+}
+
+impl SocketIoClientEvent {
+    fn new(client_id: &str, event_type: &str, message: String) -> Self {
+        SocketIoClientEvent {
+            client_id: client_id.to_string(),
+            event_type: event_type.to_string(),
+            message,
+            namespace: None,
+            event: None,
+            arguments: None,
+            ack_id: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+fn emit_socketio_event(app_handle: &Option<tauri::AppHandle>, event: SocketIoClientEvent) {
+    if let Some(app_handle) = app_handle {
+        let _ = app_handle.emit("socketio-client-event", &event);
+    }
+}
+
+fn parse_auth(auth: Option<&str>) -> Result<Option<Value>, String> {
+    match auth.map(str::trim) {
+        Some(auth) if !auth.is_empty() => {
+            let value: Value = serde_json::from_str(auth).map_err(|e| format!("Invalid auth JSON: {}", e))?;
+            if !value.is_object() {
+                return Err("Auth must be a JSON object".to_string());
+            }
+            Ok(Some(value))
+        }
+        _ => Ok(None),
+    }
+}
+
+// Engine.IO 握手参数
+struct OpenInfo {
+    sid: String,
+    ping_interval: Duration,
+    ping_timeout: Duration,
+}
+
+// 等待服务端的 Engine.IO open 报文
+async fn read_open_packet(ws_stream: &mut ClientWebSocket) -> Result<OpenInfo, String> {
+    loop {
+        let text = match ws_stream.next().await {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => return Err("Connection closed during Engine.IO handshake".to_string()),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(format!("WebSocket error: {}", e)),
+        };
+
+        match socketio_codec::decode_engine_packet(&text)? {
+            EnginePacket::Open(open) => {
+                return Ok(OpenInfo {
+                    sid: open["sid"].as_str().unwrap_or_default().to_string(),
+                    ping_interval: Duration::from_millis(open["pingInterval"].as_u64().unwrap_or(25000)),
+                    ping_timeout: Duration::from_millis(open["pingTimeout"].as_u64().unwrap_or(20000)),
+                });
+            }
+            EnginePacket::Noop => continue,
+            _ => return Err(format!("Expected Engine.IO open packet, got: {}", text)),
+        }
+    }
+}
+
+impl SocketIoClient {
+    pub fn new(url: String, client_id: String) -> Self {
+        SocketIoClient {
+            client_id,
+            url,
+            sid: None,
+            state: SocketIoClientState::Disconnected,
+            namespaces: Arc::new(RwLock::new(HashSet::new())),
+            next_ack_id: 0,
+            connection_handle: None,
+            shutdown_sender: None,
+            message_sender: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    pub async fn connect(&mut self, params: &ConnectSocketIoClientParams) -> Result<(), String> {
+        if self.state == SocketIoClientState::Connected {
+            return Err("Already connected".to_string());
+        }
+
+        self.state = SocketIoClientState::Connecting;
+        match self.start(params).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.state = SocketIoClientState::Error;
+                Err(e)
+            }
+        }
+    }
+
+    async fn start(&mut self, params: &ConnectSocketIoClientParams) -> Result<(), String> {
+        let auth = parse_auth(params.auth.as_deref())?;
+
+        // 直接使用WebSocket传输，不经过HTTP长轮询
+        let mut url = Url::parse(self.url.trim()).map_err(|e| format!("Invalid URL {}: {}", self.url, e))?;
+        let path = params.path.as_deref().filter(|path| !path.is_empty()).unwrap_or("/socket.io/");
+        url.set_path(path);
+        url.query_pairs_mut()
+            .append_pair("EIO", "4")
+            .append_pair("transport", "websocket");
+
+        let options = WebSocketConnectOptions {
+            headers: params
+                .headers
+                .iter()
+                .flatten()
+                .map(|header| (header.name.clone(), header.value.clone()))
+                .collect(),
+            tls: TlsOptions {
+                insecure: params.insecure.unwrap_or(false),
+                ..TlsOptions::default()
+            },
+            ..WebSocketConnectOptions::default()
+        };
+        let (mut ws_stream, _) = websocket_transport::connect_websocket(url.as_str(), &options).await?;
+
+        let open = tokio::time::timeout(OPEN_TIMEOUT, read_open_packet(&mut ws_stream))
+            .await
+            .map_err(|_| "Timed out waiting for Engine.IO open packet".to_string())??;
+
+        // 加入命名空间
+        let namespaces = match &params.namespaces {
+            Some(namespaces) if !namespaces.is_empty() => namespaces.clone(),
+            _ => vec!["/".to_string()],
+        };
+        for namespace in &namespaces {
+            let packet = SocketIoPacket::new(socketio_codec::CONNECT, namespace, None, auth.clone());
+            for frame in socketio_codec::encode_packet(packet)? {
+                ws_stream
+                    .send(frame)
+                    .await
+                    .map_err(|e| format!("Failed to connect namespace {}: {}", namespace, e))?;
+            }
+        }
+
+        self.sid = Some(open.sid.clone());
+        self.state = SocketIoClientState::Connected;
+
+        // 发送连接成功事件
+        emit_socketio_event(
+            &self.app_handle,
+            SocketIoClientEvent::new(
+                &self.client_id,
+                "connected",
+                format!(
+                    "Engine.IO connected to {} (sid: {}, ping interval: {} ms)",
+                    self.url,
+                    open.sid,
+                    open.ping_interval.as_millis()
+                ),
+            ),
+        );
+
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+        self.shutdown_sender = Some(shutdown_tx);
+        self.message_sender = Some(message_tx);
+
+        let connection = EngineConnection {
+            client_id: self.client_id.clone(),
+            namespaces: self.namespaces.clone(),
+            ping_deadline: open.ping_interval + open.ping_timeout,
+            auto_ack: params.auto_ack.unwrap_or(true),
+            app_handle: self.app_handle.clone(),
+            decoder: PacketDecoder::default(),
+        };
+        self.connection_handle = Some(tokio::spawn(connection.run(ws_stream, message_rx, shutdown_rx)));
+
+        Ok(())
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), String> {
+        if self.state != SocketIoClientState::Connected {
+            return Ok(());
+        }
+
+        // 发送关闭信号
+        if let Some(shutdown_sender) = &self.shutdown_sender {
+            let _ = shutdown_sender.send(());
+        }
+
+        // 等待任务完成
+        if let Some(connection_handle) = self.connection_handle.take() {
+            let _ = connection_handle.await;
+        }
+
+        self.shutdown_sender = None;
+        self.message_sender = None;
+        self.state = SocketIoClientState::Disconnected;
+
+        emit_socketio_event(&self.app_handle, SocketIoClientEvent::new(&self.client_id, "disconnected", "Disconnected".to_string()));
+
+        Ok(())
+    }
+
+    fn send_packet(&self, packet: SocketIoPacket) -> Result<(), String> {
+        if self.state != SocketIoClientState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        let frames = socketio_codec::encode_packet(packet)?;
+        if let Some(sender) = &self.message_sender {
+            sender.send(frames).map_err(|_| "Connection closed".to_string())
+        } else {
+            Err("Message sender not available".to_string())
+        }
+    }
+
+    pub fn join_namespace(&self, namespace: &str, auth: Option<Value>) -> Result<(), String> {
+        self.send_packet(SocketIoPacket::new(socketio_codec::CONNECT, namespace, None, auth))
+    }
+
+    pub async fn leave_namespace(&self, namespace: &str) -> Result<(), String> {
+        self.send_packet(SocketIoPacket::new(socketio_codec::DISCONNECT, namespace, None, None))?;
+        self.namespaces
+            .write()
+            .await
+            .remove(&socketio_codec::normalize_namespace(namespace));
+        Ok(())
+    }
+
+    pub fn emit(&mut self, namespace: &str, event: &str, arguments: Vec<Value>, ack: bool) -> Result<Option<u64>, String> {
+        let ack_id = if ack {
+            self.next_ack_id += 1;
+            Some(self.next_ack_id)
+        } else {
+            None
+        };
+
+        let data = socketio_codec::event_data(event, arguments);
+        self.send_packet(SocketIoPacket::new(socketio_codec::EVENT, namespace, ack_id, Some(data)))?;
+        Ok(ack_id)
+    }
+
+    pub fn ack(&self, namespace: &str, ack_id: u64, arguments: Vec<Value>) -> Result<(), String> {
+        self.send_packet(SocketIoPacket::new(socketio_codec::ACK, namespace, Some(ack_id), Some(Value::Array(arguments))))
+    }
+
+    async fn info(&self) -> SocketIoClientInfo {
+        let mut namespaces: Vec<String> = self.namespaces.read().await.iter().cloned().collect();
+        namespaces.sort();
+        SocketIoClientInfo {
+            client_id: self.client_id.clone(),
+            url: self.url.clone(),
+            sid: self.sid.clone(),
+            state: self.state.clone(),
+            namespaces,
+        }
+    }
+}
+
+// 连接任务：处理 Engine.IO 心跳和 Socket.IO 报文
+struct EngineConnection {
+    client_id: String,
+    namespaces: Arc<RwLock<HashSet<String>>>,
+    ping_deadline: Duration,
+    auto_ack: bool,
+    app_handle: Option<tauri::AppHandle>,
+    decoder: PacketDecoder,
+}
+
+impl EngineConnection {
+    fn emit(&self, event: SocketIoClientEvent) {
+        emit_socketio_event(&self.app_handle, event);
+    }
+
+    async fn run(
+        mut self,
+        ws_stream: ClientWebSocket,
+        mut message_rx: mpsc::UnboundedReceiver<Vec<Message>>,
+        mut shutdown_rx: broadcast::Receiver<()>,
+    ) {
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        let mut ping_timer = tokio::time::interval(Duration::from_secs(1));
+        let mut last_ping = Instant::now();
+
+        let reason = 'connection: loop {
+            tokio::select! {
+                // 检查是否收到关闭信号
+                _ = shutdown_rx.recv() => {
+                    let namespaces: Vec<String> = self.namespaces.read().await.iter().cloned().collect();
+                    for namespace in namespaces {
+                        let packet = SocketIoPacket::new(socketio_codec::DISCONNECT, &namespace, None, None);
+                        for frame in socketio_codec::encode_packet(packet).unwrap_or_default() {
+                            let _ = ws_sender.send(frame).await;
+                        }
+                    }
+                    let _ = ws_sender.close().await;
+                    return;
+                }
+                // 读取消息
+                message = ws_receiver.next() => {
+                    let packet = match message {
+                        Some(Ok(Message::Text(text))) => match socketio_codec::decode_engine_packet(&text) {
+                            Ok(EnginePacket::Ping(data)) => {
+                                // Engine.IO v4 由服务端发起 ping，客户端回复 pong
+                                last_ping = Instant::now();
+                                if let Err(e) = ws_sender.send(Message::Text(format!("3{}", data))).await {
+                                    break format!("WebSocket error: {}", e);
+                                }
+                                continue;
+                            }
+                            Ok(EnginePacket::Message(data)) => self.decoder.push_text(&data),
+                            Ok(EnginePacket::Close) => break "Server closed the Engine.IO session".to_string(),
+                            Ok(_) => continue,
+                            Err(e) => Err(e),
+                        },
+                        Some(Ok(Message::Binary(data))) => self.decoder.push_binary(data),
+                        Some(Ok(Message::Close(frame))) => {
+                            break match frame {
+                                Some(frame) => format!("Connection closed by server ({}): {}", u16::from(frame.code), frame.reason),
+                                None => "Connection closed by server".to_string(),
+                            };
+                        }
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => break format!("WebSocket error: {}", e),
+                        None => break "Connection closed".to_string(),
+                    };
+
+                    match packet {
+                        Ok(Some(packet)) => {
+                            if let Some(reply) = self.handle_packet(packet).await {
+                                for frame in socketio_codec::encode_packet(reply).unwrap_or_default() {
+                                    if let Err(e) = ws_sender.send(frame).await {
+                                        break 'connection format!("WebSocket error: {}", e);
+                                    }
+                                }
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => self.emit(SocketIoClientEvent::new(&self.client_id, "error", e)),
+                    }
+                }
+                // 发送报文（一个报文可能包含多个帧）
+                frames = message_rx.recv() => {
+                    let frames = match frames {
+                        Some(frames) => frames,
+                        None => break "Client dropped".to_string(),
+                    };
+                    for frame in frames {
+                        if let Err(e) = ws_sender.send(frame).await {
+                            break 'connection format!("WebSocket error: {}", e);
+                        }
+                    }
+                }
+                // 超时未收到服务端ping
+                _ = ping_timer.tick() => {
+                    if last_ping.elapsed() >= self.ping_deadline {
+                        break format!("Ping timeout: no ping received within {} ms", self.ping_deadline.as_millis());
+                    }
+                }
+            }
+        };
+
+        let _ = ws_sender.close().await;
+        self.emit(SocketIoClientEvent::new(&self.client_id, "disconnected", reason));
+    }
+
+    // 处理一个完整的 Socket.IO 报文，返回需要回复的报文
+    async fn handle_packet(&self, packet: SocketIoPacket) -> Option<SocketIoPacket> {
+        let namespace = packet.namespace.clone();
+        match packet.packet_type {
+            socketio_codec::CONNECT => {
+                self.namespaces.write().await.insert(namespace.clone());
+                let sid = packet.data.as_ref().and_then(|data| data["sid"].as_str()).unwrap_or_default();
+                let mut event = SocketIoClientEvent::new(
+                    &self.client_id,
+                    "namespace_connected",
+                    format!("Connected to namespace {} (sid: {})", namespace, sid),
+                );
+                event.namespace = Some(namespace);
+                self.emit(event);
+                None
+            }
+            socketio_codec::DISCONNECT => {
+                self.namespaces.write().await.remove(&namespace);
+                let mut event = SocketIoClientEvent::new(
+                    &self.client_id,
+                    "namespace_disconnected",
+                    format!("Server disconnected namespace {}", namespace),
+                );
+                event.namespace = Some(namespace);
+                self.emit(event);
+                None
+            }
+            socketio_codec::CONNECT_ERROR => {
+                let reason = packet
+                    .data
+                    .as_ref()
+                    .and_then(|data| data["message"].as_str().map(str::to_string).or_else(|| data.as_str().map(str::to_string)))
+                    .unwrap_or_else(|| "Connection refused".to_string());
+                let mut event = SocketIoClientEvent::new(
+                    &self.client_id,
+                    "connect_error",
+                    format!("Namespace {} refused the connection: {}", namespace, reason),
+                );
+                event.namespace = Some(namespace);
+                event.arguments = packet.data.map(|data| vec![data]);
+                self.emit(event);
+                None
+            }
+            socketio_codec::EVENT => {
+                let (name, arguments) = match packet.event() {
+                    Some(event) => event,
+                    None => {
+                        self.emit(SocketIoClientEvent::new(&self.client_id, "error", format!("Invalid event payload: {:?}", packet.data)));
+                        return None;
+                    }
+                };
+                let mut event = SocketIoClientEvent::new(
+                    &self.client_id,
+                    "event",
+                    socketio_codec::describe_event(&namespace, &name, &arguments),
+                );
+                event.namespace = Some(namespace.clone());
+                event.event = Some(name);
+                event.arguments = Some(arguments);
+                event.ack_id = packet.id;
+                self.emit(event);
+
+                match packet.id {
+                    Some(id) if self.auto_ack => Some(SocketIoPacket::new(socketio_codec::ACK, &namespace, Some(id), Some(Value::Array(Vec::new())))),
+                    _ => None,
+                }
+            }
+            socketio_codec::ACK => {
+                let arguments = match packet.data {
+                    Some(Value::Array(items)) => items,
+                    Some(value) => vec![value],
+                    None => Vec::new(),
+                };
+                let mut event = SocketIoClientEvent::new(
+                    &self.client_id,
+                    "ack",
+                    format!("Ack {}: {}", packet.id.unwrap_or_default(), Value::Array(arguments.clone())),
+                );
+                event.namespace = Some(namespace);
+                event.arguments = Some(arguments);
+                event.ack_id = packet.id;
+                self.emit(event);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+// Tauri命令：连接Socket.IO服务器
+#[tauri::command]
+pub async fn connect_socketio_client(
+    connect_params: ConnectSocketIoClientParams,
+    manager: State<'_, Mutex<SocketIoClientManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let client_id = connect_params.client_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut client = SocketIoClient::new(connect_params.url.clone(), client_id.clone());
+    client.set_app_handle(app_handle);
+
+    client.connect(&connect_params).await?;
+
+    let mut manager = manager.lock().await;
+    manager.clients.insert(client_id.clone(), client);
+
+    Ok(client_id)
+}
+
+// Tauri命令：断开Socket.IO客户端
+#[tauri::command]
+pub async fn disconnect_socketio_client(
+    client_id: String,
+    manager: State<'_, Mutex<SocketIoClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(client) = manager.clients.get_mut(&client_id) {
+        client.disconnect().await?;
+        manager.clients.remove(&client_id);
+        Ok(())
+    } else {
+        Err(format!("Socket.IO client {} not found", client_id))
+    }
+}
+
+// Tauri命令：加入命名空间
+#[tauri::command]
+pub async fn socketio_join_namespace(
+    namespace_params: SocketIoNamespaceParams,
+    manager: State<'_, Mutex<SocketIoClientManager>>,
+) -> Result<(), String> {
+    let auth = parse_auth(namespace_params.auth.as_deref())?;
+
+    let manager = manager.lock().await;
+    match manager.clients.get(&namespace_params.client_id) {
+        Some(client) => client.join_namespace(&namespace_params.namespace, auth),
+        None => Err(format!("Socket.IO client {} not found", namespace_params.client_id)),
+    }
+}
+
+// Tauri命令：离开命名空间
+#[tauri::command]
+pub async fn socketio_leave_namespace(
+    namespace_params: SocketIoNamespaceParams,
+    manager: State<'_, Mutex<SocketIoClientManager>>,
+) -> Result<(), String> {
+    let manager = manager.lock().await;
+    match manager.clients.get(&namespace_params.client_id) {
+        Some(client) => client.leave_namespace(&namespace_params.namespace).await,
+        None => Err(format!("Socket.IO client {} not found", namespace_params.client_id)),
+    }
+}
+
+// Tauri命令：发送事件，请求确认时返回确认ID
+#[tauri::command]
+pub async fn socketio_emit(
+    emit_params: SocketIoEmitParams,
+    manager: State<'_, Mutex<SocketIoClientManager>>,
+) -> Result<Option<u64>, String> {
+    if emit_params.event.is_empty() {
+        return Err("Event name cannot be empty".to_string());
+    }
+    let arguments = socketio_codec::parse_arguments(emit_params.arguments.as_deref().unwrap_or_default());
+    let namespace = emit_params.namespace.as_deref().unwrap_or("/");
+
+    let mut manager = manager.lock().await;
+    match manager.clients.get_mut(&emit_params.client_id) {
+        Some(client) => client.emit(namespace, &emit_params.event, arguments, emit_params.ack.unwrap_or(false)),
+        None => Err(format!("Socket.IO client {} not found", emit_params.client_id)),
+    }
+}
+
+// Tauri命令：回复服务端事件的确认请求（auto_ack 关闭时使用）
+#[tauri::command]
+pub async fn socketio_send_ack(
+    ack_params: SocketIoAckParams,
+    manager: State<'_, Mutex<SocketIoClientManager>>,
+) -> Result<(), String> {
+    let arguments = socketio_codec::parse_arguments(ack_params.arguments.as_deref().unwrap_or_default());
+    let namespace = ack_params.namespace.as_deref().unwrap_or("/");
+
+    let manager = manager.lock().await;
+    match manager.clients.get(&ack_params.client_id) {
+        Some(client) => client.ack(namespace, ack_params.ack_id, arguments),
+        None => Err(format!("Socket.IO client {} not found", ack_params.client_id)),
+    }
+}
+
+// Tauri命令：获取所有Socket.IO客户端
+#[tauri::command]
+pub async fn get_socketio_clients(
+    manager: State<'_, Mutex<SocketIoClientManager>>,
+) -> Result<Vec<SocketIoClientInfo>, String> {
+    let manager = manager.lock().await;
+    let mut clients = Vec::new();
+    for client in manager.clients.values() {
+        clients.push(client.info().await);
+    }
+    Ok(clients)
+}
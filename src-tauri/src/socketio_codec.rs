@@ -0,0 +1,305 @@
+use serde_json::{json, Map, Value};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::payload_codec;
+
+// Socket.IO v5 报文类型（Socket.IO v4 使用的协议版本）
+pub const CONNECT: u8 = 0;
+pub const DISCONNECT: u8 = 1;
+pub const EVENT: u8 = 2;
+pub const ACK: u8 = 3;
+pub const CONNECT_ERROR: u8 = 4;
+pub const BINARY_EVENT: u8 = 5;
+pub const BINARY_ACK: u8 = 6;
+
+// 前端用 {"$hex": "0102"} 表示二进制参数，发送时转为附件，接收时附件也还原为这种形式
+const BINARY_KEY: &str = "$hex";
+
+// Engine.IO v4 报文
+#[derive(Debug, Clone)]
+pub enum EnginePacket {
+    Open(Value),
+    Close,
+    Ping(String),
+    Pong,
+    Message(String),
+    Upgrade,
+    Noop,
+}
+
+pub fn decode_engine_packet(text: &str) -> Result<EnginePacket, String> {
+    let mut chars = text.chars();
+    let packet_type = chars.next().ok_or("Empty Engine.IO packet")?;
+    let data = chars.as_str();
+
+    match packet_type {
+        '0' => serde_json::from_str(data)
+            .map(EnginePacket::Open)
+            .map_err(|e| format!("Invalid Engine.IO open packet: {}", e)),
+        '1' => Ok(EnginePacket::Close),
+        '2' => Ok(EnginePacket::Ping(data.to_string())),
+        '3' => Ok(EnginePacket::Pong),
+        '4' => Ok(EnginePacket::Message(data.to_string())),
+        '5' => Ok(EnginePacket::Upgrade),
+        '6' => Ok(EnginePacket::Noop),
+        other => Err(format!("Unknown Engine.IO packet type: {}", other)),
+    }
+}
+
+// Socket.IO 报文
+#[derive(Debug, Clone)]
+pub struct SocketIoPacket {
+    pub packet_type: u8,
+    pub namespace: String,
+    pub id: Option<u64>,
+    pub data: Option<Value>,
+}
+
+impl SocketIoPacket {
+    pub fn new(packet_type: u8, namespace: &str, id: Option<u64>, data: Option<Value>) -> Self {
+        SocketIoPacket {
+            packet_type,
+            namespace: normalize_namespace(namespace),
+            id,
+            data,
+        }
+    }
+
+    // 事件名和参数，仅对 EVENT 报文有效
+    pub fn event(&self) -> Option<(String, Vec<Value>)> {
+        let mut items = self.data.as_ref()?.as_array()?.clone();
+        if items.is_empty() {
+            return None;
+        }
+        let name = items.remove(0);
+        Some((name.as_str()?.to_string(), items))
+    }
+}
+
+pub fn normalize_namespace(namespace: &str) -> String {
+    let namespace = namespace.trim();
+    if namespace.is_empty() {
+        "/".to_string()
+    } else if namespace.starts_with('/') {
+        namespace.to_string()
+    } else {
+        format!("/{}", namespace)
+    }
+}
+
+// 把 {"$hex": ...} 替换为附件占位符，返回附件列表
+fn deconstruct(value: Value, attachments: &mut Vec<Vec<u8>>) -> Result<Value, String> {
+    match value {
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| deconstruct(item, attachments))
+            .collect::<Result<Vec<Value>, String>>()
+            .map(Value::Array),
+        Value::Object(map) => {
+            if map.len() == 1 {
+                if let Some(Value::String(hex_str)) = map.get(BINARY_KEY) {
+                    let bytes = payload_codec::decode(hex_str, Some("hex"))
+                        .map_err(|e| format!("Invalid hex in binary argument: {}", e))?;
+                    attachments.push(bytes);
+                    return Ok(json!({ "_placeholder": true, "num": attachments.len() - 1 }));
+                }
+            }
+            map.into_iter()
+                .map(|(key, item)| deconstruct(item, attachments).map(|item| (key, item)))
+                .collect::<Result<Map<String, Value>, String>>()
+                .map(Value::Object)
+        }
+        other => Ok(other),
+    }
+}
+
+// 把附件占位符还原为 {"$hex": ...}
+fn reconstruct(value: Value, attachments: &[Vec<u8>]) -> Result<Value, String> {
+    match value {
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| reconstruct(item, attachments))
+            .collect::<Result<Vec<Value>, String>>()
+            .map(Value::Array),
+        Value::Object(map) => {
+            if map.get("_placeholder") == Some(&Value::Bool(true)) {
+                let index = map.get("num").and_then(Value::as_u64).ok_or("Invalid attachment placeholder")? as usize;
+                let bytes = attachments
+                    .get(index)
+                    .ok_or_else(|| format!("Attachment {} is missing", index))?;
+                return Ok(json!({ BINARY_KEY: hex::encode(bytes) }));
+            }
+            map.into_iter()
+                .map(|(key, item)| reconstruct(item, attachments).map(|item| (key, item)))
+                .collect::<Result<Map<String, Value>, String>>()
+                .map(Value::Object)
+        }
+        other => Ok(other),
+    }
+}
+
+// 编码为WebSocket帧：一个文本帧，带二进制附件时后跟若干二进制帧
+pub fn encode_packet(packet: SocketIoPacket) -> Result<Vec<Message>, String> {
+    let mut attachments = Vec::new();
+    let data = match packet.data {
+        Some(data) => Some(deconstruct(data, &mut attachments)?),
+        None => None,
+    };
+
+    let packet_type = match packet.packet_type {
+        EVENT if !attachments.is_empty() => BINARY_EVENT,
+        ACK if !attachments.is_empty() => BINARY_ACK,
+        packet_type => packet_type,
+    };
+
+    // Engine.IO message 报文前缀 "4"
+    let mut text = format!("4{}", packet_type);
+    if !attachments.is_empty() {
+        text.push_str(&format!("{}-", attachments.len()));
+    }
+    if packet.namespace != "/" {
+        text.push_str(&packet.namespace);
+        text.push(',');
+    }
+    if let Some(id) = packet.id {
+        text.push_str(&id.to_string());
+    }
+    if let Some(data) = data {
+        text.push_str(&data.to_string());
+    }
+
+    let mut frames = vec![Message::Text(text)];
+    frames.extend(attachments.into_iter().map(Message::Binary));
+    Ok(frames)
+}
+
+// 解析 Socket.IO 报文文本，返回报文和附件数量
+fn parse_packet(text: &str) -> Result<(SocketIoPacket, usize), String> {
+    let bytes = text.as_bytes();
+    let packet_type = match bytes.first() {
+        Some(digit @ b'0'..=b'6') => digit - b'0',
+        Some(_) => return Err(format!("Invalid Socket.IO packet type at position 0: {}", text)),
+        None => return Err("Empty Socket.IO packet".to_string()),
+    };
+    let mut position = 1;
+
+    let mut attachments = 0;
+    if packet_type == BINARY_EVENT || packet_type == BINARY_ACK {
+        let end = text[position..]
+            .find('-')
+            .map(|offset| position + offset)
+            .ok_or_else(|| format!("Missing attachment count in binary packet: {}", text))?;
+        attachments = text[position..end]
+            .parse()
+            .map_err(|_| format!("Invalid attachment count at position {}: {}", position, text))?;
+        position = end + 1;
+    }
+
+    let mut namespace = "/".to_string();
+    if bytes.get(position) == Some(&b'/') {
+        let end = text[position..].find(',').map(|offset| position + offset).unwrap_or(text.len());
+        namespace = text[position..end].to_string();
+        position = (end + 1).min(text.len());
+    }
+
+    let digits = bytes[position..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let id = if digits > 0 {
+        let id = text[position..position + digits]
+            .parse()
+            .map_err(|_| format!("Invalid ack id at position {}: {}", position, text))?;
+        position += digits;
+        Some(id)
+    } else {
+        None
+    };
+
+    let data = if position < text.len() {
+        Some(
+            serde_json::from_str(&text[position..])
+                .map_err(|e| format!("Invalid JSON payload at position {}: {}", position, e))?,
+        )
+    } else {
+        None
+    };
+
+    Ok((
+        SocketIoPacket {
+            packet_type,
+            namespace,
+            id,
+            data,
+        },
+        attachments,
+    ))
+}
+
+// 报文解码器：处理二进制附件的重组
+#[derive(Default)]
+pub struct PacketDecoder {
+    pending: Option<(SocketIoPacket, usize, Vec<Vec<u8>>)>,
+}
+
+impl PacketDecoder {
+    // 处理 Engine.IO message 报文的内容
+    pub fn push_text(&mut self, text: &str) -> Result<Option<SocketIoPacket>, String> {
+        if self.pending.is_some() {
+            self.pending = None;
+            return Err("Text packet received while waiting for binary attachments".to_string());
+        }
+
+        let (packet, attachments) = parse_packet(text)?;
+        if attachments == 0 {
+            return Ok(Some(packet));
+        }
+        self.pending = Some((packet, attachments, Vec::new()));
+        Ok(None)
+    }
+
+    pub fn push_binary(&mut self, data: Vec<u8>) -> Result<Option<SocketIoPacket>, String> {
+        let (packet, expected, mut received) = self
+            .pending
+            .take()
+            .ok_or("Unexpected binary frame without a pending binary packet")?;
+        received.push(data);
+
+        if received.len() < expected {
+            self.pending = Some((packet, expected, received));
+            return Ok(None);
+        }
+
+        let mut packet = packet;
+        packet.data = match packet.data {
+            Some(data) => Some(reconstruct(data, &received)?),
+            None => None,
+        };
+        packet.packet_type = if packet.packet_type == BINARY_EVENT { EVENT } else { ACK };
+        Ok(Some(packet))
+    }
+}
+
+// 解析参数JSON数组；不是合法JSON时视为单个字符串参数
+pub fn parse_arguments(arguments: &str) -> Vec<Value> {
+    match serde_json::from_str::<Value>(arguments.trim()) {
+        Ok(Value::Array(items)) => items,
+        Ok(value) => vec![value],
+        Err(_) if arguments.trim().is_empty() => Vec::new(),
+        Err(_) => vec![Value::String(arguments.to_string())],
+    }
+}
+
+// 构造 EVENT 报文的数据：[event, ...args]
+pub fn event_data(event: &str, arguments: Vec<Value>) -> Value {
+    let mut data = vec![Value::String(event.to_string())];
+    data.extend(arguments);
+    Value::Array(data)
+}
+
+// 用于事件消息的简短描述，例如 "chat message [\"hi\"]"
+pub fn describe_event(namespace: &str, event: &str, arguments: &[Value]) -> String {
+    let arguments = Value::Array(arguments.to_vec());
+    if namespace == "/" {
+        format!("{} {}", event, arguments)
+    } else {
+        format!("[{}] {} {}", namespace, event, arguments)
+    }
+}
@@ -0,0 +1,221 @@
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::socketio_codec::{self, EnginePacket, PacketDecoder, SocketIoPacket};
+use crate::websocket_queue::OutgoingMessage;
+use crate::websocket_server::{emit_server_event, CloseInfo, ServerWebSocket};
+use crate::write_ack;
+
+// 服务端心跳参数（与 Socket.IO v4 默认值一致）
+const PING_INTERVAL: Duration = Duration::from_millis(25000);
+const PING_TIMEOUT: Duration = Duration::from_millis(20000);
+const MAX_PAYLOAD: usize = 1_000_000;
+// 等待请求行到达的最长时间
+const REQUEST_LINE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 服务端发送的事件报文
+pub fn event_frames(namespace: &str, event: &str, arguments: Vec<Value>) -> Result<Vec<Message>, String> {
+    let data = socketio_codec::event_data(event, arguments);
+    socketio_codec::encode_packet(SocketIoPacket::new(socketio_codec::EVENT, namespace, None, Some(data)))
+}
+
+// 只支持 WebSocket 传输，不实现 HTTP 长轮询
+// 预读（不消费）请求行，请求其他传输（如 transport=polling）时以400应答并返回拒绝原因
+pub async fn reject_unsupported_transport(stream: &mut TcpStream) -> Option<String> {
+    let request_line = peek_request_line(stream).await?;
+    let target = request_line.split_whitespace().nth(1)?;
+    let (_, query) = target.split_once('?')?;
+    let transport = query.split('&').find_map(|pair| pair.strip_prefix("transport="))?;
+    if transport == "websocket" {
+        return None;
+    }
+
+    // 与 Engine.IO 服务端对未知传输的应答格式一致
+    let body = json!({ "code": 0, "message": "Transport unknown: only the websocket transport is supported" }).to_string();
+    let response = format!(
+        "HTTP/1.1 400 Bad Request\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+    Some(format!("Unsupported Engine.IO transport {}, only websocket is supported", transport))
+}
+
+// 预读HTTP请求行，超时、连接关闭或请求行过长时返回None
+async fn peek_request_line(stream: &TcpStream) -> Option<String> {
+    let deadline = Instant::now() + REQUEST_LINE_TIMEOUT;
+    let mut buffer = [0u8; 2048];
+    loop {
+        let n = tokio::time::timeout_at(deadline, stream.peek(&mut buffer)).await.ok()?.ok()?;
+        if n == 0 {
+            return None;
+        }
+        if let Some(end) = buffer[..n].windows(2).position(|window| window == b"\r\n") {
+            return Some(String::from_utf8_lossy(&buffer[..end]).into_owned());
+        }
+        if n == buffer.len() || Instant::now() >= deadline {
+            return None;
+        }
+        // 请求行尚未完整到达
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+// Socket.IO 会话：Engine.IO 握手、服务端 ping、命名空间连接和事件处理
+// 返回关闭信息
+pub async fn run_session(
+    ws_stream: ServerWebSocket,
+    mut rx: mpsc::UnboundedReceiver<OutgoingMessage>,
+    client_id: &str,
+    server_id: &str,
+    route: Option<&str>,
+    app_handle: &Option<tauri::AppHandle>,
+) -> CloseInfo {
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    // Engine.IO open 报文，sid 使用客户端ID；不支持长轮询，因此没有可升级的传输
+    let open = json!({
+        "sid": client_id,
+        "upgrades": [],
+        "pingInterval": PING_INTERVAL.as_millis() as u64,
+        "pingTimeout": PING_TIMEOUT.as_millis() as u64,
+        "maxPayload": MAX_PAYLOAD,
+    });
+    if let Err(e) = ws_sender.send(Message::Text(format!("0{}", open))).await {
+        return CloseInfo::from_error(&e);
+    }
+
+    let mut decoder = PacketDecoder::default();
+    let mut ping_timer = tokio::time::interval_at(Instant::now() + PING_INTERVAL, PING_INTERVAL);
+    let mut pong_deadline: Option<Instant> = None;
+
+    'session: loop {
+        // 等待 pong 超时
+        let timeout = async {
+            match pong_deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            // 发送服务端消息
+            msg = rx.recv() => {
+                match msg.map(|item| (item.message, item.ack)) {
+                    Some((Message::Close(frame), ack)) => {
+                        let close = CloseInfo::from_frame("server", frame.as_ref());
+                        let result = ws_sender.send(Message::Close(frame)).await;
+                        write_ack::notify(ack, result.map_err(|e| format!("Failed to write data: {}", e)));
+                        return close;
+                    }
+                    Some((msg, ack)) => {
+                        if let Err(e) = ws_sender.send(msg).await {
+                            write_ack::notify(ack, Err(format!("Failed to write data: {}", e)));
+                            return CloseInfo::from_error(&e);
+                        }
+                        write_ack::notify(ack, Ok(()));
+                    }
+                    None => return CloseInfo::new("server", "clean", "Server stopped".to_string()),
+                }
+            }
+            // 读取客户端报文
+            msg = ws_receiver.next() => {
+                let packet = match msg {
+                    Some(Ok(Message::Text(text))) => match socketio_codec::decode_engine_packet(&text) {
+                        Ok(EnginePacket::Pong) => {
+                            pong_deadline = None;
+                            continue;
+                        }
+                        Ok(EnginePacket::Ping(data)) => {
+                            if let Err(e) = ws_sender.send(Message::Text(format!("3{}", data))).await {
+                                return CloseInfo::from_error(&e);
+                            }
+                            continue;
+                        }
+                        Ok(EnginePacket::Message(data)) => decoder.push_text(&data),
+                        Ok(EnginePacket::Close) => return CloseInfo::new("client", "clean", "Client closed the Engine.IO session".to_string()),
+                        Ok(_) => continue,
+                        Err(e) => Err(e),
+                    },
+                    Some(Ok(Message::Binary(data))) => decoder.push_binary(data),
+                    Some(Ok(Message::Close(frame))) => return CloseInfo::from_frame("client", frame.as_ref()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return CloseInfo::from_error(&e),
+                    None => return CloseInfo::dropped(),
+                };
+
+                let packet = match packet {
+                    Ok(Some(packet)) => packet,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        emit_server_event(app_handle, server_id, client_id, route, "error", e);
+                        continue;
+                    }
+                };
+
+                let namespace = packet.namespace.clone();
+                let reply = match packet.packet_type {
+                    socketio_codec::CONNECT => {
+                        let auth = packet.data.map(|auth| format!(" with auth {}", auth)).unwrap_or_default();
+                        emit_server_event(app_handle, server_id, client_id, route, "socketio_connect", format!("Connected to namespace {}{}", namespace, auth));
+                        Some(SocketIoPacket::new(socketio_codec::CONNECT, &namespace, None, Some(json!({ "sid": client_id }))))
+                    }
+                    socketio_codec::DISCONNECT => {
+                        emit_server_event(app_handle, server_id, client_id, route, "socketio_disconnect", format!("Left namespace {}", namespace));
+                        None
+                    }
+                    socketio_codec::EVENT => match packet.event() {
+                        Some((event, arguments)) => {
+                            let mut message = socketio_codec::describe_event(&namespace, &event, &arguments);
+                            if let Some(id) = packet.id {
+                                message.push_str(&format!(" (ack {})", id));
+                            }
+                            emit_server_event(app_handle, server_id, client_id, route, "socketio_event", message);
+                            // 客户端请求确认时回复空确认
+                            packet
+                                .id
+                                .map(|id| SocketIoPacket::new(socketio_codec::ACK, &namespace, Some(id), Some(Value::Array(Vec::new()))))
+                        }
+                        None => {
+                            emit_server_event(app_handle, server_id, client_id, route, "error", format!("Invalid event payload: {:?}", packet.data));
+                            None
+                        }
+                    },
+                    socketio_codec::ACK => {
+                        let arguments = packet.data.unwrap_or_else(|| Value::Array(Vec::new()));
+                        emit_server_event(app_handle, server_id, client_id, route, "socketio_ack", format!("Ack {}: {}", packet.id.unwrap_or_default(), arguments));
+                        None
+                    }
+                    _ => None,
+                };
+
+                if let Some(reply) = reply {
+                    for frame in socketio_codec::encode_packet(reply).unwrap_or_default() {
+                        if let Err(e) = ws_sender.send(frame).await {
+                            break 'session CloseInfo::from_error(&e);
+                        }
+                    }
+                }
+            }
+            // 定时发送 ping
+            _ = ping_timer.tick() => {
+                if pong_deadline.is_none() {
+                    pong_deadline = Some(Instant::now() + PING_TIMEOUT);
+                    if let Err(e) = ws_sender.send(Message::Text("2".to_string())).await {
+                        return CloseInfo::from_error(&e);
+                    }
+                }
+            }
+            _ = timeout => {
+                return CloseInfo::new("server", "timeout", format!("Ping timeout: no pong received within {} ms", PING_TIMEOUT.as_millis()));
+            }
+        }
+    }
+}
@@ -1,13 +1,24 @@
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use tauri::{State, Emitter};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, Mutex, broadcast};
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 use chrono;
 
+use crate::checksum::{self, Checksum, ChecksumCheck, ChecksumParams};
+use crate::correlation::{Correlation, CorrelationParams};
+use crate::message_template::{self, TemplateEngine};
+use crate::payload_codec;
+use crate::receive_capture;
+use crate::tcp_framing::{self, FrameDecoder, Framing, FramingParams};
+use crate::tcp_shaping::{FragmentParams, PacedReader, SlowReadParams, TcpShaping};
+use crate::transport::{self, LocalEndpoint};
+use crate::write_ack::{self, WriteAck, WriteAckReceiver};
+
 // TCP客户端连接状态
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TcpClientState {
@@ -17,17 +28,26 @@ pub enum TcpClientState {
     Error,
 }
 
+// 待发送的消息：数据、关联的请求ID和写出通知
+type OutgoingMessage = (Vec<u8>, Option<u64>, Option<WriteAck>);
+
 // TCP客户端
 pub struct TcpClient {
     pub host: String,
     pub port: u16,
+    pub local_endpoint: LocalEndpoint, // 指定的本地源地址和端口，未指定时由系统选择
+    pub local_addr: Option<SocketAddr>, // 实际使用的本地端点
+    pub checksum: Option<Checksum>,     // 接收校验设置
+    pub framing: Option<Framing>,       // 接收分帧规则，未设置时每次读取的数据作为一条消息
+    pub shaping: Arc<TcpShaping>,       // 分段发送和慢速读取设置，可在运行时修改
+    pub correlation: Arc<Correlation>,  // 请求/响应关联设置，可在运行时修改
     pub client_id: String,
     pub state: TcpClientState,
     pub stream: Option<TcpStream>,
     pub receive_handle: Option<JoinHandle<()>>,
     pub send_handle: Option<JoinHandle<()>>,
     pub shutdown_sender: Option<broadcast::Sender<()>>,
-    pub message_sender: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    pub message_sender: Option<mpsc::UnboundedSender<OutgoingMessage>>,
     pub app_handle: Option<tauri::AppHandle>,
 }
 
@@ -57,6 +77,14 @@ pub struct ConnectTcpClientParams {
     pub host: String,
     pub port: u16,
     pub client_id: Option<String>,
+    pub local_address: Option<String>, // 本地源地址：IPv4/IPv6地址或网卡名称
+    pub local_port: Option<u16>,       // 本地源端口
+    pub reuse_address: Option<bool>,   // 是否启用SO_REUSEADDR，默认为 false
+    pub framing: Option<FramingParams>,   // 按规则切分收到的字节流，每帧发送一次接收事件
+    pub checksum: Option<ChecksumParams>, // 校验收到的每帧数据，结果附加在接收事件上；需要设置分帧规则
+    pub fragmentation: Option<FragmentParams>, // 把每条数据拆成多次写入
+    pub slow_read: Option<SlowReadParams>,     // 节流读取以模拟处理缓慢的接收方
+    pub correlation: Option<CorrelationParams>, // 把每次发送作为请求，匹配随后收到的响应并计算往返时间；未设置分帧规则时按每次读取的数据匹配
 }
 
 // 发送消息的参数
@@ -65,7 +93,9 @@ pub struct ConnectTcpClientParams {
 pub struct SendTcpClientMessageParams {
     pub client_id: String,
     pub message: String,
-    pub message_type: Option<String>, // "text" 或 "hex"，默认为 "text"
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub template: Option<bool>,       // 发送前展开 {{...}} 模板占位符，默认为 false
+    pub checksum: Option<ChecksumParams>, // 发送前计算并插入校验值
 }
 
 // 客户端状态信息
@@ -75,6 +105,8 @@ pub struct TcpClientInfo {
     pub client_id: String,
     pub host: String,
     pub port: u16,
+    pub local_address: Option<String>, // 实际使用的本地地址
+    pub local_port: Option<u16>,       // 实际使用的本地端口
     pub state: TcpClientState,
 }
 
@@ -85,6 +117,7 @@ pub struct TcpClientEvent {
     pub client_id: String,
     pub event_type: String,
     pub message: String,
+    pub checksum: Option<ChecksumCheck>, // 启用接收校验时的校验结果
     pub timestamp: String,
 }
 
@@ -93,6 +126,12 @@ impl TcpClient {
         TcpClient {
             host,
             port,
+            local_endpoint: LocalEndpoint::default(),
+            local_addr: None,
+            checksum: None,
+            framing: None,
+            shaping: Arc::new(TcpShaping::default()),
+            correlation: Arc::new(Correlation::new("tcp_client", &client_id)),
             client_id,
             state: TcpClientState::Disconnected,
             stream: None,
@@ -108,6 +147,10 @@ impl TcpClient {
         self.app_handle = Some(app_handle);
     }
 
+    pub fn set_local_endpoint(&mut self, local_endpoint: LocalEndpoint) {
+        self.local_endpoint = local_endpoint;
+    }
+
     pub async fn connect(&mut self) -> Result<(), String> {
         if self.state == TcpClientState::Connected {
             return Err("Already connected".to_string());
@@ -116,17 +159,23 @@ impl TcpClient {
         self.state = TcpClientState::Connecting;
         let addr = format!("{}:{}", self.host, self.port);
         
-        match TcpStream::connect(&addr).await {
+        match transport::connect_tcp(&self.host, self.port, &self.local_endpoint).await {
             Ok(stream) => {
+                self.local_addr = stream.local_addr().ok();
                 self.stream = Some(stream);
                 self.state = TcpClientState::Connected;
                 
                 // 发送连接成功事件
                 if let Some(app_handle) = &self.app_handle {
+                    let message = match self.local_addr {
+                        Some(local_addr) => format!("Connected to {} from {}", addr, local_addr),
+                        None => format!("Connected to {}", addr),
+                    };
                     let event = TcpClientEvent {
                         client_id: self.client_id.clone(),
                         event_type: "connected".to_string(),
-                        message: format!("Connected to {}", addr),
+                        message,
+                        checksum: None,
                         timestamp: chrono::Utc::now().to_rfc3339(),
                     };
                     let _ = app_handle.emit("tcp-client-event", &event);
@@ -138,7 +187,7 @@ impl TcpClient {
             }
             Err(e) => {
                 self.state = TcpClientState::Error;
-                Err(format!("Failed to connect to {}: {}", addr, e))
+                Err(e)
             }
         }
     }
@@ -173,6 +222,7 @@ impl TcpClient {
                 client_id: self.client_id.clone(),
                 event_type: "disconnected".to_string(),
                 message: "Disconnected from server".to_string(),
+                checksum: None,
                 timestamp: chrono::Utc::now().to_rfc3339(),
             };
             let _ = app_handle.emit("tcp-client-event", &event);
@@ -194,27 +244,47 @@ impl TcpClient {
         // 启动接收任务
         let client_id = self.client_id.clone();
         let app_handle = self.app_handle.clone();
+        let config = ReceiveConfig {
+            checksum: self.checksum.clone(),
+            framing: self.framing.clone(),
+            shaping: Arc::clone(&self.shaping),
+        };
+        let correlation = Arc::clone(&self.correlation);
         let shutdown_rx_clone = shutdown_tx.subscribe();
         self.receive_handle = Some(tokio::spawn(async move {
-            handle_tcp_client_receive(read_stream, client_id, app_handle, shutdown_rx_clone).await;
+            handle_tcp_client_receive(read_stream, client_id, app_handle, config, correlation, shutdown_rx_clone).await;
         }));
 
         // 启动发送任务
+        let shaping = Arc::clone(&self.shaping);
+        let correlation = Arc::clone(&self.correlation);
         let shutdown_rx_clone = shutdown_tx.subscribe();
         self.send_handle = Some(tokio::spawn(async move {
-            handle_tcp_client_send(write_stream, message_rx, shutdown_rx_clone).await;
+            handle_tcp_client_send(write_stream, message_rx, shaping, correlation, shutdown_rx_clone).await;
         }));
 
         Ok(())
     }
 
-    pub async fn send_message(&self, message: Vec<u8>) -> Result<(), String> {
+    // 发送消息，request_id 为 Correlation::open 登记的请求，写出后记录发送时间
+    pub async fn send_message(&self, message: Vec<u8>, request_id: Option<u64>) -> Result<(), String> {
+        self.queue_message(message, request_id, None)
+    }
+
+    // 发送消息，返回的通知在数据写入套接字后完成
+    pub fn send_message_with_ack(&self, message: Vec<u8>) -> Result<WriteAckReceiver, String> {
+        let (ack, receiver) = write_ack::channel();
+        self.queue_message(message, None, Some(ack))?;
+        Ok(receiver)
+    }
+
+    fn queue_message(&self, message: Vec<u8>, request_id: Option<u64>, ack: Option<WriteAck>) -> Result<(), String> {
         if self.state != TcpClientState::Connected {
             return Err("Not connected".to_string());
         }
 
         if let Some(sender) = &self.message_sender {
-            sender.send(message).map_err(|e| format!("Failed to send message: {}", e))?;
+            sender.send((message, request_id, ack)).map_err(|e| format!("Failed to send message: {}", e))?;
             Ok(())
         } else {
             Err("Message sender not available".to_string())
@@ -222,14 +292,27 @@ impl TcpClient {
     }
 }
 
+// 接收任务使用的设置
+struct ReceiveConfig {
+    checksum: Option<Checksum>,
+    framing: Option<Framing>,
+    shaping: Arc<TcpShaping>,
+}
+
 // 处理TCP客户端接收消息
 async fn handle_tcp_client_receive(
     mut read_stream: tokio::net::tcp::OwnedReadHalf,
     client_id: String,
     app_handle: Option<tauri::AppHandle>,
+    config: ReceiveConfig,
+    correlation: Arc<Correlation>,
     mut shutdown_rx: broadcast::Receiver<()>,
 ) {
+    let ReceiveConfig { checksum, framing, shaping } = config;
+    let capture_tap = receive_capture::tap(&app_handle, "tcp_client", &client_id).await;
+    let mut decoder = FrameDecoder::new(framing.unwrap_or(Framing::Any));
     let mut buffer = vec![0; 1024];
+    let mut reader = PacedReader::new();
     
     loop {
         tokio::select! {
@@ -237,8 +320,8 @@ async fn handle_tcp_client_receive(
             _ = shutdown_rx.recv() => {
                 break;
             }
-            // 读取数据
-            result = read_stream.read(&mut buffer) => {
+            // 读取数据，慢速读取模式下按设置节流
+            result = reader.read(&shaping, &mut read_stream, &mut buffer) => {
                 match result {
                     Ok(0) => {
                         // 连接已关闭
@@ -247,6 +330,7 @@ async fn handle_tcp_client_receive(
                                 client_id: client_id.clone(),
                                 event_type: "disconnected".to_string(),
                                 message: "Connection closed by server".to_string(),
+                                checksum: None,
                                 timestamp: chrono::Utc::now().to_rfc3339(),
                             };
                             let _ = app_handle.emit("tcp-client-event", &event);
@@ -254,18 +338,42 @@ async fn handle_tcp_client_receive(
                         break;
                     }
                     Ok(n) => {
-                        let received_data = buffer[..n].to_vec();
-                        
-                        // 发送接收到的消息事件
-                        if let Some(app_handle) = &app_handle {
-                            let message = String::from_utf8_lossy(&received_data).to_string();
-                            let event = TcpClientEvent {
-                                client_id: client_id.clone(),
-                                event_type: "message_received".to_string(),
-                                message,
-                                timestamp: chrono::Utc::now().to_rfc3339(),
-                            };
-                            let _ = app_handle.emit("tcp-client-event", &event);
+                        // 按分帧规则切分，未设置时每次读取的数据作为一帧
+                        let frames = match decoder.push(&buffer[..n]) {
+                            Ok(frames) => frames,
+                            Err(e) => {
+                                if let Some(app_handle) = &app_handle {
+                                    let event = TcpClientEvent {
+                                        client_id: client_id.clone(),
+                                        event_type: "error".to_string(),
+                                        message: e,
+                                        checksum: None,
+                                        timestamp: chrono::Utc::now().to_rfc3339(),
+                                    };
+                                    let _ = app_handle.emit("tcp-client-event", &event);
+                                }
+                                break;
+                            }
+                        };
+
+                        for received_data in frames {
+                            correlation.received(&app_handle, &received_data);
+
+                            // 写入接收记录，未被接管时发送接收到的消息事件
+                            if let Some(app_handle) = &app_handle {
+                                if capture_tap.capture(&client_id, &received_data).await {
+                                    continue;
+                                }
+                                let message = String::from_utf8_lossy(&received_data).to_string();
+                                let event = TcpClientEvent {
+                                    client_id: client_id.clone(),
+                                    event_type: "message_received".to_string(),
+                                    message,
+                                    checksum: checksum.as_ref().map(|checksum| checksum.verify(&received_data)),
+                                    timestamp: chrono::Utc::now().to_rfc3339(),
+                                };
+                                let _ = app_handle.emit("tcp-client-event", &event);
+                            }
                         }
                     }
                     Err(e) => {
@@ -274,6 +382,7 @@ async fn handle_tcp_client_receive(
                                 client_id: client_id.clone(),
                                 event_type: "error".to_string(),
                                 message: format!("Read error: {}", e),
+                                checksum: None,
                                 timestamp: chrono::Utc::now().to_rfc3339(),
                             };
                             let _ = app_handle.emit("tcp-client-event", &event);
@@ -282,14 +391,20 @@ async fn handle_tcp_client_receive(
                     }
                 }
             }
+            // 等待中的请求超时
+            _ = correlation.expire(&app_handle) => {}
         }
     }
+
+    correlation.close(&app_handle);
 }
 
 // 处理TCP客户端发送消息
 async fn handle_tcp_client_send(
     mut write_stream: tokio::net::tcp::OwnedWriteHalf,
-    mut message_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    mut message_rx: mpsc::UnboundedReceiver<OutgoingMessage>,
+    shaping: Arc<TcpShaping>,
+    correlation: Arc<Correlation>,
     mut shutdown_rx: broadcast::Receiver<()>,
 ) {
     loop {
@@ -301,11 +416,14 @@ async fn handle_tcp_client_send(
             // 发送消息
             message = message_rx.recv() => {
                 match message {
-                    Some(data) => {
-                        if let Err(e) = write_stream.write_all(&data).await {
+                    Some((data, request_id, ack)) => {
+                        if let Err(e) = shaping.write(&mut write_stream, &data).await {
                             eprintln!("Failed to write data: {}", e);
+                            write_ack::notify(ack, Err(format!("Failed to write data: {}", e)));
                             break;
                         }
+                        correlation.sent(request_id);
+                        write_ack::notify(ack, Ok(()));
                     }
                     None => {
                         break;
@@ -325,6 +443,18 @@ pub async fn connect_tcp_client(
 ) -> Result<String, String> {
     let client_id = connect_params.client_id.unwrap_or_else(|| Uuid::new_v4().to_string());
     let mut client = TcpClient::new(connect_params.host, connect_params.port, client_id.clone());
+    client.set_local_endpoint(LocalEndpoint {
+        address: connect_params.local_address,
+        port: connect_params.local_port,
+        reuse_address: connect_params.reuse_address.unwrap_or(false),
+    });
+    client.framing = connect_params.framing.as_ref().map(Framing::from_params).transpose()?;
+    client.checksum = Checksum::from_params(connect_params.checksum.as_ref())?;
+    if client.checksum.is_some() {
+        tcp_framing::require_framing(client.framing.as_ref(), "Checksum verification")?;
+    }
+    client.shaping.configure(connect_params.fragmentation.as_ref(), connect_params.slow_read.as_ref())?;
+    client.correlation.configure(connect_params.correlation.as_ref())?;
     client.set_app_handle(app_handle);
     
     client.connect().await?;
@@ -357,20 +487,26 @@ pub async fn disconnect_tcp_client(
 pub async fn send_tcp_client_message(
     send_params: SendTcpClientMessageParams,
     manager: State<'_, Mutex<TcpClientManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
 ) -> Result<(), String> {
-    let message_type = send_params.message_type.as_deref().unwrap_or("text");
-    let data = match message_type {
-        "hex" => {
-            // 将十六进制字符串转换为字节
-            let hex_str = send_params.message.replace(" ", "");
-            hex::decode(&hex_str).map_err(|e| format!("Invalid hex string: {}", e))?
-        }
-        _ => send_params.message.into_bytes(),
-    };
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.client_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
+    let data = payload_codec::decode(&message, send_params.message_type.as_deref())?;
+    let data = checksum::append_checksum(data, send_params.checksum.as_ref())?;
 
     let manager = manager.lock().await;
     if let Some(client) = manager.clients.get(&send_params.client_id) {
-        client.send_message(data).await?;
+        let request_id = client.correlation.open(&client.app_handle, &data)?;
+        if let Err(e) = client.send_message(data, request_id).await {
+            client.correlation.cancel(request_id);
+            return Err(e);
+        }
         Ok(())
     } else {
         Err(format!("TCP client {} not found", send_params.client_id))
@@ -390,6 +526,8 @@ pub async fn get_tcp_clients(
             client_id: client.client_id.clone(),
             host: client.host.clone(),
             port: client.port,
+            local_address: client.local_addr.map(|addr| addr.ip().to_string()),
+            local_port: client.local_addr.map(|addr| addr.port()),
             state: client.state.clone(),
         })
         .collect();
@@ -408,6 +546,8 @@ pub async fn get_tcp_client_info(
             client_id: client.client_id.clone(),
             host: client.host.clone(),
             port: client.port,
+            local_address: client.local_addr.map(|addr| addr.ip().to_string()),
+            local_port: client.local_addr.map(|addr| addr.port()),
             state: client.state.clone(),
         })
     } else {
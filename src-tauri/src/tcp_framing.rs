@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::payload_codec;
+
+// 尚未完整的帧最多缓存的字节数
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+// TCP字节流的分帧参数
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FramingParams {
+    pub framing: String,                // "any"（每次读到数据算一帧）、"delimiter"、"fixed" 或 "length_prefixed"
+    pub delimiter: Option<String>,      // 帧结束符
+    pub delimiter_type: Option<String>, // 结束符格式，同发送消息，默认为 "escaped"，例如 "\r\n"
+    pub length: Option<usize>,          // fixed 为帧长度；length_prefixed 为长度字段字节数（1、2 或 4，大端，不含长度字段）
+}
+
+// 分帧方式
+#[derive(Debug, Clone)]
+pub enum Framing {
+    Any,
+    Delimiter(Vec<u8>),
+    Fixed(usize),
+    LengthPrefixed(usize),
+}
+
+impl Framing {
+    pub fn from_params(params: &FramingParams) -> Result<Self, String> {
+        match params.framing.as_str() {
+            "any" => Ok(Framing::Any),
+            "delimiter" => {
+                let delimiter = params.delimiter.as_deref().ok_or("Delimiter is required for delimiter framing")?;
+                let delimiter = payload_codec::decode(delimiter, Some(params.delimiter_type.as_deref().unwrap_or("escaped")))?;
+                if delimiter.is_empty() {
+                    return Err("Delimiter cannot be empty".to_string());
+                }
+                Ok(Framing::Delimiter(delimiter))
+            }
+            "fixed" => match params.length {
+                None | Some(0) => Err("Frame length must be greater than 0".to_string()),
+                Some(length) if length > MAX_FRAME_SIZE => Err(format!("Frame length cannot exceed {} bytes", MAX_FRAME_SIZE)),
+                Some(length) => Ok(Framing::Fixed(length)),
+            },
+            "length_prefixed" => match params.length {
+                Some(size @ (1 | 2 | 4)) => Ok(Framing::LengthPrefixed(size)),
+                _ => Err("Length prefix size must be 1, 2 or 4".to_string()),
+            },
+            other => Err(format!("Unsupported framing: {}", other)),
+        }
+    }
+
+    // 是否按规则划分消息边界，"any" 只是原样返回每次读到的数据
+    pub fn is_framed(&self) -> bool {
+        !matches!(self, Framing::Any)
+    }
+}
+
+// 需要完整消息的功能（如接收校验）只能在按规则分帧时启用，单次读取的数据可能是半帧或多帧
+pub fn require_framing(framing: Option<&Framing>, feature: &str) -> Result<(), String> {
+    match framing {
+        Some(framing) if framing.is_framed() => Ok(()),
+        _ => Err(format!("{} requires a delimiter, fixed or length_prefixed framing rule", feature)),
+    }
+}
+
+// 从字节流中切分帧，帧包含结束符或长度字段
+pub struct FrameDecoder {
+    framing: Framing,
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new(framing: Framing) -> Self {
+        FrameDecoder { framing, buffer: Vec::new() }
+    }
+
+    // 追加收到的数据，返回新完成的帧；未完成的帧超过上限时返回错误
+    pub fn push(&mut self, data: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+        if let Framing::Any = self.framing {
+            return Ok(vec![data.to_vec()]);
+        }
+        self.buffer.extend_from_slice(data);
+        let mut frames = Vec::new();
+        loop {
+            let consumed = match &self.framing {
+                Framing::Any => None,
+                Framing::Delimiter(delimiter) => self
+                    .buffer
+                    .windows(delimiter.len())
+                    .position(|window| window == delimiter.as_slice())
+                    .map(|position| position + delimiter.len()),
+                Framing::Fixed(length) => (self.buffer.len() >= *length).then_some(*length),
+                Framing::LengthPrefixed(size) => self.buffer.get(..*size).and_then(|prefix| {
+                    let length = prefix.iter().fold(0usize, |length, byte| (length << 8) | *byte as usize);
+                    (self.buffer.len() >= size + length).then_some(size + length)
+                }),
+            };
+            match consumed {
+                Some(consumed) => frames.push(self.buffer.drain(..consumed).collect()),
+                None if self.buffer.len() > MAX_FRAME_SIZE => {
+                    return Err(format!("Frame exceeds maximum size of {} bytes", MAX_FRAME_SIZE));
+                }
+                None => return Ok(frames),
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, Mutex};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::load_test::{self, LoadPlan, LoadStats, LoadTest, LoadTestManager};
+use crate::message_template::TemplateEngine;
+use crate::net_address;
+use crate::payload_codec;
+use crate::tcp_framing::{FrameDecoder, Framing, FramingParams};
+
+const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 5000;
+const DEFAULT_RESPONSE_TIMEOUT_MS: u64 = 5000;
+
+// 启动TCP负载测试的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartTcpLoadTestParams {
+    pub test_id: Option<String>,
+    pub host: String,
+    pub port: u16,
+    pub connections: usize,
+    pub ramp_up_ms: Option<u64>,        // 在该时间内均匀建立所有连接，默认同时建立
+    pub duration_ms: Option<u64>,       // 测试时长，默认直到停止或所有连接完成
+    pub message: Option<String>,        // 为None则只建立并保持连接
+    pub message_type: Option<String>,   // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub template: Option<bool>,         // 每条消息发送前展开模板，计数器按连接独立
+    pub messages_per_second: Option<f64>, // 每个连接的发送速率；未设置时等待响应后发送下一条，不等待响应时连续发送
+    pub message_count: Option<u64>,     // 每个连接发送的消息数，默认不限
+    pub response: Option<FramingParams>, // 设置后按帧统计响应并计算响应延迟
+    pub response_timeout_ms: Option<u64>,  // 默认为 5000
+    pub connect_timeout_ms: Option<u64>,   // 默认为 5000
+    pub report_interval_ms: Option<u64>,   // 进度事件间隔，默认为 1000
+}
+
+// 待发送的内容
+enum Payload {
+    Fixed(Vec<u8>),
+    Template { message: String, message_type: Option<String> },
+}
+
+// 所有连接共用的测试设置
+struct TcpLoadContext {
+    app_handle: tauri::AppHandle,
+    test_id: String,
+    target: SocketAddr,
+    stats: Arc<LoadStats>,
+    payload: Option<Payload>,
+    interval: Option<Duration>,
+    message_count: Option<u64>,
+    framing: Option<Framing>,
+    response_timeout: Duration,
+    connect_timeout: Duration,
+}
+
+impl TcpLoadContext {
+    // 模板计数器按连接独立
+    fn scope(&self, index: usize) -> String {
+        format!("{}#{}", self.test_id, index)
+    }
+
+    async fn next_payload(&self, index: usize) -> Result<Vec<u8>, String> {
+        match &self.payload {
+            Some(Payload::Fixed(data)) => Ok(data.clone()),
+            Some(Payload::Template { message, message_type }) => {
+                let templates = self.app_handle.state::<Mutex<TemplateEngine>>();
+                let hex = message_type.as_deref() == Some("hex");
+                let message = templates.lock().await.render(&self.scope(index), message, hex, true)?;
+                payload_codec::decode(&message, message_type.as_deref())
+            }
+            None => Err("No payload configured".to_string()),
+        }
+    }
+}
+
+// 连接结束的原因
+enum ExchangeEnd {
+    Finished,
+    Stopped,
+    Closed(Option<String>), // 对端关闭或出错
+}
+
+// 单个连接：建立连接后按设置发送并统计响应
+async fn run_connection(ctx: Arc<TcpLoadContext>, index: usize, mut stop_rx: broadcast::Receiver<()>) {
+    let connect_started = Instant::now();
+    let connected = tokio::select! {
+        _ = stop_rx.recv() => return,
+        result = tokio::time::timeout(ctx.connect_timeout, TcpStream::connect(ctx.target)) => match result {
+            Ok(Ok(stream)) => Ok(stream),
+            Ok(Err(e)) => Err(format!("Failed to connect to {}: {}", ctx.target, e)),
+            Err(_) => Err(format!("Connection to {} timed out", ctx.target)),
+        },
+    };
+    let stream = match connected {
+        Ok(stream) => stream,
+        Err(e) => {
+            ctx.stats.update(|counters| {
+                counters.connect_failures += 1;
+                counters.last_error = Some(e);
+            });
+            return;
+        }
+    };
+    ctx.stats.update(|counters| {
+        counters.connects += 1;
+        counters.connections_active += 1;
+        counters.connect_latency.record(connect_started.elapsed());
+    });
+
+    let end = exchange(&ctx, index, stream, &mut stop_rx).await;
+
+    ctx.stats.update(|counters| {
+        counters.connections_active -= 1;
+        if let ExchangeEnd::Closed(error) = end {
+            counters.disconnects += 1;
+            if let Some(error) = error {
+                counters.error(error);
+            }
+        }
+    });
+    if let Some(Payload::Template { .. }) = ctx.payload {
+        let templates = ctx.app_handle.state::<Mutex<TemplateEngine>>();
+        templates.lock().await.reset_counters(Some(&ctx.scope(index)));
+    }
+}
+
+async fn exchange(ctx: &TcpLoadContext, index: usize, stream: TcpStream, stop_rx: &mut broadcast::Receiver<()>) -> ExchangeEnd {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut decoder = ctx.framing.clone().map(FrameDecoder::new);
+    let mut buffer = vec![0; 8192];
+    let mut pending: VecDeque<Instant> = VecDeque::new(); // 等待响应的消息的发送时间
+    let mut sent: u64 = 0;
+    let mut next_send = Instant::now();
+
+    loop {
+        let done_sending = ctx.payload.is_none() || ctx.message_count.is_some_and(|count| sent >= count);
+        if done_sending && ctx.payload.is_some() && pending.is_empty() {
+            return ExchangeEnd::Finished;
+        }
+        // 下一次发送的时刻：按速率发送，或等待响应后发送，或连续发送
+        let send_at = if done_sending {
+            None
+        } else if ctx.interval.is_some() {
+            Some(next_send)
+        } else if decoder.is_some() && !pending.is_empty() {
+            None
+        } else {
+            Some(Instant::now())
+        };
+        let timeout_at = pending.front().map(|sent_at| *sent_at + ctx.response_timeout);
+
+        tokio::select! {
+            _ = stop_rx.recv() => return ExchangeEnd::Stopped,
+            result = reader.read(&mut buffer) => {
+                let n = match result {
+                    Ok(0) => return ExchangeEnd::Closed(None),
+                    Ok(n) => n,
+                    Err(e) => return ExchangeEnd::Closed(Some(format!("Read error: {}", e))),
+                };
+                let received_at = Instant::now();
+                let frames = match decoder.as_mut().map(|decoder| decoder.push(&buffer[..n])).transpose() {
+                    Ok(frames) => frames.map_or(1, |frames| frames.len() as u64),
+                    Err(e) => return ExchangeEnd::Closed(Some(e)),
+                };
+                ctx.stats.update(|counters| {
+                    counters.bytes_received += n as u64;
+                    counters.messages_received += frames;
+                    if decoder.is_some() {
+                        for sent_at in pending.drain(..(frames as usize).min(pending.len())) {
+                            counters.response_latency.record(received_at - sent_at);
+                        }
+                    }
+                });
+            }
+            _ = tokio::time::sleep_until(send_at.unwrap_or(next_send)), if send_at.is_some() => {
+                let data = match ctx.next_payload(index).await {
+                    Ok(data) => data,
+                    Err(e) => return ExchangeEnd::Closed(Some(e)),
+                };
+                if let Err(e) = writer.write_all(&data).await {
+                    return ExchangeEnd::Closed(Some(format!("Write error: {}", e)));
+                }
+                sent += 1;
+                if decoder.is_some() {
+                    pending.push_back(Instant::now());
+                }
+                ctx.stats.update(|counters| {
+                    counters.messages_sent += 1;
+                    counters.bytes_sent += data.len() as u64;
+                });
+                if let Some(interval) = ctx.interval {
+                    // 落后于计划时不补发
+                    next_send = (next_send + interval).max(Instant::now());
+                }
+            }
+            _ = tokio::time::sleep_until(timeout_at.unwrap_or(next_send)), if timeout_at.is_some() => {
+                pending.pop_front();
+                ctx.stats.update(|counters| counters.timeouts += 1);
+            }
+        }
+    }
+}
+
+// Tauri命令：启动TCP负载测试
+#[tauri::command]
+pub async fn start_tcp_load_test(
+    load_params: StartTcpLoadTestParams,
+    tests: State<'_, Mutex<LoadTestManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let test_id = load_params.test_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut tests = tests.lock().await;
+    if tests.is_running(&test_id) {
+        return Err(format!("Load test {} is already running", test_id));
+    }
+    let plan = LoadPlan::new(
+        load_params.connections,
+        load_params.ramp_up_ms,
+        load_params.duration_ms,
+        load_params.report_interval_ms,
+    )?;
+
+    let message_type = load_params.message_type.clone();
+    let payload = match load_params.message {
+        Some(message) if load_params.template.unwrap_or(false) => {
+            // 预先展开一次以尽早发现模板错误
+            let hex = message_type.as_deref() == Some("hex");
+            let preview = templates.lock().await.render(&test_id, &message, hex, false)?;
+            payload_codec::decode(&preview, message_type.as_deref())?;
+            Some(Payload::Template { message, message_type })
+        }
+        Some(message) => Some(Payload::Fixed(payload_codec::decode(&message, message_type.as_deref())?)),
+        None => None,
+    };
+    let interval = match load_params.messages_per_second {
+        Some(rate) if rate > 0.0 && rate.is_finite() => Some(Duration::from_secs_f64(1.0 / rate)),
+        Some(rate) if rate != 0.0 => return Err(format!("Invalid message rate: {}", rate)),
+        _ => None,
+    };
+    let framing = load_params.response.as_ref().map(Framing::from_params).transpose()?;
+    let target = net_address::resolve_target(&load_params.host, load_params.port, None).await?;
+
+    let stats = Arc::new(LoadStats::new(plan.connections));
+    let ctx = Arc::new(TcpLoadContext {
+        app_handle: app_handle.clone(),
+        test_id: test_id.clone(),
+        target,
+        stats: Arc::clone(&stats),
+        payload,
+        interval,
+        message_count: load_params.message_count,
+        framing,
+        response_timeout: Duration::from_millis(load_params.response_timeout_ms.unwrap_or(DEFAULT_RESPONSE_TIMEOUT_MS)),
+        connect_timeout: Duration::from_millis(load_params.connect_timeout_ms.unwrap_or(DEFAULT_CONNECT_TIMEOUT_MS)),
+    });
+
+    load_test::emit_load_test_event(
+        &app_handle,
+        &test_id,
+        "started",
+        format!("Opening {} connections to {}", plan.connections, net_address::display_addr(target)),
+        stats.report(false),
+    );
+
+    let (stop_tx, _) = broadcast::channel(1);
+    let handle = tokio::spawn(load_test::supervise(
+        app_handle,
+        test_id.clone(),
+        Arc::clone(&stats),
+        plan,
+        stop_tx.clone(),
+        move |index, stop_rx| run_connection(Arc::clone(&ctx), index, stop_rx),
+    ));
+
+    tests.insert(LoadTest {
+        test_id: test_id.clone(),
+        protocol: "tcp".to_string(),
+        target: format!("{}:{}", load_params.host, load_params.port),
+        stats,
+        stop_sender: stop_tx,
+        handle,
+    })?;
+
+    Ok(test_id)
+}
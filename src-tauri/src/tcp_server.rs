@@ -3,19 +3,28 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use tauri::{State, Emitter};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncWriteExt;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 use chrono;
 
+use crate::checksum::{self, Checksum, ChecksumCheck, ChecksumParams};
+use crate::message_template::{self, TemplateEngine};
+use crate::modbus_slave::{self, ModbusDataStore};
+use crate::payload_codec;
+use crate::receive_capture;
+use crate::tcp_framing::{self, FrameDecoder, Framing, FramingParams};
+use crate::tcp_shaping::{FragmentParams, PacedReader, SlowReadParams, TcpShaping};
+use crate::write_ack::{self, WriteAck, WriteAckReceiver};
+
 // TCP客户端连接
 #[allow(dead_code)]
 pub struct TcpClient {
     pub id: String,
     pub addr: SocketAddr,
-    pub sender: mpsc::UnboundedSender<Vec<u8>>,
+    pub sender: mpsc::UnboundedSender<(Vec<u8>, Option<WriteAck>)>,
 }
 
 // TCP服务器
@@ -23,6 +32,10 @@ pub struct TcpServer {
     pub host: String,
     pub port: u16,
     pub server_id: String,
+    pub modbus_store: Option<Arc<RwLock<ModbusDataStore>>>, // Modbus从站模式下的寄存器表
+    pub checksum: Option<Checksum>, // 接收校验设置
+    pub framing: Option<Framing>,   // 接收分帧规则，未设置时每次读取的数据作为一条消息
+    pub shaping: Arc<TcpShaping>,   // 分段发送和慢速读取设置，所有客户端共用，可在运行时修改
     pub clients: Arc<RwLock<HashMap<String, TcpClient>>>,
     pub server_handle: Option<JoinHandle<()>>,
     pub shutdown_sender: Option<mpsc::UnboundedSender<()>>,
@@ -55,6 +68,20 @@ pub struct StartTcpServerParams {
     pub host: String,
     pub port: u16,
     pub server_id: Option<String>,
+    pub protocol: Option<String>, // "raw"（默认）或 "modbus"
+    pub framing: Option<FramingParams>,   // 按规则切分收到的字节流，每帧发送一次接收事件
+    pub checksum: Option<ChecksumParams>, // 校验收到的每帧数据，结果附加在接收事件上；需要设置分帧规则
+    pub fragmentation: Option<FragmentParams>, // 把每条数据拆成多次写入
+    pub slow_read: Option<SlowReadParams>,     // 节流读取以模拟处理缓慢的接收方
+}
+
+// 每个连接共用的服务器设置
+#[derive(Clone)]
+struct ConnectionConfig {
+    modbus_store: Option<Arc<RwLock<ModbusDataStore>>>,
+    checksum: Option<Checksum>,
+    framing: Option<Framing>,
+    shaping: Arc<TcpShaping>,
 }
 
 // 发送消息的参数
@@ -64,7 +91,9 @@ pub struct SendTcpMessageParams {
     pub server_id: String,
     pub message: String,
     pub target_client_id: Option<String>, // 如果为None则广播给所有客户端
-    pub message_type: Option<String>, // "text" 或 "hex"，默认为 "text"
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub template: Option<bool>,       // 发送前展开 {{...}} 模板占位符，默认为 false
+    pub checksum: Option<ChecksumParams>, // 发送前计算并插入校验值
 }
 
 // 服务器状态信息
@@ -86,6 +115,7 @@ pub struct TcpServerEvent {
     pub event_type: String,
     pub client_id: String,
     pub message: String,
+    pub checksum: Option<ChecksumCheck>, // 启用接收校验时的校验结果
     pub timestamp: String,
 }
 
@@ -95,6 +125,10 @@ impl TcpServer {
             host,
             port,
             server_id,
+            modbus_store: None,
+            checksum: None,
+            framing: None,
+            shaping: Arc::new(TcpShaping::default()),
             clients: Arc::new(RwLock::new(HashMap::new())),
             server_handle: None,
             shutdown_sender: None,
@@ -115,6 +149,12 @@ impl TcpServer {
         let clients = Arc::clone(&self.clients);
         let app_handle = self.app_handle.clone();
         let server_id = self.server_id.clone();
+        let config = ConnectionConfig {
+            modbus_store: self.modbus_store.clone(),
+            checksum: self.checksum.clone(),
+            framing: self.framing.clone(),
+            shaping: Arc::clone(&self.shaping),
+        };
         let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
         self.shutdown_sender = Some(shutdown_tx);
 
@@ -133,7 +173,7 @@ impl TcpServer {
                                 let clients_clone = Arc::clone(&clients);
                                 let app_handle_clone = app_handle.clone();
                                 let server_id_clone = server_id.clone();
-                                tokio::spawn(handle_tcp_connection(stream, addr, clients_clone, app_handle_clone, server_id_clone));
+                                tokio::spawn(handle_tcp_connection(stream, addr, clients_clone, app_handle_clone, server_id_clone, config.clone()));
                             }
                             Err(e) => {
                                 eprintln!("Failed to accept TCP connection: {}", e);
@@ -171,7 +211,7 @@ impl TcpServer {
         if let Some(client) = clients.get(client_id) {
             client
                 .sender
-                .send(data)
+                .send((data, None))
                 .map_err(|e| format!("Failed to send message to client {}: {}", client_id, e))?;
             Ok(())
         } else {
@@ -184,7 +224,7 @@ impl TcpServer {
         let mut sent_count = 0;
 
         for (_, client) in clients.iter() {
-            if client.sender.send(data.clone()).is_ok() {
+            if client.sender.send((data.clone(), None)).is_ok() {
                 sent_count += 1;
             }
         }
@@ -192,6 +232,26 @@ impl TcpServer {
         Ok(sent_count)
     }
 
+    // 发送给指定客户端或广播给所有客户端，返回每个客户端写出数据后完成的通知
+    pub async fn send_message_with_ack(&self, client_id: Option<&str>, data: Vec<u8>) -> Result<Vec<WriteAckReceiver>, String> {
+        let clients = self.clients.read().await;
+        let targets: Vec<&TcpClient> = match client_id {
+            Some(client_id) => vec![clients.get(client_id).ok_or_else(|| format!("Client {} not found", client_id))?],
+            None => clients.values().collect(),
+        };
+
+        let mut receivers = Vec::new();
+        for client in targets {
+            let (ack, receiver) = write_ack::channel();
+            if client.sender.send((data.clone(), Some(ack))).is_ok() {
+                receivers.push(receiver);
+            } else if client_id.is_some() {
+                return Err(format!("Failed to send message to client {}", client.id));
+            }
+        }
+        Ok(receivers)
+    }
+
     pub async fn get_client_count(&self) -> usize {
         self.clients.read().await.len()
     }
@@ -208,7 +268,9 @@ async fn handle_tcp_connection(
     clients: Arc<RwLock<HashMap<String, TcpClient>>>,
     app_handle: Option<tauri::AppHandle>,
     server_id: String,
+    config: ConnectionConfig,
 ) {
+    let ConnectionConfig { modbus_store, checksum, framing, shaping } = config;
     let client_id = Uuid::new_v4().to_string();
     println!("New TCP client connected: {} ({})", client_id, addr);
 
@@ -219,6 +281,7 @@ async fn handle_tcp_connection(
             event_type: "client_connected".to_string(),
             client_id: client_id.clone(),
             message: format!("Client connected from {}", addr),
+            checksum: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
         
@@ -227,7 +290,8 @@ async fn handle_tcp_connection(
         }
     }
 
-    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let (tx, mut rx) = mpsc::unbounded_channel::<(Vec<u8>, Option<WriteAck>)>();
+    let responder = tx.clone();
 
     // 添加客户端到集合
     {
@@ -247,19 +311,44 @@ async fn handle_tcp_connection(
 
     // 启动发送任务
     let client_id_sender = client_id.clone();
+    let shaping_sender = Arc::clone(&shaping);
     let send_task = tokio::spawn(async move {
-        while let Some(data) = rx.recv().await {
-            if writer.write_all(&data).await.is_err() {
+        while let Some((data, ack)) = rx.recv().await {
+            if shaping_sender.write(&mut writer, &data).await.is_err() {
                 println!("Failed to send data to client {}", client_id_sender);
+                write_ack::notify(ack, Err(format!("Failed to send data to client {}", client_id_sender)));
                 break;
             }
             if writer.flush().await.is_err() {
                 println!("Failed to flush data to client {}", client_id_sender);
+                write_ack::notify(ack, Err(format!("Failed to flush data to client {}", client_id_sender)));
                 break;
             }
+            write_ack::notify(ack, Ok(()));
         }
     });
 
+    // Modbus从站模式：按MBAP帧应答请求
+    if let Some(store) = modbus_store {
+        let receive_task = tokio::spawn(modbus_slave::serve_connection(
+            reader,
+            responder,
+            store,
+            client_id.clone(),
+            server_id.clone(),
+            app_handle.clone(),
+        ));
+
+        tokio::select! {
+            _ = send_task => {},
+            _ = receive_task => {},
+        }
+
+        clients.write().await.remove(&client_id);
+        println!("Client {} disconnected and cleaned up", client_id);
+        return;
+    }
+
     // 接收消息循环
     let client_id_receiver = client_id.clone();
     let clients_clone = Arc::clone(&clients);
@@ -267,9 +356,12 @@ async fn handle_tcp_connection(
     let server_id_clone = server_id.clone();
     let receive_task = tokio::spawn(async move {
         let mut buffer = [0; 1024];
+        let mut paced_reader = PacedReader::new();
+        let mut decoder = FrameDecoder::new(framing.unwrap_or(Framing::Any));
+        let capture_tap = receive_capture::tap(&app_handle_clone, "tcp_server", &server_id_clone).await;
         
         loop {
-            match reader.read(&mut buffer).await {
+            match paced_reader.read(&shaping, &mut reader, &mut buffer).await {
                 Ok(0) => {
                     // 连接关闭
                     println!("Client {} disconnected", client_id_receiver);
@@ -281,6 +373,7 @@ async fn handle_tcp_connection(
                             event_type: "client_disconnected".to_string(),
                             client_id: client_id_receiver.clone(),
                             message: "Client disconnected".to_string(),
+                            checksum: None,
                             timestamp: chrono::Utc::now().to_rfc3339(),
                         };
                         
@@ -291,36 +384,53 @@ async fn handle_tcp_connection(
                     break;
                 }
                 Ok(n) => {
-                    let received_data = &buffer[..n];
-                    
-                    // 尝试将数据转换为文本，如果失败则作为十六进制处理
-                    let message = match std::str::from_utf8(received_data) {
-                        Ok(text) => {
-                            println!("Received text from {}: {}", client_id_receiver, text);
-                            text.to_string()
-                        }
-                        Err(_) => {
-                            let hex_string = received_data.iter()
-                                .map(|b| format!("{:02x}", b))
-                                .collect::<Vec<String>>()
-                                .join(" ");
-                            println!("Received binary data from {}: {}", client_id_receiver, hex_string);
-                            format!("Binary data: {}", hex_string)
+                    // 按分帧规则切分，未设置时每次读取的数据作为一帧
+                    let frames = match decoder.push(&buffer[..n]) {
+                        Ok(frames) => frames,
+                        Err(e) => {
+                            eprintln!("TCP framing error for client {}: {}", client_id_receiver, e);
+                            break;
                         }
                     };
+
+                    for received_data in &frames {
+                        let received_data = received_data.as_slice();
+
+                        // 写入接收记录，被接管的数据不再发送事件
+                        if capture_tap.capture(&client_id_receiver, received_data).await {
+                            continue;
+                        }
                     
-                    // 发送事件到前端
-                    if let Some(ref app) = app_handle_clone {
-                        let event = TcpServerEvent {
-                            server_id: server_id_clone.clone(),
-                            event_type: "message_received".to_string(),
-                            client_id: client_id_receiver.clone(),
-                            message,
-                            timestamp: chrono::Utc::now().to_rfc3339(),
+                        // 尝试将数据转换为文本，如果失败则作为十六进制处理
+                        let message = match std::str::from_utf8(received_data) {
+                            Ok(text) => {
+                                println!("Received text from {}: {}", client_id_receiver, text);
+                                text.to_string()
+                            }
+                            Err(_) => {
+                                let hex_string = received_data.iter()
+                                    .map(|b| format!("{:02x}", b))
+                                    .collect::<Vec<String>>()
+                                    .join(" ");
+                                println!("Received binary data from {}: {}", client_id_receiver, hex_string);
+                                format!("Binary data: {}", hex_string)
+                            }
                         };
+                    
+                        // 发送事件到前端
+                        if let Some(ref app) = app_handle_clone {
+                            let event = TcpServerEvent {
+                                server_id: server_id_clone.clone(),
+                                event_type: "message_received".to_string(),
+                                client_id: client_id_receiver.clone(),
+                                message,
+                                checksum: checksum.as_ref().map(|checksum| checksum.verify(received_data)),
+                                timestamp: chrono::Utc::now().to_rfc3339(),
+                            };
                         
-                        if let Err(e) = app.emit("tcp-server-event", &event) {
-                            eprintln!("Failed to emit event to frontend: {}", e);
+                            if let Err(e) = app.emit("tcp-server-event", &event) {
+                                eprintln!("Failed to emit event to frontend: {}", e);
+                            }
                         }
                     }
                 }
@@ -346,26 +456,6 @@ async fn handle_tcp_connection(
     println!("Client {} disconnected and cleaned up", client_id);
 }
 
-// 辅助函数：解析十六进制字符串为字节数组
-fn parse_hex_string(hex_str: &str) -> Result<Vec<u8>, String> {
-    let cleaned = hex_str.replace(" ", "").replace("\n", "").replace("\r", "");
-    
-    if cleaned.len() % 2 != 0 {
-        return Err("Hex string must have even number of characters".to_string());
-    }
-    
-    let mut bytes = Vec::new();
-    for i in (0..cleaned.len()).step_by(2) {
-        let hex_byte = &cleaned[i..i+2];
-        match u8::from_str_radix(hex_byte, 16) {
-            Ok(byte) => bytes.push(byte),
-            Err(_) => return Err(format!("Invalid hex characters: {}", hex_byte)),
-        }
-    }
-    
-    Ok(bytes)
-}
-
 // Tauri命令：启动TCP服务器
 #[tauri::command]
 pub async fn start_tcp_server(
@@ -382,6 +472,17 @@ pub async fn start_tcp_server(
     }
 
     let mut server = TcpServer::new(start_params.host.clone(), start_params.port, server_id.clone());
+    server.modbus_store = match start_params.protocol.as_deref() {
+        None | Some("") | Some("raw") => None,
+        Some("modbus") => Some(Arc::new(RwLock::new(ModbusDataStore::new()))),
+        Some(other) => return Err(format!("Unsupported server protocol: {}", other)),
+    };
+    server.framing = start_params.framing.as_ref().map(Framing::from_params).transpose()?;
+    server.checksum = Checksum::from_params(start_params.checksum.as_ref())?;
+    if server.checksum.is_some() {
+        tcp_framing::require_framing(server.framing.as_ref(), "Checksum verification")?;
+    }
+    server.shaping.configure(start_params.fragmentation.as_ref(), start_params.slow_read.as_ref())?;
     server.set_app_handle(app_handle);
     server.start().await?;
 
@@ -417,6 +518,7 @@ pub async fn stop_tcp_server(
 pub async fn send_tcp_message(
     send_params: SendTcpMessageParams,
     state: State<'_, Mutex<TcpServerManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
 ) -> Result<String, String> {
     if send_params.server_id.is_empty() {
         return Err("Server ID cannot be empty".to_string());
@@ -425,21 +527,22 @@ pub async fn send_tcp_message(
     if send_params.message.is_empty() {
         return Err("Message cannot be empty".to_string());
     }
+
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.server_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
     
     let manager = state.lock().await;
 
     if let Some(server) = manager.servers.get(&send_params.server_id) {
         // 根据消息类型处理数据
-        let data = match send_params.message_type.as_deref().unwrap_or("text") {
-            "hex" => {
-                // 解析十六进制字符串
-                parse_hex_string(&send_params.message)?
-            }
-            _ => {
-                // 默认作为文本处理
-                send_params.message.as_bytes().to_vec()
-            }
-        };
+        let data = payload_codec::decode(&message, send_params.message_type.as_deref())?;
+        let data = checksum::append_checksum(data, send_params.checksum.as_ref())?;
 
         if let Some(target_client_id) = send_params.target_client_id {
             // 发送给特定客户端
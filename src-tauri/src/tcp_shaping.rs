@@ -0,0 +1,216 @@
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::tcp_client::TcpClientManager;
+use crate::tcp_server::TcpServerManager;
+
+// 分段发送参数：把每条数据拆成多次写入，模拟帧跨越多个TCP报文段
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FragmentParams {
+    pub chunk_size: Option<usize>,     // 固定分段大小
+    pub min_chunk_size: Option<usize>, // 随机分段大小的下限，与 maxChunkSize 同时设置时生效
+    pub max_chunk_size: Option<usize>, // 随机分段大小的上限
+    pub delay_ms: Option<u64>,         // 分段之间的延迟
+    pub max_delay_ms: Option<u64>,     // 设置时延迟在 delayMs 到 maxDelayMs 之间随机
+}
+
+// 慢速读取参数：限制每次读取的字节数并在读取之间等待，让对端感受到背压
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowReadParams {
+    pub read_size: Option<usize>, // 每次最多读取的字节数，默认为 1024
+    pub delay_ms: u64,            // 两次读取之间的间隔
+}
+
+// 设置连接的收发节奏
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTcpShapingParams {
+    pub connection_type: String, // "tcp_client" 或 "tcp_server"（作用于该服务器的所有客户端）
+    pub connection_id: String,
+    pub fragmentation: Option<FragmentParams>, // 为None则整块写入
+    pub slow_read: Option<SlowReadParams>,     // 为None则正常读取
+}
+
+// 当前的收发节奏设置（发送给前端）
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpShapingInfo {
+    pub fragmentation: Option<FragmentParams>,
+    pub slow_read: Option<SlowReadParams>,
+}
+
+// 校验后的分段设置
+#[derive(Clone, Debug)]
+struct Fragmentation {
+    params: FragmentParams,
+    min_size: usize,
+    max_size: usize,
+    min_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Fragmentation {
+    fn from_params(params: &FragmentParams) -> Result<Self, String> {
+        let (min_size, max_size) = match (params.chunk_size, params.min_chunk_size, params.max_chunk_size) {
+            (_, Some(min), Some(max)) => (min, max),
+            (Some(size), None, None) => (size, size),
+            (None, None, None) => return Err("Chunk size or chunk size range is required".to_string()),
+            _ => return Err("Both minChunkSize and maxChunkSize are required for random chunk sizes".to_string()),
+        };
+        if min_size == 0 || max_size < min_size {
+            return Err(format!("Chunk size range {}..{} is invalid", min_size, max_size));
+        }
+
+        let min_delay = params.delay_ms.unwrap_or(0);
+        let max_delay = params.max_delay_ms.unwrap_or(min_delay);
+        if max_delay < min_delay {
+            return Err(format!("Delay range {}..{} ms is invalid", min_delay, max_delay));
+        }
+
+        Ok(Fragmentation {
+            params: params.clone(),
+            min_size,
+            max_size,
+            min_delay: Duration::from_millis(min_delay),
+            max_delay: Duration::from_millis(max_delay),
+        })
+    }
+
+    fn chunk_size(&self) -> usize {
+        rand::thread_rng().gen_range(self.min_size..=self.max_size)
+    }
+
+    fn delay(&self) -> Duration {
+        if self.max_delay == self.min_delay {
+            return self.min_delay;
+        }
+        rand::thread_rng().gen_range(self.min_delay..=self.max_delay)
+    }
+}
+
+// 连接的收发节奏设置，可在运行时修改，服务器的所有客户端共用
+#[derive(Default)]
+pub struct TcpShaping {
+    fragmentation: StdMutex<Option<Fragmentation>>,
+    slow_read: StdMutex<Option<SlowReadParams>>,
+}
+
+impl TcpShaping {
+    pub fn configure(&self, fragmentation: Option<&FragmentParams>, slow_read: Option<&SlowReadParams>) -> Result<(), String> {
+        let fragmentation = fragmentation.map(Fragmentation::from_params).transpose()?;
+        if slow_read.is_some_and(|slow_read| slow_read.read_size == Some(0)) {
+            return Err("Read size must be greater than 0".to_string());
+        }
+        *self.fragmentation.lock().unwrap() = fragmentation;
+        *self.slow_read.lock().unwrap() = slow_read.cloned();
+        Ok(())
+    }
+
+    pub fn info(&self) -> TcpShapingInfo {
+        TcpShapingInfo {
+            fragmentation: self.fragmentation.lock().unwrap().as_ref().map(|fragmentation| fragmentation.params.clone()),
+            slow_read: self.slow_read.lock().unwrap().clone(),
+        }
+    }
+
+    // 写入一条数据：启用分段时打开TCP_NODELAY，逐段写入并在段之间等待
+    pub async fn write(&self, writer: &mut OwnedWriteHalf, data: &[u8]) -> std::io::Result<()> {
+        let fragmentation = self.fragmentation.lock().unwrap().clone();
+        let fragmentation = match fragmentation {
+            Some(fragmentation) => fragmentation,
+            None => return writer.write_all(data).await,
+        };
+
+        writer.as_ref().set_nodelay(true)?;
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            let (chunk, rest) = remaining.split_at(fragmentation.chunk_size().min(remaining.len()));
+            writer.write_all(chunk).await?;
+            writer.flush().await?;
+            remaining = rest;
+            if !remaining.is_empty() {
+                let delay = fragmentation.delay();
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 按慢速读取设置节流的读取器，由接收循环持有
+pub struct PacedReader {
+    next_read: Instant,
+}
+
+impl PacedReader {
+    pub fn new() -> Self {
+        PacedReader {
+            next_read: Instant::now(),
+        }
+    }
+
+    // 等到允许读取的时刻后读取一次，慢速模式下限制读取长度
+    // 等待和读取都可以安全地在 select! 中取消
+    pub async fn read<R: AsyncRead + Unpin>(&mut self, shaping: &TcpShaping, reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+        tokio::time::sleep_until(self.next_read).await;
+        let slow_read = shaping.slow_read.lock().unwrap().clone();
+        let len = match &slow_read {
+            Some(slow_read) => slow_read.read_size.unwrap_or(buffer.len()).min(buffer.len()),
+            None => buffer.len(),
+        };
+        let n = reader.read(&mut buffer[..len]).await?;
+        if let Some(slow_read) = slow_read {
+            self.next_read = Instant::now() + Duration::from_millis(slow_read.delay_ms);
+        }
+        Ok(n)
+    }
+}
+
+impl Default for PacedReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tauri命令：设置TCP客户端或服务器的分段发送和慢速读取
+#[tauri::command]
+pub async fn set_tcp_shaping(
+    shaping_params: SetTcpShapingParams,
+    clients: State<'_, Mutex<TcpClientManager>>,
+    servers: State<'_, Mutex<TcpServerManager>>,
+) -> Result<TcpShapingInfo, String> {
+    let fragmentation = shaping_params.fragmentation.as_ref();
+    let slow_read = shaping_params.slow_read.as_ref();
+    match shaping_params.connection_type.as_str() {
+        "tcp_client" => {
+            let manager = clients.lock().await;
+            let client = manager
+                .clients
+                .get(&shaping_params.connection_id)
+                .ok_or_else(|| format!("TCP client {} not found", shaping_params.connection_id))?;
+            client.shaping.configure(fragmentation, slow_read)?;
+            Ok(client.shaping.info())
+        }
+        "tcp_server" => {
+            let manager = servers.lock().await;
+            let server = manager
+                .servers
+                .get(&shaping_params.connection_id)
+                .ok_or_else(|| format!("TCP Server with ID {} not found", shaping_params.connection_id))?;
+            server.shaping.configure(fragmentation, slow_read)?;
+            Ok(server.shaping.info())
+        }
+        other => Err(format!("Unsupported connection type: {}", other)),
+    }
+}
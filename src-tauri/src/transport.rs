@@ -0,0 +1,211 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpSocket, TcpStream};
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+
+use crate::net_address;
+
+// 客户端本地端点设置
+#[derive(Debug, Clone, Default)]
+pub struct LocalEndpoint {
+    pub address: Option<String>, // 本地源地址：IPv4/IPv6地址或网卡名称
+    pub port: Option<u16>,       // 本地源端口
+    pub reuse_address: bool,     // 是否启用SO_REUSEADDR
+}
+
+// TLS连接设置
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    pub server_name: Option<String>, // SNI名称，默认为目标主机名
+    pub insecure: bool,              // 跳过证书校验（用于自签名证书的设备）
+    pub alpn_protocols: Vec<Vec<u8>>,
+}
+
+// 解析目标地址和本地绑定地址
+pub async fn resolve_endpoint(host: &str, port: u16, local: &LocalEndpoint) -> Result<(SocketAddr, Option<SocketAddr>), String> {
+    let local_bind = match &local.address {
+        Some(address) if !address.trim().is_empty() => {
+            Some(net_address::resolve_bind_address(Some(address), local.port.unwrap_or(0))?)
+        }
+        _ => None,
+    };
+
+    let target = net_address::resolve_target(host, port, local_bind).await?;
+
+    // 仅指定端口时，绑定到与目标相同地址族的通配地址
+    let local_bind = local_bind.or_else(|| {
+        local.port.map(|port| {
+            let ip = if target.is_ipv4() {
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+            } else {
+                IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+            };
+            SocketAddr::new(ip, port)
+        })
+    });
+
+    Ok((target, local_bind))
+}
+
+// 通过TcpSocket建立连接，以便在连接前绑定本地地址和端口
+pub async fn connect_resolved(target: SocketAddr, local_bind: Option<SocketAddr>, reuse_address: bool) -> Result<TcpStream, String> {
+    let socket = if target.is_ipv4() {
+        TcpSocket::new_v4()
+    } else {
+        TcpSocket::new_v6()
+    }
+    .map_err(|e| format!("Failed to create socket: {}", e))?;
+
+    if reuse_address {
+        socket
+            .set_reuseaddr(true)
+            .map_err(|e| format!("Failed to enable SO_REUSEADDR: {}", e))?;
+    }
+
+    if let Some(local_bind) = local_bind {
+        socket
+            .bind(local_bind)
+            .map_err(|e| format!("Failed to bind local address {}: {}", local_bind, e))?;
+    }
+
+    socket
+        .connect(target)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", target, e))
+}
+
+pub async fn connect_tcp(host: &str, port: u16, local: &LocalEndpoint) -> Result<TcpStream, String> {
+    let (target, local_bind) = resolve_endpoint(host, port, local).await?;
+    connect_resolved(target, local_bind, local.reuse_address).await
+}
+
+// 在已建立的TCP连接上进行TLS握手
+pub async fn start_tls(stream: TcpStream, host: &str, options: &TlsOptions) -> Result<TlsStream<TcpStream>, String> {
+    let config = tls_client_config(options)?;
+    let server_name = options.server_name.as_deref().unwrap_or(host);
+    let server_name = net_address::parse_scoped_ip(server_name)?
+        .map(|(ip, _)| ServerName::IpAddress(ip.into()))
+        .map(Ok)
+        .unwrap_or_else(|| ServerName::try_from(server_name.to_string()))
+        .map_err(|e| format!("Invalid TLS server name {}: {}", server_name, e))?;
+
+    TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| format!("TLS handshake failed: {}", e))
+}
+
+fn tls_client_config(options: &TlsOptions) -> Result<rustls::ClientConfig, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to configure TLS: {}", e))?;
+
+    let mut config = if options.insecure {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoCertificateVerification(provider)))
+            .with_no_client_auth()
+    } else {
+        let mut root_store = rustls::RootCertStore::empty();
+        root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        builder.with_root_certificates(root_store).with_no_client_auth()
+    };
+
+    config.alpn_protocols = options.alpn_protocols.clone();
+    Ok(config)
+}
+
+// 跳过证书校验，仅用于调试自签名证书的设备
+#[derive(Debug)]
+struct NoCertificateVerification(Arc<rustls::crypto::CryptoProvider>);
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+// 明文或TLS客户端流
+pub enum ClientStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl ClientStream {
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            ClientStream::Plain(stream) => stream.local_addr(),
+            ClientStream::Tls(stream) => stream.get_ref().0.local_addr(),
+        }
+    }
+}
+
+impl AsyncRead for ClientStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            ClientStream::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ClientStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            ClientStream::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            ClientStream::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            ClientStream::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
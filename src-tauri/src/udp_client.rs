@@ -7,6 +7,17 @@ use tokio::task::JoinHandle;
 use uuid::Uuid;
 use chrono;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use socket2::{Domain, Protocol, Socket, Type};
+
+use crate::checksum::{self, Checksum, ChecksumCheck, ChecksumParams};
+use crate::correlation::{Correlation, CorrelationParams};
+use crate::message_template::{self, TemplateEngine};
+use crate::net_address;
+use crate::payload_codec;
+use crate::receive_capture::{self, CaptureTap};
+use crate::udp_impairment::{self, Direction, ImpairmentParams, ImpairmentQueue, UdpImpairment};
+use crate::write_ack::{self, WriteAck, WriteAckReceiver};
 
 // UDP客户端连接状态
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -17,17 +28,25 @@ pub enum UdpClientState {
     Error,
 }
 
+// 待发送的数据报：数据、目标地址、关联的请求ID和写出通知
+type OutgoingDatagram = (Vec<u8>, SocketAddr, Option<u64>, Option<WriteAck>);
+
 // UDP客户端
 pub struct UdpClient {
+    pub bind_address: Option<String>, // 本地绑定地址，None表示 0.0.0.0
     pub local_port: Option<u16>,   // 本地绑定端口，None表示系统自动分配
     pub actual_port: u16,          // 实际绑定的端口
+    pub local_addr: Option<SocketAddr>, // 实际绑定的地址
+    pub checksum: Option<Checksum>,     // 接收校验设置
+    pub impairment: Arc<UdpImpairment>, // 网络损伤设置，可在运行时修改
+    pub correlation: Arc<Correlation>,  // 请求/响应关联设置，可在运行时修改
     pub client_id: String,
     pub state: UdpClientState,
     pub socket: Option<UdpSocket>,
     pub receive_handle: Option<JoinHandle<()>>,
     pub send_handle: Option<JoinHandle<()>>,
     pub shutdown_sender: Option<broadcast::Sender<()>>,
-    pub message_sender: Option<mpsc::UnboundedSender<(Vec<u8>, SocketAddr)>>,
+    pub message_sender: Option<mpsc::UnboundedSender<OutgoingDatagram>>,
     pub app_handle: Option<tauri::AppHandle>,
 }
 
@@ -54,8 +73,12 @@ impl Default for UdpClientManager {
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartUdpClientParams {
+    pub bind_address: Option<String>, // IPv4/IPv6地址（可带作用域ID）、"::"（双栈）或网卡名称，默认为 0.0.0.0
     pub local_port: Option<u16>, // 本地绑定端口，None表示系统自动分配
     pub client_id: Option<String>,
+    pub checksum: Option<ChecksumParams>, // 校验收到的每个数据报，结果附加在接收事件上
+    pub impairment: Option<ImpairmentParams>, // 模拟丢包、重复、乱序、延迟和带宽限制
+    pub correlation: Option<CorrelationParams>, // 把每次发送作为请求，匹配随后收到的响应并计算往返时间
 }
 
 // 发送消息的参数
@@ -63,10 +86,12 @@ pub struct StartUdpClientParams {
 #[serde(rename_all = "camelCase")]
 pub struct SendUdpClientMessageParams {
     pub client_id: String,
-    pub target_host: String,  // 目标主机地址
+    pub target_host: String,  // 目标主机地址，支持主机名、IPv6字面量（可带方括号和作用域ID）
     pub target_port: u16,     // 目标端口
     pub message: String,
-    pub message_type: Option<String>, // "text" 或 "hex"，默认为 "text"
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub template: Option<bool>,       // 发送前展开 {{...}} 模板占位符，默认为 false
+    pub checksum: Option<ChecksumParams>, // 发送前计算并插入校验值
 }
 
 // 客户端状态信息
@@ -74,6 +99,7 @@ pub struct SendUdpClientMessageParams {
 #[serde(rename_all = "camelCase")]
 pub struct UdpClientInfo {
     pub client_id: String,
+    pub local_address: String, // 本地绑定地址
     pub local_port: u16,      // 本地绑定端口
     pub state: UdpClientState,
 }
@@ -85,14 +111,20 @@ pub struct UdpClientEvent {
     pub client_id: String,
     pub event_type: String,
     pub message: String,
+    pub checksum: Option<ChecksumCheck>, // 启用接收校验时的校验结果
     pub timestamp: String,
 }
 
 impl UdpClient {
-    pub fn new(local_port: Option<u16>, client_id: String) -> Self {
+    pub fn new(bind_address: Option<String>, local_port: Option<u16>, client_id: String) -> Self {
         UdpClient {
+            bind_address,
             local_port,
             actual_port: 0,
+            local_addr: None,
+            checksum: None,
+            impairment: Arc::new(UdpImpairment::default()),
+            correlation: Arc::new(Correlation::new("udp_client", &client_id)),
             client_id,
             state: UdpClientState::Disconnected,
             socket: None,
@@ -115,15 +147,22 @@ impl UdpClient {
 
         self.state = UdpClientState::Connecting;
         
-        // 绑定到本地端口，如果没有指定端口则使用0让系统自动分配
-        let bind_addr = format!("0.0.0.0:{}", self.local_port.unwrap_or(0));
+        // 绑定到本地地址和端口，如果没有指定端口则使用0让系统自动分配
+        let bind_addr = match net_address::resolve_bind_address(self.bind_address.as_deref(), self.local_port.unwrap_or(0)) {
+            Ok(addr) => addr,
+            Err(e) => {
+                self.state = UdpClientState::Error;
+                return Err(e);
+            }
+        };
         
-        match UdpSocket::bind(&bind_addr).await {
+        match bind_udp_socket(bind_addr) {
             Ok(socket) => {
-                // 获取实际绑定的端口
-                self.actual_port = socket.local_addr()
-                    .map_err(|e| format!("Failed to get local address: {}", e))?
-                    .port();
+                // 获取实际绑定的地址和端口
+                let local_addr = socket.local_addr()
+                    .map_err(|e| format!("Failed to get local address: {}", e))?;
+                self.actual_port = local_addr.port();
+                self.local_addr = Some(local_addr);
                 
                 self.socket = Some(socket);
                 self.state = UdpClientState::Connected;
@@ -133,7 +172,8 @@ impl UdpClient {
                     let event = UdpClientEvent {
                         client_id: self.client_id.clone(),
                         event_type: "connected".to_string(),
-                        message: format!("UDP client started on port {}", self.actual_port),
+                        message: format!("UDP client started on {}", local_addr),
+                        checksum: None,
                         timestamp: chrono::Utc::now().to_rfc3339(),
                     };
                     let _ = app_handle.emit("udp-client-event", &event);
@@ -180,6 +220,7 @@ impl UdpClient {
                 client_id: self.client_id.clone(),
                 event_type: "disconnected".to_string(),
                 message: "UDP client stopped".to_string(),
+                checksum: None,
                 timestamp: chrono::Utc::now().to_rfc3339(),
             };
             let _ = app_handle.emit("udp-client-event", &event);
@@ -190,7 +231,7 @@ impl UdpClient {
 
     async fn start_tasks(&mut self) -> Result<(), String> {
         let socket = self.socket.take().ok_or("No socket available")?;
-        let socket = std::sync::Arc::new(socket);
+        let socket = Arc::new(socket);
         let socket_send = socket.clone();
         let socket_recv = socket.clone();
 
@@ -203,15 +244,22 @@ impl UdpClient {
         // 启动接收任务
         let client_id = self.client_id.clone();
         let app_handle = self.app_handle.clone();
+        let checksum = self.checksum.clone();
+        let impairment = Arc::clone(&self.impairment);
+        let correlation = Arc::clone(&self.correlation);
         let shutdown_rx_clone = shutdown_tx.subscribe();
         self.receive_handle = Some(tokio::spawn(async move {
-            handle_udp_client_receive(socket_recv, client_id, app_handle, shutdown_rx_clone).await;
+            handle_udp_client_receive(socket_recv, client_id, app_handle, checksum, impairment, correlation, shutdown_rx_clone).await;
         }));
 
         // 启动发送任务
+        let client_id = self.client_id.clone();
+        let app_handle = self.app_handle.clone();
+        let impairment = Arc::clone(&self.impairment);
+        let correlation = Arc::clone(&self.correlation);
         let shutdown_rx_clone = shutdown_tx.subscribe();
         self.send_handle = Some(tokio::spawn(async move {
-            handle_udp_client_send(socket_send, message_rx, shutdown_rx_clone).await;
+            handle_udp_client_send(socket_send, client_id, app_handle, message_rx, impairment, correlation, shutdown_rx_clone).await;
         }));
 
         // 将socket放回，但实际上已经被Arc包装了
@@ -220,13 +268,25 @@ impl UdpClient {
         Ok(())
     }
 
-    pub async fn send_message(&self, message: Vec<u8>, target_addr: SocketAddr) -> Result<(), String> {
+    // 发送数据报，request_id 为 Correlation::open 登记的请求，写入套接字后记录发送时间
+    pub async fn send_message(&self, message: Vec<u8>, target_addr: SocketAddr, request_id: Option<u64>) -> Result<(), String> {
+        self.queue_message(message, target_addr, request_id, None)
+    }
+
+    // 发送数据报，返回的通知在数据报写入套接字或交给损伤层排队后完成
+    pub fn send_message_with_ack(&self, message: Vec<u8>, target_addr: SocketAddr) -> Result<WriteAckReceiver, String> {
+        let (ack, receiver) = write_ack::channel();
+        self.queue_message(message, target_addr, None, Some(ack))?;
+        Ok(receiver)
+    }
+
+    fn queue_message(&self, message: Vec<u8>, target_addr: SocketAddr, request_id: Option<u64>, ack: Option<WriteAck>) -> Result<(), String> {
         if self.state != UdpClientState::Connected {
             return Err("Not started".to_string());
         }
 
         if let Some(sender) = &self.message_sender {
-            sender.send((message, target_addr)).map_err(|e| format!("Failed to send message: {}", e))?;
+            sender.send((message, target_addr, request_id, ack)).map_err(|e| format!("Failed to send message: {}", e))?;
             Ok(())
         } else {
             Err("Message sender not available".to_string())
@@ -234,14 +294,31 @@ impl UdpClient {
     }
 }
 
+// 创建并绑定UDP套接字，绑定到 "::" 时关闭IPV6_V6ONLY以同时接收IPv4数据
+fn bind_udp_socket(bind_addr: SocketAddr) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::for_address(bind_addr), Type::DGRAM, Some(Protocol::UDP))?;
+    if let SocketAddr::V6(v6) = bind_addr {
+        socket.set_only_v6(!v6.ip().is_unspecified())?;
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&bind_addr.into())?;
+    UdpSocket::from_std(socket.into())
+}
+
 // 处理UDP客户端接收消息
 async fn handle_udp_client_receive(
-    socket: std::sync::Arc<UdpSocket>,
+    socket: Arc<UdpSocket>,
     client_id: String,
     app_handle: Option<tauri::AppHandle>,
+    checksum: Option<Checksum>,
+    impairment: Arc<UdpImpairment>,
+    correlation: Arc<Correlation>,
     mut shutdown_rx: broadcast::Receiver<()>,
 ) {
-    let mut buffer = vec![0; 1024];
+    // 按最大UDP数据报分配，避免较大的数据报被截断
+    let mut buffer = vec![0; 65535];
+    let mut queue = ImpairmentQueue::new(Direction::Receive);
+    let capture_tap = receive_capture::tap(&app_handle, "udp_client", &client_id).await;
     
     loop {
         tokio::select! {
@@ -249,23 +326,11 @@ async fn handle_udp_client_receive(
             _ = shutdown_rx.recv() => {
                 break;
             }
-            // 读取数据
+            // 读取数据，经过损伤层后再处理
             result = socket.recv_from(&mut buffer) => {
                 match result {
                     Ok((n, from_addr)) => {
-                        let received_data = buffer[..n].to_vec();
-                        
-                        // 发送接收到的消息事件
-                        if let Some(app_handle) = &app_handle {
-                            let message = String::from_utf8_lossy(&received_data).to_string();
-                            let event = UdpClientEvent {
-                                client_id: client_id.clone(),
-                                event_type: "message_received".to_string(),
-                                message: format!("From {}: {}", from_addr, message),
-                                timestamp: chrono::Utc::now().to_rfc3339(),
-                            };
-                            let _ = app_handle.emit("udp-client-event", &event);
-                        }
+                        queue.push(&impairment, buffer[..n].to_vec(), from_addr);
                     }
                     Err(e) => {
                         if let Some(app_handle) = &app_handle {
@@ -273,6 +338,7 @@ async fn handle_udp_client_receive(
                                 client_id: client_id.clone(),
                                 event_type: "error".to_string(),
                                 message: format!("Read error: {}", e),
+                                checksum: None,
                                 timestamp: chrono::Utc::now().to_rfc3339(),
                             };
                             let _ = app_handle.emit("udp-client-event", &event);
@@ -281,37 +347,106 @@ async fn handle_udp_client_receive(
                     }
                 }
             }
+            // 等待延迟的数据报到期
+            _ = udp_impairment::wait_release(queue.next_release()) => {}
+            // 等待中的请求超时
+            _ = correlation.expire(&app_handle) => {}
+        }
+
+        for (received_data, from_addr) in queue.pop_due(&impairment) {
+            correlation.received(&app_handle, &received_data);
+            handle_udp_datagram(&app_handle, &client_id, &checksum, &capture_tap, received_data, from_addr).await;
         }
     }
+
+    correlation.close(&app_handle);
+}
+
+// 处理一个收到的数据报：写入接收记录，未被接管时发送接收到的消息事件
+async fn handle_udp_datagram(
+    app_handle: &Option<tauri::AppHandle>,
+    client_id: &str,
+    checksum: &Option<Checksum>,
+    capture_tap: &CaptureTap,
+    received_data: Vec<u8>,
+    from_addr: SocketAddr,
+) {
+    if let Some(app_handle) = app_handle {
+        let source = net_address::display_addr(from_addr);
+        if capture_tap.capture(&source, &received_data).await {
+            return;
+        }
+        let message = String::from_utf8_lossy(&received_data).to_string();
+        let event = UdpClientEvent {
+            client_id: client_id.to_string(),
+            event_type: "message_received".to_string(),
+            message: format!("From {}: {}", source, message),
+            checksum: checksum.as_ref().map(|checksum| checksum.verify(&received_data)),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let _ = app_handle.emit("udp-client-event", &event);
+    }
 }
 
 // 处理UDP客户端发送消息
 async fn handle_udp_client_send(
-    socket: std::sync::Arc<UdpSocket>,
-    mut message_rx: mpsc::UnboundedReceiver<(Vec<u8>, SocketAddr)>,
+    socket: Arc<UdpSocket>,
+    client_id: String,
+    app_handle: Option<tauri::AppHandle>,
+    mut message_rx: mpsc::UnboundedReceiver<OutgoingDatagram>,
+    impairment: Arc<UdpImpairment>,
+    correlation: Arc<Correlation>,
     mut shutdown_rx: broadcast::Receiver<()>,
 ) {
+    let mut queue = ImpairmentQueue::new(Direction::Send);
+
     loop {
+        let mut ack = None;
         tokio::select! {
             // 检查是否收到关闭信号
             _ = shutdown_rx.recv() => {
                 break;
             }
-            // 发送消息
+            // 发送消息，经过损伤层后再写入套接字
             message = message_rx.recv() => {
                 match message {
-                    Some((data, addr)) => {
-                        if let Err(e) = socket.send_to(&data, addr).await {
-                            eprintln!("Failed to send UDP data: {}", e);
-                            break;
-                        }
+                    Some((data, addr, request_id, message_ack)) => {
+                        queue.push(&impairment, data, (addr, request_id));
+                        ack = message_ack;
                     }
                     None => {
                         break;
                     }
                 }
             }
+            // 等待延迟的数据报到期
+            _ = udp_impairment::wait_release(queue.next_release()) => {}
         }
+
+        // 单个数据报发送失败（例如目标不可达）不影响后续数据报，错误通过写出通知和事件报告
+        let mut result = Ok(());
+        for (data, (addr, request_id)) in queue.pop_due(&impairment) {
+            if let Err(e) = socket.send_to(&data, addr).await {
+                let message = format!("Failed to send UDP data to {}: {}", addr, e);
+                eprintln!("{}", message);
+                if let Some(app_handle) = &app_handle {
+                    let event = UdpClientEvent {
+                        client_id: client_id.clone(),
+                        event_type: "error".to_string(),
+                        message: message.clone(),
+                        checksum: None,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    };
+                    let _ = app_handle.emit("udp-client-event", &event);
+                }
+                result = Err(message);
+                continue;
+            }
+            // 损伤层延迟的数据报按实际写出时间计算往返时间，重复的数据报只记录第一次
+            correlation.sent(request_id);
+        }
+        // 损伤层延迟的数据报视为已交给网络
+        write_ack::notify(ack, result);
     }
 }
 
@@ -323,7 +458,10 @@ pub async fn start_udp_client(
     app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
     let client_id = start_params.client_id.unwrap_or_else(|| Uuid::new_v4().to_string());
-    let mut client = UdpClient::new(start_params.local_port, client_id.clone());
+    let mut client = UdpClient::new(start_params.bind_address, start_params.local_port, client_id.clone());
+    client.checksum = Checksum::from_params(start_params.checksum.as_ref())?;
+    client.impairment.configure(start_params.impairment.as_ref())?;
+    client.correlation.configure(start_params.correlation.as_ref())?;
     client.set_app_handle(app_handle);
     
     client.start().await?;
@@ -356,25 +494,41 @@ pub async fn stop_udp_client(
 pub async fn send_udp_client_message(
     send_params: SendUdpClientMessageParams,
     manager: State<'_, Mutex<UdpClientManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
 ) -> Result<(), String> {
-    let message_type = send_params.message_type.as_deref().unwrap_or("text");
-    let data = match message_type {
-        "hex" => {
-            // 将十六进制字符串转换为字节
-            let hex_str = send_params.message.replace(" ", "");
-            hex::decode(&hex_str).map_err(|e| format!("Invalid hex string: {}", e))?
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.client_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
+    let data = payload_codec::decode(&message, send_params.message_type.as_deref())?;
+    let data = checksum::append_checksum(data, send_params.checksum.as_ref())?;
+
+    // 解析目标地址前先取得本地地址，避免在DNS解析期间持有锁
+    let local_addr = {
+        let manager = manager.lock().await;
+        match manager.clients.get(&send_params.client_id) {
+            Some(client) => client.local_addr,
+            None => return Err(format!("UDP client {} not found", send_params.client_id)),
         }
-        _ => send_params.message.into_bytes(),
     };
 
     // 解析目标地址
-    let target_addr = format!("{}:{}", send_params.target_host, send_params.target_port);
-    let target_sockaddr: SocketAddr = target_addr.parse()
-        .map_err(|e| format!("Invalid target address {}: {}", target_addr, e))?;
+    let mut target_sockaddr = net_address::resolve_target(&send_params.target_host, send_params.target_port, local_addr).await?;
+    if let Some(local_addr) = local_addr {
+        target_sockaddr = net_address::map_to_local_family(target_sockaddr, local_addr);
+    }
 
     let manager = manager.lock().await;
     if let Some(client) = manager.clients.get(&send_params.client_id) {
-        client.send_message(data, target_sockaddr).await?;
+        let request_id = client.correlation.open(&client.app_handle, &data)?;
+        if let Err(e) = client.send_message(data, target_sockaddr, request_id).await {
+            client.correlation.cancel(request_id);
+            return Err(e);
+        }
         Ok(())
     } else {
         Err(format!("UDP client {} not found", send_params.client_id))
@@ -392,6 +546,7 @@ pub async fn get_udp_clients(
         .values()
         .map(|client| UdpClientInfo {
             client_id: client.client_id.clone(),
+            local_address: client.local_addr.map(|addr| addr.ip().to_string()).unwrap_or_default(),
             local_port: client.actual_port,
             state: client.state.clone(),
         })
@@ -409,6 +564,7 @@ pub async fn get_udp_client_info(
     if let Some(client) = manager.clients.get(&client_id) {
         Ok(UdpClientInfo {
             client_id: client.client_id.clone(),
+            local_address: client.local_addr.map(|addr| addr.ip().to_string()).unwrap_or_default(),
             local_port: client.actual_port,
             state: client.state.clone(),
         })
@@ -0,0 +1,356 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::udp_client::UdpClientManager;
+
+// 乱序包默认的额外延迟
+const DEFAULT_REORDER_DELAY_MS: u64 = 50;
+
+// 网络损伤参数
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpairmentParams {
+    pub direction: Option<String>,      // "send"、"receive" 或 "both"（默认）
+    pub loss_percent: Option<f64>,      // 丢包率（0-100）
+    pub duplicate_percent: Option<f64>, // 重复率（0-100），重复的包紧跟原包发出
+    pub reorder_percent: Option<f64>,   // 乱序率（0-100），选中的包额外延迟后被后续包超过
+    pub reorder_delay_ms: Option<u64>,  // 乱序包的额外延迟，默认为 50
+    pub delay_ms: Option<u64>,          // 固定延迟
+    pub jitter_ms: Option<u64>,         // 抖动，延迟在 delay ± jitter 内均匀分布
+    pub bandwidth_limit: Option<u64>,   // 带宽上限（字节/秒），超出的包排队等待
+}
+
+// 单个方向的损伤计数
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpairmentStats {
+    pub packets: u64,    // 进入损伤层的包数
+    pub passed: u64,     // 已放行的包数（含重复包）
+    pub dropped: u64,    // 丢弃的包数
+    pub duplicated: u64, // 产生的重复包数
+    pub reordered: u64,  // 被乱序的包数
+    pub delayed: u64,    // 因延迟、乱序或带宽限制而推迟放行的包数
+    pub queued: u64,     // 当前排队中的包数
+}
+
+// 损伤设置和计数（发送给前端）
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UdpImpairmentInfo {
+    pub client_id: String,
+    pub send: Option<ImpairmentParams>,
+    pub receive: Option<ImpairmentParams>,
+    pub send_stats: ImpairmentStats,
+    pub receive_stats: ImpairmentStats,
+}
+
+// 设置损伤的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetUdpImpairmentParams {
+    pub client_id: String,
+    pub impairment: Option<ImpairmentParams>, // 为None则关闭两个方向的损伤
+    pub reset_stats: Option<bool>,            // 同时清零计数
+}
+
+// 校验后的损伤设置
+#[derive(Clone, Debug)]
+struct ImpairmentSettings {
+    params: ImpairmentParams,
+    loss: f64,
+    duplicate: f64,
+    reorder: f64,
+    reorder_delay: Duration,
+    delay: Duration,
+    jitter: Duration,
+    bandwidth_limit: Option<u64>,
+}
+
+impl ImpairmentSettings {
+    fn from_params(params: &ImpairmentParams) -> Result<Self, String> {
+        let probability = |name: &str, value: Option<f64>| match value {
+            Some(value) if !(0.0..=100.0).contains(&value) => {
+                Err(format!("{} must be between 0 and 100, got {}", name, value))
+            }
+            Some(value) => Ok(value / 100.0),
+            None => Ok(0.0),
+        };
+        if params.bandwidth_limit == Some(0) {
+            return Err("Bandwidth limit must be greater than 0".to_string());
+        }
+
+        Ok(ImpairmentSettings {
+            params: params.clone(),
+            loss: probability("Loss percent", params.loss_percent)?,
+            duplicate: probability("Duplicate percent", params.duplicate_percent)?,
+            reorder: probability("Reorder percent", params.reorder_percent)?,
+            reorder_delay: Duration::from_millis(params.reorder_delay_ms.unwrap_or(DEFAULT_REORDER_DELAY_MS)),
+            delay: Duration::from_millis(params.delay_ms.unwrap_or(0)),
+            jitter: Duration::from_millis(params.jitter_ms.unwrap_or(0)),
+            bandwidth_limit: params.bandwidth_limit,
+        })
+    }
+
+    // 本次的传播延迟：delay ± jitter，不小于0
+    fn sample_delay(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.delay;
+        }
+        let jitter = self.jitter.as_secs_f64();
+        let offset = rand::thread_rng().gen_range(-jitter..=jitter);
+        Duration::from_secs_f64((self.delay.as_secs_f64() + offset).max(0.0))
+    }
+}
+
+fn chance(probability: f64) -> bool {
+    probability > 0.0 && rand::thread_rng().gen::<f64>() < probability
+}
+
+// 单个方向的设置和计数
+#[derive(Default)]
+struct DirectionState {
+    settings: Option<ImpairmentSettings>,
+    stats: ImpairmentStats,
+}
+
+// UDP客户端的损伤层，设置可在运行时修改，已排队的包保持原定的放行时间
+#[derive(Default)]
+pub struct UdpImpairment {
+    send: StdMutex<DirectionState>,
+    receive: StdMutex<DirectionState>,
+}
+
+// 数据方向
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Send,
+    Receive,
+}
+
+impl UdpImpairment {
+    fn direction(&self, direction: Direction) -> &StdMutex<DirectionState> {
+        match direction {
+            Direction::Send => &self.send,
+            Direction::Receive => &self.receive,
+        }
+    }
+
+    // 按 direction 字段设置一个或两个方向，None 关闭所有损伤
+    pub fn configure(&self, params: Option<&ImpairmentParams>) -> Result<(), String> {
+        let (settings, directions) = match params {
+            Some(params) => {
+                let directions: &[Direction] = match params.direction.as_deref() {
+                    None | Some("") | Some("both") => &[Direction::Send, Direction::Receive],
+                    Some("send") => &[Direction::Send],
+                    Some("receive") => &[Direction::Receive],
+                    Some(other) => return Err(format!("Unsupported impairment direction: {}", other)),
+                };
+                (Some(ImpairmentSettings::from_params(params)?), directions)
+            }
+            None => (None, &[Direction::Send, Direction::Receive][..]),
+        };
+        for direction in directions {
+            self.direction(*direction).lock().unwrap().settings = settings.clone();
+        }
+        Ok(())
+    }
+
+    pub fn reset_stats(&self) {
+        for direction in [Direction::Send, Direction::Receive] {
+            let mut state = self.direction(direction).lock().unwrap();
+            // 排队中的包仍会被放行，保留其数量
+            let queued = state.stats.queued;
+            state.stats = ImpairmentStats { queued, ..Default::default() };
+        }
+    }
+
+    pub fn info(&self, client_id: &str) -> UdpImpairmentInfo {
+        let send = self.send.lock().unwrap();
+        let receive = self.receive.lock().unwrap();
+        UdpImpairmentInfo {
+            client_id: client_id.to_string(),
+            send: send.settings.as_ref().map(|settings| settings.params.clone()),
+            receive: receive.settings.as_ref().map(|settings| settings.params.clone()),
+            send_stats: send.stats.clone(),
+            receive_stats: receive.stats.clone(),
+        }
+    }
+}
+
+// 排队中的包，按放行时间和进入顺序排序
+struct Pending<T> {
+    release: Instant,
+    sequence: u64,
+    delayed: bool,
+    data: Vec<u8>,
+    meta: T,
+}
+
+impl<T> PartialEq for Pending<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.release, self.sequence) == (other.release, other.sequence)
+    }
+}
+
+impl<T> Eq for Pending<T> {}
+
+impl<T> PartialOrd for Pending<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Pending<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.release, self.sequence).cmp(&(other.release, other.sequence))
+    }
+}
+
+// 一个方向的延迟队列，由收发任务持有
+pub struct ImpairmentQueue<T> {
+    direction: Direction,
+    pending: BinaryHeap<Reverse<Pending<T>>>,
+    sequence: u64,
+    link_free_at: Instant, // 带宽限制下链路空闲的时刻
+}
+
+impl<T: Clone> ImpairmentQueue<T> {
+    pub fn new(direction: Direction) -> Self {
+        ImpairmentQueue {
+            direction,
+            pending: BinaryHeap::new(),
+            sequence: 0,
+            link_free_at: Instant::now(),
+        }
+    }
+
+    // 按当前设置处理一个包：丢弃、复制、计算放行时间后入队
+    pub fn push(&mut self, impairment: &UdpImpairment, data: Vec<u8>, meta: T) {
+        let mut state = impairment.direction(self.direction).lock().unwrap();
+        let state = &mut *state;
+        state.stats.packets += 1;
+
+        let now = Instant::now();
+        let settings = match &state.settings {
+            Some(settings) => settings,
+            None => {
+                self.enqueue(&mut state.stats, now, false, data, meta);
+                return;
+            }
+        };
+        if chance(settings.loss) {
+            state.stats.dropped += 1;
+            return;
+        }
+
+        let duplicate = chance(settings.duplicate);
+        let mut release = now + settings.sample_delay();
+        if chance(settings.reorder) {
+            state.stats.reordered += 1;
+            release += settings.reorder_delay;
+        }
+
+        if duplicate {
+            state.stats.duplicated += 1;
+            let at = self.reserve_link(settings, release, data.len());
+            self.enqueue(&mut state.stats, at, at > now, data.clone(), meta.clone());
+        }
+        let at = self.reserve_link(settings, release, data.len());
+        self.enqueue(&mut state.stats, at, at > now, data, meta);
+    }
+
+    // 带宽限制下包需等到链路空闲后才能放行，并占用按长度计算的传输时间
+    fn reserve_link(&mut self, settings: &ImpairmentSettings, release: Instant, len: usize) -> Instant {
+        match settings.bandwidth_limit {
+            Some(limit) => {
+                let at = release.max(self.link_free_at);
+                self.link_free_at = at + Duration::from_secs_f64(len as f64 / limit as f64);
+                at
+            }
+            None => release,
+        }
+    }
+
+    fn enqueue(&mut self, stats: &mut ImpairmentStats, release: Instant, delayed: bool, data: Vec<u8>, meta: T) {
+        self.sequence += 1;
+        stats.queued += 1;
+        self.pending.push(Reverse(Pending {
+            release,
+            sequence: self.sequence,
+            delayed,
+            data,
+            meta,
+        }));
+    }
+
+    // 下一个包的放行时间
+    pub fn next_release(&self) -> Option<Instant> {
+        self.pending.peek().map(|Reverse(pending)| pending.release)
+    }
+
+    // 取出所有已到放行时间的包
+    pub fn pop_due(&mut self, impairment: &UdpImpairment) -> Vec<(Vec<u8>, T)> {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        let mut delayed = 0;
+        while self.pending.peek().is_some_and(|Reverse(pending)| pending.release <= now) {
+            if let Some(Reverse(pending)) = self.pending.pop() {
+                delayed += pending.delayed as u64;
+                due.push((pending.data, pending.meta));
+            }
+        }
+        if !due.is_empty() {
+            let mut state = impairment.direction(self.direction).lock().unwrap();
+            state.stats.passed += due.len() as u64;
+            state.stats.delayed += delayed;
+            state.stats.queued = state.stats.queued.saturating_sub(due.len() as u64);
+        }
+        due
+    }
+}
+
+// 等待到下一个包的放行时间，队列为空时一直等待
+pub async fn wait_release(release: Option<Instant>) {
+    match release {
+        Some(release) => tokio::time::sleep_until(release).await,
+        None => std::future::pending().await,
+    }
+}
+
+// Tauri命令：设置UDP客户端的网络损伤
+#[tauri::command]
+pub async fn set_udp_impairment(
+    impairment_params: SetUdpImpairmentParams,
+    manager: State<'_, Mutex<UdpClientManager>>,
+) -> Result<UdpImpairmentInfo, String> {
+    let manager = manager.lock().await;
+    let client = manager
+        .clients
+        .get(&impairment_params.client_id)
+        .ok_or_else(|| format!("UDP client {} not found", impairment_params.client_id))?;
+    client.impairment.configure(impairment_params.impairment.as_ref())?;
+    if impairment_params.reset_stats.unwrap_or(false) {
+        client.impairment.reset_stats();
+    }
+    Ok(client.impairment.info(&client.client_id))
+}
+
+// Tauri命令：获取UDP客户端的网络损伤设置和计数
+#[tauri::command]
+pub async fn get_udp_impairment(
+    client_id: String,
+    manager: State<'_, Mutex<UdpClientManager>>,
+) -> Result<UdpImpairmentInfo, String> {
+    let manager = manager.lock().await;
+    match manager.clients.get(&client_id) {
+        Some(client) => Ok(client.impairment.info(&client.client_id)),
+        None => Err(format!("UDP client {} not found", client_id)),
+    }
+}
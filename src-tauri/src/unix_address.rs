@@ -0,0 +1,135 @@
+use std::io;
+use std::os::unix::net::{
+    SocketAddr as StdSocketAddr, UnixDatagram as StdUnixDatagram, UnixListener as StdUnixListener,
+    UnixStream as StdUnixStream,
+};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::linux::net::SocketAddrExt;
+use serde::{Deserialize, Serialize};
+use tokio::net::{UnixListener, UnixStream};
+
+// Unix套接字地址：以 "@" 开头表示Linux抽象命名空间，否则为文件系统路径
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnixAddress {
+    Pathname(String),
+    Abstract(String),
+}
+
+impl UnixAddress {
+    pub fn parse(address: &str) -> Result<Self, String> {
+        if address.is_empty() {
+            return Err("Socket path cannot be empty".to_string());
+        }
+
+        match address.strip_prefix('@') {
+            Some(name) => {
+                if !cfg!(any(target_os = "linux", target_os = "android")) {
+                    return Err("Abstract namespace addresses are only supported on Linux".to_string());
+                }
+                Ok(UnixAddress::Abstract(name.to_string()))
+            }
+            None => Ok(UnixAddress::Pathname(address.to_string())),
+        }
+    }
+
+    pub fn to_socket_addr(&self) -> io::Result<StdSocketAddr> {
+        match self {
+            UnixAddress::Pathname(path) => StdSocketAddr::from_pathname(path),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            UnixAddress::Abstract(name) => StdSocketAddr::from_abstract_name(name.as_bytes()),
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            UnixAddress::Abstract(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "abstract namespace addresses are only supported on Linux",
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for UnixAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnixAddress::Pathname(path) => write!(f, "{}", path),
+            UnixAddress::Abstract(name) => write!(f, "@{}", name),
+        }
+    }
+}
+
+// 将标准库的套接字地址格式化为与UnixAddress相同的表示，未命名地址返回 "(unnamed)"
+pub fn describe_socket_addr(addr: &StdSocketAddr) -> String {
+    if let Some(path) = addr.as_pathname() {
+        return path.display().to_string();
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let Some(name) = addr.as_abstract_name() {
+        return format!("@{}", String::from_utf8_lossy(name));
+    }
+
+    "(unnamed)".to_string()
+}
+
+// 对端进程凭据（连接建立时由内核提供）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixPeerCredentials {
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: Option<i32>,
+}
+
+impl std::fmt::Display for UnixPeerCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.pid {
+            Some(pid) => write!(f, "uid={}, gid={}, pid={}", self.uid, self.gid, pid),
+            None => write!(f, "uid={}, gid={}", self.uid, self.gid),
+        }
+    }
+}
+
+pub fn peer_credentials(stream: &UnixStream) -> Option<UnixPeerCredentials> {
+    stream.peer_cred().ok().map(|cred| UnixPeerCredentials {
+        uid: cred.uid(),
+        gid: cred.gid(),
+        pid: cred.pid(),
+    })
+}
+
+// 绑定监听套接字（支持抽象命名空间）
+pub fn bind_listener(address: &UnixAddress) -> io::Result<UnixListener> {
+    let listener = StdUnixListener::bind_addr(&address.to_socket_addr()?)?;
+    listener.set_nonblocking(true)?;
+    UnixListener::from_std(listener)
+}
+
+// 连接到流式套接字（支持抽象命名空间）
+// 文件系统路径使用tokio的异步连接；抽象地址只能通过标准库连接，放到阻塞线程池中执行
+pub async fn connect_stream(address: &UnixAddress) -> io::Result<UnixStream> {
+    if let UnixAddress::Pathname(path) = address {
+        return UnixStream::connect(path).await;
+    }
+
+    let socket_addr = address.to_socket_addr()?;
+    let stream = tokio::task::spawn_blocking(move || StdUnixStream::connect_addr(&socket_addr))
+        .await
+        .map_err(io::Error::other)??;
+    stream.set_nonblocking(true)?;
+    UnixStream::from_std(stream)
+}
+
+// 绑定数据报套接字，未指定地址时创建未命名套接字
+pub fn bind_datagram(address: Option<&UnixAddress>) -> io::Result<StdUnixDatagram> {
+    let socket = match address {
+        Some(address) => StdUnixDatagram::bind_addr(&address.to_socket_addr()?)?,
+        None => StdUnixDatagram::unbound()?,
+    };
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+// 删除文件系统中残留的套接字文件，抽象地址无需清理
+pub fn remove_socket_file(address: &UnixAddress) {
+    if let UnixAddress::Pathname(path) = address {
+        let _ = std::fs::remove_file(path);
+    }
+}
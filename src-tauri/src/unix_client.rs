@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tokio::sync::{mpsc, Mutex, broadcast};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::message_template::{self, TemplateEngine};
+use crate::payload_codec;
+use crate::unix_address::{self, UnixAddress, UnixPeerCredentials};
+
+// Unix客户端连接状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UnixClientState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Error,
+}
+
+// Unix流式套接字客户端
+pub struct UnixClient {
+    pub address: UnixAddress,
+    pub client_id: String,
+    pub state: UnixClientState,
+    pub peer_credentials: Option<UnixPeerCredentials>,
+    pub stream: Option<UnixStream>,
+    pub receive_handle: Option<JoinHandle<()>>,
+    pub send_handle: Option<JoinHandle<()>>,
+    pub shutdown_sender: Option<broadcast::Sender<()>>,
+    pub message_sender: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// Unix客户端管理器
+pub struct UnixClientManager {
+    pub clients: HashMap<String, UnixClient>,
+}
+
+impl UnixClientManager {
+    pub fn new() -> Self {
+        UnixClientManager {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl Default for UnixClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 连接Unix服务器的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectUnixClientParams {
+    pub path: String, // 以 "@" 开头表示抽象命名空间（仅Linux）
+    pub client_id: Option<String>,
+}
+
+// 发送消息的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendUnixClientMessageParams {
+    pub client_id: String,
+    pub message: String,
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub template: Option<bool>,       // 发送前展开 {{...}} 模板占位符，默认为 false
+}
+
+// 客户端状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixClientInfo {
+    pub client_id: String,
+    pub path: String,
+    pub state: UnixClientState,
+    pub peer_credentials: Option<UnixPeerCredentials>, // 服务器进程的凭据
+}
+
+// Unix客户端事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixClientEvent {
+    pub client_id: String,
+    pub event_type: String,
+    pub message: String,
+    pub peer_credentials: Option<UnixPeerCredentials>, // 仅在 connected 事件中提供
+    pub timestamp: String,
+}
+
+impl UnixClient {
+    pub fn new(address: UnixAddress, client_id: String) -> Self {
+        UnixClient {
+            address,
+            client_id,
+            state: UnixClientState::Disconnected,
+            peer_credentials: None,
+            stream: None,
+            receive_handle: None,
+            send_handle: None,
+            shutdown_sender: None,
+            message_sender: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    pub async fn connect(&mut self) -> Result<(), String> {
+        if self.state == UnixClientState::Connected {
+            return Err("Already connected".to_string());
+        }
+
+        self.state = UnixClientState::Connecting;
+
+        match unix_address::connect_stream(&self.address).await {
+            Ok(stream) => {
+                self.peer_credentials = unix_address::peer_credentials(&stream);
+                self.stream = Some(stream);
+                self.state = UnixClientState::Connected;
+
+                // 发送连接成功事件
+                if let Some(app_handle) = &self.app_handle {
+                    let message = match &self.peer_credentials {
+                        Some(credentials) => format!("Connected to {} ({})", self.address, credentials),
+                        None => format!("Connected to {}", self.address),
+                    };
+                    let event = UnixClientEvent {
+                        client_id: self.client_id.clone(),
+                        event_type: "connected".to_string(),
+                        message,
+                        peer_credentials: self.peer_credentials.clone(),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    };
+                    let _ = app_handle.emit("unix-client-event", &event);
+                }
+
+                // 启动接收和发送任务
+                self.start_tasks().await?;
+                Ok(())
+            }
+            Err(e) => {
+                self.state = UnixClientState::Error;
+                Err(format!("Failed to connect to {}: {}", self.address, e))
+            }
+        }
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), String> {
+        if self.state != UnixClientState::Connected {
+            return Ok(());
+        }
+
+        // 发送关闭信号
+        if let Some(shutdown_sender) = &self.shutdown_sender {
+            let _ = shutdown_sender.send(());
+        }
+
+        // 等待任务完成
+        if let Some(receive_handle) = self.receive_handle.take() {
+            let _ = receive_handle.await;
+        }
+        if let Some(send_handle) = self.send_handle.take() {
+            let _ = send_handle.await;
+        }
+
+        // 关闭连接
+        self.stream = None;
+        self.shutdown_sender = None;
+        self.message_sender = None;
+        self.state = UnixClientState::Disconnected;
+
+        // 发送断开连接事件
+        if let Some(app_handle) = &self.app_handle {
+            let event = UnixClientEvent {
+                client_id: self.client_id.clone(),
+                event_type: "disconnected".to_string(),
+                message: "Disconnected from server".to_string(),
+                peer_credentials: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            let _ = app_handle.emit("unix-client-event", &event);
+        }
+
+        Ok(())
+    }
+
+    async fn start_tasks(&mut self) -> Result<(), String> {
+        let stream = self.stream.take().ok_or("No stream available")?;
+        let (read_stream, write_stream) = stream.into_split();
+
+        let (shutdown_tx, _shutdown_rx) = broadcast::channel(1);
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+
+        self.shutdown_sender = Some(shutdown_tx.clone());
+        self.message_sender = Some(message_tx);
+
+        // 启动接收任务
+        let client_id = self.client_id.clone();
+        let app_handle = self.app_handle.clone();
+        let shutdown_rx_clone = shutdown_tx.subscribe();
+        self.receive_handle = Some(tokio::spawn(async move {
+            handle_unix_client_receive(read_stream, client_id, app_handle, shutdown_rx_clone).await;
+        }));
+
+        // 启动发送任务
+        let shutdown_rx_clone = shutdown_tx.subscribe();
+        self.send_handle = Some(tokio::spawn(async move {
+            handle_unix_client_send(write_stream, message_rx, shutdown_rx_clone).await;
+        }));
+
+        Ok(())
+    }
+
+    pub async fn send_message(&self, message: Vec<u8>) -> Result<(), String> {
+        if self.state != UnixClientState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        if let Some(sender) = &self.message_sender {
+            sender.send(message).map_err(|e| format!("Failed to send message: {}", e))?;
+            Ok(())
+        } else {
+            Err("Message sender not available".to_string())
+        }
+    }
+
+    fn info(&self) -> UnixClientInfo {
+        UnixClientInfo {
+            client_id: self.client_id.clone(),
+            path: self.address.to_string(),
+            state: self.state.clone(),
+            peer_credentials: self.peer_credentials.clone(),
+        }
+    }
+}
+
+fn emit_unix_client_event(app_handle: &Option<tauri::AppHandle>, client_id: &str, event_type: &str, message: String) {
+    if let Some(app_handle) = app_handle {
+        let event = UnixClientEvent {
+            client_id: client_id.to_string(),
+            event_type: event_type.to_string(),
+            message,
+            peer_credentials: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let _ = app_handle.emit("unix-client-event", &event);
+    }
+}
+
+// 处理Unix客户端接收消息
+async fn handle_unix_client_receive(
+    mut read_stream: tokio::net::unix::OwnedReadHalf,
+    client_id: String,
+    app_handle: Option<tauri::AppHandle>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let mut buffer = vec![0; 1024];
+
+    loop {
+        tokio::select! {
+            // 检查是否收到关闭信号
+            _ = shutdown_rx.recv() => {
+                break;
+            }
+            // 读取数据
+            result = read_stream.read(&mut buffer) => {
+                match result {
+                    Ok(0) => {
+                        // 连接已关闭
+                        emit_unix_client_event(&app_handle, &client_id, "disconnected", "Connection closed by server".to_string());
+                        break;
+                    }
+                    Ok(n) => {
+                        let message = String::from_utf8_lossy(&buffer[..n]).to_string();
+                        emit_unix_client_event(&app_handle, &client_id, "message_received", message);
+                    }
+                    Err(e) => {
+                        emit_unix_client_event(&app_handle, &client_id, "error", format!("Read error: {}", e));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 处理Unix客户端发送消息
+async fn handle_unix_client_send(
+    mut write_stream: tokio::net::unix::OwnedWriteHalf,
+    mut message_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            // 检查是否收到关闭信号
+            _ = shutdown_rx.recv() => {
+                break;
+            }
+            // 发送消息
+            message = message_rx.recv() => {
+                match message {
+                    Some(data) => {
+                        if let Err(e) = write_stream.write_all(&data).await {
+                            eprintln!("Failed to write data: {}", e);
+                            break;
+                        }
+                    }
+                    None => {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Tauri命令：连接Unix服务器
+#[tauri::command]
+pub async fn connect_unix_client(
+    connect_params: ConnectUnixClientParams,
+    manager: State<'_, Mutex<UnixClientManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let address = UnixAddress::parse(&connect_params.path)?;
+    let client_id = connect_params.client_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut client = UnixClient::new(address, client_id.clone());
+    client.set_app_handle(app_handle);
+
+    client.connect().await?;
+
+    let mut manager = manager.lock().await;
+    manager.clients.insert(client_id.clone(), client);
+
+    Ok(client_id)
+}
+
+// Tauri命令：断开Unix客户端
+#[tauri::command]
+pub async fn disconnect_unix_client(
+    client_id: String,
+    manager: State<'_, Mutex<UnixClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(client) = manager.clients.get_mut(&client_id) {
+        client.disconnect().await?;
+        manager.clients.remove(&client_id);
+        Ok(())
+    } else {
+        Err(format!("Unix client {} not found", client_id))
+    }
+}
+
+// Tauri命令：发送Unix消息
+#[tauri::command]
+pub async fn send_unix_client_message(
+    send_params: SendUnixClientMessageParams,
+    manager: State<'_, Mutex<UnixClientManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<(), String> {
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.client_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
+    let data = payload_codec::decode(&message, send_params.message_type.as_deref())?;
+
+    let manager = manager.lock().await;
+    if let Some(client) = manager.clients.get(&send_params.client_id) {
+        client.send_message(data).await?;
+        Ok(())
+    } else {
+        Err(format!("Unix client {} not found", send_params.client_id))
+    }
+}
+
+// Tauri命令：获取所有Unix客户端
+#[tauri::command]
+pub async fn get_unix_clients(
+    manager: State<'_, Mutex<UnixClientManager>>,
+) -> Result<Vec<UnixClientInfo>, String> {
+    let manager = manager.lock().await;
+    Ok(manager.clients.values().map(UnixClient::info).collect())
+}
+
+// Tauri命令：获取Unix客户端信息
+#[tauri::command]
+pub async fn get_unix_client_info(
+    client_id: String,
+    manager: State<'_, Mutex<UnixClientManager>>,
+) -> Result<UnixClientInfo, String> {
+    let manager = manager.lock().await;
+    if let Some(client) = manager.clients.get(&client_id) {
+        Ok(client.info())
+    } else {
+        Err(format!("Unix client {} not found", client_id))
+    }
+}
@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::os::unix::net::UnixDatagram as StdUnixDatagram;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::io::unix::AsyncFd;
+use tokio::sync::{mpsc, Mutex, RwLock, broadcast};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::message_template::{self, TemplateEngine};
+use crate::payload_codec;
+use crate::unix_address::{self, UnixAddress};
+
+// Unix数据报套接字状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UnixDatagramState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Error,
+}
+
+// Unix数据报套接字
+pub struct UnixDatagramSocket {
+    pub address: Option<UnixAddress>, // 本地绑定地址，None表示未命名套接字（只能发送）
+    pub socket_id: String,
+    pub state: UnixDatagramState,
+    pub peers: Arc<RwLock<Vec<UnixAddress>>>, // 曾经发来数据的已命名对端，用于广播
+    pub receive_handle: Option<JoinHandle<()>>,
+    pub send_handle: Option<JoinHandle<()>>,
+    pub shutdown_sender: Option<broadcast::Sender<()>>,
+    pub message_sender: Option<mpsc::UnboundedSender<(Vec<u8>, UnixAddress)>>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// Unix数据报套接字管理器
+pub struct UnixDatagramManager {
+    pub sockets: HashMap<String, UnixDatagramSocket>,
+}
+
+impl UnixDatagramManager {
+    pub fn new() -> Self {
+        UnixDatagramManager {
+            sockets: HashMap::new(),
+        }
+    }
+}
+
+impl Default for UnixDatagramManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 启动数据报套接字的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartUnixDatagramParams {
+    pub path: Option<String>, // 本地绑定地址，以 "@" 开头表示抽象命名空间
+    pub socket_id: Option<String>,
+    pub remove_existing: Option<bool>, // 绑定前删除残留的套接字文件，默认为 false
+}
+
+// 发送消息的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendUnixDatagramParams {
+    pub socket_id: String,
+    pub target_path: Option<String>, // 如果为None则广播给所有已知对端
+    pub message: String,
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub template: Option<bool>,       // 发送前展开 {{...}} 模板占位符，默认为 false
+}
+
+// 数据报套接字状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixDatagramInfo {
+    pub socket_id: String,
+    pub path: Option<String>,
+    pub peers: Vec<String>,
+    pub state: UnixDatagramState,
+}
+
+// 数据报套接字事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixDatagramEvent {
+    pub socket_id: String,
+    pub event_type: String,
+    pub peer: Option<String>,
+    pub message: String,
+    pub timestamp: String,
+}
+
+impl UnixDatagramSocket {
+    pub fn new(address: Option<UnixAddress>, socket_id: String) -> Self {
+        UnixDatagramSocket {
+            address,
+            socket_id,
+            state: UnixDatagramState::Disconnected,
+            peers: Arc::new(RwLock::new(Vec::new())),
+            receive_handle: None,
+            send_handle: None,
+            shutdown_sender: None,
+            message_sender: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    pub async fn start(&mut self, remove_existing: bool) -> Result<(), String> {
+        if self.state == UnixDatagramState::Connected {
+            return Err("Already started".to_string());
+        }
+
+        self.state = UnixDatagramState::Connecting;
+
+        if remove_existing {
+            if let Some(address) = &self.address {
+                unix_address::remove_socket_file(address);
+            }
+        }
+
+        let description = match &self.address {
+            Some(address) => address.to_string(),
+            None => "(unnamed)".to_string(),
+        };
+
+        let socket = match unix_address::bind_datagram(self.address.as_ref()).and_then(AsyncFd::new) {
+            Ok(socket) => Arc::new(socket),
+            Err(e) => {
+                self.state = UnixDatagramState::Error;
+                return Err(format!("Failed to bind Unix datagram socket to {}: {}", description, e));
+            }
+        };
+
+        self.state = UnixDatagramState::Connected;
+        emit_unix_datagram_event(&self.app_handle, &self.socket_id, "connected", None, format!("Unix datagram socket bound to {}", description));
+
+        let (shutdown_tx, _shutdown_rx) = broadcast::channel(1);
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+
+        self.shutdown_sender = Some(shutdown_tx.clone());
+        self.message_sender = Some(message_tx);
+
+        // 启动接收任务（未命名套接字无法接收数据）
+        if self.address.is_some() {
+            let socket_recv = Arc::clone(&socket);
+            let socket_id = self.socket_id.clone();
+            let peers = Arc::clone(&self.peers);
+            let app_handle = self.app_handle.clone();
+            let shutdown_rx_clone = shutdown_tx.subscribe();
+            self.receive_handle = Some(tokio::spawn(async move {
+                handle_unix_datagram_receive(socket_recv, socket_id, peers, app_handle, shutdown_rx_clone).await;
+            }));
+        }
+
+        // 启动发送任务
+        let socket_id = self.socket_id.clone();
+        let app_handle = self.app_handle.clone();
+        let shutdown_rx_clone = shutdown_tx.subscribe();
+        self.send_handle = Some(tokio::spawn(async move {
+            handle_unix_datagram_send(socket, socket_id, app_handle, message_rx, shutdown_rx_clone).await;
+        }));
+
+        Ok(())
+    }
+
+    pub async fn stop(&mut self) -> Result<(), String> {
+        if self.state != UnixDatagramState::Connected {
+            return Ok(());
+        }
+
+        // 发送关闭信号
+        if let Some(shutdown_sender) = &self.shutdown_sender {
+            let _ = shutdown_sender.send(());
+        }
+
+        // 等待任务完成
+        if let Some(receive_handle) = self.receive_handle.take() {
+            let _ = receive_handle.await;
+        }
+        if let Some(send_handle) = self.send_handle.take() {
+            let _ = send_handle.await;
+        }
+
+        self.shutdown_sender = None;
+        self.message_sender = None;
+        self.state = UnixDatagramState::Disconnected;
+
+        // 清理套接字文件
+        if let Some(address) = &self.address {
+            unix_address::remove_socket_file(address);
+        }
+
+        emit_unix_datagram_event(&self.app_handle, &self.socket_id, "disconnected", None, "Unix datagram socket stopped".to_string());
+
+        Ok(())
+    }
+
+    pub async fn send_message(&self, message: Vec<u8>, target: UnixAddress) -> Result<(), String> {
+        if self.state != UnixDatagramState::Connected {
+            return Err("Not started".to_string());
+        }
+
+        if let Some(sender) = &self.message_sender {
+            sender.send((message, target)).map_err(|e| format!("Failed to send message: {}", e))?;
+            Ok(())
+        } else {
+            Err("Message sender not available".to_string())
+        }
+    }
+
+    pub async fn broadcast_message(&self, message: Vec<u8>) -> Result<usize, String> {
+        let peers = self.peers.read().await;
+        for peer in peers.iter() {
+            self.send_message(message.clone(), peer.clone()).await?;
+        }
+        Ok(peers.len())
+    }
+
+    async fn info(&self) -> UnixDatagramInfo {
+        UnixDatagramInfo {
+            socket_id: self.socket_id.clone(),
+            path: self.address.as_ref().map(|address| address.to_string()),
+            peers: self.peers.read().await.iter().map(|peer| peer.to_string()).collect(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+fn emit_unix_datagram_event(app_handle: &Option<tauri::AppHandle>, socket_id: &str, event_type: &str, peer: Option<String>, message: String) {
+    if let Some(app_handle) = app_handle {
+        let event = UnixDatagramEvent {
+            socket_id: socket_id.to_string(),
+            event_type: event_type.to_string(),
+            peer,
+            message,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let _ = app_handle.emit("unix-datagram-event", &event);
+    }
+}
+
+// 处理数据报接收
+async fn handle_unix_datagram_receive(
+    socket: Arc<AsyncFd<StdUnixDatagram>>,
+    socket_id: String,
+    peers: Arc<RwLock<Vec<UnixAddress>>>,
+    app_handle: Option<tauri::AppHandle>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let mut buffer = vec![0; 65536];
+
+    loop {
+        let result = tokio::select! {
+            // 检查是否收到关闭信号
+            _ = shutdown_rx.recv() => {
+                break;
+            }
+            // 读取数据
+            result = socket.async_io(tokio::io::Interest::READABLE, |inner| inner.recv_from(&mut buffer)) => result,
+        };
+
+        match result {
+            Ok((n, from_addr)) => {
+                let peer = unix_address::describe_socket_addr(&from_addr);
+
+                // 记录已命名的对端以便广播，未命名对端无法回复
+                if !from_addr.is_unnamed() {
+                    if let Ok(address) = UnixAddress::parse(&peer) {
+                        let mut peers = peers.write().await;
+                        if !peers.contains(&address) {
+                            peers.push(address);
+                        }
+                    }
+                }
+
+                let message = String::from_utf8_lossy(&buffer[..n]).to_string();
+                emit_unix_datagram_event(&app_handle, &socket_id, "message_received", Some(peer), message);
+            }
+            Err(e) => {
+                emit_unix_datagram_event(&app_handle, &socket_id, "error", None, format!("Read error: {}", e));
+                break;
+            }
+        }
+    }
+}
+
+// 处理数据报发送
+async fn handle_unix_datagram_send(
+    socket: Arc<AsyncFd<StdUnixDatagram>>,
+    socket_id: String,
+    app_handle: Option<tauri::AppHandle>,
+    mut message_rx: mpsc::UnboundedReceiver<(Vec<u8>, UnixAddress)>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            // 检查是否收到关闭信号
+            _ = shutdown_rx.recv() => {
+                break;
+            }
+            // 发送消息
+            message = message_rx.recv() => {
+                match message {
+                    Some((data, target)) => {
+                        let result = match target.to_socket_addr() {
+                            Ok(addr) => socket.async_io(tokio::io::Interest::WRITABLE, |inner| inner.send_to_addr(&data, &addr)).await,
+                            Err(e) => Err(e),
+                        };
+                        // 对端不存在等错误不影响后续发送
+                        if let Err(e) = result {
+                            emit_unix_datagram_event(&app_handle, &socket_id, "error", Some(target.to_string()), format!("Failed to send datagram: {}", e));
+                        }
+                    }
+                    None => {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Tauri命令：启动Unix数据报套接字
+#[tauri::command]
+pub async fn start_unix_datagram(
+    start_params: StartUnixDatagramParams,
+    manager: State<'_, Mutex<UnixDatagramManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let address = match start_params.path.as_deref() {
+        Some(path) if !path.is_empty() => Some(UnixAddress::parse(path)?),
+        _ => None,
+    };
+    let socket_id = start_params.socket_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let mut manager = manager.lock().await;
+    if manager.sockets.contains_key(&socket_id) {
+        return Err(format!("Unix datagram socket with ID {} already exists", socket_id));
+    }
+
+    let mut socket = UnixDatagramSocket::new(address, socket_id.clone());
+    socket.set_app_handle(app_handle);
+    socket.start(start_params.remove_existing.unwrap_or(false)).await?;
+
+    manager.sockets.insert(socket_id.clone(), socket);
+    Ok(socket_id)
+}
+
+// Tauri命令：停止Unix数据报套接字
+#[tauri::command]
+pub async fn stop_unix_datagram(
+    socket_id: String,
+    manager: State<'_, Mutex<UnixDatagramManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(socket) = manager.sockets.get_mut(&socket_id) {
+        socket.stop().await?;
+        manager.sockets.remove(&socket_id);
+        Ok(())
+    } else {
+        Err(format!("Unix datagram socket {} not found", socket_id))
+    }
+}
+
+// Tauri命令：发送Unix数据报
+#[tauri::command]
+pub async fn send_unix_datagram_message(
+    send_params: SendUnixDatagramParams,
+    manager: State<'_, Mutex<UnixDatagramManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<String, String> {
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.socket_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
+    let data = payload_codec::decode(&message, send_params.message_type.as_deref())?;
+
+    let manager = manager.lock().await;
+    let socket = manager.sockets.get(&send_params.socket_id)
+        .ok_or_else(|| format!("Unix datagram socket {} not found", send_params.socket_id))?;
+
+    match send_params.target_path.as_deref() {
+        Some(target_path) if !target_path.is_empty() => {
+            let target = UnixAddress::parse(target_path)?;
+            socket.send_message(data, target).await?;
+            Ok(format!("Datagram sent to {}", target_path))
+        }
+        _ => {
+            let sent_count = socket.broadcast_message(data).await?;
+            Ok(format!("Datagram broadcast to {} peers", sent_count))
+        }
+    }
+}
+
+// Tauri命令：获取所有Unix数据报套接字
+#[tauri::command]
+pub async fn get_unix_datagrams(
+    manager: State<'_, Mutex<UnixDatagramManager>>,
+) -> Result<Vec<UnixDatagramInfo>, String> {
+    let manager = manager.lock().await;
+    let mut sockets = Vec::new();
+    for socket in manager.sockets.values() {
+        sockets.push(socket.info().await);
+    }
+    Ok(sockets)
+}
+
+// Tauri命令：获取Unix数据报套接字信息
+#[tauri::command]
+pub async fn get_unix_datagram_info(
+    socket_id: String,
+    manager: State<'_, Mutex<UnixDatagramManager>>,
+) -> Result<UnixDatagramInfo, String> {
+    let manager = manager.lock().await;
+    if let Some(socket) = manager.sockets.get(&socket_id) {
+        Ok(socket.info().await)
+    } else {
+        Err(format!("Unix datagram socket {} not found", socket_id))
+    }
+}
@@ -0,0 +1,479 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::message_template::{self, TemplateEngine};
+use crate::payload_codec;
+use crate::unix_address::{self, UnixAddress, UnixPeerCredentials};
+
+// Unix流式套接字客户端连接
+#[allow(dead_code)]
+pub struct UnixServerClient {
+    pub id: String,
+    pub peer_credentials: Option<UnixPeerCredentials>,
+    pub sender: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+// Unix流式套接字服务器
+pub struct UnixServer {
+    pub address: UnixAddress,
+    pub server_id: String,
+    pub clients: Arc<RwLock<HashMap<String, UnixServerClient>>>,
+    pub server_handle: Option<JoinHandle<()>>,
+    pub shutdown_sender: Option<mpsc::UnboundedSender<()>>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// Unix服务器管理器
+pub struct UnixServerManager {
+    pub servers: HashMap<String, UnixServer>,
+}
+
+impl UnixServerManager {
+    pub fn new() -> Self {
+        UnixServerManager {
+            servers: HashMap::new(),
+        }
+    }
+}
+
+impl Default for UnixServerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 启动Unix服务器的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartUnixServerParams {
+    pub path: String, // 以 "@" 开头表示抽象命名空间（仅Linux）
+    pub server_id: Option<String>,
+    pub remove_existing: Option<bool>, // 绑定前删除残留的套接字文件，默认为 false
+}
+
+// 发送消息的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendUnixMessageParams {
+    pub server_id: String,
+    pub message: String,
+    pub target_client_id: Option<String>, // 如果为None则广播给所有客户端
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"
+    pub template: Option<bool>,       // 发送前展开 {{...}} 模板占位符，默认为 false
+}
+
+// 服务器状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixServerInfo {
+    pub server_id: String,
+    pub path: String,
+    pub client_count: usize,
+    pub is_running: bool,
+}
+
+// Unix服务器事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixServerEvent {
+    pub server_id: String,
+    pub event_type: String,
+    pub client_id: String,
+    pub message: String,
+    pub peer_credentials: Option<UnixPeerCredentials>, // 仅在 client_connected 事件中提供
+    pub timestamp: String,
+}
+
+impl UnixServer {
+    pub fn new(address: UnixAddress, server_id: String) -> Self {
+        UnixServer {
+            address,
+            server_id,
+            clients: Arc::new(RwLock::new(HashMap::new())),
+            server_handle: None,
+            shutdown_sender: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    pub async fn start(&mut self, remove_existing: bool) -> Result<(), String> {
+        if remove_existing {
+            unix_address::remove_socket_file(&self.address);
+        }
+
+        let listener = unix_address::bind_listener(&self.address)
+            .map_err(|e| format!("Failed to bind to {}: {}", self.address, e))?;
+
+        let clients = Arc::clone(&self.clients);
+        let app_handle = self.app_handle.clone();
+        let server_id = self.server_id.clone();
+        let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
+        self.shutdown_sender = Some(shutdown_tx);
+
+        let server_handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    // 检查是否收到关闭信号
+                    _ = shutdown_rx.recv() => {
+                        println!("Unix server shutting down...");
+                        break;
+                    }
+                    // 接受新的连接
+                    accept_result = listener.accept() => {
+                        match accept_result {
+                            Ok((stream, _)) => {
+                                let clients_clone = Arc::clone(&clients);
+                                let app_handle_clone = app_handle.clone();
+                                let server_id_clone = server_id.clone();
+                                tokio::spawn(handle_unix_connection(stream, clients_clone, app_handle_clone, server_id_clone));
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to accept Unix connection: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        self.server_handle = Some(server_handle);
+        Ok(())
+    }
+
+    pub async fn stop(&mut self) -> Result<(), String> {
+        // 发送关闭信号
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(());
+        }
+
+        // 等待服务器任务完成
+        if let Some(handle) = self.server_handle.take() {
+            handle.await.map_err(|e| format!("Failed to stop Unix server: {}", e))?;
+        }
+
+        // 关闭所有客户端连接
+        let mut clients = self.clients.write().await;
+        clients.clear();
+
+        // 清理套接字文件
+        unix_address::remove_socket_file(&self.address);
+
+        Ok(())
+    }
+
+    pub async fn send_message_to_client(&self, client_id: &str, data: Vec<u8>) -> Result<(), String> {
+        let clients = self.clients.read().await;
+        if let Some(client) = clients.get(client_id) {
+            client
+                .sender
+                .send(data)
+                .map_err(|e| format!("Failed to send message to client {}: {}", client_id, e))?;
+            Ok(())
+        } else {
+            Err(format!("Client {} not found", client_id))
+        }
+    }
+
+    pub async fn broadcast_message(&self, data: Vec<u8>) -> Result<usize, String> {
+        let clients = self.clients.read().await;
+        let mut sent_count = 0;
+
+        for (_, client) in clients.iter() {
+            if client.sender.send(data.clone()).is_ok() {
+                sent_count += 1;
+            }
+        }
+
+        Ok(sent_count)
+    }
+
+    pub async fn get_client_count(&self) -> usize {
+        self.clients.read().await.len()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.server_handle.is_some()
+    }
+}
+
+fn emit_unix_server_event(app_handle: &Option<tauri::AppHandle>, event: UnixServerEvent) {
+    if let Some(app) = app_handle {
+        if let Err(e) = app.emit("unix-server-event", &event) {
+            eprintln!("Failed to emit event to frontend: {}", e);
+        }
+    }
+}
+
+// 处理Unix连接
+async fn handle_unix_connection(
+    stream: UnixStream,
+    clients: Arc<RwLock<HashMap<String, UnixServerClient>>>,
+    app_handle: Option<tauri::AppHandle>,
+    server_id: String,
+) {
+    let client_id = Uuid::new_v4().to_string();
+    let peer_credentials = unix_address::peer_credentials(&stream);
+    let peer_description = match &peer_credentials {
+        Some(credentials) => credentials.to_string(),
+        None => "unknown peer".to_string(),
+    };
+    println!("New Unix client connected: {} ({})", client_id, peer_description);
+
+    // 发送客户端连接事件到前端
+    emit_unix_server_event(&app_handle, UnixServerEvent {
+        server_id: server_id.clone(),
+        event_type: "client_connected".to_string(),
+        client_id: client_id.clone(),
+        message: format!("Client connected ({})", peer_description),
+        peer_credentials: peer_credentials.clone(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    });
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+
+    // 添加客户端到集合
+    {
+        let mut clients_guard = clients.write().await;
+        clients_guard.insert(
+            client_id.clone(),
+            UnixServerClient {
+                id: client_id.clone(),
+                peer_credentials,
+                sender: tx,
+            },
+        );
+    }
+
+    let (mut reader, mut writer) = stream.into_split();
+
+    // 启动发送任务
+    let client_id_sender = client_id.clone();
+    let send_task = tokio::spawn(async move {
+        while let Some(data) = rx.recv().await {
+            if writer.write_all(&data).await.is_err() {
+                println!("Failed to send data to client {}", client_id_sender);
+                break;
+            }
+            if writer.flush().await.is_err() {
+                println!("Failed to flush data to client {}", client_id_sender);
+                break;
+            }
+        }
+    });
+
+    // 接收消息循环
+    let client_id_receiver = client_id.clone();
+    let clients_clone = Arc::clone(&clients);
+    let app_handle_clone = app_handle.clone();
+    let server_id_clone = server_id.clone();
+    let receive_task = tokio::spawn(async move {
+        let mut buffer = [0; 1024];
+
+        loop {
+            match reader.read(&mut buffer).await {
+                Ok(0) => {
+                    // 连接关闭
+                    println!("Unix client {} disconnected", client_id_receiver);
+                    emit_unix_server_event(&app_handle_clone, UnixServerEvent {
+                        server_id: server_id_clone.clone(),
+                        event_type: "client_disconnected".to_string(),
+                        client_id: client_id_receiver.clone(),
+                        message: "Client disconnected".to_string(),
+                        peer_credentials: None,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    });
+                    break;
+                }
+                Ok(n) => {
+                    let received_data = &buffer[..n];
+
+                    // 尝试将数据转换为文本，如果失败则作为十六进制处理
+                    let message = match std::str::from_utf8(received_data) {
+                        Ok(text) => text.to_string(),
+                        Err(_) => format!("Binary data: {}", hex_dump(received_data)),
+                    };
+
+                    emit_unix_server_event(&app_handle_clone, UnixServerEvent {
+                        server_id: server_id_clone.clone(),
+                        event_type: "message_received".to_string(),
+                        client_id: client_id_receiver.clone(),
+                        message,
+                        peer_credentials: None,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Unix socket error for client {}: {}", client_id_receiver, e);
+                    break;
+                }
+            }
+        }
+
+        // 从客户端集合中移除
+        clients_clone.write().await.remove(&client_id_receiver);
+    });
+
+    // 等待任何一个任务完成
+    tokio::select! {
+        _ = send_task => {},
+        _ = receive_task => {},
+    }
+
+    // 清理：从客户端集合中移除
+    clients.write().await.remove(&client_id);
+    println!("Unix client {} disconnected and cleaned up", client_id);
+}
+
+fn hex_dump(data: &[u8]) -> String {
+    data.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Tauri命令：启动Unix服务器
+#[tauri::command]
+pub async fn start_unix_server(
+    app_handle: tauri::AppHandle,
+    start_params: StartUnixServerParams,
+    state: State<'_, Mutex<UnixServerManager>>,
+) -> Result<String, String> {
+    let address = UnixAddress::parse(&start_params.path)?;
+    let server_id = start_params.server_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut manager = state.lock().await;
+
+    // 检查服务器ID是否已存在
+    if manager.servers.contains_key(&server_id) {
+        return Err(format!("Unix Server with ID {} already exists", server_id));
+    }
+
+    let mut server = UnixServer::new(address, server_id.clone());
+    server.set_app_handle(app_handle);
+    server.start(start_params.remove_existing.unwrap_or(false)).await?;
+
+    manager.servers.insert(server_id.clone(), server);
+    Ok(server_id)
+}
+
+// Tauri命令：停止Unix服务器
+#[tauri::command]
+pub async fn stop_unix_server(
+    server_id: Option<String>,
+    state: State<'_, Mutex<UnixServerManager>>,
+) -> Result<(), String> {
+    let server_id = server_id.ok_or("Server ID is required")?;
+
+    if server_id.is_empty() {
+        return Err("Server ID cannot be empty".to_string());
+    }
+
+    let mut manager = state.lock().await;
+
+    if let Some(server) = manager.servers.get_mut(&server_id) {
+        server.stop().await?;
+        manager.servers.remove(&server_id);
+        Ok(())
+    } else {
+        Err(format!("Unix Server with ID {} not found", server_id))
+    }
+}
+
+// Tauri命令：发送消息
+#[tauri::command]
+pub async fn send_unix_message(
+    send_params: SendUnixMessageParams,
+    state: State<'_, Mutex<UnixServerManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<String, String> {
+    if send_params.server_id.is_empty() {
+        return Err("Server ID cannot be empty".to_string());
+    }
+
+    if send_params.message.is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
+
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.server_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
+
+    let manager = state.lock().await;
+
+    if let Some(server) = manager.servers.get(&send_params.server_id) {
+        // 根据消息类型处理数据
+        let data = payload_codec::decode(&message, send_params.message_type.as_deref())?;
+
+        if let Some(target_client_id) = send_params.target_client_id {
+            // 发送给特定客户端
+            server.send_message_to_client(&target_client_id, data).await?;
+            Ok(format!("Message sent to client {}", target_client_id))
+        } else {
+            // 广播给所有客户端
+            let sent_count = server.broadcast_message(data).await?;
+            Ok(format!("Message broadcast to {} clients", sent_count))
+        }
+    } else {
+        Err(format!("Unix Server with ID {} not found", send_params.server_id))
+    }
+}
+
+// Tauri命令：获取服务器列表
+#[tauri::command]
+pub async fn get_unix_servers(
+    state: State<'_, Mutex<UnixServerManager>>,
+) -> Result<Vec<UnixServerInfo>, String> {
+    let manager = state.lock().await;
+    let mut servers_info = Vec::new();
+
+    for (server_id, server) in manager.servers.iter() {
+        servers_info.push(UnixServerInfo {
+            server_id: server_id.clone(),
+            path: server.address.to_string(),
+            client_count: server.get_client_count().await,
+            is_running: server.is_running(),
+        });
+    }
+
+    Ok(servers_info)
+}
+
+// Tauri命令：获取特定服务器信息
+#[tauri::command]
+pub async fn get_unix_server_info(
+    server_id: Option<String>,
+    state: State<'_, Mutex<UnixServerManager>>,
+) -> Result<UnixServerInfo, String> {
+    let server_id = server_id.ok_or("Server ID is required")?;
+    if server_id.is_empty() {
+        return Err("Server ID cannot be empty".to_string());
+    }
+
+    let manager = state.lock().await;
+
+    if let Some(server) = manager.servers.get(&server_id) {
+        Ok(UnixServerInfo {
+            server_id: server_id.clone(),
+            path: server.address.to_string(),
+            client_count: server.get_client_count().await,
+            is_running: server.is_running(),
+        })
+    } else {
+        Err(format!("Unix Server with ID {} not found", server_id))
+    }
+}
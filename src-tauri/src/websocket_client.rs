@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::{State, Emitter};
+use tokio::sync::{mpsc, Mutex, broadcast};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use crate::checksum::{Checksum, ChecksumCheck, ChecksumParams};
+use crate::correlation::{Correlation, CorrelationParams};
+use crate::http_client::HttpHeader;
+use crate::message_template::{self, TemplateEngine};
+use crate::receive_capture;
+use crate::transport::{LocalEndpoint, TlsOptions};
+use crate::websocket_deflate::{CompressionParams, DeflateConfig};
+use crate::websocket_server::{encode_message, to_hex};
+use crate::websocket_queue::{FragmentGate, OutgoingMessage};
+use crate::websocket_transport::{self, ClientWebSocket, WebSocketConnectOptions};
+use crate::write_ack::{self, WriteAckReceiver};
+
+// WebSocket客户端连接状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WebSocketClientState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Error,
+}
+
+// WebSocket客户端
+pub struct WebSocketClient {
+    pub client_id: String,
+    pub url: String,
+    pub state: WebSocketClientState,
+    pub subprotocol: Option<String>,        // 服务端选择的子协议
+    pub compression: Option<DeflateConfig>, // 协商好的压缩参数
+    pub correlation: Arc<Correlation>,      // 请求/响应关联设置，可在运行时修改
+    pub connection_handle: Option<JoinHandle<()>>,
+    pub shutdown_sender: Option<broadcast::Sender<()>>,
+    pub message_sender: Option<mpsc::UnboundedSender<OutgoingMessage>>,
+    pub app_handle: Option<tauri::AppHandle>,
+}
+
+// WebSocket客户端管理器
+pub struct WebSocketClientManager {
+    pub clients: HashMap<String, WebSocketClient>,
+}
+
+impl WebSocketClientManager {
+    pub fn new() -> Self {
+        WebSocketClientManager {
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl Default for WebSocketClientManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 连接WebSocket服务器的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectWebSocketClientParams {
+    pub client_id: Option<String>,
+    pub url: String, // ws(s):// 或 http(s):// 地址
+    pub headers: Option<Vec<HttpHeader>>,
+    pub subprotocols: Option<Vec<String>>,
+    pub insecure: Option<bool>,           // 跳过TLS证书校验，默认为 false
+    pub local_address: Option<String>,    // 本地源地址：IPv4/IPv6地址或网卡名称
+    pub local_port: Option<u16>,          // 本地源端口
+    pub compression: Option<CompressionParams>, // permessage-deflate 设置，不指定时不请求压缩
+    pub checksum: Option<ChecksumParams>, // 校验收到的每条数据消息，结果附加在接收事件上
+    pub correlation: Option<CorrelationParams>, // 把每次发送作为请求，匹配随后收到的响应并计算往返时间
+}
+
+// 发送消息的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendWebSocketClientMessageParams {
+    pub client_id: String,
+    pub message: String,
+    pub message_type: Option<String>, // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"；非文本格式作为二进制帧发送
+    pub template: Option<bool>,       // 发送前展开 {{...}} 模板占位符，默认为 false
+    pub checksum: Option<ChecksumParams>, // 发送前计算并插入校验值，仅用于二进制消息
+}
+
+// 客户端状态信息
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketClientInfo {
+    pub client_id: String,
+    pub url: String,
+    pub state: WebSocketClientState,
+    pub subprotocol: Option<String>,
+    pub compression: Option<String>,
+}
+
+// WebSocket客户端事件数据（发送给前端）
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketClientEvent {
+    pub client_id: String,
+    pub event_type: String,
+    pub message: String,
+    pub checksum: Option<ChecksumCheck>, // 启用接收校验时的校验结果
+    pub timestamp: String,
+}
+
+fn emit_client_event(app_handle: &Option<tauri::AppHandle>, client_id: &str, event_type: &str, message: String) {
+    emit_received_event(app_handle, client_id, event_type, message, None);
+}
+
+fn emit_received_event(
+    app_handle: &Option<tauri::AppHandle>,
+    client_id: &str,
+    event_type: &str,
+    message: String,
+    checksum: Option<ChecksumCheck>,
+) {
+    if let Some(app_handle) = app_handle {
+        let event = WebSocketClientEvent {
+            client_id: client_id.to_string(),
+            event_type: event_type.to_string(),
+            message,
+            checksum,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let _ = app_handle.emit("websocket-client-event", &event);
+    }
+}
+
+impl WebSocketClient {
+    pub fn new(url: String, client_id: String) -> Self {
+        WebSocketClient {
+            correlation: Arc::new(Correlation::new("websocket_client", &client_id)),
+            client_id,
+            url,
+            state: WebSocketClientState::Disconnected,
+            subprotocol: None,
+            compression: None,
+            connection_handle: None,
+            shutdown_sender: None,
+            message_sender: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    pub async fn connect(&mut self, params: &ConnectWebSocketClientParams) -> Result<(), String> {
+        if self.state == WebSocketClientState::Connected {
+            return Err("Already connected".to_string());
+        }
+
+        self.state = WebSocketClientState::Connecting;
+        match self.start(params).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.state = WebSocketClientState::Error;
+                Err(e)
+            }
+        }
+    }
+
+    async fn start(&mut self, params: &ConnectWebSocketClientParams) -> Result<(), String> {
+        let checksum = Checksum::from_params(params.checksum.as_ref())?;
+        let options = WebSocketConnectOptions {
+            headers: params
+                .headers
+                .iter()
+                .flatten()
+                .map(|header| (header.name.clone(), header.value.clone()))
+                .collect(),
+            subprotocols: params.subprotocols.clone().unwrap_or_default(),
+            tls: TlsOptions {
+                insecure: params.insecure.unwrap_or(false),
+                ..TlsOptions::default()
+            },
+            local: LocalEndpoint {
+                address: params.local_address.clone(),
+                port: params.local_port,
+                reuse_address: false,
+            },
+            compression: DeflateConfig::from_params(params.compression.as_ref())?,
+        };
+        let (ws_stream, response) = websocket_transport::connect_websocket(self.url.trim(), &options).await?;
+
+        self.subprotocol = response
+            .headers()
+            .get("Sec-WebSocket-Protocol")
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string());
+        self.compression = ws_stream.get_ref().negotiated().cloned();
+        self.state = WebSocketClientState::Connected;
+
+        // 发送连接成功事件，附带协商结果
+        let mut negotiated = Vec::new();
+        if let Some(protocol) = &self.subprotocol {
+            negotiated.push(format!("subprotocol: {}", protocol));
+        }
+        match &self.compression {
+            Some(compression) => negotiated.push(format!("extension: {}", compression.describe())),
+            None if options.compression.is_some() => negotiated.push("compression declined by server".to_string()),
+            None => {}
+        }
+        let message = if negotiated.is_empty() {
+            format!("Connected to {}", self.url)
+        } else {
+            format!("Connected to {} ({})", self.url, negotiated.join(", "))
+        };
+        emit_client_event(&self.app_handle, &self.client_id, "connected", message);
+
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+        self.shutdown_sender = Some(shutdown_tx);
+        self.message_sender = Some(message_tx);
+        self.connection_handle = Some(tokio::spawn(run_connection(
+            ws_stream,
+            message_rx,
+            shutdown_rx,
+            self.client_id.clone(),
+            self.app_handle.clone(),
+            checksum,
+            Arc::clone(&self.correlation),
+        )));
+
+        Ok(())
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), String> {
+        if self.state != WebSocketClientState::Connected {
+            return Ok(());
+        }
+
+        // 发送关闭信号
+        if let Some(shutdown_sender) = &self.shutdown_sender {
+            let _ = shutdown_sender.send(());
+        }
+
+        // 等待任务完成
+        if let Some(connection_handle) = self.connection_handle.take() {
+            let _ = connection_handle.await;
+        }
+
+        self.shutdown_sender = None;
+        self.message_sender = None;
+        self.state = WebSocketClientState::Disconnected;
+
+        emit_client_event(&self.app_handle, &self.client_id, "disconnected", "Disconnected from server".to_string());
+
+        Ok(())
+    }
+
+    // 发送消息，request_id 为 Correlation::open 登记的请求，写出后记录发送时间
+    pub fn send_message(&self, message: Message, request_id: Option<u64>) -> Result<(), String> {
+        self.queue_message(OutgoingMessage {
+            request_id,
+            ..message.into()
+        })
+    }
+
+    // 发送一帧，返回的通知在数据写出后完成；fragment_stream 标识分片消息，分片期间其他数据消息排在其后
+    pub fn send_message_with_ack(&self, message: Message, fragment_stream: Option<u64>) -> Result<WriteAckReceiver, String> {
+        let (ack, receiver) = write_ack::channel();
+        self.queue_message(OutgoingMessage {
+            fragment_stream,
+            ack: Some(ack),
+            ..message.into()
+        })?;
+        Ok(receiver)
+    }
+
+    fn queue_message(&self, item: OutgoingMessage) -> Result<(), String> {
+        if self.state != WebSocketClientState::Connected {
+            return Err("Not connected".to_string());
+        }
+
+        if let Some(sender) = &self.message_sender {
+            sender.send(item).map_err(|e| format!("Failed to send message: {}", e))
+        } else {
+            Err("Message sender not available".to_string())
+        }
+    }
+
+    fn info(&self) -> WebSocketClientInfo {
+        WebSocketClientInfo {
+            client_id: self.client_id.clone(),
+            url: self.url.clone(),
+            state: self.state.clone(),
+            subprotocol: self.subprotocol.clone(),
+            compression: self.compression.as_ref().map(DeflateConfig::describe),
+        }
+    }
+}
+
+// 连接任务：转发收发的消息，连接结束时发送断开事件
+async fn run_connection(
+    ws_stream: ClientWebSocket,
+    mut message_rx: mpsc::UnboundedReceiver<OutgoingMessage>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+    client_id: String,
+    app_handle: Option<tauri::AppHandle>,
+    checksum: Option<Checksum>,
+    correlation: Arc<Correlation>,
+) {
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    let mut gate = FragmentGate::default();
+    // 写入接收记录，被接管的数据不再发送接收事件
+    let capture_tap = receive_capture::tap(&app_handle, "websocket_client", &client_id).await;
+
+    let reason = 'connection: loop {
+        tokio::select! {
+            // 检查是否收到关闭信号
+            _ = shutdown_rx.recv() => {
+                let _ = ws_sender.close().await;
+                correlation.close(&app_handle);
+                return;
+            }
+            // 读取消息
+            message = ws_receiver.next() => {
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        correlation.received(&app_handle, text.as_bytes());
+                        if !capture_tap.capture(&client_id, text.as_bytes()).await {
+                            let check = checksum.as_ref().map(|checksum| checksum.verify(text.as_bytes()));
+                            emit_received_event(&app_handle, &client_id, "message_received", text, check);
+                        }
+                    }
+                    Some(Ok(Message::Binary(data))) => {
+                        correlation.received(&app_handle, &data);
+                        if !capture_tap.capture(&client_id, &data).await {
+                            let message = format!("Binary data ({} bytes): {}", data.len(), to_hex(&data));
+                            let check = checksum.as_ref().map(|checksum| checksum.verify(&data));
+                            emit_received_event(&app_handle, &client_id, "binary_received", message, check);
+                        }
+                    }
+                    Some(Ok(Message::Close(frame))) => {
+                        break match frame {
+                            Some(frame) => format!("Connection closed by server ({}): {}", u16::from(frame.code), frame.reason),
+                            None => "Connection closed by server".to_string(),
+                        };
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => break format!("WebSocket error: {}", e),
+                    None => break "Connection closed".to_string(),
+                }
+            }
+            // 发送消息
+            message = message_rx.recv() => {
+                let item = match message {
+                    Some(item) => item,
+                    None => break "Client dropped".to_string(),
+                };
+                for OutgoingMessage { message, request_id, ack, .. } in gate.admit(item) {
+                    if let Err(e) = ws_sender.send(message).await {
+                        write_ack::notify(ack, Err(format!("Failed to write data: {}", e)));
+                        break 'connection format!("WebSocket error: {}", e);
+                    }
+                    correlation.sent(request_id);
+                    write_ack::notify(ack, Ok(()));
+                }
+            }
+            // 等待中的请求超时
+            _ = correlation.expire(&app_handle) => {}
+        }
+    };
+
+    let _ = ws_sender.close().await;
+    correlation.close(&app_handle);
+    emit_client_event(&app_handle, &client_id, "disconnected", reason);
+}
+
+// Tauri命令：连接WebSocket服务器
+#[tauri::command]
+pub async fn connect_websocket_client(
+    connect_params: ConnectWebSocketClientParams,
+    manager: State<'_, Mutex<WebSocketClientManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let client_id = connect_params.client_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut client = WebSocketClient::new(connect_params.url.clone(), client_id.clone());
+    client.correlation.configure(connect_params.correlation.as_ref())?;
+    client.set_app_handle(app_handle);
+
+    client.connect(&connect_params).await?;
+
+    let mut manager = manager.lock().await;
+    manager.clients.insert(client_id.clone(), client);
+
+    Ok(client_id)
+}
+
+// Tauri命令：断开WebSocket客户端
+#[tauri::command]
+pub async fn disconnect_websocket_client(
+    client_id: String,
+    manager: State<'_, Mutex<WebSocketClientManager>>,
+) -> Result<(), String> {
+    let mut manager = manager.lock().await;
+
+    if let Some(client) = manager.clients.get_mut(&client_id) {
+        client.disconnect().await?;
+        manager.clients.remove(&client_id);
+        Ok(())
+    } else {
+        Err(format!("WebSocket client {} not found", client_id))
+    }
+}
+
+// Tauri命令：发送WebSocket消息
+#[tauri::command]
+pub async fn send_websocket_client_message(
+    send_params: SendWebSocketClientMessageParams,
+    manager: State<'_, Mutex<WebSocketClientManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+) -> Result<(), String> {
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.client_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
+    let message = encode_message(
+        &message,
+        send_params.message_type.as_deref().unwrap_or("text"),
+        send_params.checksum.as_ref(),
+    )?;
+
+    let manager = manager.lock().await;
+    match manager.clients.get(&send_params.client_id) {
+        Some(client) => {
+            let data: &[u8] = match &message {
+                Message::Text(text) => text.as_bytes(),
+                Message::Binary(data) => data,
+                _ => &[],
+            };
+            let request_id = client.correlation.open(&client.app_handle, data)?;
+            client.send_message(message, request_id).inspect_err(|_| client.correlation.cancel(request_id))
+        }
+        None => Err(format!("WebSocket client {} not found", send_params.client_id)),
+    }
+}
+
+// Tauri命令：获取所有WebSocket客户端
+#[tauri::command]
+pub async fn get_websocket_clients(
+    manager: State<'_, Mutex<WebSocketClientManager>>,
+) -> Result<Vec<WebSocketClientInfo>, String> {
+    let manager = manager.lock().await;
+    Ok(manager.clients.values().map(WebSocketClient::info).collect())
+}
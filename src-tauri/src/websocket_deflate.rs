@@ -0,0 +1,776 @@
+use std::io::{self, Cursor};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::{Data, OpCode};
+use tokio_tungstenite::tungstenite::protocol::frame::FrameHeader;
+
+pub const EXTENSION_NAME: &str = "permessage-deflate";
+// zlib的原始deflate流不支持 8 位窗口，因此最小为 9
+const MIN_WINDOW_BITS: u8 = 9;
+const MAX_WINDOW_BITS: u8 = 15;
+// 压缩消息末尾省略的空存储块（RFC 7692 7.2.1）
+const DEFLATE_TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+// 单条消息解压后的最大长度
+const MAX_MESSAGE_SIZE: usize = 64 << 20;
+// 写入积压超过该长度时等待底层写出
+const MAX_PENDING_WRITE: usize = 1 << 20;
+
+// permessage-deflate 压缩参数（前端传入）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressionParams {
+    pub enabled: bool,
+    pub server_max_window_bits: Option<u8>,       // 9-15，默认为 15
+    pub client_max_window_bits: Option<u8>,       // 9-15，默认为 15
+    pub server_no_context_takeover: Option<bool>, // 服务端每条消息重置压缩上下文，默认为 false
+    pub client_no_context_takeover: Option<bool>, // 客户端每条消息重置压缩上下文，默认为 false
+}
+
+// 本端的压缩设置，协商完成后表示双方实际使用的参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeflateConfig {
+    pub server_max_window_bits: u8,
+    pub client_max_window_bits: u8,
+    pub server_no_context_takeover: bool,
+    pub client_no_context_takeover: bool,
+}
+
+impl Default for DeflateConfig {
+    fn default() -> Self {
+        DeflateConfig {
+            server_max_window_bits: MAX_WINDOW_BITS,
+            client_max_window_bits: MAX_WINDOW_BITS,
+            server_no_context_takeover: false,
+            client_no_context_takeover: false,
+        }
+    }
+}
+
+impl DeflateConfig {
+    // 由前端参数生成设置，未启用压缩时返回None
+    pub fn from_params(params: Option<&CompressionParams>) -> Result<Option<Self>, String> {
+        let params = match params {
+            Some(params) if params.enabled => params,
+            _ => return Ok(None),
+        };
+
+        let window_bits = |bits: Option<u8>, name: &str| match bits.unwrap_or(MAX_WINDOW_BITS) {
+            bits @ MIN_WINDOW_BITS..=MAX_WINDOW_BITS => Ok(bits),
+            bits => Err(format!("{} must be between {} and {}, got {}", name, MIN_WINDOW_BITS, MAX_WINDOW_BITS, bits)),
+        };
+
+        Ok(Some(DeflateConfig {
+            server_max_window_bits: window_bits(params.server_max_window_bits, "serverMaxWindowBits")?,
+            client_max_window_bits: window_bits(params.client_max_window_bits, "clientMaxWindowBits")?,
+            server_no_context_takeover: params.server_no_context_takeover.unwrap_or(false),
+            client_no_context_takeover: params.client_no_context_takeover.unwrap_or(false),
+        }))
+    }
+
+    // 客户端的协商请求，始终声明支持 client_max_window_bits 以便服务端限制客户端窗口
+    pub fn offer(&self) -> String {
+        let mut offer = EXTENSION_NAME.to_string();
+        if self.server_no_context_takeover {
+            offer.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            offer.push_str("; client_no_context_takeover");
+        }
+        if self.server_max_window_bits < MAX_WINDOW_BITS {
+            offer.push_str(&format!("; server_max_window_bits={}", self.server_max_window_bits));
+        }
+        if self.client_max_window_bits < MAX_WINDOW_BITS {
+            offer.push_str(&format!("; client_max_window_bits={}", self.client_max_window_bits));
+        } else {
+            offer.push_str("; client_max_window_bits");
+        }
+        offer
+    }
+
+    // 服务端的协商响应，只列出与默认值不同的参数
+    pub fn response(&self) -> String {
+        let mut response = EXTENSION_NAME.to_string();
+        if self.server_no_context_takeover {
+            response.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            response.push_str("; client_no_context_takeover");
+        }
+        if self.server_max_window_bits < MAX_WINDOW_BITS {
+            response.push_str(&format!("; server_max_window_bits={}", self.server_max_window_bits));
+        }
+        if self.client_max_window_bits < MAX_WINDOW_BITS {
+            response.push_str(&format!("; client_max_window_bits={}", self.client_max_window_bits));
+        }
+        response
+    }
+
+    // 完整的协商结果，用于事件报告
+    pub fn describe(&self) -> String {
+        format!(
+            "{} (server_max_window_bits={}, client_max_window_bits={}, server_no_context_takeover={}, client_no_context_takeover={})",
+            EXTENSION_NAME,
+            self.server_max_window_bits,
+            self.client_max_window_bits,
+            self.server_no_context_takeover,
+            self.client_no_context_takeover
+        )
+    }
+
+    // 服务端：按客户端的偏好顺序接受第一个能满足的请求
+    pub fn negotiate<'a>(&self, header_values: impl Iterator<Item = &'a str>) -> Option<DeflateConfig> {
+        parse_extensions(header_values)
+            .into_iter()
+            .filter(|(name, _)| name == EXTENSION_NAME)
+            .find_map(|(_, params)| self.accept_offer(&params))
+    }
+
+    fn accept_offer(&self, params: &[(String, Option<String>)]) -> Option<DeflateConfig> {
+        let mut agreed = self.clone();
+        let mut client_window_supported = false;
+
+        for (index, (name, value)) in params.iter().enumerate() {
+            // 同一参数重复出现时拒绝该请求
+            if params[..index].iter().any(|(seen, _)| seen == name) {
+                return None;
+            }
+            match (name.as_str(), value) {
+                ("server_no_context_takeover", None) => agreed.server_no_context_takeover = true,
+                ("client_no_context_takeover", None) => agreed.client_no_context_takeover = true,
+                ("server_max_window_bits", Some(value)) => {
+                    let bits = parse_window_bits(value)?;
+                    if bits < MIN_WINDOW_BITS {
+                        return None;
+                    }
+                    agreed.server_max_window_bits = agreed.server_max_window_bits.min(bits);
+                }
+                ("client_max_window_bits", None) => client_window_supported = true,
+                ("client_max_window_bits", Some(value)) => {
+                    client_window_supported = true;
+                    agreed.client_max_window_bits = agreed.client_max_window_bits.min(parse_window_bits(value)?);
+                }
+                _ => return None,
+            }
+        }
+
+        // 客户端未声明 client_max_window_bits 时无法限制其压缩窗口
+        if agreed.client_max_window_bits < MAX_WINDOW_BITS && !client_window_supported {
+            return None;
+        }
+        Some(agreed)
+    }
+
+    // 客户端：校验服务端的协商响应，服务端未接受压缩时返回None
+    pub fn accept_response<'a>(&self, header_values: impl Iterator<Item = &'a str>) -> Result<Option<DeflateConfig>, String> {
+        let extensions = parse_extensions(header_values);
+        let params = match extensions.as_slice() {
+            [] => return Ok(None),
+            [(name, params)] if name == EXTENSION_NAME => params,
+            _ => {
+                let names: Vec<&str> = extensions.iter().map(|(name, _)| name.as_str()).collect();
+                return Err(format!("Server responded with unrequested extensions: {}", names.join(", ")));
+            }
+        };
+
+        // 服务端的窗口和上下文复用以响应为准，客户端的设置只能被收紧
+        let mut agreed = DeflateConfig {
+            server_max_window_bits: MAX_WINDOW_BITS,
+            server_no_context_takeover: false,
+            ..self.clone()
+        };
+        for (name, value) in params {
+            match (name.as_str(), value) {
+                ("server_no_context_takeover", None) => agreed.server_no_context_takeover = true,
+                ("client_no_context_takeover", None) => agreed.client_no_context_takeover = true,
+                ("server_max_window_bits", Some(value)) => {
+                    let bits = parse_window_bits(value).ok_or_else(|| format!("Invalid server_max_window_bits: {}", value))?;
+                    if bits > self.server_max_window_bits {
+                        return Err(format!(
+                            "Server window of {} bits exceeds the requested {} bits",
+                            bits, self.server_max_window_bits
+                        ));
+                    }
+                    agreed.server_max_window_bits = bits;
+                }
+                ("client_max_window_bits", Some(value)) => {
+                    let bits = parse_window_bits(value).ok_or_else(|| format!("Invalid client_max_window_bits: {}", value))?;
+                    if bits < MIN_WINDOW_BITS {
+                        return Err(format!("Client window of {} bits is not supported", bits));
+                    }
+                    agreed.client_max_window_bits = agreed.client_max_window_bits.min(bits);
+                }
+                _ => return Err(format!("Unexpected {} parameter in response: {}", EXTENSION_NAME, name)),
+            }
+        }
+        Ok(Some(agreed))
+    }
+}
+
+// 扩展名和参数列表，无值参数的值为None
+type Extension = (String, Vec<(String, Option<String>)>);
+
+// 解析 Sec-WebSocket-Extensions 头部："name; param; param=value, name2"
+fn parse_extensions<'a>(header_values: impl Iterator<Item = &'a str>) -> Vec<Extension> {
+    header_values
+        .flat_map(|value| value.split(','))
+        .filter_map(|extension| {
+            let mut parts = extension.split(';').map(str::trim);
+            let name = parts.next().filter(|name| !name.is_empty())?.to_ascii_lowercase();
+            let params = parts
+                .filter(|param| !param.is_empty())
+                .map(|param| match param.split_once('=') {
+                    Some((key, value)) => (key.trim().to_ascii_lowercase(), Some(value.trim().trim_matches('"').to_string())),
+                    None => (param.to_ascii_lowercase(), None),
+                })
+                .collect();
+            Some((name, params))
+        })
+        .collect()
+}
+
+// RFC 7692 允许 8-15
+fn parse_window_bits(value: &str) -> Option<u8> {
+    value.parse::<u8>().ok().filter(|bits| (8..=MAX_WINDOW_BITS).contains(bits))
+}
+
+// 单个连接的压缩/解压状态
+pub struct DeflateCodec {
+    config: DeflateConfig,
+    compress: Compress,
+    decompress: Decompress,
+    reset_compress: bool,
+    reset_decompress: bool,
+}
+
+impl DeflateCodec {
+    // 按本端角色选择压缩窗口；解压始终使用最大窗口，可以处理任意较小窗口的数据
+    pub fn new(config: DeflateConfig, is_server: bool) -> Self {
+        let (window_bits, reset_compress, reset_decompress) = if is_server {
+            (config.server_max_window_bits, config.server_no_context_takeover, config.client_no_context_takeover)
+        } else {
+            (config.client_max_window_bits, config.client_no_context_takeover, config.server_no_context_takeover)
+        };
+        DeflateCodec {
+            compress: Compress::new_with_window_bits(Compression::default(), false, window_bits),
+            decompress: Decompress::new_with_window_bits(false, MAX_WINDOW_BITS),
+            reset_compress,
+            reset_decompress,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &DeflateConfig {
+        &self.config
+    }
+
+    pub fn compress(&mut self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut output = Vec::with_capacity(data.len() / 2 + 64);
+        let start = self.compress.total_in();
+        loop {
+            let consumed = (self.compress.total_in() - start) as usize;
+            if output.capacity() - output.len() < 64 {
+                output.reserve(output.capacity().max(1024));
+            }
+            self.compress
+                .compress_vec(&data[consumed..], &mut output, FlushCompress::Sync)
+                .map_err(|e| format!("Compression failed: {}", e))?;
+            // 同步刷新后仍有剩余输出空间，说明全部数据已写出
+            if (self.compress.total_in() - start) as usize == data.len() && output.len() < output.capacity() {
+                break;
+            }
+        }
+
+        if output.ends_with(&DEFLATE_TAIL) {
+            output.truncate(output.len() - DEFLATE_TAIL.len());
+        }
+        // 空消息至少需要一个空存储块的块头，否则对端补上尾部后无法解析
+        if output.is_empty() {
+            output.push(0x00);
+        }
+        if self.reset_compress {
+            self.compress.reset();
+        }
+        Ok(output)
+    }
+
+    pub fn decompress(&mut self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut input = Vec::with_capacity(data.len() + DEFLATE_TAIL.len());
+        input.extend_from_slice(data);
+        input.extend_from_slice(&DEFLATE_TAIL);
+
+        // 容量最多比上限多一个字节，超出上限即可发现，压缩炸弹不会多分配内存
+        let limit = MAX_MESSAGE_SIZE + 1;
+        let mut output = Vec::with_capacity((data.len() * 2 + 64).min(limit));
+        let start = self.decompress.total_in();
+        let mut ended = false;
+        loop {
+            let consumed = (self.decompress.total_in() - start) as usize;
+            if output.capacity() - output.len() < 1024 {
+                output.reserve_exact(output.capacity().max(4096).min(limit - output.len()));
+            }
+            let produced = output.len();
+            let status = self
+                .decompress
+                .decompress_vec(&input[consumed..], &mut output, FlushDecompress::Sync)
+                .map_err(|e| format!("Decompression failed: {}", e))?;
+            if output.len() > MAX_MESSAGE_SIZE {
+                return Err(format!("Decompressed message exceeds {} bytes", MAX_MESSAGE_SIZE));
+            }
+            if status == Status::StreamEnd {
+                ended = true;
+                break;
+            }
+
+            let total = (self.decompress.total_in() - start) as usize;
+            if total == input.len() && output.len() < output.capacity() {
+                break;
+            }
+            if total == consumed && output.len() == produced {
+                return Err("Decompression made no progress".to_string());
+            }
+        }
+
+        // 对端不复用上下文，或对端以最终块结束了deflate流时重置解压器
+        if self.reset_decompress || ended {
+            self.decompress.reset(false);
+        }
+        Ok(output)
+    }
+}
+
+fn apply_mask(data: &mut [u8], mask: [u8; 4]) {
+    for (index, byte) in data.iter_mut().enumerate() {
+        *byte ^= mask[index & 3];
+    }
+}
+
+// 从缓冲区开头解析一个完整帧，返回帧头、载荷起始位置和帧长度
+fn parse_frame(buffer: &[u8]) -> Result<Option<(FrameHeader, usize, usize)>, String> {
+    let mut cursor = Cursor::new(buffer);
+    let (header, length) = match FrameHeader::parse(&mut cursor).map_err(|e| format!("Invalid frame: {}", e))? {
+        Some(parsed) => parsed,
+        None => return Ok(None),
+    };
+    if length > MAX_MESSAGE_SIZE as u64 {
+        return Err(format!("Frame of {} bytes exceeds {} bytes", length, MAX_MESSAGE_SIZE));
+    }
+    let start = cursor.position() as usize;
+    let end = start + length as usize;
+    if buffer.len() < end {
+        return Ok(None);
+    }
+    Ok(Some((header, start, end)))
+}
+
+// 写入一个完整帧，掩码沿用原帧的掩码
+fn write_frame(output: &mut Vec<u8>, header: &FrameHeader, rsv1: bool, mut payload: Vec<u8>) {
+    let header = FrameHeader {
+        is_final: true,
+        rsv1,
+        opcode: header.opcode,
+        mask: header.mask,
+        ..FrameHeader::default()
+    };
+    if let Some(mask) = header.mask {
+        apply_mask(&mut payload, mask);
+    }
+    // 写入Vec不会失败
+    let _ = header.format(payload.len() as u64, output);
+    output.extend_from_slice(&payload);
+}
+
+// 位于tungstenite之下的透明压缩层（tungstenite 0.21 不支持扩展）：
+// 读取时把RSV1压缩消息解压成普通帧，写入时把完整的数据帧压缩并置RSV1
+pub struct DeflateStream<S> {
+    inner: S,
+    codec: Option<DeflateCodec>,
+    handshake: bool,       // 握手头部尚未读完，此前不能把后续帧交给tungstenite
+    read_buffer: Vec<u8>,  // 从底层读取、尚未处理的字节
+    readable: Vec<u8>,     // 已处理、等待tungstenite读取的字节
+    read_pos: usize,
+    fragments: Option<(FrameHeader, Vec<u8>)>, // 正在接收的分片压缩消息
+    write_buffer: Vec<u8>, // tungstenite写入、尚未组成完整帧的字节
+    writable: Vec<u8>,     // 等待写入底层的字节
+}
+
+impl<S> DeflateStream<S> {
+    // negotiate 为 false 时完全透传
+    pub fn new(inner: S, negotiate: bool) -> Self {
+        DeflateStream {
+            inner,
+            codec: None,
+            handshake: negotiate,
+            read_buffer: Vec::new(),
+            readable: Vec::new(),
+            read_pos: 0,
+            fragments: None,
+            write_buffer: Vec::new(),
+            writable: Vec::new(),
+        }
+    }
+
+    // 握手完成后启用协商好的压缩
+    pub fn activate(&mut self, codec: Option<DeflateCodec>) {
+        self.handshake = false;
+        self.codec = codec;
+    }
+
+    pub fn negotiated(&self) -> Option<&DeflateConfig> {
+        self.codec.as_ref().map(DeflateCodec::config)
+    }
+
+    fn decode_frames(&mut self) -> Result<(), String> {
+        let codec = match self.codec.as_mut() {
+            Some(codec) => codec,
+            None => return Ok(()),
+        };
+
+        while let Some((header, start, end)) = parse_frame(&self.read_buffer)? {
+            let frame: Vec<u8> = self.read_buffer.drain(..end).collect();
+            let mut payload = frame[start..].to_vec();
+            if let Some(mask) = header.mask {
+                apply_mask(&mut payload, mask);
+            }
+
+            match header.opcode {
+                OpCode::Data(Data::Continue) if self.fragments.is_some() => {
+                    if let Some((_, data)) = self.fragments.as_mut() {
+                        if data.len() + payload.len() > MAX_MESSAGE_SIZE {
+                            return Err(format!("Fragmented message exceeds {} bytes", MAX_MESSAGE_SIZE));
+                        }
+                        data.extend_from_slice(&payload);
+                    }
+                    if header.is_final {
+                        if let Some((first, data)) = self.fragments.take() {
+                            let message = codec.decompress(&data)?;
+                            write_frame(&mut self.readable, &first, false, message);
+                        }
+                    }
+                }
+                OpCode::Data(Data::Text | Data::Binary) if header.rsv1 => {
+                    if header.is_final {
+                        let message = codec.decompress(&payload)?;
+                        write_frame(&mut self.readable, &header, false, message);
+                    } else {
+                        self.fragments = Some((header, payload));
+                    }
+                }
+                // 控制帧和未压缩的数据帧原样交出
+                _ => self.readable.extend_from_slice(&frame),
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_frames(&mut self) -> Result<(), String> {
+        let codec = match self.codec.as_mut() {
+            Some(codec) => codec,
+            None => return Ok(()),
+        };
+
+        while let Some((header, start, end)) = parse_frame(&self.write_buffer)? {
+            let frame: Vec<u8> = self.write_buffer.drain(..end).collect();
+            match header.opcode {
+                OpCode::Data(Data::Text | Data::Binary) if header.is_final && !header.rsv1 => {
+                    let mut payload = frame[start..].to_vec();
+                    if let Some(mask) = header.mask {
+                        apply_mask(&mut payload, mask);
+                    }
+                    let compressed = codec.compress(&payload)?;
+                    write_frame(&mut self.writable, &header, true, compressed);
+                }
+                _ => self.writable.extend_from_slice(&frame),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: AsyncWrite + Unpin> DeflateStream<S> {
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.writable.is_empty() {
+            let written = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.writable))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.writable.drain(..written);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn find_header_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|window| window == b"\r\n\r\n").map(|position| position + 4)
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DeflateStream<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        loop {
+            if this.read_pos < this.readable.len() {
+                let count = buf.remaining().min(this.readable.len() - this.read_pos);
+                buf.put_slice(&this.readable[this.read_pos..this.read_pos + count]);
+                this.read_pos += count;
+                if this.read_pos == this.readable.len() {
+                    this.readable.clear();
+                    this.read_pos = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+
+            if this.handshake {
+                if let Some(end) = find_header_end(&this.read_buffer) {
+                    this.readable = this.read_buffer.drain(..end).collect();
+                    this.handshake = false;
+                    continue;
+                }
+            } else if this.codec.is_none() {
+                if !this.read_buffer.is_empty() {
+                    this.readable = std::mem::take(&mut this.read_buffer);
+                    continue;
+                }
+                return Pin::new(&mut this.inner).poll_read(cx, buf);
+            } else {
+                this.decode_frames().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                if !this.readable.is_empty() {
+                    continue;
+                }
+            }
+
+            let mut chunk = [0u8; 8192];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+            if chunk_buf.filled().is_empty() {
+                // 连接已关闭，剩余的不完整数据原样交出，由tungstenite报告错误
+                if this.read_buffer.is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+                this.readable = std::mem::take(&mut this.read_buffer);
+                continue;
+            }
+            this.read_buffer.extend_from_slice(chunk_buf.filled());
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DeflateStream<S> {
+    // 返回的长度包含仍在 writable 中等待写出的字节（积压最多约 MAX_PENDING_WRITE），
+    // 数据在 poll_flush 或 poll_shutdown 完成后才真正写出，两者都会先写完积压并返回写出错误
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        if this.codec.is_none() && this.writable.is_empty() {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+
+        if this.writable.len() >= MAX_PENDING_WRITE {
+            ready!(this.poll_drain(cx))?;
+        }
+        this.write_buffer.extend_from_slice(buf);
+        this.encode_frames().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if let Poll::Ready(Err(e)) = this.poll_drain(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(config: DeflateConfig) -> (DeflateCodec, DeflateCodec) {
+        (DeflateCodec::new(config.clone(), false), DeflateCodec::new(config, true))
+    }
+
+    #[test]
+    fn negotiate_picks_first_acceptable_offer() {
+        let server = DeflateConfig::default();
+        let offers = [
+            "permessage-deflate; server_max_window_bits=8",
+            "permessage-deflate; client_max_window_bits=10; server_no_context_takeover, permessage-deflate",
+        ];
+        let agreed = server.negotiate(offers.into_iter()).unwrap();
+        assert_eq!(
+            agreed,
+            DeflateConfig {
+                server_max_window_bits: 15,
+                client_max_window_bits: 10,
+                server_no_context_takeover: true,
+                client_no_context_takeover: false,
+            }
+        );
+        assert_eq!(agreed.response(), "permessage-deflate; server_no_context_takeover; client_max_window_bits=10");
+    }
+
+    #[test]
+    fn negotiate_rejects_invalid_offers() {
+        let server = DeflateConfig::default();
+        assert_eq!(server.negotiate(["x-webkit-deflate-frame"].into_iter()), None);
+        assert_eq!(server.negotiate(["permessage-deflate; client_no_context_takeover; client_no_context_takeover"].into_iter()), None);
+        assert_eq!(server.negotiate(["permessage-deflate; server_max_window_bits=16"].into_iter()), None);
+        assert_eq!(server.negotiate(["permessage-deflate; unknown"].into_iter()), None);
+
+        // 服务端要限制客户端窗口时，客户端必须声明 client_max_window_bits
+        let server = DeflateConfig { client_max_window_bits: 12, ..DeflateConfig::default() };
+        assert_eq!(server.negotiate(["permessage-deflate"].into_iter()), None);
+        let agreed = server.negotiate(["permessage-deflate; client_max_window_bits"].into_iter()).unwrap();
+        assert_eq!(agreed.client_max_window_bits, 12);
+        let agreed = server.negotiate(["permessage-deflate; client_max_window_bits=9"].into_iter()).unwrap();
+        assert_eq!(agreed.client_max_window_bits, 9);
+    }
+
+    #[test]
+    fn offer_lists_requested_params() {
+        let client = DeflateConfig {
+            server_max_window_bits: 10,
+            client_no_context_takeover: true,
+            ..DeflateConfig::default()
+        };
+        assert_eq!(client.offer(), "permessage-deflate; client_no_context_takeover; server_max_window_bits=10; client_max_window_bits");
+        assert_eq!(DeflateConfig::default().response(), "permessage-deflate");
+    }
+
+    #[test]
+    fn accept_response_applies_server_params() {
+        let client = DeflateConfig { server_max_window_bits: 12, ..DeflateConfig::default() };
+        let agreed = client
+            .accept_response(["permessage-deflate; server_max_window_bits=10; client_max_window_bits=9; client_no_context_takeover"].into_iter())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            agreed,
+            DeflateConfig {
+                server_max_window_bits: 10,
+                client_max_window_bits: 9,
+                server_no_context_takeover: false,
+                client_no_context_takeover: true,
+            }
+        );
+
+        // 服务端未在响应中限制窗口时使用默认的 15 位
+        let agreed = client.accept_response(["permessage-deflate"].into_iter()).unwrap().unwrap();
+        assert_eq!(agreed.server_max_window_bits, 15);
+        assert_eq!(client.accept_response(std::iter::empty()), Ok(None));
+    }
+
+    #[test]
+    fn accept_response_rejects_invalid_responses() {
+        let client = DeflateConfig { server_max_window_bits: 12, ..DeflateConfig::default() };
+        assert!(client.accept_response(["permessage-deflate; server_max_window_bits=13"].into_iter()).is_err());
+        assert!(client.accept_response(["permessage-deflate; client_max_window_bits=8"].into_iter()).is_err());
+        assert!(client.accept_response(["permessage-deflate; server_max_window_bits=abc"].into_iter()).is_err());
+        assert!(client.accept_response(["permessage-deflate; unknown"].into_iter()).is_err());
+        assert!(client.accept_response(["permessage-deflate, permessage-deflate"].into_iter()).is_err());
+        assert!(client.accept_response(["x-webkit-deflate-frame"].into_iter()).is_err());
+    }
+
+    #[test]
+    fn round_trip_with_context_takeover() {
+        let (mut client, mut server) = pair(DeflateConfig::default());
+        let message = b"The quick brown fox jumps over the lazy dog".repeat(4);
+        let first = client.compress(&message).unwrap();
+        let second = client.compress(&message).unwrap();
+        // 第二条消息可以引用第一条的内容
+        assert!(second.len() < first.len());
+        assert_eq!(server.decompress(&first).unwrap(), message);
+        assert_eq!(server.decompress(&second).unwrap(), message);
+    }
+
+    #[test]
+    fn round_trip_without_context_takeover() {
+        let config = DeflateConfig {
+            server_no_context_takeover: true,
+            client_no_context_takeover: true,
+            ..DeflateConfig::default()
+        };
+        let (mut client, mut server) = pair(config);
+        let message = b"The quick brown fox jumps over the lazy dog".repeat(4);
+        let first = client.compress(&message).unwrap();
+        let second = client.compress(&message).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(server.decompress(&first).unwrap(), message);
+        assert_eq!(server.decompress(&second).unwrap(), message);
+
+        // 每条消息都可以由新的解压器单独解压
+        let (_, mut fresh) = pair(DeflateConfig::default());
+        assert_eq!(fresh.decompress(&second).unwrap(), message);
+
+        let reply = server.compress(b"reply").unwrap();
+        assert_eq!(client.decompress(&reply).unwrap(), b"reply");
+    }
+
+    #[test]
+    fn round_trip_empty_message() {
+        let (mut client, mut server) = pair(DeflateConfig::default());
+        let compressed = client.compress(b"").unwrap();
+        assert!(!compressed.is_empty());
+        assert_eq!(server.decompress(&compressed).unwrap(), b"");
+        // 空消息之后上下文仍然可用
+        let compressed = client.compress(b"Hello").unwrap();
+        assert_eq!(server.decompress(&compressed).unwrap(), b"Hello");
+    }
+
+    #[test]
+    fn rfc7692_hello_vectors() {
+        let (mut client, _) = pair(DeflateConfig::default());
+        // 7.2.3.1 单条压缩消息
+        assert_eq!(client.decompress(&[0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00]).unwrap(), b"Hello");
+        // 7.2.3.2 复用上下文的第二条消息
+        assert_eq!(client.decompress(&[0xf2, 0x00, 0x11, 0x00, 0x00]).unwrap(), b"Hello");
+
+        let (mut client, _) = pair(DeflateConfig::default());
+        // 7.2.3.3 未压缩的存储块
+        assert_eq!(client.decompress(&[0x00, 0x05, 0x00, 0xfa, 0xff, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x00]).unwrap(), b"Hello");
+        // 7.2.3.4 设置了BFINAL的块，之后的消息从新的deflate流开始
+        assert_eq!(client.decompress(&[0xf3, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x00]).unwrap(), b"Hello");
+        assert_eq!(client.decompress(&[0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00]).unwrap(), b"Hello");
+
+        let (mut client, _) = pair(DeflateConfig::default());
+        assert_eq!(client.compress(b"Hello").unwrap(), [0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00]);
+    }
+
+    #[test]
+    fn decode_frames_joins_fragmented_compressed_message() {
+        let mut stream = DeflateStream::new((), false);
+        stream.activate(Some(DeflateCodec::new(DeflateConfig::default(), false)));
+        // 7.2.3.1 分成两帧的 "Hello"，中间插入一个Ping
+        stream.read_buffer.extend_from_slice(&[0x41, 0x03, 0xf2, 0x48, 0xcd]);
+        stream.read_buffer.extend_from_slice(&[0x89, 0x00]);
+        stream.read_buffer.extend_from_slice(&[0x80, 0x04, 0xc9, 0xc9]);
+        stream.decode_frames().unwrap();
+        assert_eq!(stream.readable, [0x89, 0x00]);
+
+        // 最后一帧尚未收全时保留在缓冲区
+        stream.read_buffer.extend_from_slice(&[0x07, 0x00]);
+        stream.decode_frames().unwrap();
+        assert_eq!(stream.readable, [0x89, 0x00, 0x81, 0x05, b'H', b'e', b'l', b'l', b'o']);
+        assert!(stream.read_buffer.is_empty());
+        assert!(stream.fragments.is_none());
+    }
+
+    #[test]
+    fn decode_frames_passes_uncompressed_frames_through() {
+        let mut stream = DeflateStream::new((), false);
+        stream.activate(Some(DeflateCodec::new(DeflateConfig::default(), true)));
+        // 客户端发来的带掩码的未压缩文本帧
+        let frame = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+        stream.read_buffer.extend_from_slice(&frame);
+        stream.decode_frames().unwrap();
+        assert_eq!(stream.readable, frame);
+    }
+}
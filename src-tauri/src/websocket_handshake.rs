@@ -0,0 +1,185 @@
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue, StatusCode};
+
+use crate::http_client::HttpHeader;
+use crate::websocket_deflate::DeflateConfig;
+
+// 握手阶段的接受策略
+#[derive(Debug, Clone, Default)]
+pub struct HandshakePolicy {
+    pub subprotocols: Vec<String>,    // 服务端支持的子协议，非空时客户端必须请求其中之一
+    pub allowed_origins: Vec<String>, // 允许的Origin，为空时不检查
+    pub bearer_token: Option<String>, // 要求 "Authorization: Bearer <token>"
+    pub response_headers: Vec<(HeaderName, HeaderValue)>, // 附加到所有握手响应的头部
+    pub reject_status: Option<StatusCode>, // 强制以该HTTP状态拒绝所有握手
+    pub compression: Option<DeflateConfig>, // 启用时接受客户端的 permessage-deflate 请求
+}
+
+// 握手结果，用于事件报告
+#[derive(Default)]
+pub struct HandshakeOutcome {
+    pub request: String,
+    pub subprotocol: Option<String>,
+    pub compression: Option<DeflateConfig>, // 协商好的压缩参数
+    pub route: Option<String>,
+    pub rejection: Option<String>,
+}
+
+impl HandshakePolicy {
+    pub fn new(
+        subprotocols: Option<Vec<String>>,
+        allowed_origins: Option<Vec<String>>,
+        bearer_token: Option<String>,
+        response_headers: Option<Vec<HttpHeader>>,
+        reject_status: Option<u16>,
+    ) -> Result<Self, String> {
+        let response_headers = response_headers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|header| {
+                let name = HeaderName::from_bytes(header.name.trim().as_bytes())
+                    .map_err(|_| format!("Invalid header name: {}", header.name))?;
+                let value = HeaderValue::from_str(header.value.trim())
+                    .map_err(|_| format!("Invalid value for header {}", header.name))?;
+                Ok((name, value))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let reject_status = match reject_status {
+            Some(code) => {
+                let status = StatusCode::from_u16(code).map_err(|_| format!("Invalid HTTP status: {}", code))?;
+                if status == StatusCode::SWITCHING_PROTOCOLS {
+                    return Err("Reject status cannot be 101".to_string());
+                }
+                Some(status)
+            }
+            None => None,
+        };
+
+        Ok(HandshakePolicy {
+            subprotocols: subprotocols.unwrap_or_default(),
+            allowed_origins: allowed_origins.unwrap_or_default(),
+            bearer_token: bearer_token.filter(|token| !token.is_empty()),
+            response_headers,
+            reject_status,
+            compression: None,
+        })
+    }
+
+    // 握手回调：检查请求并生成响应，结果写入 outcome
+    // 返回类型由 tungstenite 的回调接口决定
+    #[allow(clippy::result_large_err)]
+    pub fn accept(&self, request: &Request, mut response: Response, outcome: &mut HandshakeOutcome) -> Result<Response, ErrorResponse> {
+        outcome.request = describe_request(request);
+
+        if let Err((status, reason)) = self.check(request) {
+            return Err(self.reject(status, reason, outcome));
+        }
+
+        let headers = response.headers_mut();
+        if let Some(protocol) = self.select_subprotocol(request) {
+            if let Ok(value) = HeaderValue::from_str(&protocol) {
+                headers.insert("Sec-WebSocket-Protocol", value);
+            }
+            outcome.subprotocol = Some(protocol);
+        }
+        if let Some(compression) = &self.compression {
+            let offers = request
+                .headers()
+                .get_all("Sec-WebSocket-Extensions")
+                .iter()
+                .filter_map(|value| value.to_str().ok());
+            if let Some(agreed) = compression.negotiate(offers) {
+                if let Ok(value) = HeaderValue::from_str(&agreed.response()) {
+                    headers.insert("Sec-WebSocket-Extensions", value);
+                    outcome.compression = Some(agreed);
+                }
+            }
+        }
+        for (name, value) in &self.response_headers {
+            headers.append(name.clone(), value.clone());
+        }
+        Ok(response)
+    }
+
+    fn check(&self, request: &Request) -> Result<(), (StatusCode, String)> {
+        if let Some(status) = self.reject_status {
+            return Err((status, "Handshake rejected by server policy".to_string()));
+        }
+
+        if !self.allowed_origins.is_empty() {
+            let origin = header_value(request, "Origin");
+            let allowed = origin
+                .as_deref()
+                .is_some_and(|origin| self.allowed_origins.iter().any(|allowed| allowed.eq_ignore_ascii_case(origin)));
+            if !allowed {
+                return Err((StatusCode::FORBIDDEN, format!("Origin not allowed: {}", origin.unwrap_or_default())));
+            }
+        }
+
+        if let Some(token) = &self.bearer_token {
+            let authorization = header_value(request, "Authorization").unwrap_or_default();
+            let provided = authorization
+                .split_once(' ')
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Bearer"))
+                .map(|(_, provided)| provided.trim());
+            if provided != Some(token.as_str()) {
+                return Err((StatusCode::UNAUTHORIZED, "Missing or invalid bearer token".to_string()));
+            }
+        }
+
+        if !self.subprotocols.is_empty() && self.select_subprotocol(request).is_none() {
+            let offered = header_value(request, "Sec-WebSocket-Protocol").unwrap_or_default();
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("None of the offered subprotocols [{}] is supported", offered),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // 按客户端的偏好顺序选择第一个服务端支持的子协议
+    fn select_subprotocol(&self, request: &Request) -> Option<String> {
+        request
+            .headers()
+            .get_all("Sec-WebSocket-Protocol")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .find(|offered| self.subprotocols.iter().any(|supported| supported == offered))
+            .map(str::to_string)
+    }
+
+    // 以指定状态拒绝握手，附加头部同样生效
+    pub fn reject(&self, status: StatusCode, reason: String, outcome: &mut HandshakeOutcome) -> ErrorResponse {
+        outcome.rejection = Some(format!("HTTP {}: {}", status.as_u16(), reason));
+        let mut response = ErrorResponse::new(Some(reason));
+        *response.status_mut() = status;
+        let headers = response.headers_mut();
+        if status == StatusCode::UNAUTHORIZED {
+            headers.insert("WWW-Authenticate", HeaderValue::from_static("Bearer"));
+        }
+        for (name, value) in &self.response_headers {
+            headers.append(name.clone(), value.clone());
+        }
+        response
+    }
+}
+
+fn header_value(request: &Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+}
+
+// 请求行和全部头部，例如 "GET /chat?room=1 HTTP/1.1\nHost: ..."
+pub fn describe_request(request: &Request) -> String {
+    let mut lines = vec![format!("{} {} {:?}", request.method(), request.uri(), request.version())];
+    for (name, value) in request.headers() {
+        lines.push(format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes())));
+    }
+    lines.join("\n")
+}
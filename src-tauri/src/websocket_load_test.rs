@@ -0,0 +1,435 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+use tokio::sync::{broadcast, Mutex};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use crate::correlation;
+use crate::http_client::HttpHeader;
+use crate::load_test::{self, LoadPlan, LoadStats, LoadTest, LoadTestManager};
+use crate::message_template::{self, TemplateEngine};
+use crate::transport::TlsOptions;
+use crate::websocket_server::encode_message;
+use crate::websocket_transport::{self, ClientWebSocket, WebSocketConnectOptions};
+
+const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 5000;
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+// 按序号统计时保留的最近序号数，超出后丢弃最早的记录
+const SEQUENCE_WINDOW: usize = 65536;
+
+// 推送延迟的测量方式
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FanoutLatencyParams {
+    pub mode: String,                   // "timestamp"：与消息中的发送时间比较；"sequence"：与第一个收到同一序号的连接比较
+    pub field: Option<String>,          // JSON字段路径，例如 "data.ts" 或 "items.0.seq"；为None则整条文本消息就是该值
+    pub binary_offset: Option<usize>,   // 二进制消息中大端 u64 值的偏移，为None则忽略二进制消息
+    pub timestamp_unit: Option<String>, // "s"、"ms"、"us" 或 "ns"，默认为 "ms"；非数字的字符串按 RFC3339 解析
+}
+
+// 启动WebSocket负载测试的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartWebSocketLoadTestParams {
+    pub test_id: Option<String>,
+    pub url: String, // ws(s):// 或 http(s):// 地址
+    pub connections: usize,
+    pub ramp_up_ms: Option<u64>,        // 在该时间内均匀建立所有连接，默认同时建立
+    pub duration_ms: Option<u64>,       // 测试时长，默认直到停止
+    pub headers: Option<Vec<HttpHeader>>,
+    pub subprotocols: Option<Vec<String>>,
+    pub insecure: Option<bool>,         // 跳过TLS证书校验，默认为 false
+    pub subscriptions: Option<Vec<String>>, // 每次连接成功后依次发送的订阅消息
+    pub subscription_type: Option<String>,  // 订阅消息格式，同发送消息，默认为 "text"
+    pub template: Option<bool>,         // 发送前展开订阅消息中的模板，计数器按连接独立
+    pub latency: Option<FanoutLatencyParams>, // 设置后从收到的消息中提取时间戳或序号计算推送延迟
+    pub reconnect_delay_ms: Option<u64>,    // 设置后连接断开或连接失败时等待该时间重连
+    pub max_reconnects: Option<u32>,        // 每个连接最多重连的次数，默认不限
+    pub connect_timeout_ms: Option<u64>,    // 默认为 5000
+    pub report_interval_ms: Option<u64>,    // 进度事件间隔，默认为 1000
+}
+
+// 从消息中取出的值
+#[derive(Debug, Clone, Copy)]
+enum Marker {
+    Timestamp(SystemTime),
+    Sequence(u64),
+}
+
+// 校验后的推送延迟设置
+struct FanoutLatency {
+    sequence: bool,
+    field: Vec<String>,
+    binary_offset: Option<usize>,
+    unit_ns: u64,
+}
+
+impl FanoutLatency {
+    fn from_params(params: &FanoutLatencyParams) -> Result<Self, String> {
+        let sequence = match params.mode.as_str() {
+            "timestamp" => false,
+            "sequence" => true,
+            other => return Err(format!("Unsupported latency mode: {}", other)),
+        };
+        let unit_ns = match params.timestamp_unit.as_deref().unwrap_or("ms") {
+            "s" => 1_000_000_000,
+            "ms" => 1_000_000,
+            "us" => 1_000,
+            "ns" => 1,
+            other => return Err(format!("Unsupported timestamp unit: {}", other)),
+        };
+        let field = match params.field.as_deref() {
+            Some(path) => correlation::parse_field_path(path)?,
+            None => Vec::new(),
+        };
+        Ok(FanoutLatency {
+            sequence,
+            field,
+            binary_offset: params.binary_offset,
+            unit_ns,
+        })
+    }
+
+    fn extract(&self, message: &Message) -> Option<Marker> {
+        match message {
+            Message::Text(text) => self.extract_text(text),
+            Message::Binary(data) => {
+                let offset = self.binary_offset?;
+                let bytes = data.get(offset..offset.checked_add(8)?)?;
+                Some(self.marker_from_number(u64::from_be_bytes(bytes.try_into().ok()?)))
+            }
+            _ => None,
+        }
+    }
+
+    fn extract_text(&self, text: &str) -> Option<Marker> {
+        if self.field.is_empty() {
+            return self.marker_from_str(text.trim());
+        }
+        let root: serde_json::Value = serde_json::from_str(text).ok()?;
+        match correlation::json_field(&root, &self.field)? {
+            serde_json::Value::Number(number) => number
+                .as_u64()
+                .map(|number| self.marker_from_number(number))
+                .or_else(|| (!self.sequence).then(|| number.as_f64()).flatten().and_then(|number| self.timestamp_from_f64(number))),
+            serde_json::Value::String(text) => self.marker_from_str(text),
+            _ => None,
+        }
+    }
+
+    fn marker_from_str(&self, text: &str) -> Option<Marker> {
+        if let Ok(number) = text.parse::<u64>() {
+            return Some(self.marker_from_number(number));
+        }
+        if self.sequence {
+            return None;
+        }
+        if let Ok(number) = text.parse::<f64>() {
+            return self.timestamp_from_f64(number);
+        }
+        chrono::DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|time| Marker::Timestamp(SystemTime::from(time)))
+    }
+
+    fn marker_from_number(&self, number: u64) -> Marker {
+        if self.sequence {
+            Marker::Sequence(number)
+        } else {
+            Marker::Timestamp(UNIX_EPOCH + Duration::from_nanos(number.saturating_mul(self.unit_ns)))
+        }
+    }
+
+    // 带小数的时间戳，例如以秒为单位的 1718000000.123
+    fn timestamp_from_f64(&self, number: f64) -> Option<Marker> {
+        let seconds = number * self.unit_ns as f64 / 1e9;
+        Duration::try_from_secs_f64(seconds).ok().map(|offset| Marker::Timestamp(UNIX_EPOCH + offset))
+    }
+}
+
+// 所有连接共用的测试设置
+struct WebSocketLoadContext {
+    app_handle: tauri::AppHandle,
+    test_id: String,
+    url: String,
+    options: WebSocketConnectOptions,
+    stats: Arc<LoadStats>,
+    subscriptions: Vec<String>,
+    subscription_type: String,
+    template: Option<bool>,
+    latency: Option<FanoutLatency>,
+    first_seen: StdMutex<BTreeMap<u64, Instant>>, // 序号第一次被任一连接收到的时间
+    reconnect_delay: Option<Duration>,
+    max_reconnects: Option<u32>,
+    connect_timeout: Duration,
+}
+
+impl WebSocketLoadContext {
+    // 模板计数器按连接独立
+    fn scope(&self, index: usize) -> String {
+        format!("{}#{}", self.test_id, index)
+    }
+
+    async fn subscription_messages(&self, index: usize) -> Result<Vec<Message>, String> {
+        let templates = self.app_handle.state::<Mutex<TemplateEngine>>();
+        let mut messages = Vec::with_capacity(self.subscriptions.len());
+        for subscription in &self.subscriptions {
+            let message = message_template::expand_message(
+                &templates,
+                self.template,
+                &self.scope(index),
+                subscription.clone(),
+                Some(&self.subscription_type),
+            )
+            .await?;
+            messages.push(encode_message(&message, &self.subscription_type, None)?);
+        }
+        Ok(messages)
+    }
+
+    // 记录一条消息的推送延迟，按序号统计时同时检查该连接是否漏收
+    fn record_latency(&self, message: &Message, received_at: Instant, last_sequence: &mut Option<u64>) {
+        let Some(marker) = self.latency.as_ref().and_then(|latency| latency.extract(message)) else {
+            return;
+        };
+        match marker {
+            Marker::Timestamp(sent_at) => {
+                // 两端时钟不同步时延迟可能为负，按 0 计
+                let latency = SystemTime::now().duration_since(sent_at).unwrap_or_default();
+                self.stats.update(|counters| counters.response_latency.record(latency));
+            }
+            Marker::Sequence(sequence) => {
+                let first_seen = {
+                    let mut first_seen = self.first_seen.lock().unwrap();
+                    let seen = *first_seen.entry(sequence).or_insert(received_at);
+                    while first_seen.len() > SEQUENCE_WINDOW {
+                        first_seen.pop_first();
+                    }
+                    seen
+                };
+                let gap = match *last_sequence {
+                    Some(last) if sequence > last => sequence - last - 1,
+                    _ => 0,
+                };
+                *last_sequence = Some(last_sequence.map_or(sequence, |last| last.max(sequence)));
+                self.stats.update(|counters| {
+                    counters.sequence_gaps += gap;
+                    counters.response_latency.record(received_at.saturating_duration_since(first_seen));
+                });
+            }
+        }
+    }
+}
+
+// 一次连接结束的原因
+enum SessionEnd {
+    Stopped,
+    Closed(Option<String>), // 对端关闭或出错
+}
+
+// 单个连接：建立连接、发送订阅并接收推送，按设置在断开后重连
+async fn run_connection(ctx: Arc<WebSocketLoadContext>, index: usize, mut stop_rx: broadcast::Receiver<()>) {
+    let mut attempts: u32 = 0;
+    let mut connected_before = false;
+    loop {
+        let connect_started = Instant::now();
+        let connected = tokio::select! {
+            _ = stop_rx.recv() => break,
+            result = tokio::time::timeout(ctx.connect_timeout, websocket_transport::connect_websocket(&ctx.url, &ctx.options)) => match result {
+                Ok(result) => result.map(|(ws_stream, _)| ws_stream),
+                Err(_) => Err(format!("Connection to {} timed out", ctx.url)),
+            },
+        };
+        match connected {
+            Ok(ws_stream) => {
+                ctx.stats.update(|counters| {
+                    counters.connects += 1;
+                    counters.connections_active += 1;
+                    if connected_before {
+                        counters.reconnects += 1;
+                    }
+                    counters.connect_latency.record(connect_started.elapsed());
+                });
+                connected_before = true;
+
+                let end = session(&ctx, index, ws_stream, &mut stop_rx).await;
+                ctx.stats.update(|counters| {
+                    counters.connections_active -= 1;
+                    if let SessionEnd::Closed(error) = &end {
+                        counters.disconnects += 1;
+                        if let Some(error) = error {
+                            counters.error(error.clone());
+                        }
+                    }
+                });
+                if let SessionEnd::Stopped = end {
+                    break;
+                }
+            }
+            Err(e) => ctx.stats.update(|counters| {
+                counters.connect_failures += 1;
+                counters.last_error = Some(e);
+            }),
+        }
+
+        let delay = match ctx.reconnect_delay {
+            Some(delay) if ctx.max_reconnects.is_none_or(|max| attempts < max) => delay,
+            _ => break,
+        };
+        attempts += 1;
+        tokio::select! {
+            _ = stop_rx.recv() => break,
+            _ = tokio::time::sleep(delay) => {}
+        }
+    }
+
+    if ctx.template.unwrap_or(false) {
+        let templates = ctx.app_handle.state::<Mutex<TemplateEngine>>();
+        templates.lock().await.reset_counters(Some(&ctx.scope(index)));
+    }
+}
+
+async fn session(ctx: &WebSocketLoadContext, index: usize, mut ws_stream: ClientWebSocket, stop_rx: &mut broadcast::Receiver<()>) -> SessionEnd {
+    let subscriptions = match ctx.subscription_messages(index).await {
+        Ok(subscriptions) => subscriptions,
+        Err(e) => return SessionEnd::Closed(Some(e)),
+    };
+    for subscription in subscriptions {
+        let len = subscription.len() as u64;
+        if let Err(e) = ws_stream.send(subscription).await {
+            return SessionEnd::Closed(Some(format!("WebSocket error: {}", e)));
+        }
+        ctx.stats.update(|counters| {
+            counters.messages_sent += 1;
+            counters.bytes_sent += len;
+        });
+    }
+
+    let mut last_sequence = None;
+    loop {
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                let _ = tokio::time::timeout(CLOSE_TIMEOUT, ws_stream.close(None)).await;
+                return SessionEnd::Stopped;
+            }
+            message = ws_stream.next() => match message {
+                Some(Ok(message @ (Message::Text(_) | Message::Binary(_)))) => {
+                    let received_at = Instant::now();
+                    let len = message.len() as u64;
+                    ctx.stats.update(|counters| {
+                        counters.messages_received += 1;
+                        counters.bytes_received += len;
+                    });
+                    ctx.record_latency(&message, received_at, &mut last_sequence);
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    return SessionEnd::Closed(frame.map(|frame| format!("Connection closed by server ({}): {}", u16::from(frame.code), frame.reason)));
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return SessionEnd::Closed(Some(format!("WebSocket error: {}", e))),
+                None => return SessionEnd::Closed(None),
+            }
+        }
+    }
+}
+
+// Tauri命令：启动WebSocket负载测试
+#[tauri::command]
+pub async fn start_websocket_load_test(
+    load_params: StartWebSocketLoadTestParams,
+    tests: State<'_, Mutex<LoadTestManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let test_id = load_params.test_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut tests = tests.lock().await;
+    if tests.is_running(&test_id) {
+        return Err(format!("Load test {} is already running", test_id));
+    }
+    let plan = LoadPlan::new(
+        load_params.connections,
+        load_params.ramp_up_ms,
+        load_params.duration_ms,
+        load_params.report_interval_ms,
+    )?;
+
+    // 预先展开并编码一次订阅消息以尽早发现错误
+    let subscriptions = load_params.subscriptions.unwrap_or_default();
+    let subscription_type = load_params.subscription_type.unwrap_or_else(|| "text".to_string());
+    for subscription in &subscriptions {
+        let preview = if load_params.template.unwrap_or(false) {
+            let hex = subscription_type == "hex";
+            templates.lock().await.render(&test_id, subscription, hex, false)?
+        } else {
+            subscription.clone()
+        };
+        encode_message(&preview, &subscription_type, None)?;
+    }
+    let latency = load_params.latency.as_ref().map(FanoutLatency::from_params).transpose()?;
+    let url = load_params.url.trim().to_string();
+    let options = WebSocketConnectOptions {
+        headers: load_params
+            .headers
+            .iter()
+            .flatten()
+            .map(|header| (header.name.clone(), header.value.clone()))
+            .collect(),
+        subprotocols: load_params.subprotocols.unwrap_or_default(),
+        tls: TlsOptions {
+            insecure: load_params.insecure.unwrap_or(false),
+            ..TlsOptions::default()
+        },
+        ..WebSocketConnectOptions::default()
+    };
+
+    let stats = Arc::new(LoadStats::new(plan.connections));
+    let ctx = Arc::new(WebSocketLoadContext {
+        app_handle: app_handle.clone(),
+        test_id: test_id.clone(),
+        url: url.clone(),
+        options,
+        stats: Arc::clone(&stats),
+        subscriptions,
+        subscription_type,
+        template: load_params.template,
+        latency,
+        first_seen: StdMutex::new(BTreeMap::new()),
+        reconnect_delay: load_params.reconnect_delay_ms.map(Duration::from_millis),
+        max_reconnects: load_params.max_reconnects,
+        connect_timeout: Duration::from_millis(load_params.connect_timeout_ms.unwrap_or(DEFAULT_CONNECT_TIMEOUT_MS)),
+    });
+
+    load_test::emit_load_test_event(
+        &app_handle,
+        &test_id,
+        "started",
+        format!("Opening {} connections to {}", plan.connections, url),
+        stats.report(false),
+    );
+
+    let (stop_tx, _) = broadcast::channel(1);
+    let handle = tokio::spawn(load_test::supervise(
+        app_handle,
+        test_id.clone(),
+        Arc::clone(&stats),
+        plan,
+        stop_tx.clone(),
+        move |index, stop_rx| run_connection(Arc::clone(&ctx), index, stop_rx),
+    ));
+
+    tests.insert(LoadTest {
+        test_id: test_id.clone(),
+        protocol: "websocket".to_string(),
+        target: url,
+        stats,
+        stop_sender: stop_tx,
+        handle,
+    })?;
+
+    Ok(test_id)
+}
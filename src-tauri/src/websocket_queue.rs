@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::write_ack::WriteAck;
+
+// 分片消息标识的分配计数
+static NEXT_FRAGMENT_STREAM: AtomicU64 = AtomicU64::new(1);
+
+// WebSocket连接发送队列中的一项
+pub struct OutgoingMessage {
+    pub message: Message,
+    pub fragment_stream: Option<u64>, // 分片消息的标识，同一条分片消息的各帧相同
+    pub request_id: Option<u64>,      // 关联的请求ID，写出后记录发送时间
+    pub ack: Option<WriteAck>,        // 写出后通知
+}
+
+impl From<Message> for OutgoingMessage {
+    fn from(message: Message) -> Self {
+        OutgoingMessage {
+            message,
+            fragment_stream: None,
+            request_id: None,
+            ack: None,
+        }
+    }
+}
+
+pub fn next_fragment_stream() -> u64 {
+    NEXT_FRAGMENT_STREAM.fetch_add(1, Ordering::Relaxed)
+}
+
+// 分片消息发送期间暂存其他数据消息，保证分片帧连续（RFC 6455 5.4）；控制帧可以插在分片之间
+#[derive(Default)]
+pub struct FragmentGate {
+    current: Option<u64>,
+    deferred: VecDeque<OutgoingMessage>,
+}
+
+impl FragmentGate {
+    // 放入从队列取出的一项，返回按顺序可以立即发送的项
+    pub fn admit(&mut self, item: OutgoingMessage) -> Vec<OutgoingMessage> {
+        let mut ready = Vec::new();
+        self.push(item, &mut ready);
+        ready
+    }
+
+    fn push(&mut self, item: OutgoingMessage, ready: &mut Vec<OutgoingMessage>) {
+        let control = matches!(item.message, Message::Ping(_) | Message::Pong(_) | Message::Close(_));
+        if self.current.is_some() && !control && item.fragment_stream != self.current {
+            self.deferred.push_back(item);
+            return;
+        }
+
+        let mut finished = false;
+        if let (Some(stream), Message::Frame(frame)) = (item.fragment_stream, &item.message) {
+            if frame.header().is_final {
+                finished = self.current.take().is_some();
+            } else {
+                self.current = Some(stream);
+            }
+        }
+        ready.push(item);
+
+        // 分片消息结束，按原顺序放行暂存的消息，其中可能开始新的分片消息
+        if finished {
+            for item in std::mem::take(&mut self.deferred) {
+                self.push(item, ready);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::{Data, OpCode};
+    use tokio_tungstenite::tungstenite::protocol::frame::Frame;
+
+    fn fragment(stream: u64, first: bool, last: bool, byte: u8) -> OutgoingMessage {
+        let opcode = if first { OpCode::Data(Data::Binary) } else { OpCode::Data(Data::Continue) };
+        OutgoingMessage {
+            fragment_stream: Some(stream),
+            ..Message::Frame(Frame::message(vec![byte], opcode, last)).into()
+        }
+    }
+
+    fn payloads(items: Vec<OutgoingMessage>) -> Vec<Vec<u8>> {
+        items.into_iter().map(|item| item.message.into_data()).collect()
+    }
+
+    #[test]
+    fn holds_data_messages_until_the_fragmented_message_ends() {
+        let mut gate = FragmentGate::default();
+        assert_eq!(payloads(gate.admit(fragment(1, true, false, 1))), [vec![1]]);
+        assert!(gate.admit(Message::Binary(vec![9]).into()).is_empty());
+        assert_eq!(payloads(gate.admit(Message::Ping(vec![8]).into())), [vec![8]]);
+        assert_eq!(payloads(gate.admit(fragment(1, false, false, 2))), [vec![2]]);
+        assert!(gate.admit(fragment(2, true, false, 5)).is_empty());
+        assert_eq!(payloads(gate.admit(fragment(1, false, true, 3))), [vec![3], vec![9], vec![5]]);
+
+        // 暂存的第二条分片消息开始后，其余数据消息继续等待
+        assert!(gate.admit(Message::Text("x".to_string()).into()).is_empty());
+        assert_eq!(payloads(gate.admit(fragment(2, false, true, 6))), [vec![6], b"x".to_vec()]);
+        assert_eq!(payloads(gate.admit(Message::Binary(vec![7]).into())), [vec![7]]);
+    }
+}
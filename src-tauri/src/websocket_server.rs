@@ -1,22 +1,141 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use tauri::{State, Emitter};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::task::JoinHandle;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_tungstenite::accept_hdr_async;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::error::ProtocolError;
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::WebSocketStream;
 use uuid::Uuid;
 use chrono;
 
+use crate::checksum::{self, Checksum, ChecksumCheck, ChecksumParams};
+use crate::http_client::HttpHeader;
+use crate::message_template::{self, TemplateEngine};
+use crate::payload_codec;
+use crate::receive_capture;
+use crate::socketio_codec;
+use crate::socketio_server;
+use crate::websocket_deflate::{CompressionParams, DeflateCodec, DeflateConfig, DeflateStream};
+use crate::websocket_queue::{FragmentGate, OutgoingMessage};
+use crate::write_ack::{self, WriteAckReceiver};
+use crate::websocket_handshake::{HandshakeOutcome, HandshakePolicy};
+
+// 控制帧载荷的最大长度（RFC 6455）
+const MAX_CONTROL_PAYLOAD: usize = 125;
+// 未指定时的pong超时
+const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+// pong超时后等待关闭帧写出的最长时间
+const CLOSE_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+// 最多记录的未应答ping数量
+const MAX_PENDING_PINGS: usize = 32;
+
+// 服务端连接，压缩层位于TCP和tungstenite之间
+pub type ServerWebSocket = WebSocketStream<DeflateStream<TcpStream>>;
+
 // WebSocket客户端连接
 #[allow(dead_code)]
 pub struct WebSocketClient {
     pub id: String,
     pub addr: SocketAddr,
-    pub sender: mpsc::UnboundedSender<Message>,
+    pub sender: mpsc::UnboundedSender<OutgoingMessage>,
+    pub pings: Arc<PingTracker>,
+    pub route: Option<String>, // 匹配的路由路径模式
+}
+
+impl WebSocketClient {
+    // 发送ping并记录发送时间，用于计算往返时间
+    pub fn send_ping(&self, payload: Vec<u8>) -> Result<(), String> {
+        self.pings.sent(payload.clone());
+        self.sender
+            .send(Message::Ping(payload).into())
+            .map_err(|e| format!("Failed to send ping to client {}: {}", self.id, e))
+    }
+}
+
+// 记录已发送但尚未收到pong的ping
+#[derive(Default)]
+pub struct PingTracker {
+    pending: std::sync::Mutex<VecDeque<(Vec<u8>, Instant)>>,
+}
+
+impl PingTracker {
+    pub fn sent(&self, payload: Vec<u8>) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.len() >= MAX_PENDING_PINGS {
+            pending.pop_front();
+        }
+        pending.push_back((payload, Instant::now()));
+    }
+
+    // 匹配pong并返回往返时间；对端可能只回复最近一次ping，因此更早的记录一并清除
+    pub fn pong(&self, payload: &[u8]) -> Option<Duration> {
+        let mut pending = self.pending.lock().unwrap();
+        let position = pending.iter().rposition(|(sent, _)| sent.as_slice() == payload)?;
+        let sent_at = pending[position].1;
+        pending.drain(..=position);
+        Some(sent_at.elapsed())
+    }
+
+    pub fn oldest(&self) -> Option<Instant> {
+        self.pending.lock().unwrap().front().map(|(_, sent_at)| *sent_at)
+    }
+}
+
+// WebSocket路由
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketRoute {
+    pub path: String,                  // "/ws/telemetry" 精确匹配，"/ws/*" 前缀匹配
+    pub auto_reply: Option<String>,    // "echo" 回显收到的消息，"fixed" 回复 reply_message
+    pub reply_message: Option<String>,
+    pub broadcast_to: Option<String>,  // 将收到的消息转发给该路由（路径模式）下的所有其他客户端
+}
+
+impl WebSocketRoute {
+    pub fn matches(&self, path: &str) -> bool {
+        match self.path.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => self.path == path,
+        }
+    }
+
+    // 对收到的消息生成自动回复
+    fn reply(&self, msg: &Message) -> Option<Message> {
+        match self.auto_reply.as_deref() {
+            Some("echo") => Some(msg.clone()),
+            Some("fixed") => Some(Message::Text(self.reply_message.clone().unwrap_or_default())),
+            _ => None,
+        }
+    }
+}
+
+// WebSocket服务器配置
+#[derive(Debug, Clone, Default)]
+pub struct WebSocketServerConfig {
+    pub socketio: bool,                  // 是否以Socket.IO服务端模式运行
+    pub ping_interval: Option<Duration>, // 自动ping间隔，None表示不自动发送
+    pub pong_timeout: Option<Duration>,  // 超过该时间未收到pong则断开客户端
+    pub handshake: HandshakePolicy,
+    pub routes: Vec<WebSocketRoute>, // 为空时接受任意路径
+    pub checksum: Option<Checksum>,  // 接收校验设置
+}
+
+impl WebSocketServerConfig {
+    // 按定义顺序匹配第一个路由
+    pub fn match_route(&self, path: &str) -> Option<&WebSocketRoute> {
+        self.routes.iter().find(|route| route.matches(path))
+    }
 }
 
 // WebSocket服务器
@@ -24,6 +143,7 @@ pub struct WebSocketServer {
     pub host: String,
     pub port: u16,
     pub server_id: String,
+    pub config: WebSocketServerConfig,
     pub clients: Arc<RwLock<HashMap<String, WebSocketClient>>>,
     pub server_handle: Option<JoinHandle<()>>,
     pub shutdown_sender: Option<mpsc::UnboundedSender<()>>,
@@ -56,6 +176,17 @@ pub struct StartServerParams {
     pub host: String,
     pub port: u16,
     pub server_id: Option<String>,
+    pub protocol: Option<String>,     // "websocket"（默认）或 "socketio"（仅支持WebSocket传输，HTTP长轮询请求以400拒绝）
+    pub ping_interval_ms: Option<u64>, // 自动ping间隔，不指定时不自动发送；Socket.IO模式不支持，由 Engine.IO 心跳代替
+    pub pong_timeout_ms: Option<u64>,  // pong超时，默认为 10000；Socket.IO模式不支持
+    pub subprotocols: Option<Vec<String>>,     // 支持的子协议，指定时客户端必须请求其中之一
+    pub allowed_origins: Option<Vec<String>>,  // 允许的Origin，不指定时不检查
+    pub bearer_token: Option<String>,          // 要求客户端携带的Bearer令牌
+    pub response_headers: Option<Vec<HttpHeader>>, // 握手响应附加的头部
+    pub reject_status: Option<u16>,            // 强制以该HTTP状态拒绝握手，用于测试客户端的错误处理
+    pub routes: Option<Vec<WebSocketRoute>>,   // 按路径划分的路由，未匹配的请求返回404
+    pub compression: Option<CompressionParams>, // permessage-deflate 设置，不指定时不启用
+    pub checksum: Option<ChecksumParams>,       // 校验收到的每条数据消息，结果附加在接收事件上
 }
 
 // 发送消息的参数
@@ -65,6 +196,22 @@ pub struct SendMessageParams {
     pub server_id: String,
     pub message: String,
     pub target_client_id: Option<String>, // 如果为None则广播给所有客户端
+    pub target_route: Option<String>,     // 发送给该路由下的所有客户端
+    pub message_type: Option<String>,     // "text"、"hex"、"escaped"、"base64" 或 "decimal"，默认为 "text"；非文本格式作为二进制帧发送
+    pub template: Option<bool>,           // 发送前展开 {{...}} 模板占位符，默认为 false
+    pub checksum: Option<ChecksumParams>, // 发送前计算并插入校验值，仅用于二进制消息
+    pub event: Option<String>,            // Socket.IO模式下的事件名，默认为 "message"
+    pub namespace: Option<String>,        // Socket.IO模式下的命名空间，默认为 "/"
+}
+
+// 发送ping的参数
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PingParams {
+    pub server_id: String,
+    pub target_client_id: Option<String>, // 如果为None则发送给所有客户端
+    pub payload: Option<String>,
+    pub payload_type: Option<String>, // 格式同发送消息，默认为 "text"
 }
 
 // 服务器状态信息
@@ -85,16 +232,93 @@ pub struct WebSocketServerEvent {
     pub server_id: String,
     pub event_type: String,
     pub client_id: String,
+    pub route: Option<String>,
     pub message: String,
+    pub close: Option<CloseInfo>, // 仅 client_disconnected 事件携带
+    pub checksum: Option<ChecksumCheck>, // 启用接收校验时的校验结果
+    pub data: Option<Vec<u8>>, // 收到的二进制数据，仅 binary_received 事件携带
     pub timestamp: String,
 }
 
+// 连接关闭信息
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseInfo {
+    pub initiator: String,          // "client" 或 "server"
+    pub kind: String,               // "clean" 关闭握手、"abrupt" 连接中断、"protocol_error" 协议错误、"timeout" 心跳超时
+    pub code: Option<u16>,          // 关闭帧中的关闭码，未携带时为None
+    pub reason: String,
+    pub error_kind: Option<String>, // tungstenite错误类型，例如 "Io(ConnectionReset)"
+}
+
+impl CloseInfo {
+    pub fn new(initiator: &str, kind: &str, reason: String) -> Self {
+        CloseInfo {
+            initiator: initiator.to_string(),
+            kind: kind.to_string(),
+            code: None,
+            reason,
+            error_kind: None,
+        }
+    }
+
+    // 关闭握手：关闭码和原因取自发起方的关闭帧
+    pub fn from_frame(initiator: &str, frame: Option<&CloseFrame>) -> Self {
+        let mut close = CloseInfo::new(initiator, "clean", String::new());
+        if let Some(frame) = frame {
+            close.code = Some(u16::from(frame.code));
+            close.reason = frame.reason.to_string();
+        }
+        close
+    }
+
+    // 读写出错：连接中断归因于客户端，其余错误由服务端检测并断开连接
+    pub fn from_error(error: &WsError) -> Self {
+        let (initiator, kind) = match error {
+            WsError::ConnectionClosed | WsError::AlreadyClosed => ("client", "clean"),
+            WsError::Io(_) | WsError::Protocol(ProtocolError::ResetWithoutClosingHandshake) => ("client", "abrupt"),
+            _ => ("server", "protocol_error"),
+        };
+        let mut close = CloseInfo::new(initiator, kind, error.to_string());
+        close.error_kind = Some(match error {
+            WsError::Io(e) => format!("Io({:?})", e.kind()),
+            error => format!("{:?}", error),
+        });
+        close
+    }
+
+    // 连接在没有关闭帧的情况下结束
+    pub fn dropped() -> Self {
+        CloseInfo::new("client", "abrupt", "Connection closed without a close frame".to_string())
+    }
+
+    pub fn describe(&self) -> String {
+        match self.kind.as_str() {
+            "clean" => {
+                let code = match self.code {
+                    Some(code) => format!("code {}", code),
+                    None => "no status code".to_string(),
+                };
+                if self.reason.is_empty() {
+                    format!("Closed by {} ({})", self.initiator, code)
+                } else {
+                    format!("Closed by {} ({}): {}", self.initiator, code, self.reason)
+                }
+            }
+            "abrupt" => format!("Connection dropped without a closing handshake: {}", self.reason),
+            "protocol_error" => format!("Connection failed: {}", self.reason),
+            _ => self.reason.clone(),
+        }
+    }
+}
+
 impl WebSocketServer {
     pub fn new(host: String, port: u16, server_id: String) -> Self {
         WebSocketServer {
             host,
             port,
             server_id,
+            config: WebSocketServerConfig::default(),
             clients: Arc::new(RwLock::new(HashMap::new())),
             server_handle: None,
             shutdown_sender: None,
@@ -115,6 +339,7 @@ impl WebSocketServer {
         let clients = Arc::clone(&self.clients);
         let app_handle = self.app_handle.clone();
         let server_id = self.server_id.clone();
+        let config = Arc::new(self.config.clone());
         let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
         self.shutdown_sender = Some(shutdown_tx);
 
@@ -133,7 +358,8 @@ impl WebSocketServer {
                                 let clients_clone = Arc::clone(&clients);
                                 let app_handle_clone = app_handle.clone();
                                 let server_id_clone = server_id.clone();
-                                tokio::spawn(handle_connection(stream, addr, clients_clone, app_handle_clone, server_id_clone));
+                                let config_clone = Arc::clone(&config);
+                                tokio::spawn(handle_connection(stream, addr, clients_clone, app_handle_clone, server_id_clone, config_clone));
                             }
                             Err(e) => {
                                 eprintln!("Failed to accept connection: {}", e);
@@ -162,32 +388,34 @@ impl WebSocketServer {
         // 关闭所有客户端连接
         let mut clients = self.clients.write().await;
         for (_, client) in clients.drain() {
-            let _ = client.sender.send(Message::Close(None));
+            let _ = client.sender.send(Message::Close(None).into());
         }
 
         Ok(())
     }
 
-    pub async fn send_message_to_client(&self, client_id: &str, message: &str) -> Result<(), String> {
+    // 发送一组帧（Socket.IO二进制事件由文本帧和附件帧组成）
+    pub async fn send_frames_to_client(&self, client_id: &str, frames: Vec<Message>) -> Result<(), String> {
         let clients = self.clients.read().await;
         if let Some(client) = clients.get(client_id) {
-            client
-                .sender
-                .send(Message::Text(message.to_string()))
-                .map_err(|e| format!("Failed to send message to client {}: {}", client_id, e))?;
+            for frame in frames {
+                client
+                    .sender
+                    .send(frame.into())
+                    .map_err(|e| format!("Failed to send message to client {}: {}", client_id, e))?;
+            }
             Ok(())
         } else {
             Err(format!("Client {} not found", client_id))
         }
     }
 
-    pub async fn broadcast_message(&self, message: &str) -> Result<usize, String> {
+    pub async fn broadcast_frames(&self, frames: Vec<Message>) -> Result<usize, String> {
         let clients = self.clients.read().await;
         let mut sent_count = 0;
-        let msg = Message::Text(message.to_string());
 
         for (_, client) in clients.iter() {
-            if client.sender.send(msg.clone()).is_ok() {
+            if frames.iter().all(|frame| client.sender.send(frame.clone().into()).is_ok()) {
                 sent_count += 1;
             }
         }
@@ -195,6 +423,52 @@ impl WebSocketServer {
         Ok(sent_count)
     }
 
+    pub async fn send_frames_to_route(&self, route: &str, frames: Vec<Message>) -> Result<usize, String> {
+        if !self.config.routes.iter().any(|defined| defined.path == route) {
+            return Err(format!("Route {} not found", route));
+        }
+        Ok(send_to_route(&self.clients, route, None, &frames).await)
+    }
+
+    pub async fn client_ids(&self) -> Vec<String> {
+        self.clients.read().await.keys().cloned().collect()
+    }
+
+    // 向指定的客户端发送一帧，返回在数据写出后完成的通知；已断开的客户端被跳过
+    pub async fn send_to_clients_with_ack(&self, client_ids: &[String], message: Message, fragment_stream: Option<u64>) -> Vec<WriteAckReceiver> {
+        let clients = self.clients.read().await;
+        let mut acks = Vec::new();
+        for client in client_ids.iter().filter_map(|client_id| clients.get(client_id)) {
+            let (ack, receiver) = write_ack::channel();
+            let item = OutgoingMessage {
+                message: message.clone(),
+                fragment_stream,
+                request_id: None,
+                ack: Some(ack),
+            };
+            if client.sender.send(item).is_ok() {
+                acks.push(receiver);
+            }
+        }
+        acks
+    }
+
+    pub async fn ping_client(&self, client_id: &str, payload: Vec<u8>) -> Result<(), String> {
+        let clients = self.clients.read().await;
+        match clients.get(client_id) {
+            Some(client) => client.send_ping(payload),
+            None => Err(format!("Client {} not found", client_id)),
+        }
+    }
+
+    pub async fn ping_all(&self, payload: Vec<u8>) -> usize {
+        let clients = self.clients.read().await;
+        clients
+            .values()
+            .filter(|client| client.send_ping(payload.clone()).is_ok())
+            .count()
+    }
+
     pub async fn get_client_count(&self) -> usize {
         self.clients.read().await.len()
     }
@@ -206,21 +480,66 @@ impl WebSocketServer {
 
 // 处理WebSocket连接
 async fn handle_connection(
-    stream: TcpStream,
+    mut stream: TcpStream,
     addr: SocketAddr,
     clients: Arc<RwLock<HashMap<String, WebSocketClient>>>,
     app_handle: Option<tauri::AppHandle>,
     server_id: String,
+    config: Arc<WebSocketServerConfig>,
 ) {
-    let ws_stream = match accept_async(stream).await {
+    // 握手回调：记录升级请求并执行接受策略
+    let mut outcome = HandshakeOutcome::default();
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, response: Response| {
+        let response = config.handshake.accept(request, response, &mut outcome)?;
+        if config.routes.is_empty() {
+            return Ok(response);
+        }
+        match config.match_route(request.uri().path()) {
+            Some(route) => {
+                outcome.route = Some(route.path.clone());
+                Ok(response)
+            }
+            None => {
+                let reason = format!("No route for path {}", request.uri().path());
+                Err(config.handshake.reject(StatusCode::NOT_FOUND, reason, &mut outcome))
+            }
+        }
+    };
+    // Socket.IO模式不支持长轮询，客户端需指定 transports: ["websocket"]
+    if config.socketio {
+        if let Some(reason) = socketio_server::reject_unsupported_transport(&mut stream).await {
+            eprintln!("Rejected Socket.IO request from {}: {}", addr, reason);
+            emit_server_event(&app_handle, &server_id, "", None, "handshake_rejected", format!("Rejected handshake from {}: {}", addr, reason));
+            return;
+        }
+    }
+
+    let stream = DeflateStream::new(stream, config.handshake.compression.is_some());
+    let mut ws_stream = match accept_hdr_async(stream, callback).await {
         Ok(ws) => ws,
         Err(e) => {
             eprintln!("Failed to accept WebSocket connection from {}: {}", addr, e);
+            if let Some(rejection) = outcome.rejection {
+                emit_server_event(
+                    &app_handle,
+                    &server_id,
+                    "",
+                    None,
+                    "handshake_rejected",
+                    format!("Rejected handshake from {} with {}\n\n{}", addr, rejection, outcome.request),
+                );
+            }
             return;
         }
     };
 
+    ws_stream
+        .get_mut()
+        .activate(outcome.compression.clone().map(|compression| DeflateCodec::new(compression, true)));
+
     let client_id = Uuid::new_v4().to_string();
+    let route = outcome.route.clone();
     println!("New WebSocket client connected: {} ({})", client_id, addr);
 
     // 发送客户端连接事件到前端
@@ -229,7 +548,11 @@ async fn handle_connection(
             server_id: server_id.clone(),
             event_type: "client_connected".to_string(),
             client_id: client_id.clone(),
-            message: format!("Client connected from {}", addr),
+            route: route.clone(),
+            message: describe_connection(addr, &outcome),
+            close: None,
+            checksum: None,
+            data: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
         
@@ -238,8 +561,9 @@ async fn handle_connection(
         }
     }
 
-    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-    let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+    let (tx, mut rx) = mpsc::unbounded_channel::<OutgoingMessage>();
+    let pings = Arc::new(PingTracker::default());
+    let ping_sender = tx.clone();
 
     // 添加客户端到集合
     {
@@ -250,17 +574,43 @@ async fn handle_connection(
                 id: client_id.clone(),
                 addr,
                 sender: tx,
+                pings: Arc::clone(&pings),
+                route: route.clone(),
             },
         );
     }
 
-    // 启动发送任务
-    let send_task = tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            if ws_sender.send(msg).await.is_err() {
-                break;
+    // Socket.IO模式：由会话处理 Engine.IO 报文
+    if config.socketio {
+        let close = socketio_server::run_session(ws_stream, rx, &client_id, &server_id, route.as_deref(), &app_handle).await;
+        clients.write().await.remove(&client_id);
+        emit_close_event(&app_handle, &server_id, &client_id, route.as_deref(), close);
+        println!("Client {} disconnected and cleaned up", client_id);
+        return;
+    }
+
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    // 服务端发出的关闭帧，用于区分关闭握手的发起方
+    let server_close: Arc<std::sync::Mutex<Option<Option<CloseFrame<'static>>>>> = Arc::default();
+
+    // 启动发送任务，写入失败时返回错误
+    let server_close_clone = Arc::clone(&server_close);
+    let mut send_task = tokio::spawn(async move {
+        let mut gate = FragmentGate::default();
+        while let Some(item) = rx.recv().await {
+            for OutgoingMessage { message, ack, .. } in gate.admit(item) {
+                if let Message::Close(frame) = &message {
+                    *server_close_clone.lock().unwrap() = Some(frame.clone());
+                }
+                if let Err(e) = ws_sender.send(message).await {
+                    write_ack::notify(ack, Err(format!("Failed to write data: {}", e)));
+                    return Some(e);
+                }
+                write_ack::notify(ack, Ok(()));
             }
         }
+        None
     });
 
     // 接收消息循环
@@ -268,8 +618,34 @@ async fn handle_connection(
     let clients_clone = Arc::clone(&clients);
     let app_handle_clone = app_handle.clone();
     let server_id_clone = server_id.clone();
-    let receive_task = tokio::spawn(async move {
-        while let Some(msg) = ws_receiver.next().await {
+    let pings_clone = Arc::clone(&pings);
+    let route_clone = route.clone();
+    let route_config = route.as_deref().and_then(|path| config.routes.iter().find(|defined| defined.path == path)).cloned();
+    let reply_sender = ping_sender.clone();
+    let checksum = config.checksum.clone();
+    let mut receive_task = tokio::spawn(async move {
+        let capture_tap = receive_capture::tap(&app_handle_clone, "websocket_server", &server_id_clone).await;
+        let close = loop {
+            let msg = match ws_receiver.next().await {
+                Some(msg) => msg,
+                None => break CloseInfo::dropped(),
+            };
+
+            // 按路由自动回复和转发数据消息
+            if let (Some(route), Ok(message @ (Message::Text(_) | Message::Binary(_)))) = (&route_config, &msg) {
+                route_message(route, message, &reply_sender, &clients_clone, &client_id_clone2).await;
+            }
+
+            // 写入接收记录，被接管的数据消息不再发送事件
+            let captured = match &msg {
+                Ok(Message::Text(text)) => capture_tap.capture(&client_id_clone2, text.as_bytes()).await,
+                Ok(Message::Binary(bin)) => capture_tap.capture(&client_id_clone2, bin).await,
+                _ => false,
+            };
+            if captured {
+                continue;
+            }
+
             match msg {
                 Ok(Message::Text(text)) => {
                     println!("Received from {}: {}", client_id_clone2, text);
@@ -280,7 +656,11 @@ async fn handle_connection(
                             server_id: server_id_clone.clone(),
                             event_type: "message_received".to_string(),
                             client_id: client_id_clone2.clone(),
+                            route: route_clone.clone(),
                             message: text.clone(),
+                            close: None,
+                            checksum: checksum.as_ref().map(|checksum| checksum.verify(text.as_bytes())),
+                            data: None,
                             timestamp: chrono::Utc::now().to_rfc3339(),
                         };
                         
@@ -298,7 +678,11 @@ async fn handle_connection(
                             server_id: server_id_clone.clone(),
                             event_type: "binary_received".to_string(),
                             client_id: client_id_clone2.clone(),
-                            message: format!("Binary data: {} bytes", bin.len()),
+                            route: route_clone.clone(),
+                            message: format!("Binary data ({} bytes): {}", bin.len(), to_hex(&bin)),
+                            close: None,
+                            checksum: checksum.as_ref().map(|checksum| checksum.verify(&bin)),
+                            data: Some(bin),
                             timestamp: chrono::Utc::now().to_rfc3339(),
                         };
                         
@@ -307,48 +691,249 @@ async fn handle_connection(
                         }
                     }
                 }
-                Ok(Message::Close(_)) => {
-                    println!("Client {} disconnected", client_id_clone2);
-                    
-                    // 发送客户端断开事件到前端
-                    if let Some(ref app) = app_handle_clone {
-                        let event = WebSocketServerEvent {
-                            server_id: server_id_clone.clone(),
-                            event_type: "client_disconnected".to_string(),
-                            client_id: client_id_clone2.clone(),
-                            message: "Client disconnected".to_string(),
-                            timestamp: chrono::Utc::now().to_rfc3339(),
-                        };
-                        
-                        if let Err(e) = app.emit("websocket-server-event", &event) {
-                            eprintln!("Failed to emit event to frontend: {}", e);
-                        }
-                    }
-                    break;
+                Ok(Message::Close(frame)) => {
+                    // 服务端先发送了关闭帧时，收到的是客户端的确认
+                    break match server_close.lock().unwrap().take() {
+                        Some(sent) => CloseInfo::from_frame("server", sent.as_ref()),
+                        None => CloseInfo::from_frame("client", frame.as_ref()),
+                    };
+                }
+                Ok(Message::Ping(payload)) => {
+                    // tungstenite会自动回复pong
+                    emit_server_event(
+                        &app_handle_clone,
+                        &server_id_clone,
+                        &client_id_clone2,
+                        route_clone.as_deref(),
+                        "ping_received",
+                        format!("Ping received ({} bytes): {}", payload.len(), to_hex(&payload)),
+                    );
+                }
+                Ok(Message::Pong(payload)) => {
+                    let message = match pings_clone.pong(&payload) {
+                        Some(rtt) => format!(
+                            "Pong received, RTT {:.2} ms ({} bytes): {}",
+                            rtt.as_secs_f64() * 1000.0,
+                            payload.len(),
+                            to_hex(&payload)
+                        ),
+                        None => format!("Unsolicited pong ({} bytes): {}", payload.len(), to_hex(&payload)),
+                    };
+                    emit_server_event(&app_handle_clone, &server_id_clone, &client_id_clone2, route_clone.as_deref(), "pong_received", message);
                 }
                 Err(e) => {
                     eprintln!("WebSocket error for client {}: {}", client_id_clone2, e);
-                    break;
+                    break CloseInfo::from_error(&e);
                 }
                 _ => {}
             }
-        }
+        };
 
-        // 从客户端集合中移除
-        clients_clone.write().await.remove(&client_id_clone2);
+        println!("Client {} disconnected: {}", client_id_clone2, close.describe());
+        close
     });
 
-    // 等待任何一个任务完成
-    tokio::select! {
-        _ = send_task => {},
-        _ = receive_task => {},
+    // 自动ping任务，超时未收到pong时返回发出的关闭帧
+    let mut ping_task = config.ping_interval.map(|interval| {
+        let timeout = config.pong_timeout.unwrap_or(DEFAULT_PONG_TIMEOUT);
+        tokio::spawn(run_auto_ping(ping_sender, pings, interval, timeout))
+    });
+    let ping_timeout = async {
+        match ping_task.as_mut() {
+            Some(task) => task.await.ok().flatten(),
+            None => std::future::pending().await,
+        }
+    };
+
+    // 等待任何一个任务完成，得出断开原因
+    let close = tokio::select! {
+        result = &mut send_task => match result {
+            Ok(Some(e)) => {
+                eprintln!("WebSocket error for client {}: {}", client_id, e);
+                CloseInfo::from_error(&e)
+            }
+            // 发送端已全部释放，断开原因由接收任务给出
+            _ => match tokio::time::timeout(CLOSE_FLUSH_TIMEOUT, &mut receive_task).await {
+                Ok(Ok(close)) => close,
+                _ => CloseInfo::dropped(),
+            },
+        },
+        result = &mut receive_task => result.unwrap_or_else(|_| CloseInfo::dropped()),
+        Some(frame) = ping_timeout => {
+            println!("Client {} timed out: {}", client_id, frame.reason);
+
+            // 释放其余发送端，让发送任务写完队列中的关闭帧后退出
+            receive_task.abort();
+            clients.write().await.remove(&client_id);
+            let _ = tokio::time::timeout(CLOSE_FLUSH_TIMEOUT, &mut send_task).await;
+            CloseInfo { kind: "timeout".to_string(), ..CloseInfo::from_frame("server", Some(&frame)) }
+        },
+    };
+    send_task.abort();
+    receive_task.abort();
+    if let Some(task) = ping_task {
+        task.abort();
     }
 
     // 清理：从客户端集合中移除
     clients.write().await.remove(&client_id);
+    emit_close_event(&app_handle, &server_id, &client_id, route.as_deref(), close);
     println!("Client {} disconnected and cleaned up", client_id);
 }
 
+// 按间隔发送ping，最早的未应答ping超过超时时间时发送携带原因的关闭帧并返回该帧
+async fn run_auto_ping(
+    sender: mpsc::UnboundedSender<OutgoingMessage>,
+    pings: Arc<PingTracker>,
+    interval: Duration,
+    timeout: Duration,
+) -> Option<CloseFrame<'static>> {
+    let mut next_ping = Instant::now() + interval;
+    let mut sequence: u64 = 0;
+
+    loop {
+        let wake = match pings.oldest() {
+            Some(sent_at) => next_ping.min(sent_at + timeout),
+            None => next_ping,
+        };
+        tokio::time::sleep_until(tokio::time::Instant::from_std(wake)).await;
+
+        if let Some(sent_at) = pings.oldest() {
+            if sent_at.elapsed() >= timeout {
+                let frame = CloseFrame {
+                    code: CloseCode::Policy,
+                    reason: format!("No pong received within {} ms", timeout.as_millis()).into(),
+                };
+                let _ = sender.send(Message::Close(Some(frame.clone())).into());
+                return Some(frame);
+            }
+        }
+
+        if Instant::now() >= next_ping {
+            sequence += 1;
+            let payload = sequence.to_be_bytes().to_vec();
+            pings.sent(payload.clone());
+            if sender.send(Message::Ping(payload).into()).is_err() {
+                return None;
+            }
+            next_ping += interval;
+        }
+    }
+}
+
+// 路由的自动回复和转发
+async fn route_message(
+    route: &WebSocketRoute,
+    message: &Message,
+    sender: &mpsc::UnboundedSender<OutgoingMessage>,
+    clients: &RwLock<HashMap<String, WebSocketClient>>,
+    client_id: &str,
+) {
+    if let Some(reply) = route.reply(message) {
+        let _ = sender.send(reply.into());
+    }
+    if let Some(target) = &route.broadcast_to {
+        send_to_route(clients, target, Some(client_id), std::slice::from_ref(message)).await;
+    }
+}
+
+// 发送给某个路由下的所有客户端（可排除发送者），返回发送成功的客户端数量
+async fn send_to_route(
+    clients: &RwLock<HashMap<String, WebSocketClient>>,
+    route: &str,
+    exclude: Option<&str>,
+    frames: &[Message],
+) -> usize {
+    let clients = clients.read().await;
+    clients
+        .values()
+        .filter(|client| client.route.as_deref() == Some(route) && Some(client.id.as_str()) != exclude)
+        .filter(|client| frames.iter().all(|frame| client.sender.send(frame.clone().into()).is_ok()))
+        .count()
+}
+
+// 连接事件的描述：协商结果和升级请求
+fn describe_connection(addr: SocketAddr, outcome: &HandshakeOutcome) -> String {
+    let mut negotiated = Vec::new();
+    if let Some(protocol) = &outcome.subprotocol {
+        negotiated.push(format!("subprotocol: {}", protocol));
+    }
+    if let Some(compression) = &outcome.compression {
+        negotiated.push(format!("extension: {}", compression.describe()));
+    }
+
+    if negotiated.is_empty() {
+        format!("Client connected from {}\n\n{}", addr, outcome.request)
+    } else {
+        format!("Client connected from {} ({})\n\n{}", addr, negotiated.join(", "), outcome.request)
+    }
+}
+
+// 断开事件，附带关闭码、原因和发起方
+pub fn emit_close_event(app_handle: &Option<tauri::AppHandle>, server_id: &str, client_id: &str, route: Option<&str>, close: CloseInfo) {
+    if let Some(app) = app_handle {
+        let event = WebSocketServerEvent {
+            server_id: server_id.to_string(),
+            event_type: "client_disconnected".to_string(),
+            client_id: client_id.to_string(),
+            route: route.map(str::to_string),
+            message: close.describe(),
+            close: Some(close),
+            checksum: None,
+            data: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+
+        if let Err(e) = app.emit("websocket-server-event", &event) {
+            eprintln!("Failed to emit event to frontend: {}", e);
+        }
+    }
+}
+
+pub fn emit_server_event(
+    app_handle: &Option<tauri::AppHandle>,
+    server_id: &str,
+    client_id: &str,
+    route: Option<&str>,
+    event_type: &str,
+    message: String,
+) {
+    if let Some(app) = app_handle {
+        let event = WebSocketServerEvent {
+            server_id: server_id.to_string(),
+            event_type: event_type.to_string(),
+            client_id: client_id.to_string(),
+            route: route.map(str::to_string),
+            message,
+            close: None,
+            checksum: None,
+            data: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+
+        if let Err(e) = app.emit("websocket-server-event", &event) {
+            eprintln!("Failed to emit event to frontend: {}", e);
+        }
+    }
+}
+
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// 按消息类型构造WebSocket帧，二进制类型作为二进制帧发送
+// 校验值会破坏文本帧的UTF-8编码，因此只能追加到二进制消息
+pub fn encode_message(message: &str, message_type: &str, checksum: Option<&ChecksumParams>) -> Result<Message, String> {
+    match (message_type, checksum) {
+        ("text", None) => Ok(Message::Text(message.to_string())),
+        ("text", Some(_)) => Err("Checksums can only be appended to binary messages".to_string()),
+        _ => checksum::append_checksum(payload_codec::decode(message, Some(message_type))?, checksum).map(Message::Binary),
+    }
+}
+
 // Tauri命令：启动WebSocket服务器
 #[tauri::command]
 pub async fn start_websocket_server(
@@ -365,6 +950,40 @@ pub async fn start_websocket_server(
     }
 
     let mut server = WebSocketServer::new(start_params.host.clone(), start_params.port, server_id.clone());
+    server.config.socketio = match start_params.protocol.as_deref() {
+        None | Some("") | Some("websocket") => false,
+        Some("socketio") => true,
+        Some(other) => return Err(format!("Unsupported server protocol: {}", other)),
+    };
+    server.config.ping_interval = start_params.ping_interval_ms.filter(|ms| *ms > 0).map(Duration::from_millis);
+    server.config.pong_timeout = start_params.pong_timeout_ms.map(Duration::from_millis);
+    if server.config.socketio && (server.config.ping_interval.is_some() || server.config.pong_timeout.is_some()) {
+        return Err("Ping interval and pong timeout are not supported in Socket.IO mode, which uses Engine.IO heartbeats".to_string());
+    }
+    server.config.handshake = HandshakePolicy::new(
+        start_params.subprotocols,
+        start_params.allowed_origins,
+        start_params.bearer_token,
+        start_params.response_headers,
+        start_params.reject_status,
+    )?;
+    server.config.handshake.compression = DeflateConfig::from_params(start_params.compression.as_ref())?;
+    server.config.checksum = Checksum::from_params(start_params.checksum.as_ref())?;
+    server.config.routes = start_params.routes.unwrap_or_default();
+    for route in &server.config.routes {
+        if !route.path.starts_with('/') {
+            return Err(format!("Route path must start with '/': {}", route.path));
+        }
+        match route.auto_reply.as_deref() {
+            None | Some("") | Some("none") | Some("echo") | Some("fixed") => {}
+            Some(other) => return Err(format!("Unsupported auto reply mode for route {}: {}", route.path, other)),
+        }
+        if let Some(target) = &route.broadcast_to {
+            if !server.config.routes.iter().any(|defined| &defined.path == target) {
+                return Err(format!("Broadcast target of route {} is not a defined route: {}", route.path, target));
+            }
+        }
+    }
     server.set_app_handle(app_handle);
     server.start().await?;
 
@@ -400,6 +1019,7 @@ pub async fn stop_websocket_server(
 pub async fn send_websocket_message(
     send_params: SendMessageParams,
     state: State<'_, Mutex<WebSocketServerManager>>,
+    templates: State<'_, Mutex<TemplateEngine>>,
 ) -> Result<String, String> {
     if send_params.server_id.is_empty() {
         return Err("Server ID cannot be empty".to_string());
@@ -408,17 +1028,47 @@ pub async fn send_websocket_message(
     if send_params.message.is_empty() {
         return Err("Message cannot be empty".to_string());
     }
+
+    let message = message_template::expand_message(
+        &templates,
+        send_params.template,
+        &send_params.server_id,
+        send_params.message,
+        send_params.message_type.as_deref(),
+    )
+    .await?;
     
     let manager = state.lock().await;
 
     if let Some(server) = manager.servers.get(&send_params.server_id) {
+        let message_type = send_params.message_type.as_deref().unwrap_or("text");
+
+        // Socket.IO模式下消息内容作为事件参数（JSON数组）
+        let frames = if server.config.socketio {
+            if message_type != "text" {
+                return Err("Socket.IO mode sends binary data as {\"$hex\": \"...\"} event arguments".to_string());
+            }
+            if send_params.checksum.is_some() {
+                return Err("Checksums are not supported in Socket.IO mode".to_string());
+            }
+            let event = send_params.event.as_deref().filter(|event| !event.is_empty()).unwrap_or("message");
+            let namespace = send_params.namespace.as_deref().unwrap_or("/");
+            socketio_server::event_frames(namespace, event, socketio_codec::parse_arguments(&message))?
+        } else {
+            vec![encode_message(&message, message_type, send_params.checksum.as_ref())?]
+        };
+
         if let Some(target_client_id) = send_params.target_client_id {
             // 发送给特定客户端
-            server.send_message_to_client(&target_client_id, &send_params.message).await?;
+            server.send_frames_to_client(&target_client_id, frames).await?;
             Ok(format!("Message sent to client {}", target_client_id))
+        } else if let Some(target_route) = send_params.target_route {
+            // 发送给路由下的所有客户端
+            let sent_count = server.send_frames_to_route(&target_route, frames).await?;
+            Ok(format!("Message sent to {} clients on route {}", sent_count, target_route))
         } else {
             // 广播给所有客户端
-            let sent_count = server.broadcast_message(&send_params.message).await?;
+            let sent_count = server.broadcast_frames(frames).await?;
             Ok(format!("Message broadcast to {} clients", sent_count))
         }
     } else {
@@ -426,6 +1076,35 @@ pub async fn send_websocket_message(
     }
 }
 
+// Tauri命令：向客户端发送ping，收到pong时在事件中报告往返时间
+#[tauri::command]
+pub async fn ping_websocket_clients(
+    ping_params: PingParams,
+    state: State<'_, Mutex<WebSocketServerManager>>,
+) -> Result<String, String> {
+    let payload = payload_codec::decode(
+        ping_params.payload.as_deref().unwrap_or_default(),
+        ping_params.payload_type.as_deref(),
+    )?;
+    if payload.len() > MAX_CONTROL_PAYLOAD {
+        return Err(format!("Ping payload is {} bytes, the limit is {}", payload.len(), MAX_CONTROL_PAYLOAD));
+    }
+
+    let manager = state.lock().await;
+    let server = manager
+        .servers
+        .get(&ping_params.server_id)
+        .ok_or_else(|| format!("Server with ID {} not found", ping_params.server_id))?;
+
+    if let Some(target_client_id) = ping_params.target_client_id {
+        server.ping_client(&target_client_id, payload).await?;
+        Ok(format!("Ping sent to client {}", target_client_id))
+    } else {
+        let sent_count = server.ping_all(payload).await;
+        Ok(format!("Ping sent to {} clients", sent_count))
+    }
+}
+
 // Tauri命令：获取服务器列表
 #[tauri::command]
 pub async fn get_websocket_servers(
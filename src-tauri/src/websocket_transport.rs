@@ -0,0 +1,118 @@
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Response;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::Error as WsError;
+use tokio_tungstenite::WebSocketStream;
+use url::Url;
+
+use crate::transport::{self, ClientStream, LocalEndpoint, TlsOptions};
+use crate::websocket_deflate::{DeflateCodec, DeflateConfig, DeflateStream};
+
+pub type ClientWebSocket = WebSocketStream<DeflateStream<ClientStream>>;
+
+// WebSocket客户端连接设置
+#[derive(Debug, Clone, Default)]
+pub struct WebSocketConnectOptions {
+    pub headers: Vec<(String, String)>,
+    pub subprotocols: Vec<String>,
+    pub tls: TlsOptions,
+    pub local: LocalEndpoint,
+    pub compression: Option<DeflateConfig>, // 请求 permessage-deflate，协商结果可由 DeflateStream::negotiated 获取
+}
+
+// 将 http(s) 地址转换为 ws(s) 地址，其余scheme保持不变
+pub fn to_websocket_url(url: &Url) -> Result<Url, String> {
+    let scheme = match url.scheme() {
+        "ws" | "http" => "ws",
+        "wss" | "https" => "wss",
+        scheme => return Err(format!("Unsupported WebSocket URL scheme: {}", scheme)),
+    };
+
+    let mut ws_url = url.clone();
+    if ws_url.scheme() != scheme {
+        // 只有特殊scheme之间可以互相转换，http/https/ws/wss都属于此类
+        ws_url
+            .set_scheme(scheme)
+            .map_err(|_| format!("Cannot convert {} to a WebSocket URL", url))?;
+    }
+    Ok(ws_url)
+}
+
+// 建立WebSocket连接：TCP（可绑定本地地址）、可选TLS，然后完成升级握手
+pub async fn connect_websocket(url: &str, options: &WebSocketConnectOptions) -> Result<(ClientWebSocket, Response), String> {
+    let url = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    let url = to_websocket_url(&url)?;
+    let host = url.host_str().ok_or("URL has no host")?.to_string();
+    let port = url.port_or_known_default().ok_or("URL has no port")?;
+
+    let tcp_stream = transport::connect_tcp(&host, port, &options.local).await?;
+    let stream = if url.scheme() == "wss" {
+        let tls = TlsOptions {
+            alpn_protocols: vec![b"http/1.1".to_vec()],
+            ..options.tls.clone()
+        };
+        ClientStream::Tls(Box::new(transport::start_tls(tcp_stream, &host, &tls).await?))
+    } else {
+        ClientStream::Plain(tcp_stream)
+    };
+    let stream = DeflateStream::new(stream, options.compression.is_some());
+
+    let mut request = url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| format!("Invalid WebSocket URL {}: {}", url, e))?;
+    let headers = request.headers_mut();
+    for (name, value) in &options.headers {
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| format!("Invalid header name: {}", name))?;
+        let value = HeaderValue::from_str(value.trim())
+            .map_err(|_| format!("Invalid value for header {}", name))?;
+        headers.append(name, value);
+    }
+    if !options.subprotocols.is_empty() {
+        let protocols = options.subprotocols.join(", ");
+        let value = HeaderValue::from_str(&protocols)
+            .map_err(|_| format!("Invalid subprotocol list: {}", protocols))?;
+        headers.insert("Sec-WebSocket-Protocol", value);
+    }
+    if let Some(compression) = &options.compression {
+        let value = HeaderValue::from_str(&compression.offer())
+            .map_err(|_| "Invalid compression settings".to_string())?;
+        headers.insert("Sec-WebSocket-Extensions", value);
+    }
+
+    let (mut ws_stream, response) = tokio_tungstenite::client_async(request, stream)
+        .await
+        .map_err(|e| match e {
+            // 升级被拒绝时带上状态码和响应正文，便于排查鉴权问题
+            WsError::Http(response) => {
+                let body = response
+                    .body()
+                    .as_ref()
+                    .map(|body| String::from_utf8_lossy(body).to_string())
+                    .unwrap_or_default();
+                format!("WebSocket handshake rejected with HTTP {}: {}", response.status(), body.trim())
+            }
+            e => format!("WebSocket handshake failed: {}", e),
+        })?;
+
+    // 校验服务端接受的压缩参数，不符合请求时按RFC 7692关闭连接
+    if let Some(compression) = &options.compression {
+        let extensions = response
+            .headers()
+            .get_all("Sec-WebSocket-Extensions")
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+        match compression.accept_response(extensions) {
+            Ok(agreed) => ws_stream
+                .get_mut()
+                .activate(agreed.map(|agreed| DeflateCodec::new(agreed, false))),
+            Err(e) => {
+                let _ = ws_stream.close(None).await;
+                return Err(format!("Extension negotiation failed: {}", e));
+            }
+        }
+    }
+
+    Ok((ws_stream, response))
+}
@@ -0,0 +1,23 @@
+use tokio::sync::oneshot;
+
+// 数据写出后由发送任务通知，供需要背压的调用方（如文件传输）等待
+pub type WriteAck = oneshot::Sender<Result<(), String>>;
+pub type WriteAckReceiver = oneshot::Receiver<Result<(), String>>;
+
+pub fn channel() -> (WriteAck, WriteAckReceiver) {
+    oneshot::channel()
+}
+
+// 通知写出结果，调用方已不再等待时忽略
+pub fn notify(ack: Option<WriteAck>, result: Result<(), String>) {
+    if let Some(ack) = ack {
+        let _ = ack.send(result);
+    }
+}
+
+// 等待数据写出，发送任务在写出前退出时返回错误
+pub async fn written(receiver: WriteAckReceiver) -> Result<(), String> {
+    receiver
+        .await
+        .unwrap_or_else(|_| Err("Connection closed before the data was written".to_string()))
+}